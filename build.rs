@@ -5,6 +5,11 @@ fn main() {
         "proto/vertex_query.proto",
         "proto/vertex_execute.proto",
         "proto/vertex_symbols.proto",
+        "proto/oms.proto",
+        "proto/positions.proto",
+        "proto/strategies.proto",
+        "proto/algos.proto",
+        "proto/backtest.proto",
     ];
 
     tonic_build::configure()
@@ -1,10 +1,14 @@
 // src/api/handlers.rs
 
-use crate::services::vertex::client::VertexClient;
+use crate::services::{trading::gateway::TradingGateway, vertex::client::VertexClient};
 use crate::trading_service::trading_service_server::TradingService;
 use crate::trading_service::{ConnectionRequest, ConnectionResponse};
 use crate::vertex_query::vertex_query_service_server::VertexQueryService;
 use crate::vertex_query::{StatusRequest, StatusResponse};
+use crate::vertex_execute::vertex_execute_service_server::VertexExecuteService;
+use crate::vertex_execute::{
+    HeartbeatRequest, HeartbeatResponse, KillSwitchRequest, KillSwitchResponse,
+};
 
 use crate::shared::errors::api_error::ApiError;
 use axum::{Extension, Json};
@@ -15,7 +19,7 @@ use tracing::{error, info};
 
 #[axum::debug_handler]
 pub async fn initiate_connection_handler(
-    Extension(trading_service): Extension<Arc<VertexClient>>,
+    Extension(trading_service): Extension<Arc<TradingGateway>>,
     Json(payload): Json<ConnectionRequest>,
 ) -> Result<Json<ConnectionResponse>, ApiError> {
     info!("Received initiate_connection request: {:?}", payload);
@@ -49,3 +53,30 @@ pub async fn query_status_handler(
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
+
+#[axum::debug_handler]
+pub async fn kill_switch_handler(
+    Extension(vertex_client): Extension<Arc<VertexClient>>,
+    Json(payload): Json<KillSwitchRequest>,
+) -> Result<Json<KillSwitchResponse>, ApiError> {
+    info!("Received kill_switch request: {:?}", payload);
+
+    match vertex_client.as_ref().kill_switch(Request::new(payload)).await {
+        Ok(response) => Ok(Json(response.into_inner())),
+        Err(e) => Err({
+            error!("Error handling kill switch: {:?}", e);
+            e.into()
+        }),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn heartbeat_handler(
+    Extension(vertex_client): Extension<Arc<VertexClient>>,
+    Json(payload): Json<HeartbeatRequest>,
+) -> Result<Json<HeartbeatResponse>, ApiError> {
+    match vertex_client.as_ref().heartbeat(Request::new(payload)).await {
+        Ok(response) => Ok(Json(response.into_inner())),
+        Err(e) => Err(e.into()),
+    }
+}
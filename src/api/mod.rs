@@ -2,17 +2,20 @@
 
 mod handlers;
 
-use crate::services::vertex::client::VertexClient;
+use crate::services::{trading::gateway::TradingGateway, vertex::client::VertexClient};
 use axum::{routing::post, Extension, Router};
 use std::sync::Arc;
 
-pub fn router(trading_service: Arc<VertexClient>) -> Router {
+pub fn router(trading_service: Arc<VertexClient>, trading_gateway: Arc<TradingGateway>) -> Router {
     Router::new()
         .route(
             "/initiate_connection",
             post(handlers::initiate_connection_handler),
         )
         .route("/query/status", post(handlers::query_status_handler))
+        .route("/execute/kill_switch", post(handlers::kill_switch_handler))
+        .route("/execute/heartbeat", post(handlers::heartbeat_handler))
         // Add more routes here for other gRPC methods
         .layer(Extension(trading_service))
+        .layer(Extension(trading_gateway))
 }
@@ -1,6 +1,106 @@
 use dotenvy::dotenv;
 use lazy_static::lazy_static;
-use std::env;
+use std::{env, str::FromStr};
+
+// What to do with prices/sizes that are off the product's tick or size increment
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingPolicy {
+    #[default]
+    Reject,
+    // Round price and size to the closest increment
+    Nearest,
+    // Round price away from the touch (buys down, sells up) and size toward zero
+    Passive,
+}
+
+impl FromStr for RoundingPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "reject" => Ok(RoundingPolicy::Reject),
+            "nearest" => Ok(RoundingPolicy::Nearest),
+            "passive" => Ok(RoundingPolicy::Passive),
+            other => Err(format!("unknown rounding policy: {}", other)),
+        }
+    }
+}
+
+// dYdX v4 settings; the connector is only enabled when DYDX_PRIVATE_KEY is set
+#[derive(Debug, Clone)]
+pub struct DydxConfig {
+    pub private_key: String,
+    pub chain_id: String,
+    pub subaccount_number: u32,
+    pub indexer_url: String,
+    pub indexer_ws_url: String,
+    // Cosmos REST endpoint of a full node, used for account lookups and broadcasting
+    pub validator_url: String,
+    pub gas_limit: u64,
+    // Short-term (IOC/FOK) orders expire this many blocks past the current height, at most 20
+    pub short_term_blocks: u32,
+    // Stateful (GTC/post-only) orders expire this long after placement
+    pub stateful_order_ttl_secs: u64,
+}
+
+impl DydxConfig {
+    fn from_env() -> Option<Self> {
+        let private_key = env::var("DYDX_PRIVATE_KEY").ok()?;
+        Some(DydxConfig {
+            private_key,
+            chain_id: env::var("DYDX_CHAIN_ID").unwrap_or_else(|_| "dydx-testnet-4".to_string()),
+            subaccount_number: env::var("DYDX_SUBACCOUNT_NUMBER")
+                .ok()
+                .map(|v| v.parse().expect("DYDX_SUBACCOUNT_NUMBER must be an integer"))
+                .unwrap_or(0),
+            indexer_url: env::var("DYDX_INDEXER_URL")
+                .unwrap_or_else(|_| "https://indexer.v4testnet.dydx.exchange".to_string()),
+            indexer_ws_url: env::var("DYDX_INDEXER_WS_URL")
+                .unwrap_or_else(|_| "wss://indexer.v4testnet.dydx.exchange/v4/ws".to_string()),
+            validator_url: env::var("DYDX_VALIDATOR_URL").expect("DYDX_VALIDATOR_URL not set"),
+            gas_limit: env::var("DYDX_GAS_LIMIT")
+                .ok()
+                .map(|v| v.parse().expect("DYDX_GAS_LIMIT must be an integer"))
+                .unwrap_or(1_000_000),
+            short_term_blocks: env::var("DYDX_SHORT_TERM_BLOCKS")
+                .ok()
+                .map(|v| v.parse().expect("DYDX_SHORT_TERM_BLOCKS must be an integer"))
+                .unwrap_or(20),
+            stateful_order_ttl_secs: env::var("DYDX_STATEFUL_ORDER_TTL_SECS")
+                .ok()
+                .map(|v| v.parse().expect("DYDX_STATEFUL_ORDER_TTL_SECS must be an integer"))
+                .unwrap_or(28 * 24 * 60 * 60),
+        })
+    }
+}
+
+// Orderly Network settings; the connector is only enabled when ORDERLY_ACCOUNT_ID is set
+#[derive(Debug, Clone)]
+pub struct OrderlyConfig {
+    pub account_id: String,
+    // Orderly key secret, "ed25519:<base58>"
+    pub secret_key: String,
+    pub rest_url: String,
+    pub ws_public_url: String,
+    pub ws_private_url: String,
+}
+
+impl OrderlyConfig {
+    fn from_env() -> Option<Self> {
+        let account_id = env::var("ORDERLY_ACCOUNT_ID").ok()?;
+        Some(OrderlyConfig {
+            account_id,
+            secret_key: env::var("ORDERLY_SECRET_KEY").expect("ORDERLY_SECRET_KEY not set"),
+            rest_url: env::var("ORDERLY_REST_URL")
+                .unwrap_or_else(|_| "https://testnet-api-evm.orderly.org".to_string()),
+            ws_public_url: env::var("ORDERLY_WS_PUBLIC_URL")
+                .unwrap_or_else(|_| "wss://testnet-ws-evm.orderly.org/ws/stream".to_string()),
+            ws_private_url: env::var("ORDERLY_WS_PRIVATE_URL").unwrap_or_else(|_| {
+                "wss://testnet-ws-private-evm.orderly.org/v2/ws/private/stream".to_string()
+            }),
+        })
+    }
+}
 
 #[derive(Debug, Default, Clone)]
 pub struct Config {
@@ -10,12 +110,54 @@ pub struct Config {
     pub arbitrum_testnet_chain_id: i32,
     pub arbitrum_vertex_testnet_subscribe_url: String,
     pub arbitrum_vertex_testnet_gateway_url: String,
+    // Archive (indexer) endpoint for historical candles, used by the VWAP volume profile
+    pub vertex_archive_url: String,
+    pub registry_refresh_secs: u64,
+    pub order_rounding_policy: RoundingPolicy,
+    pub risk_limits_path: Option<String>,
+    pub subaccounts: Vec<String>,
+    pub dead_man_switch_timeout_secs: Option<u64>,
+    pub journal_dir: String,
+    pub journal_segment_bytes: u64,
+    pub journal_fsync: bool,
+    pub reconcile_cancel_orphans: bool,
+    // Cancelled, filled, rejected and expired orders the OMS keeps for queries
+    pub oms_max_terminal_orders: usize,
+    // Route Vertex executes to an in-process simulator instead of the gateway
+    pub paper_trading: bool,
+    // Market data is recorded only when a directory is set
+    pub recorder_dir: Option<String>,
+    pub recorder_max_file_bytes: u64,
+    pub recorder_rotate_secs: u64,
+    // Recording file or directory the subscription replays instead of connecting to Vertex
+    pub replay_path: Option<String>,
+    // Replay pace relative to the recording, 1.0 is the original speed
+    pub replay_speed: f64,
+    // Backtests only read event files from here; defaults to the recorder's directory
+    pub backtest_data_dir: Option<String>,
+    pub dydx: Option<DydxConfig>,
+    pub orderly: Option<OrderlyConfig>,
+    pub router_config_path: Option<String>,
 }
 
 impl Config {
     pub fn new() -> Self {
         dotenv().ok();
 
+        let replay_path = env::var("REPLAY_PATH").ok();
+        // A replayed session never trades for real
+        let paper_trading = replay_path.is_some()
+            || env::var("PAPER_TRADING")
+                .ok()
+                .map(|v| v.parse().expect("PAPER_TRADING must be true or false"))
+                .unwrap_or(false);
+        // Paper orders must not mix with the live order journal
+        let default_journal_dir = if paper_trading {
+            "journal-paper"
+        } else {
+            "journal"
+        };
+
         Self {
             sender_address: env::var("SENDER_ADDRESS").expect("SENDER_ADDRESS not set"),
             private_key: env::var("PRIVATE_KEY").expect("PRIVATE_KEY not set"),
@@ -31,6 +173,69 @@ impl Config {
             .expect("ARBITRUM_VERTEX_TESTNET_SUBSCRIBE_URL not set"),
             arbitrum_vertex_testnet_gateway_url: env::var("ARBITRUM_VERTEX_TESTNET_GATEWAY_URL")
                 .expect("ARBITRUM_VERTEX_TESTNET_GATEWAY_URL is not set"),
+            vertex_archive_url: env::var("VERTEX_ARCHIVE_URL").unwrap_or_else(|_| {
+                "https://archive.sepolia-test.vertexprotocol.com/v1".to_string()
+            }),
+            registry_refresh_secs: env::var("REGISTRY_REFRESH_SECS")
+                .ok()
+                .map(|v| v.parse().expect("REGISTRY_REFRESH_SECS must be an integer"))
+                .unwrap_or(60),
+            order_rounding_policy: env::var("ORDER_ROUNDING_POLICY")
+                .ok()
+                .map(|v| v.parse().expect("ORDER_ROUNDING_POLICY must be reject, nearest or passive"))
+                .unwrap_or_default(),
+            risk_limits_path: env::var("RISK_LIMITS_PATH").ok(),
+            subaccounts: env::var("SUBACCOUNTS")
+                .unwrap_or_else(|_| "default".to_string())
+                .split(',')
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect(),
+            dead_man_switch_timeout_secs: env::var("DEAD_MAN_SWITCH_TIMEOUT_SECS")
+                .ok()
+                .map(|v| v.parse().expect("DEAD_MAN_SWITCH_TIMEOUT_SECS must be an integer")),
+            journal_dir: env::var("JOURNAL_DIR")
+                .unwrap_or_else(|_| default_journal_dir.to_string()),
+            journal_segment_bytes: env::var("JOURNAL_SEGMENT_BYTES")
+                .ok()
+                .map(|v| v.parse().expect("JOURNAL_SEGMENT_BYTES must be an integer"))
+                .unwrap_or(64 * 1024 * 1024),
+            journal_fsync: env::var("JOURNAL_FSYNC")
+                .ok()
+                .map(|v| v.parse().expect("JOURNAL_FSYNC must be true or false"))
+                .unwrap_or(false),
+            reconcile_cancel_orphans: env::var("RECONCILE_CANCEL_ORPHANS")
+                .ok()
+                .map(|v| v.parse().expect("RECONCILE_CANCEL_ORPHANS must be true or false"))
+                .unwrap_or(false),
+            oms_max_terminal_orders: env::var("OMS_MAX_TERMINAL_ORDERS")
+                .ok()
+                .map(|v| v.parse().expect("OMS_MAX_TERMINAL_ORDERS must be an integer"))
+                .unwrap_or(10_000),
+            paper_trading,
+            recorder_dir: env::var("RECORDER_DIR").ok(),
+            recorder_max_file_bytes: env::var("RECORDER_MAX_FILE_BYTES")
+                .ok()
+                .map(|v| v.parse().expect("RECORDER_MAX_FILE_BYTES must be an integer"))
+                .unwrap_or(256 * 1024 * 1024),
+            recorder_rotate_secs: env::var("RECORDER_ROTATE_SECS")
+                .ok()
+                .map(|v| v.parse().expect("RECORDER_ROTATE_SECS must be an integer"))
+                .unwrap_or(3600),
+            replay_path,
+            replay_speed: env::var("REPLAY_SPEED")
+                .ok()
+                .map(|v| match v.parse::<f64>() {
+                    Ok(speed) if speed > 0.0 => speed,
+                    _ => panic!("REPLAY_SPEED must be a positive number"),
+                })
+                .unwrap_or(1.0),
+            backtest_data_dir: env::var("BACKTEST_DATA_DIR")
+                .ok()
+                .or_else(|| env::var("RECORDER_DIR").ok()),
+            dydx: DydxConfig::from_env(),
+            orderly: OrderlyConfig::from_env(),
+            router_config_path: env::var("ROUTER_CONFIG_PATH").ok(),
         }
     }
 }
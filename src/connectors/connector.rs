@@ -0,0 +1,63 @@
+use std::fmt;
+
+use futures::stream::BoxStream;
+
+use crate::{
+    domain::models::trading::{
+        CancelRequest, Instrument, MarketEvent, OrderAck, OrderRequest, Venue, VenueOrder,
+        VenuePosition,
+    },
+    shared::{errors::connector_error::ConnectorError, utils::decimal::Decimal},
+};
+
+pub type MarketEventStream = BoxStream<'static, MarketEvent>;
+
+/// Order entry, queries and streams of one venue behind venue-neutral models.
+///
+/// The service layer only talks to venues through this trait, so a new venue is a new
+/// implementation rather than a new set of services.
+#[tonic::async_trait]
+pub trait Connector: Send + Sync + fmt::Debug {
+    fn venue(&self) -> Venue;
+
+    // True when place_order already runs the shared pre-trade risk checks itself
+    fn checks_risk(&self) -> bool {
+        false
+    }
+
+    // Open the venue's market data and private streams
+    async fn connect(&self) -> Result<(), ConnectorError>;
+
+    async fn place_order(&self, order: &OrderRequest) -> Result<OrderAck, ConnectorError>;
+
+    async fn cancel_order(&self, cancel: &CancelRequest) -> Result<(), ConnectorError>;
+
+    // Replace a resting order; the new order is only sent once the cancel went through
+    async fn cancel_and_place(
+        &self,
+        cancel: &CancelRequest,
+        order: &OrderRequest,
+    ) -> Result<OrderAck, ConnectorError> {
+        self.cancel_order(cancel).await?;
+        self.place_order(order).await
+    }
+
+    // Cancel every resting order, on one instrument or on all of them
+    async fn cancel_all(&self, instrument: Option<&str>) -> Result<(), ConnectorError>;
+
+    async fn instruments(&self) -> Result<Vec<Instrument>, ConnectorError>;
+
+    // Resting orders as the venue reports them
+    async fn open_orders(
+        &self,
+        instrument: Option<&str>,
+    ) -> Result<Vec<VenueOrder>, ConnectorError>;
+
+    async fn positions(&self) -> Result<Vec<VenuePosition>, ConnectorError>;
+
+    // Collateral still free for new orders, in quote units; None when the venue doesn't say
+    async fn available_margin(&self) -> Result<Option<Decimal>, ConnectorError>;
+
+    // Normalized market data and private order events; lagging subscribers skip events
+    fn market_events(&self) -> MarketEventStream;
+}
@@ -0,0 +1,674 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use futures::stream;
+use log::warn;
+use prost_types::Any;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    config::DydxConfig,
+    connectors::connector::{Connector, MarketEventStream},
+    domain::models::{
+        dydx::{
+            indexer::{IndexerOrder, IndexerOrderStatus, IndexerSide, PerpetualMarket},
+            stream_events::{
+                OrderbookContents, StreamLevel, StreamMessage, SubaccountContents, TradesContents,
+            },
+        },
+        order_book::OrderBook,
+        trading::{
+            BookLevel, CancelRequest, Instrument, MarketEvent, OrderAck, OrderRequest, Side,
+            TimeInForce, Venue, VenueOrder, VenueOrderStatus, VenuePosition,
+        },
+    },
+    shared::{errors::connector_error::ConnectorError, utils::decimal::Decimal},
+};
+
+use super::{
+    rest_client::RestClient,
+    stream_client::StreamClient,
+    tx::{
+        self, AccountState, CancelGoodTil, GoodTil, OrderId, OrderSide, OrderTimeInForce,
+        SubaccountId, ORDER_FLAGS_LONG_TERM, ORDER_FLAGS_SHORT_TERM,
+    },
+    wallet::Wallet,
+};
+
+// Quote quantums are USDC with 6 decimals
+const QUOTE_ATOMIC_RESOLUTION: i32 = -6;
+// Cosmos SDK ErrWrongSequence
+const WRONG_SEQUENCE_CODE: u32 = 32;
+
+/// dYdX v4 connector: orders are signed Cosmos txs broadcast to a full node, while queries
+/// and streams go through the indexer.
+///
+/// IOC orders are short-term orders that live for a few blocks; GTC and post-only orders are
+/// stateful long-term orders that expire after `stateful_order_ttl_secs`.
+#[derive(Debug)]
+pub struct DydxConnector {
+    config: DydxConfig,
+    wallet: Wallet,
+    rest: RestClient,
+    stream_client: Arc<StreamClient>,
+    // Perpetual markets keyed by ticker, refreshed on connect
+    markets: RwLock<HashMap<String, PerpetualMarket>>,
+    // Cached account number and sequence, dropped whenever the chain disagrees
+    account: tokio::sync::Mutex<Option<AccountState>>,
+    next_client_id: AtomicU32,
+}
+
+impl DydxConnector {
+    pub fn new(config: &DydxConfig) -> Result<Self, ConnectorError> {
+        let wallet = Wallet::new(&config.private_key)?;
+        let stream_client = Arc::new(StreamClient::new(
+            &config.indexer_ws_url,
+            wallet.address(),
+            config.subaccount_number,
+        ));
+        // Seeded from the clock so ids don't repeat across restarts
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs() as u32;
+
+        Ok(DydxConnector {
+            rest: RestClient::new(&config.indexer_url, &config.validator_url),
+            config: config.clone(),
+            wallet,
+            stream_client,
+            markets: RwLock::new(HashMap::new()),
+            account: tokio::sync::Mutex::new(None),
+            next_client_id: AtomicU32::new(seed),
+        })
+    }
+
+    pub async fn load_markets(&self) -> Result<(), ConnectorError> {
+        let response = self.rest.perpetual_markets().await?;
+        *self.markets.write().unwrap() = response.markets;
+        Ok(())
+    }
+
+    async fn market(&self, ticker: &str) -> Result<PerpetualMarket, ConnectorError> {
+        if self.markets.read().unwrap().is_empty() {
+            self.load_markets().await?;
+        }
+        self.markets
+            .read()
+            .unwrap()
+            .get(ticker)
+            .cloned()
+            .ok_or_else(|| ConnectorError::InvalidRequest(format!("Unknown symbol: {}", ticker)))
+    }
+
+    fn subaccount_id(&self) -> SubaccountId {
+        SubaccountId {
+            owner: self.wallet.address().to_string(),
+            number: self.config.subaccount_number,
+        }
+    }
+
+    // Short-term orders and cancels expire at a block, stateful ones at a unix time
+    async fn short_term_good_til_block(&self) -> Result<u32, ConnectorError> {
+        let height = self.rest.height().await?;
+        Ok(height + self.config.short_term_blocks.min(20))
+    }
+
+    fn stateful_good_til_block_time(&self) -> u32 {
+        let expiry = SystemTime::now() + Duration::from_secs(self.config.stateful_order_ttl_secs);
+        expiry
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs() as u32
+    }
+
+    // Sign and broadcast one tx. Short-term messages skip the sequence check, so only stateful
+    // txs consume a sequence number.
+    async fn broadcast(
+        &self,
+        messages: Vec<Any>,
+        stateful: bool,
+    ) -> Result<String, ConnectorError> {
+        let mut account = self.account.lock().await;
+        let state = match *account {
+            Some(state) => state,
+            None => {
+                let base = self.rest.account(self.wallet.address()).await?;
+                AccountState {
+                    account_number: base.account_number,
+                    sequence: base.sequence,
+                }
+            }
+        };
+
+        let tx_bytes = tx::sign_tx(
+            &self.wallet,
+            messages,
+            &self.config.chain_id,
+            state,
+            self.config.gas_limit,
+        )?;
+        let response = self.rest.broadcast(&tx_bytes).await?;
+        match response.code {
+            0 => {
+                *account = Some(AccountState {
+                    sequence: state.sequence + stateful as u64,
+                    ..state
+                });
+                Ok(response.txhash)
+            }
+            WRONG_SEQUENCE_CODE => {
+                *account = None;
+                Err(ConnectorError::Unavailable(format!(
+                    "Account sequence out of date, retry: {}",
+                    response.raw_log
+                )))
+            }
+            _ => {
+                *account = Some(state);
+                Err(ConnectorError::Rejected(response.raw_log))
+            }
+        }
+    }
+
+    async fn cancel(
+        &self,
+        market: &PerpetualMarket,
+        client_id: u32,
+        order_flags: u32,
+    ) -> Result<(), ConnectorError> {
+        let order_id = OrderId {
+            subaccount_id: Some(self.subaccount_id()),
+            client_id,
+            order_flags,
+            clob_pair_id: market.clob_pair_id,
+        };
+        let stateful = order_flags != ORDER_FLAGS_SHORT_TERM;
+        let good_til = if stateful {
+            CancelGoodTil::BlockTime(self.stateful_good_til_block_time())
+        } else {
+            CancelGoodTil::Block(self.short_term_good_til_block().await?)
+        };
+        self.broadcast(vec![tx::cancel_order_msg(order_id, good_til)], stateful)
+            .await?;
+        Ok(())
+    }
+}
+
+// The chain identifies an order by client id and flags within a market and subaccount
+fn venue_order_id(client_id: u32, order_flags: u32) -> String {
+    format!("{}:{}", client_id, order_flags)
+}
+
+fn parse_venue_order_id(order_id: &str) -> Result<(u32, u32), ConnectorError> {
+    order_id
+        .split_once(':')
+        .and_then(|(client_id, flags)| Some((client_id.parse().ok()?, flags.parse().ok()?)))
+        .ok_or_else(|| ConnectorError::InvalidRequest(format!("Invalid order id: {}", order_id)))
+}
+
+// `value * 10^exponent` as an integer; None when that would drop digits or overflow
+fn scale_exact(value: Decimal, exponent: i32) -> Option<u64> {
+    let raw = value.to_x18();
+    let shift = exponent - 18;
+    let scaled = if shift >= 0 {
+        raw.checked_mul(10i128.checked_pow(shift as u32)?)?
+    } else {
+        let divisor = 10i128.checked_pow((-shift) as u32)?;
+        if raw % divisor != 0 {
+            return None;
+        }
+        raw / divisor
+    };
+    u64::try_from(scaled).ok()
+}
+
+fn to_quantums(market: &PerpetualMarket, size: Decimal) -> Result<u64, ConnectorError> {
+    scale_exact(size, -market.atomic_resolution)
+        .filter(|quantums| *quantums > 0 && quantums % market.step_base_quantums.max(1) == 0)
+        .ok_or_else(|| {
+            ConnectorError::InvalidRequest(format!(
+                "Size {} is not a multiple of step size {} on {}",
+                size, market.step_size, market.ticker
+            ))
+        })
+}
+
+fn to_subticks(market: &PerpetualMarket, price: Decimal) -> Result<u64, ConnectorError> {
+    let exponent =
+        market.atomic_resolution - market.quantum_conversion_exponent - QUOTE_ATOMIC_RESOLUTION;
+    scale_exact(price, exponent)
+        .filter(|subticks| *subticks > 0 && subticks % market.subticks_per_tick.max(1) == 0)
+        .ok_or_else(|| {
+            ConnectorError::InvalidRequest(format!(
+                "Price {} is not a multiple of tick size {} on {}",
+                price, market.tick_size, market.ticker
+            ))
+        })
+}
+
+fn side(side: IndexerSide) -> Side {
+    match side {
+        IndexerSide::Buy => Side::Buy,
+        IndexerSide::Sell => Side::Sell,
+    }
+}
+
+fn order_status(order: &IndexerOrder) -> VenueOrderStatus {
+    match order.status {
+        IndexerOrderStatus::Filled => VenueOrderStatus::Filled,
+        IndexerOrderStatus::Canceled | IndexerOrderStatus::BestEffortCanceled => {
+            VenueOrderStatus::Cancelled
+        }
+        _ if !order.total_filled.is_zero() => VenueOrderStatus::PartiallyFilled,
+        _ => VenueOrderStatus::Open,
+    }
+}
+
+fn remaining(order: &IndexerOrder) -> Decimal {
+    order
+        .size
+        .checked_sub(order.total_filled)
+        .unwrap_or_default()
+}
+
+fn book_levels(levels: &[StreamLevel]) -> Vec<BookLevel> {
+    levels
+        .iter()
+        .map(|level| {
+            let (price, quantity) = level.price_size();
+            BookLevel { price, quantity }
+        })
+        .collect()
+}
+
+// Indexer timestamps are ISO strings, so events are stamped on receipt instead
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_millis() as u64
+}
+
+// Per-stream state needed to normalize indexer messages
+#[derive(Default)]
+struct StreamState {
+    books: HashMap<String, OrderBook>,
+    // Indexer order uuid to venue order id, so fills can name their order
+    order_ids: HashMap<String, String>,
+}
+
+impl StreamState {
+    fn normalize(&mut self, message: StreamMessage) -> Vec<MarketEvent> {
+        let venue = Venue::Dydx;
+        let timestamp_ms = now_ms();
+        let snapshot = message.is_snapshot();
+        let id = message.id.unwrap_or_default();
+        let mut events = Vec::new();
+
+        match message.channel.as_deref() {
+            Some("v4_orderbook") => {
+                let Ok(contents) = serde_json::from_value::<OrderbookContents>(message.contents)
+                else {
+                    return events;
+                };
+                let bids = book_levels(&contents.bids);
+                let asks = book_levels(&contents.asks);
+                let book = self.books.entry(id.clone()).or_default();
+                book.apply(&bids, &asks, snapshot);
+                let top = book.best_bid().zip(book.best_ask());
+
+                events.push(MarketEvent::BookUpdate {
+                    venue,
+                    instrument: id.clone(),
+                    bids,
+                    asks,
+                    snapshot,
+                    timestamp_ms,
+                });
+                if let Some((bid, ask)) = top {
+                    events.push(MarketEvent::Quote {
+                        venue,
+                        instrument: id,
+                        bid_price: bid.price,
+                        bid_quantity: bid.quantity,
+                        ask_price: ask.price,
+                        ask_quantity: ask.quantity,
+                        timestamp_ms,
+                    });
+                }
+            }
+            // The subscribe snapshot replays recent trades, which were not just printed
+            Some("v4_trades") if !snapshot => {
+                let Ok(contents) = serde_json::from_value::<TradesContents>(message.contents)
+                else {
+                    return events;
+                };
+                events.extend(contents.trades.into_iter().map(|trade| MarketEvent::Trade {
+                    venue,
+                    instrument: id.clone(),
+                    price: trade.price,
+                    quantity: trade.size,
+                    taker_side: side(trade.side),
+                    timestamp_ms,
+                }));
+            }
+            Some("v4_subaccounts") => {
+                let Ok(contents) = serde_json::from_value::<SubaccountContents>(message.contents)
+                else {
+                    return events;
+                };
+                for order in contents.orders {
+                    let order_id = venue_order_id(order.client_id, order.order_flags);
+                    self.order_ids.insert(order.id.clone(), order_id.clone());
+                    events.push(MarketEvent::OrderUpdate {
+                        venue,
+                        instrument: order.ticker.clone(),
+                        order_id,
+                        status: order_status(&order),
+                        remaining: remaining(&order),
+                        timestamp_ms,
+                    });
+                }
+                for fill in contents.fills {
+                    // An order not seen on this stream yet keeps its indexer uuid, which at
+                    // least names it uniquely; a fill naming no order at all is unusable
+                    let Some(uuid) = fill.order_id else {
+                        warn!("Dropping dYdX fill on {} without an order id", fill.ticker);
+                        continue;
+                    };
+                    let order_id = self.order_ids.get(&uuid).cloned().unwrap_or(uuid);
+                    events.push(MarketEvent::Fill {
+                        venue,
+                        instrument: fill.ticker,
+                        order_id,
+                        side: side(fill.side),
+                        price: fill.price,
+                        quantity: fill.size,
+                        fee: fill.fee,
+                        timestamp_ms,
+                    });
+                }
+            }
+            _ => {}
+        }
+        events
+    }
+}
+
+#[tonic::async_trait]
+impl Connector for DydxConnector {
+    fn venue(&self) -> Venue {
+        Venue::Dydx
+    }
+
+    async fn connect(&self) -> Result<(), ConnectorError> {
+        self.load_markets().await?;
+        let tickers: Vec<String> = self.markets.read().unwrap().keys().cloned().collect();
+        self.stream_client
+            .start_subscription(&tickers)
+            .await
+            .map_err(|e| {
+                ConnectorError::Unavailable(format!("Failed to start dYdX subscription: {}", e))
+            })?;
+
+        let stream_client = Arc::clone(&self.stream_client);
+        tokio::spawn(async move {
+            loop {
+                stream_client.check_and_reconnect().await;
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+        Ok(())
+    }
+
+    async fn place_order(&self, order: &OrderRequest) -> Result<OrderAck, ConnectorError> {
+        let market = self.market(&order.instrument).await?;
+        let quantums = to_quantums(&market, order.quantity)?;
+        let subticks = to_subticks(&market, order.price)?;
+
+        let (order_flags, time_in_force, good_til) = match order.time_in_force {
+            TimeInForce::Ioc => (
+                ORDER_FLAGS_SHORT_TERM,
+                OrderTimeInForce::Ioc,
+                GoodTil::Block(self.short_term_good_til_block().await?),
+            ),
+            TimeInForce::Gtc => (
+                ORDER_FLAGS_LONG_TERM,
+                OrderTimeInForce::Unspecified,
+                GoodTil::BlockTime(self.stateful_good_til_block_time()),
+            ),
+            TimeInForce::PostOnly => (
+                ORDER_FLAGS_LONG_TERM,
+                OrderTimeInForce::PostOnly,
+                GoodTil::BlockTime(self.stateful_good_til_block_time()),
+            ),
+            TimeInForce::Fok => {
+                return Err(ConnectorError::Unsupported(
+                    "dYdX v4 does not support fill-or-kill orders".to_string(),
+                ))
+            }
+        };
+
+        // Numeric client order ids double as the chain client id
+        let client_id = order
+            .client_order_id
+            .as_deref()
+            .and_then(|id| id.parse().ok())
+            .unwrap_or_else(|| self.next_client_id.fetch_add(1, Ordering::Relaxed));
+
+        let chain_order = tx::Order {
+            order_id: Some(OrderId {
+                subaccount_id: Some(self.subaccount_id()),
+                client_id,
+                order_flags,
+                clob_pair_id: market.clob_pair_id,
+            }),
+            side: match order.side {
+                Side::Buy => OrderSide::Buy,
+                Side::Sell => OrderSide::Sell,
+            } as i32,
+            quantums,
+            subticks,
+            good_til: Some(good_til),
+            time_in_force: time_in_force as i32,
+            ..Default::default()
+        };
+
+        let stateful = order_flags != ORDER_FLAGS_SHORT_TERM;
+        self.broadcast(vec![tx::place_order_msg(chain_order)], stateful)
+            .await?;
+        Ok(OrderAck {
+            order_id: venue_order_id(client_id, order_flags),
+            local_id: None,
+            client_order_id: order.client_order_id.clone(),
+        })
+    }
+
+    async fn cancel_order(&self, cancel: &CancelRequest) -> Result<(), ConnectorError> {
+        let market = self.market(&cancel.instrument).await?;
+        let (client_id, order_flags) = parse_venue_order_id(&cancel.order_id)?;
+        self.cancel(&market, client_id, order_flags).await
+    }
+
+    // The chain has no cancel-all message, so every open order is cancelled on its own
+    async fn cancel_all(&self, instrument: Option<&str>) -> Result<(), ConnectorError> {
+        let orders = self
+            .rest
+            .open_orders(self.wallet.address(), self.config.subaccount_number, instrument)
+            .await?;
+
+        let mut first_error = None;
+        for order in orders {
+            let result = match self.market(&order.ticker).await {
+                Ok(market) => self.cancel(&market, order.client_id, order.order_flags).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                warn!("Failed to cancel dYdX order {}: {}", order.id, e);
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    async fn instruments(&self) -> Result<Vec<Instrument>, ConnectorError> {
+        self.load_markets().await?;
+        Ok(self
+            .markets
+            .read()
+            .unwrap()
+            .values()
+            .filter(|market| market.status == "ACTIVE")
+            .map(|market| Instrument {
+                venue: Venue::Dydx,
+                symbol: market.ticker.clone(),
+                tick_size: market.tick_size,
+                step_size: market.step_size,
+                // The smallest order is one step
+                min_size: market.step_size,
+            })
+            .collect())
+    }
+
+    async fn open_orders(
+        &self,
+        instrument: Option<&str>,
+    ) -> Result<Vec<VenueOrder>, ConnectorError> {
+        let orders = self
+            .rest
+            .open_orders(self.wallet.address(), self.config.subaccount_number, instrument)
+            .await?;
+        Ok(orders
+            .into_iter()
+            .map(|order| VenueOrder {
+                order_id: venue_order_id(order.client_id, order.order_flags),
+                side: side(order.side),
+                price: order.price,
+                quantity: order.size,
+                remaining: remaining(&order),
+                status: order_status(&order),
+                instrument: order.ticker,
+            })
+            .collect())
+    }
+
+    async fn positions(&self) -> Result<Vec<VenuePosition>, ConnectorError> {
+        let subaccount = self
+            .rest
+            .subaccount(self.wallet.address(), self.config.subaccount_number)
+            .await?;
+        Ok(subaccount
+            .open_perpetual_positions
+            .into_values()
+            .filter(|position| !position.size.is_zero())
+            .map(|position| VenuePosition {
+                instrument: position.market,
+                amount: position.size,
+                entry_price: Some(position.entry_price),
+            })
+            .collect())
+    }
+
+    async fn available_margin(&self) -> Result<Option<Decimal>, ConnectorError> {
+        let subaccount = self
+            .rest
+            .subaccount(self.wallet.address(), self.config.subaccount_number)
+            .await?;
+        Ok(Some(subaccount.free_collateral))
+    }
+
+    fn market_events(&self) -> MarketEventStream {
+        let messages = self.stream_client.subscribe_events();
+        Box::pin(stream::unfold(
+            (messages, StreamState::default(), VecDeque::new()),
+            |(mut messages, mut state, mut pending)| async move {
+                loop {
+                    if let Some(event) = pending.pop_front() {
+                        return Some((event, (messages, state, pending)));
+                    }
+                    match messages.recv().await {
+                        Ok(message) => pending.extend(state.normalize(message)),
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("dYdX market event stream lagged, skipped {}", skipped)
+                        }
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn subaccount_message(contents: serde_json::Value) -> StreamMessage {
+        serde_json::from_value(json!({
+            "type": "channel_data",
+            "channel": "v4_subaccounts",
+            "id": "dydx1abc/0",
+            "contents": contents,
+        }))
+        .unwrap()
+    }
+
+    fn fill(order_id: Option<&str>) -> serde_json::Value {
+        json!({
+            "side": "BUY",
+            "size": "0.1",
+            "price": "60000",
+            "fee": "0.3",
+            "orderId": order_id,
+            "ticker": "BTC-USD",
+        })
+    }
+
+    fn fill_order_ids(events: &[MarketEvent]) -> Vec<String> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                MarketEvent::Fill { order_id, .. } => Some(order_id.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fills_name_the_venue_order_id_once_the_order_was_seen() {
+        let mut state = StreamState::default();
+        let events = state.normalize(subaccount_message(json!({
+            "orders": [{
+                "id": "uuid-1",
+                "clientId": "42",
+                "orderFlags": "64",
+                "ticker": "BTC-USD",
+                "side": "BUY",
+                "size": "0.1",
+                "totalFilled": "0.1",
+                "price": "60000",
+                "status": "FILLED",
+            }],
+            "fills": [fill(Some("uuid-1"))],
+        })));
+        assert_eq!(fill_order_ids(&events), vec![venue_order_id(42, 64)]);
+    }
+
+    #[test]
+    fn fills_for_unseen_orders_keep_the_indexer_id_and_fills_without_one_are_dropped() {
+        let mut state = StreamState::default();
+        let events = state.normalize(subaccount_message(json!({
+            "fills": [fill(Some("uuid-2")), fill(None)],
+        })));
+        assert_eq!(fill_order_ids(&events), vec!["uuid-2".to_string()]);
+    }
+}
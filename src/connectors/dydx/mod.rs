@@ -0,0 +1,5 @@
+pub mod connector;
+pub mod rest_client;
+pub mod stream_client;
+pub mod tx;
+pub mod wallet;
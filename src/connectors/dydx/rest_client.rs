@@ -0,0 +1,324 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use crate::{
+    domain::models::dydx::indexer::{
+        AccountResponse, BaseAccount, BroadcastResponse, HeightResponse, IndexerOrder,
+        PerpetualMarketsResponse, Subaccount, SubaccountResponse, TxResponse,
+    },
+    shared::errors::connector_error::ConnectorError,
+};
+
+/// REST access to the dYdX indexer for queries and to a full node for accounts and broadcasts.
+///
+/// Both base URLs come from config, so tests can point the client at a local mock indexer.
+#[derive(Debug, Clone)]
+pub struct RestClient {
+    http: reqwest::Client,
+    indexer_url: String,
+    validator_url: String,
+}
+
+impl RestClient {
+    pub fn new(indexer_url: &str, validator_url: &str) -> Self {
+        RestClient {
+            http: reqwest::Client::new(),
+            indexer_url: indexer_url.trim_end_matches('/').to_string(),
+            validator_url: validator_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, ConnectorError> {
+        let response = self
+            .http
+            .get(url)
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| ConnectorError::Unavailable(format!("GET {} failed: {}", url, e)))?;
+        read_json(url, response).await
+    }
+
+    pub async fn height(&self) -> Result<u32, ConnectorError> {
+        let url = format!("{}/v4/height", self.indexer_url);
+        let response: HeightResponse = self.get(&url, &[]).await?;
+        Ok(response.height)
+    }
+
+    pub async fn perpetual_markets(&self) -> Result<PerpetualMarketsResponse, ConnectorError> {
+        let url = format!("{}/v4/perpetualMarkets", self.indexer_url);
+        self.get(&url, &[]).await
+    }
+
+    // Orders the indexer still considers open, optionally on one market
+    pub async fn open_orders(
+        &self,
+        address: &str,
+        subaccount_number: u32,
+        ticker: Option<&str>,
+    ) -> Result<Vec<IndexerOrder>, ConnectorError> {
+        let url = format!("{}/v4/orders", self.indexer_url);
+        let mut query = vec![
+            ("address", address.to_string()),
+            ("subaccountNumber", subaccount_number.to_string()),
+            ("status", "OPEN".to_string()),
+        ];
+        if let Some(ticker) = ticker {
+            query.push(("ticker", ticker.to_string()));
+        }
+        self.get(&url, &query).await
+    }
+
+    pub async fn subaccount(
+        &self,
+        address: &str,
+        subaccount_number: u32,
+    ) -> Result<Subaccount, ConnectorError> {
+        let url = format!(
+            "{}/v4/addresses/{}/subaccountNumber/{}",
+            self.indexer_url, address, subaccount_number
+        );
+        let response: SubaccountResponse = self.get(&url, &[]).await?;
+        Ok(response.subaccount)
+    }
+
+    pub async fn account(&self, address: &str) -> Result<BaseAccount, ConnectorError> {
+        let url = format!(
+            "{}/cosmos/auth/v1beta1/accounts/{}",
+            self.validator_url, address
+        );
+        let response: AccountResponse = self.get(&url, &[]).await?;
+        Ok(response.account)
+    }
+
+    // Sync mode returns once CheckTx passed, before the tx is in a block
+    pub async fn broadcast(&self, tx_bytes: &[u8]) -> Result<TxResponse, ConnectorError> {
+        let url = format!("{}/cosmos/tx/v1beta1/txs", self.validator_url);
+        let body = json!({
+            "tx_bytes": STANDARD.encode(tx_bytes),
+            "mode": "BROADCAST_MODE_SYNC",
+        });
+        let response = self
+            .http
+            .post(&url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .map_err(|e| ConnectorError::Unavailable(format!("POST {} failed: {}", url, e)))?;
+        let response: BroadcastResponse = read_json(&url, response).await?;
+        Ok(response.tx_response)
+    }
+}
+
+async fn read_json<T: DeserializeOwned>(
+    url: &str,
+    response: reqwest::Response,
+) -> Result<T, ConnectorError> {
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| ConnectorError::Unavailable(format!("Failed to read {}: {}", url, e)))?;
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Err(ConnectorError::NotFound(format!("{}: {}", url, body)));
+    }
+    if !status.is_success() {
+        return Err(ConnectorError::Unavailable(format!(
+            "{} returned {}: {}",
+            url, status, body
+        )));
+    }
+    serde_json::from_str(&body)
+        .map_err(|e| ConnectorError::Internal(format!("Failed to parse {}: {}", url, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use axum::{
+        extract::{Path, Query},
+        http::StatusCode,
+        routing::{get, post},
+        Json, Router,
+    };
+    use serde_json::Value;
+
+    use super::*;
+    use crate::shared::utils::decimal::Decimal;
+
+    const ADDRESS: &str = "dydx1test";
+
+    // Serves `app` on a free local port and returns its base url
+    async fn serve(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}/", addr)
+    }
+
+    fn mock_indexer() -> Router {
+        Router::new()
+            .route(
+                "/v4/height",
+                get(|| async { Json(json!({ "height": "1234" })) }),
+            )
+            .route(
+                "/v4/perpetualMarkets",
+                get(|| async {
+                    Json(json!({ "markets": { "BTC-USD": {
+                        "ticker": "BTC-USD",
+                        "clobPairId": "0",
+                        "status": "ACTIVE",
+                        "tickSize": "1",
+                        "stepSize": "0.0001",
+                        "atomicResolution": -10,
+                        "quantumConversionExponent": -9,
+                        "stepBaseQuantums": 1000000,
+                        "subticksPerTick": 100000
+                    } } }))
+                }),
+            )
+            .route(
+                "/v4/orders",
+                get(|Query(query): Query<HashMap<String, String>>| async move {
+                    // Only open orders of the requested subaccount and market
+                    let expected = [
+                        ("address", ADDRESS),
+                        ("subaccountNumber", "0"),
+                        ("status", "OPEN"),
+                        ("ticker", "BTC-USD"),
+                    ];
+                    if expected
+                        .iter()
+                        .any(|(key, value)| query.get(*key).map(String::as_str) != Some(*value))
+                    {
+                        return Json(json!([]));
+                    }
+                    Json(json!([{
+                        "id": "uuid-1",
+                        "clientId": "42",
+                        "orderFlags": "64",
+                        "ticker": "BTC-USD",
+                        "side": "BUY",
+                        "size": "0.5",
+                        "totalFilled": "0.1",
+                        "price": "60000",
+                        "status": "OPEN"
+                    }]))
+                }),
+            )
+            .route(
+                "/v4/addresses/:address/subaccountNumber/:number",
+                get(|Path((address, number)): Path<(String, u32)>| async move {
+                    if address != ADDRESS || number != 0 {
+                        return Err((StatusCode::NOT_FOUND, "no such subaccount"));
+                    }
+                    Ok(Json(json!({ "subaccount": {
+                        "equity": "2000",
+                        "freeCollateral": "1500.25",
+                        "openPerpetualPositions": { "BTC-USD": {
+                            "market": "BTC-USD",
+                            "size": "-0.2",
+                            "entryPrice": "61000"
+                        } }
+                    } })))
+                }),
+            )
+            .route(
+                "/cosmos/auth/v1beta1/accounts/:address",
+                get(|| async {
+                    Json(json!({ "account": { "account_number": "7", "sequence": 3 } }))
+                }),
+            )
+            .route(
+                "/cosmos/tx/v1beta1/txs",
+                post(|Json(body): Json<Value>| async move {
+                    let code = if body["mode"] == "BROADCAST_MODE_SYNC"
+                        && body["tx_bytes"] == STANDARD.encode(b"signed tx")
+                    {
+                        0
+                    } else {
+                        1
+                    };
+                    Json(json!({ "tx_response": { "txhash": "ABC", "code": code } }))
+                }),
+            )
+            .route(
+                "/v4/broken",
+                get(|| async { (StatusCode::INTERNAL_SERVER_ERROR, "boom") }),
+            )
+    }
+
+    #[tokio::test]
+    async fn reads_indexer_queries() {
+        let url = serve(mock_indexer()).await;
+        let client = RestClient::new(&url, &url);
+
+        assert_eq!(client.height().await.unwrap(), 1234);
+
+        let markets = client.perpetual_markets().await.unwrap();
+        let btc = &markets.markets["BTC-USD"];
+        assert_eq!(btc.clob_pair_id, 0);
+        assert_eq!(btc.atomic_resolution, -10);
+        assert_eq!(btc.subticks_per_tick, 100_000);
+
+        let orders = client
+            .open_orders(ADDRESS, 0, Some("BTC-USD"))
+            .await
+            .unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].client_id, 42);
+        assert_eq!(orders[0].total_filled, "0.1".parse::<Decimal>().unwrap());
+
+        let subaccount = client.subaccount(ADDRESS, 0).await.unwrap();
+        assert_eq!(
+            subaccount.free_collateral,
+            "1500.25".parse::<Decimal>().unwrap()
+        );
+        assert_eq!(
+            subaccount.open_perpetual_positions["BTC-USD"].size,
+            "-0.2".parse::<Decimal>().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn reads_accounts_and_broadcasts_from_the_node() {
+        let url = serve(mock_indexer()).await;
+        let client = RestClient::new(&url, &url);
+
+        let account = client.account(ADDRESS).await.unwrap();
+        assert_eq!((account.account_number, account.sequence), (7, 3));
+
+        let response = client.broadcast(b"signed tx").await.unwrap();
+        assert_eq!(response.txhash, "ABC");
+        assert_eq!(response.code, 0);
+    }
+
+    #[tokio::test]
+    async fn maps_http_errors() {
+        let url = serve(mock_indexer()).await;
+        let client = RestClient::new(&url, &url);
+
+        assert!(matches!(
+            client.subaccount("dydx1other", 0).await,
+            Err(ConnectorError::NotFound(_))
+        ));
+        assert!(matches!(
+            client.get::<Value>(&format!("{}v4/broken", url), &[]).await,
+            Err(ConnectorError::Unavailable(_))
+        ));
+        // Nothing listens on the discard port
+        let offline = RestClient::new("http://127.0.0.1:9", "http://127.0.0.1:9");
+        assert!(matches!(
+            offline.height().await,
+            Err(ConnectorError::Unavailable(_))
+        ));
+    }
+}
@@ -0,0 +1,234 @@
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use futures_util::stream::SplitStream;
+use futures_util::{SinkExt, StreamExt};
+use log::{error, info};
+use serde_json::json;
+use tokio::net::TcpStream;
+use tokio::sync::broadcast;
+use tokio_tungstenite::{tungstenite::protocol::Message, MaybeTlsStream, WebSocketStream};
+
+use crate::domain::models::dydx::stream_events::StreamMessage;
+use crate::shared::utils::websocket_utils::connect_websocket;
+
+// Buffered messages per subscriber before slow consumers start lagging
+const EVENT_CHANNEL_CAPACITY: usize = 4096;
+
+/// Indexer websocket client: orderbook and trades per market plus one subaccount channel.
+#[derive(Debug)]
+pub struct StreamClient {
+    ws_url: String,
+    // "address/number" as the subaccounts channel expects it
+    subaccount_id: String,
+    needs_reconnect: Arc<AtomicBool>,
+    events: broadcast::Sender<StreamMessage>,
+    tickers: Mutex<Vec<String>>,
+}
+
+impl StreamClient {
+    pub fn new(ws_url: &str, address: &str, subaccount_number: u32) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        StreamClient {
+            ws_url: ws_url.to_string(),
+            subaccount_id: format!("{}/{}", address, subaccount_number),
+            needs_reconnect: Arc::new(AtomicBool::new(false)),
+            events,
+            tickers: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn subscribe_events(&self) -> broadcast::Receiver<StreamMessage> {
+        self.events.subscribe()
+    }
+
+    pub async fn start_subscription(
+        &self,
+        tickers: &[String],
+    ) -> Result<(), Box<dyn Error + Send>> {
+        *self.tickers.lock().unwrap() = tickers.to_vec();
+        self.open_stream().await
+    }
+
+    // Connect, subscribe to every channel and spawn the reader task
+    async fn open_stream(&self) -> Result<(), Box<dyn Error + Send>> {
+        let ws_stream = connect_websocket(&self.ws_url).await?;
+        let (mut ws_writer, ws_reader) = ws_stream.split();
+
+        let tickers = self.tickers.lock().unwrap().clone();
+        for subscribe in subscription_messages(&tickers, &self.subaccount_id) {
+            ws_writer
+                .send(Message::Text(subscribe.to_string()))
+                .await
+                .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+        }
+
+        // The indexer pings us and tungstenite answers while reading, so no ping task is needed.
+        // The writer is kept alive with the reader so the connection isn't half-closed.
+        let events = self.events.clone();
+        let needs_reconnect = Arc::clone(&self.needs_reconnect);
+        tokio::spawn(async move {
+            forward_stream_messages(ws_reader, events).await;
+            drop(ws_writer);
+            needs_reconnect.store(true, Ordering::Relaxed);
+        });
+
+        Ok(())
+    }
+
+    pub async fn check_and_reconnect(&self) {
+        if self.needs_reconnect.load(Ordering::Relaxed) {
+            match self.open_stream().await {
+                Ok(()) => self.needs_reconnect.store(false, Ordering::Relaxed),
+                Err(e) => error!("Failed to reconnect dYdX stream: {}", e),
+            }
+        }
+    }
+}
+
+fn subscription_messages(tickers: &[String], subaccount_id: &str) -> Vec<serde_json::Value> {
+    let mut messages = Vec::new();
+    for ticker in tickers {
+        for channel in ["v4_orderbook", "v4_trades"] {
+            messages.push(json!({ "type": "subscribe", "channel": channel, "id": ticker }));
+        }
+    }
+    messages.push(json!({
+        "type": "subscribe",
+        "channel": "v4_subaccounts",
+        "id": subaccount_id
+    }));
+    messages
+}
+
+async fn forward_stream_messages(
+    mut ws_reader: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+    events: broadcast::Sender<StreamMessage>,
+) {
+    while let Some(message) = ws_reader.next().await {
+        match message {
+            Ok(Message::Text(text)) => match serde_json::from_str::<StreamMessage>(&text) {
+                Ok(message) if message.channel.is_some() => {
+                    let _ = events.send(message);
+                }
+                // "connected", "unsubscribed" and errors carry no channel data
+                Ok(_) | Err(_) => info!("Received dYdX message: {}", text),
+            },
+            Ok(Message::Close(_)) => break,
+            Ok(_) => {}
+            Err(e) => {
+                error!("Error receiving dYdX message: {:?}", e);
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::{net::TcpListener, sync::mpsc};
+    use tokio_tungstenite::accept_async;
+
+    use super::*;
+
+    const ADDRESS: &str = "dydx1test";
+
+    // Mock indexer socket: reports each connection's subscriptions, then pushes `frames` and
+    // closes the connection
+    async fn mock_indexer(frames: Vec<String>) -> (String, mpsc::UnboundedReceiver<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (subscriptions_tx, subscriptions) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let mut ws = accept_async(stream).await.unwrap();
+                // Two market channels for the one ticker plus the subaccount channel
+                let mut subscribed = Vec::new();
+                while subscribed.len() < 3 {
+                    if let Some(Ok(Message::Text(text))) = ws.next().await {
+                        subscribed.push(text);
+                    }
+                }
+                subscriptions_tx.send(subscribed).unwrap();
+                for frame in &frames {
+                    ws.send(Message::Text(frame.clone())).await.unwrap();
+                }
+                ws.close(None).await.unwrap();
+            }
+        });
+        (url, subscriptions)
+    }
+
+    #[tokio::test]
+    async fn subscribes_and_forwards_channel_data() {
+        let frames = vec![
+            json!({ "type": "connected", "connection_id": "c1" }).to_string(),
+            json!({
+                "type": "channel_data",
+                "channel": "v4_orderbook",
+                "id": "BTC-USD",
+                "contents": { "bids": [["60000", "1.5"]] }
+            })
+            .to_string(),
+        ];
+        let (url, mut subscriptions) = mock_indexer(frames).await;
+        let client = StreamClient::new(&url, ADDRESS, 0);
+        let mut events = client.subscribe_events();
+        client
+            .start_subscription(&["BTC-USD".to_string()])
+            .await
+            .unwrap();
+
+        let subscribed: Vec<serde_json::Value> = subscriptions
+            .recv()
+            .await
+            .unwrap()
+            .iter()
+            .map(|text| serde_json::from_str(text).unwrap())
+            .collect();
+        assert_eq!(
+            subscribed,
+            vec![
+                json!({ "type": "subscribe", "channel": "v4_orderbook", "id": "BTC-USD" }),
+                json!({ "type": "subscribe", "channel": "v4_trades", "id": "BTC-USD" }),
+                json!({ "type": "subscribe", "channel": "v4_subaccounts", "id": "dydx1test/0" }),
+            ]
+        );
+
+        // "connected" carries no channel and is not forwarded
+        let message = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(message.channel.as_deref(), Some("v4_orderbook"));
+        assert_eq!(message.id.as_deref(), Some("BTC-USD"));
+        assert!(!message.is_snapshot());
+    }
+
+    #[tokio::test]
+    async fn reconnects_and_resubscribes_after_the_indexer_closes() {
+        let (url, mut subscriptions) = mock_indexer(Vec::new()).await;
+        let client = StreamClient::new(&url, ADDRESS, 0);
+        client
+            .start_subscription(&["BTC-USD".to_string()])
+            .await
+            .unwrap();
+        subscriptions.recv().await.unwrap();
+
+        // The reader notices the close and flags the stream
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !client.needs_reconnect.load(Ordering::Relaxed) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        client.check_and_reconnect().await;
+        let resubscribed = subscriptions.recv().await.unwrap();
+        assert_eq!(resubscribed.len(), 3);
+    }
+}
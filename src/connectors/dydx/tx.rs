@@ -0,0 +1,271 @@
+use prost::Message;
+use prost_types::Any;
+
+use crate::shared::errors::connector_error::ConnectorError;
+
+use super::wallet::Wallet;
+
+// Hand-written subsets of the dYdX and Cosmos SDK protobufs, enough to sign and broadcast
+// order placement and cancellation with SIGN_MODE_DIRECT.
+
+pub const MSG_PLACE_ORDER_TYPE_URL: &str = "/dydxprotocol.clob.MsgPlaceOrder";
+pub const MSG_CANCEL_ORDER_TYPE_URL: &str = "/dydxprotocol.clob.MsgCancelOrder";
+const SECP256K1_PUB_KEY_TYPE_URL: &str = "/cosmos.crypto.secp256k1.PubKey";
+const SIGN_MODE_DIRECT: i32 = 1;
+
+// Order flags select how the chain stores the order
+pub const ORDER_FLAGS_SHORT_TERM: u32 = 0;
+pub const ORDER_FLAGS_LONG_TERM: u32 = 64;
+
+#[derive(Clone, PartialEq, Message)]
+pub struct SubaccountId {
+    #[prost(string, tag = "1")]
+    pub owner: String,
+    #[prost(uint32, tag = "2")]
+    pub number: u32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct OrderId {
+    #[prost(message, optional, tag = "1")]
+    pub subaccount_id: Option<SubaccountId>,
+    #[prost(fixed32, tag = "2")]
+    pub client_id: u32,
+    #[prost(uint32, tag = "3")]
+    pub order_flags: u32,
+    #[prost(uint32, tag = "4")]
+    pub clob_pair_id: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, prost::Enumeration)]
+#[repr(i32)]
+pub enum OrderSide {
+    Unspecified = 0,
+    Buy = 1,
+    Sell = 2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, prost::Enumeration)]
+#[repr(i32)]
+pub enum OrderTimeInForce {
+    Unspecified = 0,
+    Ioc = 1,
+    PostOnly = 2,
+    FillOrKill = 3,
+}
+
+// Short-term orders expire at a block height, stateful orders at a unix time
+#[derive(Clone, PartialEq, prost::Oneof)]
+pub enum GoodTil {
+    #[prost(uint32, tag = "5")]
+    Block(u32),
+    #[prost(fixed32, tag = "6")]
+    BlockTime(u32),
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Order {
+    #[prost(message, optional, tag = "1")]
+    pub order_id: Option<OrderId>,
+    #[prost(enumeration = "OrderSide", tag = "2")]
+    pub side: i32,
+    #[prost(uint64, tag = "3")]
+    pub quantums: u64,
+    #[prost(uint64, tag = "4")]
+    pub subticks: u64,
+    #[prost(oneof = "GoodTil", tags = "5, 6")]
+    pub good_til: Option<GoodTil>,
+    #[prost(enumeration = "OrderTimeInForce", tag = "7")]
+    pub time_in_force: i32,
+    #[prost(bool, tag = "8")]
+    pub reduce_only: bool,
+    #[prost(uint32, tag = "9")]
+    pub client_metadata: u32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgPlaceOrder {
+    #[prost(message, optional, tag = "1")]
+    pub order: Option<Order>,
+}
+
+// Same oneof as `GoodTil`, but MsgCancelOrder numbers the fields 2 and 3
+#[derive(Clone, PartialEq, prost::Oneof)]
+pub enum CancelGoodTil {
+    #[prost(uint32, tag = "2")]
+    Block(u32),
+    #[prost(fixed32, tag = "3")]
+    BlockTime(u32),
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgCancelOrder {
+    #[prost(message, optional, tag = "1")]
+    pub order_id: Option<OrderId>,
+    #[prost(oneof = "CancelGoodTil", tags = "2, 3")]
+    pub good_til: Option<CancelGoodTil>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct TxBody {
+    #[prost(message, repeated, tag = "1")]
+    messages: Vec<Any>,
+    #[prost(string, tag = "2")]
+    memo: String,
+    #[prost(uint64, tag = "3")]
+    timeout_height: u64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct PubKey {
+    #[prost(bytes = "vec", tag = "1")]
+    key: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Single {
+    #[prost(int32, tag = "1")]
+    mode: i32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ModeInfo {
+    #[prost(message, optional, tag = "1")]
+    single: Option<Single>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct SignerInfo {
+    #[prost(message, optional, tag = "1")]
+    public_key: Option<Any>,
+    #[prost(message, optional, tag = "2")]
+    mode_info: Option<ModeInfo>,
+    #[prost(uint64, tag = "3")]
+    sequence: u64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Coin {
+    #[prost(string, tag = "1")]
+    denom: String,
+    #[prost(string, tag = "2")]
+    amount: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Fee {
+    #[prost(message, repeated, tag = "1")]
+    amount: Vec<Coin>,
+    #[prost(uint64, tag = "2")]
+    gas_limit: u64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct AuthInfo {
+    #[prost(message, repeated, tag = "1")]
+    signer_infos: Vec<SignerInfo>,
+    #[prost(message, optional, tag = "2")]
+    fee: Option<Fee>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct SignDoc {
+    #[prost(bytes = "vec", tag = "1")]
+    body_bytes: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    auth_info_bytes: Vec<u8>,
+    #[prost(string, tag = "3")]
+    chain_id: String,
+    #[prost(uint64, tag = "4")]
+    account_number: u64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct TxRaw {
+    #[prost(bytes = "vec", tag = "1")]
+    body_bytes: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    auth_info_bytes: Vec<u8>,
+    #[prost(bytes = "vec", repeated, tag = "3")]
+    signatures: Vec<Vec<u8>>,
+}
+
+pub fn place_order_msg(order: Order) -> Any {
+    Any {
+        type_url: MSG_PLACE_ORDER_TYPE_URL.to_string(),
+        value: MsgPlaceOrder { order: Some(order) }.encode_to_vec(),
+    }
+}
+
+pub fn cancel_order_msg(order_id: OrderId, good_til: CancelGoodTil) -> Any {
+    Any {
+        type_url: MSG_CANCEL_ORDER_TYPE_URL.to_string(),
+        value: MsgCancelOrder {
+            order_id: Some(order_id),
+            good_til: Some(good_til),
+        }
+        .encode_to_vec(),
+    }
+}
+
+// Signer account state the chain checks every tx against
+#[derive(Debug, Clone, Copy)]
+pub struct AccountState {
+    pub account_number: u64,
+    pub sequence: u64,
+}
+
+// Sign `messages` in one tx and return the encoded TxRaw, ready to broadcast.
+// dYdX charges no gas for clob messages, so the fee only carries the gas limit.
+pub fn sign_tx(
+    wallet: &Wallet,
+    messages: Vec<Any>,
+    chain_id: &str,
+    account: AccountState,
+    gas_limit: u64,
+) -> Result<Vec<u8>, ConnectorError> {
+    let body_bytes = TxBody {
+        messages,
+        ..Default::default()
+    }
+    .encode_to_vec();
+
+    let public_key = Any {
+        type_url: SECP256K1_PUB_KEY_TYPE_URL.to_string(),
+        value: PubKey {
+            key: wallet.public_key().to_vec(),
+        }
+        .encode_to_vec(),
+    };
+    let auth_info_bytes = AuthInfo {
+        signer_infos: vec![SignerInfo {
+            public_key: Some(public_key),
+            mode_info: Some(ModeInfo {
+                single: Some(Single {
+                    mode: SIGN_MODE_DIRECT,
+                }),
+            }),
+            sequence: account.sequence,
+        }],
+        fee: Some(Fee {
+            amount: Vec::new(),
+            gas_limit,
+        }),
+    }
+    .encode_to_vec();
+
+    let sign_doc = SignDoc {
+        body_bytes: body_bytes.clone(),
+        auth_info_bytes: auth_info_bytes.clone(),
+        chain_id: chain_id.to_string(),
+        account_number: account.account_number,
+    };
+    let signature = wallet.sign(&sign_doc.encode_to_vec())?;
+
+    Ok(TxRaw {
+        body_bytes,
+        auth_info_bytes,
+        signatures: vec![signature],
+    }
+    .encode_to_vec())
+}
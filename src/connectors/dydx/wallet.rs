@@ -0,0 +1,71 @@
+use bech32::{ToBase32, Variant};
+use ethsign::{Protected, SecretKey};
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+
+use crate::shared::errors::connector_error::ConnectorError;
+
+// dYdX chain addresses are bech32 with this prefix
+const ADDRESS_PREFIX: &str = "dydx";
+
+/// secp256k1 account key for signing Cosmos transactions on the dYdX chain.
+///
+/// Uses the same curve as the Vertex signer, but Cosmos signs the SHA-256 of the sign doc
+/// and identifies accounts by the compressed public key.
+pub struct Wallet {
+    secret_key: SecretKey,
+    public_key: [u8; 33],
+    address: String,
+}
+
+impl std::fmt::Debug for Wallet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Wallet")
+            .field("address", &self.address)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Wallet {
+    pub fn new(private_key: &str) -> Result<Self, ConnectorError> {
+        let key_bytes = hex::decode(private_key.trim_start_matches("0x"))
+            .map_err(|e| ConnectorError::Internal(format!("Invalid dYdX private key: {}", e)))?;
+        let protected_key = Protected::new(key_bytes);
+        let secret_key = SecretKey::from_raw(protected_key.as_ref())
+            .map_err(|e| ConnectorError::Internal(format!("Invalid dYdX private key: {}", e)))?;
+
+        // Uncompressed x || y, compressed to a parity prefix and x
+        let uncompressed = secret_key.public().bytes().to_vec();
+        let mut public_key = [0u8; 33];
+        public_key[0] = if uncompressed[63] % 2 == 0 { 0x02 } else { 0x03 };
+        public_key[1..].copy_from_slice(&uncompressed[..32]);
+
+        let key_hash = Ripemd160::digest(Sha256::digest(public_key));
+        let address = bech32::encode(ADDRESS_PREFIX, key_hash.to_base32(), Variant::Bech32)
+            .map_err(|e| ConnectorError::Internal(format!("Failed to encode address: {}", e)))?;
+
+        Ok(Wallet {
+            secret_key,
+            public_key,
+            address,
+        })
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub fn public_key(&self) -> &[u8; 33] {
+        &self.public_key
+    }
+
+    // Compact r || s signature over SHA-256 of the sign doc bytes
+    pub fn sign(&self, sign_doc: &[u8]) -> Result<Vec<u8>, ConnectorError> {
+        let hash = Sha256::digest(sign_doc);
+        let signature = self
+            .secret_key
+            .sign(&hash)
+            .map_err(|e| ConnectorError::Internal(format!("Failed to sign tx: {}", e)))?;
+        Ok([signature.r.as_ref(), signature.s.as_ref()].concat())
+    }
+}
@@ -0,0 +1,679 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use futures::StreamExt;
+
+use crate::{
+    domain::{
+        models::trading::{
+            CancelRequest, Instrument, MarketEvent, OrderAck, OrderRequest, Venue, VenueOrder,
+            VenueOrderStatus, VenuePosition,
+        },
+        risk::engine::{OrderCheck, Reservation, RiskEngine},
+    },
+    services::vertex::kill_switch::KillSwitch,
+    shared::{errors::connector_error::ConnectorError, utils::decimal::Decimal},
+};
+
+use super::connector::{Connector, MarketEventStream};
+
+// What the guard knows about a venue: risk ids, quoted mids and the orders it has placed
+#[derive(Debug, Default)]
+struct GuardState {
+    ids: HashMap<String, u32>,
+    mids: HashMap<String, Decimal>,
+    // Orders whose exposure is held under their venue id
+    orders: HashSet<String>,
+    // Sends still waiting for the venue's ack
+    in_flight: usize,
+    // Events for unknown orders seen while sends were in flight, held until the acks tell
+    // whether they belong to one of them
+    early: Vec<ExposureEvent>,
+}
+
+#[derive(Debug)]
+enum ExposureEvent {
+    Fill {
+        order_id: String,
+        product_id: u32,
+        amount: Decimal,
+    },
+    Closed {
+        order_id: String,
+    },
+}
+
+impl ExposureEvent {
+    fn order_id(&self) -> &str {
+        match self {
+            ExposureEvent::Fill { order_id, .. } | ExposureEvent::Closed { order_id } => order_id,
+        }
+    }
+}
+
+impl GuardState {
+    // The risk engine counts exposure per product id; other venues get ids in first-use order.
+    // Their limits go by instrument symbol, so these ids never leave the process
+    fn id(&mut self, instrument: &str) -> u32 {
+        let next = self.ids.len() as u32 + 1;
+        *self.ids.entry(instrument.to_string()).or_insert(next)
+    }
+
+    fn on_event(&mut self, event: ExposureEvent, risk_engine: &RiskEngine, sender: &str) {
+        if self.in_flight > 0 && !self.orders.contains(event.order_id()) {
+            self.early.push(event);
+        } else {
+            self.apply(event, risk_engine, sender);
+        }
+    }
+
+    fn apply(&mut self, event: ExposureEvent, risk_engine: &RiskEngine, sender: &str) {
+        match event {
+            ExposureEvent::Fill {
+                order_id,
+                product_id,
+                amount,
+            } => risk_engine.on_fill(sender, &order_id, product_id, amount),
+            ExposureEvent::Closed { order_id } => {
+                self.orders.remove(&order_id);
+                risk_engine.on_order_closed(sender, &order_id);
+            }
+        }
+    }
+
+    // A send was answered, with the order's id when it was accepted. Its early events apply
+    // now, and everything still held once no send is left in flight was for other orders
+    fn answered(&mut self, order_id: Option<&str>, risk_engine: &RiskEngine, sender: &str) {
+        self.in_flight -= 1;
+        if let Some(order_id) = order_id {
+            self.orders.insert(order_id.to_string());
+        }
+        let early = std::mem::take(&mut self.early);
+        for event in early {
+            if self.in_flight == 0 || Some(event.order_id()) == order_id {
+                self.apply(event, risk_engine, sender);
+            } else {
+                self.early.push(event);
+            }
+        }
+    }
+}
+
+// A checked order on its way to the venue. Dropping it unanswered, as when the caller goes
+// away mid-send, releases its exposure rather than holding it and the early events forever
+struct PendingOrder<'a> {
+    guard: &'a GuardedConnector,
+    reservation: Option<Reservation>,
+}
+
+impl PendingOrder<'_> {
+    // Confirm the reservation under the accepted order's id, or release it
+    fn finish(&mut self, order_id: Option<&str>) {
+        let Some(reservation) = self.reservation.take() else {
+            return;
+        };
+        let guard = self.guard;
+        let mut state = guard.state.lock().unwrap();
+        match order_id {
+            Some(order_id) => {
+                guard
+                    .risk_engine
+                    .confirm_reservation(&guard.sender, reservation, order_id)
+            }
+            None => guard
+                .risk_engine
+                .release_reservation(&guard.sender, reservation),
+        }
+        state.answered(order_id, &guard.risk_engine, &guard.sender);
+    }
+}
+
+impl Drop for PendingOrder<'_> {
+    fn drop(&mut self) {
+        self.finish(None);
+    }
+}
+
+/// Kill switch and pre-trade risk in front of a venue's connector.
+///
+/// Every connector is wrapped on registration, so orders from clients, the router, strategies
+/// and algos all stop when the kill switch is engaged. Venues whose own order path already runs
+/// the risk engine (`Connector::checks_risk`) are only held to the kill switch; for the others
+/// exposure is counted under the venue name as sender, kept current from the connector's order
+/// and fill events, and the quoted mid serves as the price band reference. Fills and updates
+/// that beat an order's ack are held until the ack names the order.
+pub struct GuardedConnector {
+    inner: Arc<dyn Connector>,
+    kill_switch: Arc<KillSwitch>,
+    risk_engine: Arc<RiskEngine>,
+    sender: String,
+    state: Arc<Mutex<GuardState>>,
+}
+
+impl fmt::Debug for GuardedConnector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GuardedConnector")
+            .field("venue", &self.inner.venue())
+            .finish()
+    }
+}
+
+impl GuardedConnector {
+    pub fn new(
+        inner: Arc<dyn Connector>,
+        kill_switch: Arc<KillSwitch>,
+        risk_engine: Arc<RiskEngine>,
+    ) -> Self {
+        let guard = GuardedConnector {
+            sender: inner.venue().to_string(),
+            inner,
+            kill_switch,
+            risk_engine,
+            state: Arc::new(Mutex::new(GuardState::default())),
+        };
+        if !guard.inner.checks_risk() {
+            guard.spawn_exposure_tracker();
+        }
+        guard
+    }
+
+    // Fills move positions and shrink resting orders, terminal updates release them
+    fn spawn_exposure_tracker(&self) {
+        let mut events = self.inner.market_events();
+        let risk_engine = Arc::clone(&self.risk_engine);
+        let state = Arc::clone(&self.state);
+        let sender = self.sender.clone();
+        tokio::spawn(async move {
+            while let Some(event) = events.next().await {
+                let mut state = state.lock().unwrap();
+                match event {
+                    MarketEvent::Quote {
+                        instrument,
+                        bid_price,
+                        ask_price,
+                        ..
+                    } => {
+                        let mid = bid_price
+                            .checked_add(ask_price)
+                            .and_then(|sum| sum.checked_div(Decimal::from_int(2)));
+                        if let Some(mid) = mid {
+                            state.mids.insert(instrument, mid);
+                        }
+                    }
+                    MarketEvent::Fill {
+                        instrument,
+                        order_id,
+                        side,
+                        quantity,
+                        ..
+                    } => {
+                        let product_id = state.id(&instrument);
+                        let fill = ExposureEvent::Fill {
+                            order_id,
+                            product_id,
+                            amount: side.signed(quantity),
+                        };
+                        state.on_event(fill, &risk_engine, &sender);
+                    }
+                    MarketEvent::OrderUpdate {
+                        order_id, status, ..
+                    } => {
+                        if matches!(
+                            status,
+                            VenueOrderStatus::Filled
+                                | VenueOrderStatus::Cancelled
+                                | VenueOrderStatus::Rejected
+                        ) {
+                            state.on_event(
+                                ExposureEvent::Closed { order_id },
+                                &risk_engine,
+                                &sender,
+                            );
+                        }
+                    }
+                    MarketEvent::Trade { .. } | MarketEvent::BookUpdate { .. } => {}
+                }
+            }
+        });
+    }
+
+    // The venue confirmed the order is gone
+    fn close(&self, order_id: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.apply(
+            ExposureEvent::Closed {
+                order_id: order_id.to_string(),
+            },
+            &self.risk_engine,
+            &self.sender,
+        );
+    }
+
+    fn check_kill_switch(&self) -> Result<(), ConnectorError> {
+        if self.kill_switch.is_engaged() {
+            return Err(ConnectorError::Rejected(format!(
+                "Kill switch engaged: {}",
+                self.kill_switch.reason()
+            )));
+        }
+        Ok(())
+    }
+
+    // Check and reserve the order's exposure, then send it; the reservation follows the answer
+    async fn place_checked(&self, order: &OrderRequest) -> Result<OrderAck, ConnectorError> {
+        let reservation = {
+            let mut state = self.state.lock().unwrap();
+            let product_id = state.id(&order.instrument);
+            let reservation = self
+                .risk_engine
+                .check_order(&OrderCheck {
+                    sender: &self.sender,
+                    product_id,
+                    instrument: Some(&order.instrument),
+                    price: order.price,
+                    amount: order.side.signed(order.quantity),
+                    reference_price: state.mids.get(&order.instrument).copied(),
+                })
+                .map_err(|v| ConnectorError::Rejected(format!("Risk check failed: {}", v)))?;
+            state.in_flight += 1;
+            reservation
+        };
+
+        let mut pending = PendingOrder {
+            guard: self,
+            reservation: Some(reservation),
+        };
+        let result = self.inner.place_order(order).await;
+        pending.finish(result.as_ref().ok().map(|ack| ack.order_id.as_str()));
+        result
+    }
+}
+
+#[tonic::async_trait]
+impl Connector for GuardedConnector {
+    fn venue(&self) -> Venue {
+        self.inner.venue()
+    }
+
+    fn checks_risk(&self) -> bool {
+        true
+    }
+
+    async fn connect(&self) -> Result<(), ConnectorError> {
+        self.inner.connect().await
+    }
+
+    async fn place_order(&self, order: &OrderRequest) -> Result<OrderAck, ConnectorError> {
+        self.check_kill_switch()?;
+        if self.inner.checks_risk() {
+            return self.inner.place_order(order).await;
+        }
+        self.place_checked(order).await
+    }
+
+    async fn cancel_order(&self, cancel: &CancelRequest) -> Result<(), ConnectorError> {
+        self.inner.cancel_order(cancel).await?;
+        if !self.inner.checks_risk() {
+            self.close(&cancel.order_id);
+        }
+        Ok(())
+    }
+
+    async fn cancel_and_place(
+        &self,
+        cancel: &CancelRequest,
+        order: &OrderRequest,
+    ) -> Result<OrderAck, ConnectorError> {
+        self.check_kill_switch()?;
+        if self.inner.checks_risk() {
+            return self.inner.cancel_and_place(cancel, order).await;
+        }
+        // Venues the guard checks have no atomic replace, so the legs go out one by one. The
+        // cancelled order stops counting as soon as the cancel went through, whatever becomes of
+        // the new one, which is checked without it
+        self.cancel_order(cancel).await?;
+        self.place_checked(order).await
+    }
+
+    async fn cancel_all(&self, instrument: Option<&str>) -> Result<(), ConnectorError> {
+        self.inner.cancel_all(instrument).await?;
+        if !self.inner.checks_risk() {
+            let product_ids = match instrument {
+                Some(instrument) => vec![self.state.lock().unwrap().id(instrument)],
+                None => Vec::new(),
+            };
+            self.risk_engine
+                .on_products_cancelled(&self.sender, &product_ids);
+        }
+        Ok(())
+    }
+
+    async fn instruments(&self) -> Result<Vec<Instrument>, ConnectorError> {
+        self.inner.instruments().await
+    }
+
+    async fn open_orders(
+        &self,
+        instrument: Option<&str>,
+    ) -> Result<Vec<VenueOrder>, ConnectorError> {
+        self.inner.open_orders(instrument).await
+    }
+
+    async fn positions(&self) -> Result<Vec<VenuePosition>, ConnectorError> {
+        self.inner.positions().await
+    }
+
+    async fn available_margin(&self) -> Result<Option<Decimal>, ConnectorError> {
+        self.inner.available_margin().await
+    }
+
+    fn market_events(&self) -> MarketEventStream {
+        self.inner.market_events()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicBool, AtomicU64, Ordering},
+        time::Duration,
+    };
+
+    use futures::stream;
+    use tokio::sync::broadcast;
+
+    use super::*;
+    use crate::domain::{
+        models::trading::{Side, TimeInForce},
+        risk::limits::{RiskConfig, RiskLimits},
+    };
+
+    // Accepts every order unless told to fail, numbering them
+    #[derive(Debug)]
+    struct StubConnector {
+        checks_risk: bool,
+        fail: AtomicBool,
+        // Fill every order on the event stream before acking it
+        fill_before_ack: bool,
+        placed: AtomicU64,
+        events: broadcast::Sender<MarketEvent>,
+    }
+
+    impl Default for StubConnector {
+        fn default() -> Self {
+            StubConnector {
+                checks_risk: false,
+                fail: AtomicBool::new(false),
+                fill_before_ack: false,
+                placed: AtomicU64::new(0),
+                events: broadcast::channel(16).0,
+            }
+        }
+    }
+
+    #[tonic::async_trait]
+    impl Connector for StubConnector {
+        fn venue(&self) -> Venue {
+            Venue::Dydx
+        }
+
+        fn checks_risk(&self) -> bool {
+            self.checks_risk
+        }
+
+        async fn connect(&self) -> Result<(), ConnectorError> {
+            Ok(())
+        }
+
+        async fn place_order(&self, order: &OrderRequest) -> Result<OrderAck, ConnectorError> {
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(ConnectorError::Unavailable("down".to_string()));
+            }
+            let id = self.placed.fetch_add(1, Ordering::SeqCst) + 1;
+            let order_id = format!("order-{}", id);
+            if self.fill_before_ack {
+                let _ = self.events.send(MarketEvent::Fill {
+                    venue: Venue::Dydx,
+                    instrument: order.instrument.clone(),
+                    order_id: order_id.clone(),
+                    side: order.side,
+                    price: order.price,
+                    quantity: order.quantity,
+                    fee: Decimal::ZERO,
+                    timestamp_ms: 0,
+                });
+                let _ = self.events.send(MarketEvent::OrderUpdate {
+                    venue: Venue::Dydx,
+                    instrument: order.instrument.clone(),
+                    order_id: order_id.clone(),
+                    status: VenueOrderStatus::Filled,
+                    remaining: Decimal::ZERO,
+                    timestamp_ms: 0,
+                });
+                // Let the guard see both before the ack
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            Ok(OrderAck {
+                order_id,
+                local_id: None,
+                client_order_id: order.client_order_id.clone(),
+            })
+        }
+
+        async fn cancel_order(&self, _cancel: &CancelRequest) -> Result<(), ConnectorError> {
+            Ok(())
+        }
+
+        async fn cancel_all(&self, _instrument: Option<&str>) -> Result<(), ConnectorError> {
+            Ok(())
+        }
+
+        async fn instruments(&self) -> Result<Vec<Instrument>, ConnectorError> {
+            Ok(Vec::new())
+        }
+
+        async fn open_orders(
+            &self,
+            _instrument: Option<&str>,
+        ) -> Result<Vec<VenueOrder>, ConnectorError> {
+            Ok(Vec::new())
+        }
+
+        async fn positions(&self) -> Result<Vec<VenuePosition>, ConnectorError> {
+            Ok(Vec::new())
+        }
+
+        async fn available_margin(&self) -> Result<Option<Decimal>, ConnectorError> {
+            Ok(None)
+        }
+
+        fn market_events(&self) -> MarketEventStream {
+            Box::pin(stream::unfold(
+                self.events.subscribe(),
+                |mut events| async move {
+                    let event = events.recv().await.ok()?;
+                    Some((event, events))
+                },
+            ))
+        }
+    }
+
+    fn one_open_order() -> RiskLimits {
+        RiskLimits {
+            max_open_orders: Some(1),
+            ..RiskLimits::default()
+        }
+    }
+
+    fn guard_with(
+        stub: StubConnector,
+        limits: RiskLimits,
+    ) -> (GuardedConnector, Arc<KillSwitch>, Arc<RiskEngine>) {
+        let kill_switch = Arc::new(KillSwitch::new());
+        let risk_engine = Arc::new(RiskEngine::new(RiskConfig {
+            subaccounts: HashMap::from([("dydx".to_string(), limits)]),
+            ..RiskConfig::default()
+        }));
+        let guard = GuardedConnector::new(
+            Arc::new(stub),
+            Arc::clone(&kill_switch),
+            Arc::clone(&risk_engine),
+        );
+        (guard, kill_switch, risk_engine)
+    }
+
+    fn guard(stub: StubConnector) -> (GuardedConnector, Arc<KillSwitch>) {
+        let (guard, kill_switch, _) = guard_with(stub, one_open_order());
+        (guard, kill_switch)
+    }
+
+    fn order() -> OrderRequest {
+        OrderRequest {
+            instrument: "BTC-USD".to_string(),
+            side: Side::Buy,
+            price: Decimal::from_int(100),
+            quantity: Decimal::from_int(1),
+            time_in_force: TimeInForce::Gtc,
+            client_order_id: None,
+        }
+    }
+
+    fn cancel(order_id: String) -> CancelRequest {
+        CancelRequest {
+            instrument: "BTC-USD".to_string(),
+            order_id,
+        }
+    }
+
+    #[tokio::test]
+    async fn engaged_kill_switch_blocks_orders_but_not_cancels() {
+        let (guard, kill_switch) = guard(StubConnector::default());
+        kill_switch.engage("test");
+
+        assert!(matches!(
+            guard.place_order(&order()).await,
+            Err(ConnectorError::Rejected(_))
+        ));
+        guard.cancel_all(None).await.unwrap();
+
+        kill_switch.release();
+        guard.place_order(&order()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn resting_orders_count_against_limits_until_cancelled() {
+        let (guard, _) = guard(StubConnector::default());
+
+        let ack = guard.place_order(&order()).await.unwrap();
+        assert!(matches!(
+            guard.place_order(&order()).await,
+            Err(ConnectorError::Rejected(_))
+        ));
+
+        guard.cancel_order(&cancel(ack.order_id)).await.unwrap();
+        guard.place_order(&order()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_orders_release_their_exposure() {
+        let (guard, _) = guard(StubConnector {
+            fail: AtomicBool::new(true),
+            ..StubConnector::default()
+        });
+
+        for _ in 0..2 {
+            assert!(matches!(
+                guard.place_order(&order()).await,
+                Err(ConnectorError::Unavailable(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn venues_that_check_risk_themselves_are_not_counted_twice() {
+        let (guard, _) = guard(StubConnector {
+            checks_risk: true,
+            ..StubConnector::default()
+        });
+
+        guard.place_order(&order()).await.unwrap();
+        guard.place_order(&order()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn events_that_beat_the_ack_apply_once_it_names_the_order() {
+        let (guard, _, risk_engine) = guard_with(
+            StubConnector {
+                fill_before_ack: true,
+                ..StubConnector::default()
+            },
+            one_open_order(),
+        );
+
+        // The first order filled and closed before its ack, so it no longer rests
+        guard.place_order(&order()).await.unwrap();
+        guard.place_order(&order()).await.unwrap();
+        assert_eq!(risk_engine.position("dydx", 1), Decimal::from_int(2));
+    }
+
+    #[tokio::test]
+    async fn a_replace_whose_new_order_fails_still_closes_the_cancelled_one() {
+        let stub = Arc::new(StubConnector::default());
+        let risk_engine = Arc::new(RiskEngine::new(RiskConfig {
+            default: one_open_order(),
+            ..RiskConfig::default()
+        }));
+        let guard = GuardedConnector::new(
+            Arc::clone(&stub) as Arc<dyn Connector>,
+            Arc::new(KillSwitch::new()),
+            risk_engine,
+        );
+
+        let ack = guard.place_order(&order()).await.unwrap();
+        stub.fail.store(true, Ordering::SeqCst);
+        assert!(matches!(
+            guard
+                .cancel_and_place(&cancel(ack.order_id), &order())
+                .await,
+            Err(ConnectorError::Unavailable(_))
+        ));
+
+        stub.fail.store(false, Ordering::SeqCst);
+        guard.place_order(&order()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn position_caps_go_by_instrument_symbol() {
+        let (guard, _, _) = guard_with(
+            StubConnector::default(),
+            RiskLimits {
+                max_position: HashMap::from([(1, Decimal::from_int(1))]),
+                max_instrument_position: HashMap::from([(
+                    "ETH-USD".to_string(),
+                    Decimal::from_int(1),
+                )]),
+                ..RiskLimits::default()
+            },
+        );
+
+        // BTC-USD is local product 1, but ids are not what the limits file means
+        let btc = OrderRequest {
+            quantity: Decimal::from_int(5),
+            ..order()
+        };
+        guard.place_order(&btc).await.unwrap();
+
+        let eth = OrderRequest {
+            instrument: "ETH-USD".to_string(),
+            quantity: Decimal::from_int(2),
+            ..order()
+        };
+        assert!(matches!(
+            guard.place_order(&eth).await,
+            Err(ConnectorError::Rejected(_))
+        ));
+    }
+}
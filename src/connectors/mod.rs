@@ -1,3 +1,6 @@
+pub mod connector;
 pub mod dydx;
+pub mod guard;
 pub mod orderly;
+pub mod registry;
 pub mod vertex;
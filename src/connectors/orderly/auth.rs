@@ -0,0 +1,85 @@
+use base64::{engine::general_purpose::URL_SAFE, Engine};
+use ed25519_dalek::{Signer as _, SigningKey};
+
+use crate::shared::errors::connector_error::ConnectorError;
+
+const KEY_PREFIX: &str = "ed25519:";
+
+/// Orderly key: an ed25519 key registered to the account, signing every private request.
+pub struct OrderlyAuth {
+    account_id: String,
+    signing_key: SigningKey,
+    // "ed25519:<base58 public key>" as sent in the orderly-key header
+    orderly_key: String,
+}
+
+impl std::fmt::Debug for OrderlyAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OrderlyAuth")
+            .field("account_id", &self.account_id)
+            .field("orderly_key", &self.orderly_key)
+            .finish_non_exhaustive()
+    }
+}
+
+// Headers of one signed REST request
+#[derive(Debug, Clone)]
+pub struct SignedHeaders {
+    pub timestamp: String,
+    pub account_id: String,
+    pub orderly_key: String,
+    pub signature: String,
+}
+
+impl OrderlyAuth {
+    pub fn new(account_id: &str, secret_key: &str) -> Result<Self, ConnectorError> {
+        let encoded = secret_key.trim_start_matches(KEY_PREFIX);
+        let bytes = bs58::decode(encoded)
+            .into_vec()
+            .map_err(|e| ConnectorError::Internal(format!("Invalid Orderly secret: {}", e)))?;
+        // Some exports carry the 64-byte keypair, whose first half is the seed
+        let seed: [u8; 32] = bytes
+            .get(..32)
+            .and_then(|seed| seed.try_into().ok())
+            .ok_or_else(|| ConnectorError::Internal("Orderly secret is too short".to_string()))?;
+
+        let signing_key = SigningKey::from_bytes(&seed);
+        let public_key = bs58::encode(signing_key.verifying_key().to_bytes()).into_string();
+        Ok(OrderlyAuth {
+            account_id: account_id.to_string(),
+            signing_key,
+            orderly_key: format!("{}{}", KEY_PREFIX, public_key),
+        })
+    }
+
+    pub fn account_id(&self) -> &str {
+        &self.account_id
+    }
+
+    pub fn orderly_key(&self) -> &str {
+        &self.orderly_key
+    }
+
+    // base64url ed25519 signature over the message
+    pub fn sign(&self, message: &str) -> String {
+        URL_SAFE.encode(self.signing_key.sign(message.as_bytes()).to_bytes())
+    }
+
+    // REST requests sign timestamp + method + path with query + body
+    pub fn sign_request(
+        &self,
+        timestamp_ms: u64,
+        method: &str,
+        path_with_query: &str,
+        body: &str,
+    ) -> SignedHeaders {
+        let timestamp = timestamp_ms.to_string();
+        let message = format!("{}{}{}{}", timestamp, method, path_with_query, body);
+        SignedHeaders {
+            signature: self.sign(&message),
+            timestamp,
+            account_id: self.account_id.clone(),
+            orderly_key: self.orderly_key.clone(),
+        }
+    }
+}
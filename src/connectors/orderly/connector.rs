@@ -0,0 +1,358 @@
+use std::{collections::VecDeque, sync::Arc, time::Duration};
+
+use futures::stream;
+use log::warn;
+use serde_json::json;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    config::OrderlyConfig,
+    connectors::connector::{Connector, MarketEventStream},
+    domain::models::{
+        orderly::{
+            rest::{OrderlyOrderStatus, OrderlySide},
+            stream_events::{BboData, ExecutionReport, OrderbookData, StreamMessage, TradeData},
+        },
+        trading::{
+            BookLevel, CancelRequest, Instrument, MarketEvent, OrderAck, OrderRequest, Side,
+            TimeInForce, Venue, VenueOrder, VenueOrderStatus, VenuePosition,
+        },
+    },
+    shared::{errors::connector_error::ConnectorError, utils::decimal::Decimal},
+};
+
+use super::{auth::OrderlyAuth, rest_client::RestClient, stream_client::StreamClient};
+
+/// Orderly Network connector: signed REST order entry and queries, public market data and
+/// private execution reports over websocket.
+#[derive(Debug)]
+pub struct OrderlyConnector {
+    rest: RestClient,
+    stream_client: Arc<StreamClient>,
+}
+
+impl OrderlyConnector {
+    pub fn new(config: &OrderlyConfig) -> Result<Self, ConnectorError> {
+        let auth = Arc::new(OrderlyAuth::new(&config.account_id, &config.secret_key)?);
+        Ok(OrderlyConnector {
+            rest: RestClient::new(&config.rest_url, Arc::clone(&auth)),
+            stream_client: Arc::new(StreamClient::new(
+                &config.ws_public_url,
+                &config.ws_private_url,
+                auth,
+            )),
+        })
+    }
+}
+
+fn order_type(time_in_force: TimeInForce) -> &'static str {
+    match time_in_force {
+        TimeInForce::Gtc => "LIMIT",
+        TimeInForce::Ioc => "IOC",
+        TimeInForce::Fok => "FOK",
+        TimeInForce::PostOnly => "POST_ONLY",
+    }
+}
+
+// Orderly takes prices and quantities as JSON numbers, which go out as doubles; a value that
+// does not survive that exactly is refused rather than rounded
+fn json_number(name: &str, value: Decimal) -> Result<serde_json::Value, ConnectorError> {
+    value
+        .to_string()
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+        .filter(|number| number.to_string().parse::<Decimal>().ok() == Some(value))
+        .map(serde_json::Value::Number)
+        .ok_or_else(|| {
+            ConnectorError::InvalidRequest(format!(
+                "{} {} cannot be sent as an exact JSON number",
+                name, value
+            ))
+        })
+}
+
+fn side(side: OrderlySide) -> Side {
+    match side {
+        OrderlySide::Buy => Side::Buy,
+        OrderlySide::Sell => Side::Sell,
+    }
+}
+
+fn order_status(status: OrderlyOrderStatus) -> VenueOrderStatus {
+    match status {
+        OrderlyOrderStatus::New => VenueOrderStatus::Open,
+        OrderlyOrderStatus::PartialFilled => VenueOrderStatus::PartiallyFilled,
+        OrderlyOrderStatus::Filled => VenueOrderStatus::Filled,
+        OrderlyOrderStatus::Cancelled => VenueOrderStatus::Cancelled,
+        OrderlyOrderStatus::Rejected => VenueOrderStatus::Rejected,
+    }
+}
+
+fn book_levels(levels: &[[Decimal; 2]]) -> Vec<BookLevel> {
+    levels
+        .iter()
+        .map(|[price, quantity]| BookLevel {
+            price: *price,
+            quantity: *quantity,
+        })
+        .collect()
+}
+
+fn parse_order_id(order_id: &str) -> Result<u64, ConnectorError> {
+    order_id
+        .parse()
+        .map_err(|_| ConnectorError::InvalidRequest(format!("Invalid order id: {}", order_id)))
+}
+
+// Map a stream message onto the venue-neutral model; unparseable data is dropped
+fn to_market_events(message: StreamMessage) -> Vec<MarketEvent> {
+    let venue = Venue::Orderly;
+    let topic = message.topic.unwrap_or_default();
+    let data = message.data;
+    let mut events = Vec::new();
+
+    match topic
+        .split_once('@')
+        .map(|(_, kind)| kind)
+        .unwrap_or(&topic)
+    {
+        kind @ ("orderbook" | "orderbookupdate") => {
+            if let Ok(book) = serde_json::from_value::<OrderbookData>(data) {
+                events.push(MarketEvent::BookUpdate {
+                    venue,
+                    bids: book_levels(&book.bids),
+                    asks: book_levels(&book.asks),
+                    instrument: book.symbol,
+                    snapshot: kind == "orderbook",
+                    timestamp_ms: message.ts,
+                });
+            }
+        }
+        "bbo" => {
+            if let Ok(bbo) = serde_json::from_value::<BboData>(data) {
+                events.push(MarketEvent::Quote {
+                    venue,
+                    instrument: bbo.symbol,
+                    bid_price: bbo.bid,
+                    bid_quantity: bbo.bid_size,
+                    ask_price: bbo.ask,
+                    ask_quantity: bbo.ask_size,
+                    timestamp_ms: message.ts,
+                });
+            }
+        }
+        "trade" => {
+            if let Ok(trade) = serde_json::from_value::<TradeData>(data) {
+                events.push(MarketEvent::Trade {
+                    venue,
+                    instrument: trade.symbol,
+                    price: trade.price,
+                    quantity: trade.size,
+                    taker_side: side(trade.side),
+                    timestamp_ms: message.ts,
+                });
+            }
+        }
+        "executionreport" => {
+            if let Ok(report) = serde_json::from_value::<ExecutionReport>(data) {
+                let order_id = report.order_id.to_string();
+                if !report.executed_quantity.is_zero() {
+                    events.push(MarketEvent::Fill {
+                        venue,
+                        instrument: report.symbol.clone(),
+                        order_id: order_id.clone(),
+                        side: side(report.side),
+                        price: report.executed_price,
+                        quantity: report.executed_quantity,
+                        fee: report.fee,
+                        timestamp_ms: report.timestamp,
+                    });
+                }
+                events.push(MarketEvent::OrderUpdate {
+                    venue,
+                    instrument: report.symbol,
+                    order_id,
+                    status: order_status(report.status),
+                    remaining: report
+                        .quantity
+                        .checked_sub(report.total_executed_quantity)
+                        .unwrap_or_default(),
+                    timestamp_ms: report.timestamp,
+                });
+            }
+        }
+        _ => {}
+    }
+    events
+}
+
+#[tonic::async_trait]
+impl Connector for OrderlyConnector {
+    fn venue(&self) -> Venue {
+        Venue::Orderly
+    }
+
+    async fn connect(&self) -> Result<(), ConnectorError> {
+        let symbols: Vec<String> = self
+            .rest
+            .symbols()
+            .await?
+            .into_iter()
+            .map(|info| info.symbol)
+            .collect();
+        self.stream_client
+            .start_subscription(&symbols)
+            .await
+            .map_err(|e| {
+                ConnectorError::Unavailable(format!("Failed to start Orderly subscription: {}", e))
+            })?;
+
+        let stream_client = Arc::clone(&self.stream_client);
+        tokio::spawn(async move {
+            loop {
+                stream_client.check_and_reconnect().await;
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+        Ok(())
+    }
+
+    async fn place_order(&self, order: &OrderRequest) -> Result<OrderAck, ConnectorError> {
+        let mut body = json!({
+            "symbol": order.instrument,
+            "order_type": order_type(order.time_in_force),
+            "order_price": json_number("price", order.price)?,
+            "order_quantity": json_number("quantity", order.quantity)?,
+            "side": match order.side {
+                Side::Buy => "BUY",
+                Side::Sell => "SELL",
+            },
+        });
+        if let Some(client_order_id) = &order.client_order_id {
+            body["client_order_id"] = json!(client_order_id);
+        }
+
+        let placed = self.rest.place_order(&body).await?;
+        Ok(OrderAck {
+            order_id: placed.order_id.to_string(),
+            local_id: None,
+            client_order_id: order.client_order_id.clone(),
+        })
+    }
+
+    async fn cancel_order(&self, cancel: &CancelRequest) -> Result<(), ConnectorError> {
+        let order_id = parse_order_id(&cancel.order_id)?;
+        self.rest.cancel_order(&cancel.instrument, order_id).await
+    }
+
+    async fn cancel_all(&self, instrument: Option<&str>) -> Result<(), ConnectorError> {
+        self.rest.cancel_all(instrument).await
+    }
+
+    async fn instruments(&self) -> Result<Vec<Instrument>, ConnectorError> {
+        Ok(self
+            .rest
+            .symbols()
+            .await?
+            .into_iter()
+            .map(|info| Instrument {
+                venue: Venue::Orderly,
+                symbol: info.symbol,
+                tick_size: info.quote_tick,
+                step_size: info.base_tick,
+                min_size: info.base_min,
+            })
+            .collect())
+    }
+
+    async fn open_orders(
+        &self,
+        instrument: Option<&str>,
+    ) -> Result<Vec<VenueOrder>, ConnectorError> {
+        Ok(self
+            .rest
+            .open_orders(instrument)
+            .await?
+            .into_iter()
+            .map(|order| VenueOrder {
+                order_id: order.order_id.to_string(),
+                side: side(order.side),
+                price: order.price.unwrap_or_default(),
+                quantity: order.quantity,
+                remaining: order
+                    .quantity
+                    .checked_sub(order.executed)
+                    .unwrap_or_default(),
+                status: order_status(order.status),
+                instrument: order.symbol,
+            })
+            .collect())
+    }
+
+    async fn positions(&self) -> Result<Vec<VenuePosition>, ConnectorError> {
+        Ok(self
+            .rest
+            .positions()
+            .await?
+            .rows
+            .into_iter()
+            .filter(|position| !position.position_qty.is_zero())
+            .map(|position| VenuePosition {
+                instrument: position.symbol,
+                amount: position.position_qty,
+                entry_price: Some(position.average_open_price),
+            })
+            .collect())
+    }
+
+    async fn available_margin(&self) -> Result<Option<Decimal>, ConnectorError> {
+        Ok(Some(self.rest.positions().await?.free_collateral))
+    }
+
+    fn market_events(&self) -> MarketEventStream {
+        let messages = self.stream_client.subscribe_events();
+        Box::pin(stream::unfold(
+            (messages, VecDeque::new()),
+            |(mut messages, mut pending)| async move {
+                loop {
+                    if let Some(event) = pending.pop_front() {
+                        return Some((event, (messages, pending)));
+                    }
+                    match messages.recv().await {
+                        Ok(message) => pending.extend(to_market_events(message)),
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("Orderly market event stream lagged, skipped {}", skipped)
+                        }
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_numbers_keep_the_exact_decimal() {
+        let price: Decimal = "60123.45".parse().unwrap();
+        assert_eq!(json_number("price", price).unwrap().to_string(), "60123.45");
+        let quantity: Decimal = "0.0001".parse().unwrap();
+        assert_eq!(
+            json_number("quantity", quantity).unwrap().to_string(),
+            "0.0001"
+        );
+    }
+
+    #[test]
+    fn json_numbers_refuse_values_a_double_would_round() {
+        let price: Decimal = "12345678901234.123456789".parse().unwrap();
+        assert!(matches!(
+            json_number("price", price),
+            Err(ConnectorError::InvalidRequest(_))
+        ));
+    }
+}
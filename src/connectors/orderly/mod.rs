@@ -0,0 +1,4 @@
+pub mod auth;
+pub mod connector;
+pub mod rest_client;
+pub mod stream_client;
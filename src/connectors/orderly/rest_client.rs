@@ -0,0 +1,141 @@
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::{header::CONTENT_TYPE, Method};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    domain::models::orderly::rest::{
+        OrderlyOrder, OrderlyResponse, PlacedOrder, PositionsData, Rows, SymbolInfo,
+    },
+    shared::errors::connector_error::ConnectorError,
+};
+
+use super::auth::OrderlyAuth;
+
+/// Orderly REST API; private endpoints are signed with the account's Orderly key.
+#[derive(Debug, Clone)]
+pub struct RestClient {
+    http: reqwest::Client,
+    base_url: String,
+    auth: Arc<OrderlyAuth>,
+}
+
+impl RestClient {
+    pub fn new(base_url: &str, auth: Arc<OrderlyAuth>) -> Self {
+        RestClient {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            auth,
+        }
+    }
+
+    // `path` includes the query string, which is part of the signed message
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<String>,
+        signed: bool,
+    ) -> Result<T, ConnectorError> {
+        let url = format!("{}{}", self.base_url, path);
+        let mut request = self.http.request(method.clone(), &url);
+
+        if signed {
+            let timestamp_ms = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Time went backwards")
+                .as_millis() as u64;
+            let headers = self.auth.sign_request(
+                timestamp_ms,
+                method.as_str(),
+                path,
+                body.as_deref().unwrap_or(""),
+            );
+            request = request
+                .header("orderly-timestamp", headers.timestamp)
+                .header("orderly-account-id", headers.account_id)
+                .header("orderly-key", headers.orderly_key)
+                .header("orderly-signature", headers.signature);
+        }
+
+        // Orderly wants form encoding declared on requests without a JSON body
+        request = match body {
+            Some(body) => request.header(CONTENT_TYPE, "application/json").body(body),
+            None => request.header(CONTENT_TYPE, "application/x-www-form-urlencoded"),
+        };
+
+        let response = request.send().await.map_err(|e| {
+            ConnectorError::Unavailable(format!("{} {} failed: {}", method, path, e))
+        })?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| ConnectorError::Unavailable(format!("Failed to read {}: {}", path, e)))?;
+
+        let parsed: OrderlyResponse<T> = serde_json::from_str(&text).map_err(|e| {
+            if status.is_success() {
+                ConnectorError::Internal(format!("Failed to parse {}: {}", path, e))
+            } else {
+                ConnectorError::Unavailable(format!("{} returned {}: {}", path, status, text))
+            }
+        })?;
+        match parsed.data {
+            Some(data) if parsed.success => Ok(data),
+            _ if status.is_client_error() || !parsed.success => Err(ConnectorError::Rejected(
+                parsed.message.unwrap_or_else(|| text.clone()),
+            )),
+            _ => Err(ConnectorError::Internal(format!("{} returned no data", path))),
+        }
+    }
+
+    pub async fn symbols(&self) -> Result<Vec<SymbolInfo>, ConnectorError> {
+        let rows: Rows<SymbolInfo> = self
+            .request(Method::GET, "/v1/public/info", None, false)
+            .await?;
+        Ok(rows.rows)
+    }
+
+    pub async fn place_order(
+        &self,
+        body: &serde_json::Value,
+    ) -> Result<PlacedOrder, ConnectorError> {
+        self.request(Method::POST, "/v1/order", Some(body.to_string()), true)
+            .await
+    }
+
+    pub async fn cancel_order(&self, symbol: &str, order_id: u64) -> Result<(), ConnectorError> {
+        let path = format!("/v1/order?order_id={}&symbol={}", order_id, symbol);
+        let _: serde_json::Value = self.request(Method::DELETE, &path, None, true).await?;
+        Ok(())
+    }
+
+    pub async fn cancel_all(&self, symbol: Option<&str>) -> Result<(), ConnectorError> {
+        let path = match symbol {
+            Some(symbol) => format!("/v1/orders?symbol={}", symbol),
+            None => "/v1/orders".to_string(),
+        };
+        let _: serde_json::Value = self.request(Method::DELETE, &path, None, true).await?;
+        Ok(())
+    }
+
+    // New and partially filled orders
+    pub async fn open_orders(
+        &self,
+        symbol: Option<&str>,
+    ) -> Result<Vec<OrderlyOrder>, ConnectorError> {
+        let mut path = "/v1/orders?status=INCOMPLETE".to_string();
+        if let Some(symbol) = symbol {
+            path.push_str(&format!("&symbol={}", symbol));
+        }
+        let rows: Rows<OrderlyOrder> = self.request(Method::GET, &path, None, true).await?;
+        Ok(rows.rows)
+    }
+
+    pub async fn positions(&self) -> Result<PositionsData, ConnectorError> {
+        self.request(Method::GET, "/v1/positions", None, true).await
+    }
+}
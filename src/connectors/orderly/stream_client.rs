@@ -0,0 +1,164 @@
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures_util::{SinkExt, StreamExt};
+use log::{error, info};
+use serde_json::json;
+use tokio::net::TcpStream;
+use tokio::sync::broadcast;
+use tokio_tungstenite::{tungstenite::protocol::Message, MaybeTlsStream, WebSocketStream};
+
+use crate::domain::models::orderly::stream_events::StreamMessage;
+use crate::shared::utils::websocket_utils::connect_websocket;
+
+use super::auth::OrderlyAuth;
+
+// Buffered messages per subscriber before slow consumers start lagging
+const EVENT_CHANNEL_CAPACITY: usize = 4096;
+
+/// Orderly public (market data) and private (execution report) websocket streams.
+///
+/// Both are merged into one broadcast channel; Orderly pings with an application-level
+/// `{"event":"ping"}`, which the reader answers itself.
+#[derive(Debug)]
+pub struct StreamClient {
+    public_url: String,
+    private_url: String,
+    auth: Arc<OrderlyAuth>,
+    // Set by each reader task when its connection drops
+    public_down: Arc<AtomicBool>,
+    private_down: Arc<AtomicBool>,
+    events: broadcast::Sender<StreamMessage>,
+    symbols: Mutex<Vec<String>>,
+}
+
+impl StreamClient {
+    pub fn new(public_url: &str, private_url: &str, auth: Arc<OrderlyAuth>) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        StreamClient {
+            // Both endpoints are per account
+            public_url: format!("{}/{}", public_url.trim_end_matches('/'), auth.account_id()),
+            private_url: format!("{}/{}", private_url.trim_end_matches('/'), auth.account_id()),
+            auth,
+            public_down: Arc::new(AtomicBool::new(false)),
+            private_down: Arc::new(AtomicBool::new(false)),
+            events,
+            symbols: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn subscribe_events(&self) -> broadcast::Receiver<StreamMessage> {
+        self.events.subscribe()
+    }
+
+    pub async fn start_subscription(&self, symbols: &[String]) -> Result<(), Box<dyn Error + Send>> {
+        *self.symbols.lock().unwrap() = symbols.to_vec();
+        self.open_public().await?;
+        self.open_private().await
+    }
+
+    async fn open_public(&self) -> Result<(), Box<dyn Error + Send>> {
+        let symbols = self.symbols.lock().unwrap().clone();
+        let mut messages = Vec::new();
+        for symbol in &symbols {
+            for topic in ["orderbook", "orderbookupdate", "trade", "bbo"] {
+                let topic = format!("{}@{}", symbol, topic);
+                messages.push(json!({ "id": topic, "event": "subscribe", "topic": topic }));
+            }
+        }
+        self.open_stream(&self.public_url, messages, Arc::clone(&self.public_down))
+            .await
+    }
+
+    async fn open_private(&self) -> Result<(), Box<dyn Error + Send>> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            .to_string();
+        let messages = vec![
+            // The private stream signs only the timestamp
+            json!({
+                "id": "auth",
+                "event": "auth",
+                "params": {
+                    "orderly_key": self.auth.orderly_key(),
+                    "sign": self.auth.sign(&timestamp),
+                    "timestamp": timestamp,
+                }
+            }),
+            json!({ "id": "executionreport", "event": "subscribe", "topic": "executionreport" }),
+        ];
+        self.open_stream(&self.private_url, messages, Arc::clone(&self.private_down))
+            .await
+    }
+
+    async fn open_stream(
+        &self,
+        url: &str,
+        messages: Vec<serde_json::Value>,
+        down: Arc<AtomicBool>,
+    ) -> Result<(), Box<dyn Error + Send>> {
+        let mut ws_stream = connect_websocket(url).await?;
+        for message in messages {
+            ws_stream
+                .send(Message::Text(message.to_string()))
+                .await
+                .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+        }
+
+        let events = self.events.clone();
+        tokio::spawn(async move {
+            forward_stream_messages(ws_stream, events).await;
+            down.store(true, Ordering::Relaxed);
+        });
+        Ok(())
+    }
+
+    pub async fn check_and_reconnect(&self) {
+        if self.public_down.load(Ordering::Relaxed) {
+            match self.open_public().await {
+                Ok(()) => self.public_down.store(false, Ordering::Relaxed),
+                Err(e) => error!("Failed to reconnect Orderly public stream: {}", e),
+            }
+        }
+        if self.private_down.load(Ordering::Relaxed) {
+            match self.open_private().await {
+                Ok(()) => self.private_down.store(false, Ordering::Relaxed),
+                Err(e) => error!("Failed to reconnect Orderly private stream: {}", e),
+            }
+        }
+    }
+}
+
+async fn forward_stream_messages(
+    mut ws_stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    events: broadcast::Sender<StreamMessage>,
+) {
+    while let Some(message) = ws_stream.next().await {
+        match message {
+            Ok(Message::Text(text)) => match serde_json::from_str::<StreamMessage>(&text) {
+                Ok(message) if message.event.as_deref() == Some("ping") => {
+                    let pong = json!({ "event": "pong", "ts": message.ts });
+                    if let Err(e) = ws_stream.send(Message::Text(pong.to_string())).await {
+                        error!("Failed to answer Orderly ping: {:?}", e);
+                        break;
+                    }
+                }
+                Ok(message) if message.topic.is_some() && !message.data.is_null() => {
+                    let _ = events.send(message);
+                }
+                // Subscribe and auth acks, errors
+                Ok(_) | Err(_) => info!("Received Orderly message: {}", text),
+            },
+            Ok(Message::Close(_)) => break,
+            Ok(_) => {}
+            Err(e) => {
+                error!("Error receiving Orderly message: {:?}", e);
+                break;
+            }
+        }
+    }
+}
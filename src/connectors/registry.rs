@@ -0,0 +1,51 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    domain::{models::trading::Venue, risk::engine::RiskEngine},
+    services::vertex::kill_switch::KillSwitch,
+    shared::errors::connector_error::ConnectorError,
+};
+
+use super::{connector::Connector, guard::GuardedConnector};
+
+/// The connectors this process trades through, one per venue.
+///
+/// Connectors are registered behind a `GuardedConnector`, so every order path is held to the
+/// kill switch and the risk limits, and the kill switch can cancel everything on every venue.
+#[derive(Debug, Clone)]
+pub struct ConnectorRegistry {
+    connectors: HashMap<Venue, Arc<dyn Connector>>,
+    kill_switch: Arc<KillSwitch>,
+    risk_engine: Arc<RiskEngine>,
+}
+
+impl ConnectorRegistry {
+    pub fn new(kill_switch: Arc<KillSwitch>, risk_engine: Arc<RiskEngine>) -> Self {
+        ConnectorRegistry {
+            connectors: HashMap::new(),
+            kill_switch,
+            risk_engine,
+        }
+    }
+
+    pub fn register(&mut self, connector: Arc<dyn Connector>) {
+        self.kill_switch.watch(Arc::clone(&connector));
+        let guarded = GuardedConnector::new(
+            connector,
+            Arc::clone(&self.kill_switch),
+            Arc::clone(&self.risk_engine),
+        );
+        self.connectors.insert(guarded.venue(), Arc::new(guarded));
+    }
+
+    pub fn get(&self, venue: Venue) -> Result<Arc<dyn Connector>, ConnectorError> {
+        self.connectors
+            .get(&venue)
+            .cloned()
+            .ok_or_else(|| ConnectorError::Unavailable(format!("{} is not configured", venue)))
+    }
+
+    pub fn venues(&self) -> Vec<Venue> {
+        self.connectors.keys().copied().collect()
+    }
+}
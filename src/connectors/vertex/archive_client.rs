@@ -0,0 +1,120 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    domain::models::vertex::archive::{
+        Candlestick, CandlesticksParams, CandlesticksQuery, CandlesticksResponse, FundingRate,
+        FundingRateParams, FundingRateQuery, MarketSnapshotsParams, MarketSnapshotsQuery,
+        MarketSnapshotsResponse, SnapshotInterval,
+    },
+    shared::{errors::connector_error::ConnectorError, utils::decimal::Decimal},
+};
+
+// Vertex settles funding hourly
+const FUNDING_GRANULARITY_SECS: u32 = 60 * 60;
+
+/// HTTP client for the Vertex archive, the indexer holding historical candles and funding.
+#[derive(Debug, Clone)]
+pub struct ArchiveClient {
+    http: reqwest::Client,
+    url: String,
+}
+
+impl ArchiveClient {
+    pub fn new(url: &str) -> Self {
+        ArchiveClient {
+            http: reqwest::Client::new(),
+            url: url.to_string(),
+        }
+    }
+
+    async fn query<Q: Serialize, T: DeserializeOwned>(
+        &self,
+        query: &Q,
+    ) -> Result<T, ConnectorError> {
+        let body = serde_json::to_string(query)
+            .map_err(|e| ConnectorError::Internal(format!("Failed to encode query: {}", e)))?;
+        let response = self
+            .http
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| ConnectorError::Unavailable(format!("Archive query failed: {}", e)))?;
+
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| ConnectorError::Unavailable(format!("Failed to read archive: {}", e)))?;
+        if !status.is_success() {
+            return Err(ConnectorError::Unavailable(format!(
+                "Archive returned {}: {}",
+                status, body
+            )));
+        }
+        serde_json::from_str(&body).map_err(|e| {
+            ConnectorError::Internal(format!("Failed to parse archive response: {}", e))
+        })
+    }
+
+    // Most recent `limit` candles of one product, newest first
+    pub async fn candlesticks(
+        &self,
+        product_id: u32,
+        granularity: u32,
+        limit: u32,
+    ) -> Result<Vec<Candlestick>, ConnectorError> {
+        let query = CandlesticksQuery {
+            candlesticks: CandlesticksParams {
+                product_id,
+                granularity,
+                limit,
+            },
+        };
+        let response: CandlesticksResponse = self.query(&query).await?;
+        Ok(response.candlesticks)
+    }
+
+    pub async fn funding_rate(&self, product_id: u32) -> Result<FundingRate, ConnectorError> {
+        let query = FundingRateQuery {
+            funding_rate: FundingRateParams { product_id },
+        };
+        self.query(&query).await
+    }
+
+    // Hourly 24h funding rates of one perp between two unix times, as (unix ms, rate) oldest
+    // first
+    pub async fn funding_history(
+        &self,
+        product_id: u32,
+        start_secs: u64,
+        end_secs: u64,
+    ) -> Result<Vec<(u64, Decimal)>, ConnectorError> {
+        let granularity = FUNDING_GRANULARITY_SECS;
+        // One more so the rate in force at the start is included
+        let count = end_secs.saturating_sub(start_secs) / granularity as u64 + 2;
+        let query = MarketSnapshotsQuery {
+            market_snapshots: MarketSnapshotsParams {
+                interval: SnapshotInterval {
+                    count: u32::try_from(count).unwrap_or(u32::MAX),
+                    granularity,
+                    max_time: end_secs,
+                },
+                product_ids: vec![product_id],
+            },
+        };
+        let response: MarketSnapshotsResponse = self.query(&query).await?;
+        let mut rates: Vec<(u64, Decimal)> = response
+            .snapshots
+            .into_iter()
+            .filter_map(|snapshot| {
+                let timestamp_secs: u64 = snapshot.timestamp.parse().ok()?;
+                let rate = snapshot.funding_rates.get(&product_id.to_string())?;
+                Some((timestamp_secs * 1000, Decimal::from_x18_str(rate).ok()?))
+            })
+            .collect();
+        rates.sort_by_key(|(timestamp_ms, _)| *timestamp_ms);
+        Ok(rates)
+    }
+}
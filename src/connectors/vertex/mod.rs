@@ -1,3 +1,5 @@
+pub mod archive_client;
 pub mod gateway_client;
 pub mod payload_signer;
+pub mod replay;
 pub mod subscription_client;
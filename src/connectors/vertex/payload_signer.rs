@@ -1,11 +1,12 @@
 use crate::config::CONFIG;
-use crate::domain::models::vertex::sol_structs::{Order, StreamAuthentication};
+use crate::domain::models::vertex::sol_structs::{
+    Cancellation, CancellationProducts, Order, StreamAuthentication,
+};
 use crate::shared::utils::eth_signer::EthSigner;
 use crate::shared::utils::type_conv;
 use alloy_primitives::{Address, Uint};
 use alloy_sol_types::{Eip712Domain, SolStruct};
 use std::borrow::Cow;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Debug)]
 pub struct Signer {
@@ -23,13 +24,8 @@ impl Signer {
         }
     }
 
-    pub fn sign_subscription_auth_payload(&self, sender_address: &str) -> String {
-        let current_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
-        let expiration = current_time + 60_000; // Expiration time set to 1 minute from now
-
+    // Expiration must match the one sent in the authenticate tx
+    pub fn sign_subscription_auth_payload(&self, sender_address: &str, expiration: u64) -> String {
         // Initialize StreamAuthentication using the abstracted method for generating sender bytes
         let tx_data = StreamAuthentication {
             sender: type_conv::hex_to_fixed_bytes32(sender_address).unwrap(),
@@ -54,6 +50,26 @@ impl Signer {
 
         signature
     }
+
+    // The digest Vertex identifies the order by, known before the gateway answers
+    pub fn order_digest(&self, order: &Order) -> String {
+        format!("0x{}", hex::encode(order.eip712_signing_hash(&self.domain)))
+    }
+
+    // Cancellations are verified against the endpoint contract, not the order book
+    pub fn sign_cancel_orders_payload(&self, cancellation: &Cancellation) -> String {
+        let signing_hash = cancellation.eip712_signing_hash(&self.domain);
+        self.eth_signer
+            .generate_signature(signing_hash.as_ref())
+            .unwrap()
+    }
+
+    pub fn sign_cancel_product_orders_payload(&self, cancellation: &CancellationProducts) -> String {
+        let signing_hash = cancellation.eip712_signing_hash(&self.domain);
+        self.eth_signer
+            .generate_signature(signing_hash.as_ref())
+            .unwrap()
+    }
 }
 
 // pub fn adjust_field_names(serialized: &str) -> Result<String, regex::Error> {
@@ -61,6 +77,12 @@ impl Signer {
 //     Ok(re.replace_all(serialized, r#""priceX18":"#).into_owned()) // Replace with the desired field name
 // }
 
+// Order digest under the book contract `book_addr`, without needing the signing key
+pub fn order_digest(order: &Order, book_addr: String) -> Option<String> {
+    let domain = create_domain(Some(book_addr)).ok()?;
+    Some(format!("0x{}", hex::encode(order.eip712_signing_hash(&domain))))
+}
+
 fn create_domain(
     place_order_addrs: Option<String>,
 ) -> Result<Eip712Domain, Box<dyn std::error::Error>> {
@@ -0,0 +1,242 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use log::{error, info};
+use tokio::{
+    sync::{broadcast, mpsc},
+    time::Instant,
+};
+
+use crate::{
+    domain::models::vertex::stream_events::StreamEvent,
+    storage::recorder::{self, RecordedEvent},
+};
+
+// Records read ahead of the pace, so a file is never held in memory whole
+const READ_AHEAD: usize = 1024;
+
+// A single recording file, or every recording in a directory in time order
+fn replay_files(path: &Path) -> io::Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    recorder::list_recordings(path)
+}
+
+// Account events belong to the session that was recorded; replayed orders fill in paper trading
+fn is_market_data(event: &StreamEvent) -> bool {
+    matches!(
+        event,
+        StreamEvent::BestBidOffer(_) | StreamEvent::Trade(_) | StreamEvent::BookDepth(_)
+    )
+}
+
+/// Re-emit recorded subscription messages as if the websocket were pushing them.
+///
+/// Messages keep the spacing of their receive times divided by `speed`, so 1.0 replays at the
+/// original pace and 10.0 ten times faster. The pace is kept against the replay start rather than
+/// message to message, so slow reads of the next file do not add up.
+pub async fn replay(path: PathBuf, speed: f64, events: broadcast::Sender<StreamEvent>) {
+    let files = match replay_files(&path) {
+        Ok(files) => files,
+        Err(e) => {
+            error!("Failed to list recordings in {}: {}", path.display(), e);
+            return;
+        }
+    };
+    info!(
+        "Replaying {} recordings from {} at {}x",
+        files.len(),
+        path.display(),
+        speed
+    );
+
+    let started = Instant::now();
+    let mut first_ms = None;
+    let mut replayed = 0u64;
+    for file in files {
+        let (records, mut recording) = mpsc::channel(READ_AHEAD);
+        let read_path = file.clone();
+        let reader = tokio::task::spawn_blocking(move || -> io::Result<()> {
+            for record in recorder::read_recording(&read_path)? {
+                if records.blocking_send(record).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        });
+
+        while let Some(RecordedEvent { received_ms, event }) = recording.recv().await {
+            if !is_market_data(&event) {
+                continue;
+            }
+            let start_ms = *first_ms.get_or_insert(received_ms);
+            let offset_ms = received_ms.saturating_sub(start_ms) as f64 / speed;
+            tokio::time::sleep_until(started + Duration::from_secs_f64(offset_ms / 1000.0)).await;
+            // No receivers is fine, nobody is interested yet
+            let _ = events.send(event);
+            replayed += 1;
+        }
+        match reader.await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => error!("Failed to read recording {}: {}", file.display(), e),
+            Err(e) => error!("Recording reader for {} failed: {}", file.display(), e),
+        }
+    }
+    info!(
+        "Replay of {} finished after {} messages",
+        path.display(),
+        replayed
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, fs::File, io::Write};
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+    use crate::{
+        domain::models::vertex::stream_events::{
+            BestBidOffer, Fill, OrderUpdate, OrderUpdateReason, PositionChange, Trade,
+        },
+        shared::utils::decimal::Decimal,
+    };
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("replay-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_recording(dir: &Path, opened_ms: u64, records: Vec<(u64, StreamEvent)>) -> PathBuf {
+        let path = dir.join(format!("market-{:013}.jsonl.gz", opened_ms));
+        let mut file = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+        for (received_ms, event) in records {
+            let record = RecordedEvent { received_ms, event };
+            writeln!(file, "{}", serde_json::to_string(&record).unwrap()).unwrap();
+        }
+        file.finish().unwrap();
+        path
+    }
+
+    fn bbo(product_id: u32) -> StreamEvent {
+        StreamEvent::BestBidOffer(BestBidOffer {
+            timestamp: "0".to_string(),
+            product_id,
+            bid_price: Decimal::ONE,
+            bid_qty: Decimal::ONE,
+            ask_price: Decimal::ONE,
+            ask_qty: Decimal::ONE,
+        })
+    }
+
+    fn trade(product_id: u32) -> StreamEvent {
+        StreamEvent::Trade(Trade {
+            timestamp: "0".to_string(),
+            product_id,
+            price: Decimal::ONE,
+            taker_qty: Decimal::ONE,
+            maker_qty: Decimal::ONE,
+            is_taker_buyer: true,
+        })
+    }
+
+    fn fill() -> StreamEvent {
+        StreamEvent::Fill(Fill {
+            timestamp: "0".to_string(),
+            product_id: 90,
+            subaccount: "0x01".to_string(),
+            order_digest: "0x02".to_string(),
+            filled_qty: Decimal::ONE,
+            remaining_qty: Decimal::ZERO,
+            original_qty: Decimal::ONE,
+            price: Decimal::ONE,
+            is_taker: true,
+            is_bid: true,
+            fee: Decimal::ZERO,
+        })
+    }
+
+    fn order_update() -> StreamEvent {
+        StreamEvent::OrderUpdate(OrderUpdate {
+            timestamp: "0".to_string(),
+            product_id: 91,
+            digest: "0x02".to_string(),
+            amount: Decimal::ZERO,
+            reason: OrderUpdateReason::Filled,
+        })
+    }
+
+    fn position_change() -> StreamEvent {
+        StreamEvent::PositionChange(PositionChange {
+            timestamp: "0".to_string(),
+            product_id: 92,
+            subaccount: "0x01".to_string(),
+            is_lp: false,
+            amount: Decimal::ONE,
+            v_quote_amount: Decimal::ONE,
+        })
+    }
+
+    // Everything a replay emits, with when it arrived
+    async fn run(path: PathBuf, speed: f64) -> Vec<(u32, Duration)> {
+        let (events, mut received) = broadcast::channel(64);
+        let started = Instant::now();
+        tokio::spawn(replay(path, speed, events));
+        let mut replayed = Vec::new();
+        while let Ok(event) = received.recv().await {
+            replayed.push((event.product_id(), started.elapsed()));
+        }
+        replayed
+    }
+
+    fn products(replayed: &[(u32, Duration)]) -> Vec<u32> {
+        replayed.iter().map(|(product_id, _)| *product_id).collect()
+    }
+
+    #[tokio::test]
+    async fn only_market_data_is_replayed_file_after_file() {
+        let dir = test_dir("filter");
+        let first = write_recording(
+            &dir,
+            1_000,
+            vec![
+                (1_000, bbo(1)),
+                (1_001, fill()),
+                (1_002, trade(2)),
+                (1_003, order_update()),
+            ],
+        );
+        write_recording(
+            &dir,
+            1_004,
+            vec![(1_004, position_change()), (1_005, bbo(3))],
+        );
+
+        assert_eq!(products(&run(dir.clone(), 1_000.0).await), vec![1, 2, 3]);
+        assert_eq!(products(&run(first, 1_000.0).await), vec![1, 2]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn messages_keep_their_recorded_spacing_divided_by_speed() {
+        let dir = test_dir("pace");
+        write_recording(&dir, 10_000, vec![(10_000, bbo(1)), (10_200, bbo(2))]);
+        write_recording(&dir, 10_300, vec![(10_300, fill()), (10_400, bbo(3))]);
+
+        let replayed = run(dir.clone(), 2.0).await;
+        assert_eq!(products(&replayed), vec![1, 2, 3]);
+        for ((_, arrived), offset_ms) in replayed.iter().zip([0, 100, 200]) {
+            assert!(*arrived >= Duration::from_millis(offset_ms));
+        }
+        // Twice as fast as recorded
+        assert!(replayed[2].1 < Duration::from_millis(400));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
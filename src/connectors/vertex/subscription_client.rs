@@ -1,35 +1,91 @@
 use crate::config::CONFIG;
-use crate::shared::utils::websocket_utils::{connect_websocket, handle_websocket_messages};
-use futures_util::stream::SplitSink;
+use crate::domain::models::vertex::stream_events::StreamEvent;
+use crate::shared::utils::type_conv::subaccount_sender_hex;
+use crate::shared::utils::websocket_utils::connect_websocket;
+use futures_util::stream::{SplitSink, SplitStream};
 use futures_util::{SinkExt, StreamExt};
+use log::{error, info};
 use serde_json::json;
 use std::error::Error;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::net::TcpStream;
+use tokio::sync::broadcast;
 use tokio::time::{interval, Duration};
 use tokio_tungstenite::{tungstenite::protocol::Message, MaybeTlsStream, WebSocketStream};
 
-use super::payload_signer::Signer;
+use super::{payload_signer::Signer, replay};
+
+// Buffered events per subscriber before slow consumers start lagging
+const EVENT_CHANNEL_CAPACITY: usize = 4096;
+
+// A subscription message exactly as the websocket delivered it
+#[derive(Debug, Clone)]
+pub struct RawFrame {
+    // Unix milliseconds
+    pub received_ms: u64,
+    pub text: Arc<str>,
+}
 
 #[derive(Debug)]
 pub struct SubscriptionClient {
     signer: Signer,
     needs_reconnect: Arc<AtomicBool>,
+    events: broadcast::Sender<StreamEvent>,
+    raw_frames: broadcast::Sender<RawFrame>,
+    product_ids: Mutex<Vec<u32>>,
+    // Set once the replay task runs, so later connects don't start another
+    replay_started: AtomicBool,
 }
 
 impl SubscriptionClient {
     pub fn new() -> Self {
         let signer = Signer::new(None);
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let (raw_frames, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
 
         SubscriptionClient {
             signer,
             needs_reconnect: Arc::new(AtomicBool::new(false)),
+            events,
+            raw_frames,
+            product_ids: Mutex::new(Vec::new()),
+            replay_started: AtomicBool::new(false),
+        }
+    }
+
+    // Every parsed stream event is fanned out to all subscribers
+    pub fn subscribe_events(&self) -> broadcast::Receiver<StreamEvent> {
+        self.events.subscribe()
+    }
+
+    // Text of every frame that parsed as a stream event; published and replayed events never
+    // show up here
+    pub fn subscribe_raw_frames(&self) -> broadcast::Receiver<RawFrame> {
+        self.raw_frames.subscribe()
+    }
+
+    // Inject an event as if the websocket had pushed it, e.g. paper-trading fills
+    pub fn publish(&self, event: StreamEvent) {
+        let _ = self.events.send(event);
+    }
+
+    pub async fn start_subscription(&self, product_ids: &[u32]) -> Result<(), Box<dyn Error + Send>> {
+        *self.product_ids.lock().unwrap() = product_ids.to_vec();
+        // Replay mode feeds recorded messages instead of the live socket
+        if let Some(path) = &CONFIG.replay_path {
+            if !self.replay_started.swap(true, Ordering::SeqCst) {
+                let events = self.events.clone();
+                tokio::spawn(replay::replay(path.into(), CONFIG.replay_speed, events));
+            }
+            return Ok(());
         }
+        self.open_stream().await
     }
 
-    pub async fn start_subscription(&self) -> Result<(), Box<dyn Error + Send>> {
+    // Connect, authenticate, subscribe to all streams and spawn the reader and ping tasks
+    async fn open_stream(&self) -> Result<(), Box<dyn Error + Send>> {
         let subscribe_url = CONFIG.arbitrum_vertex_testnet_subscribe_url.clone();
 
         // Establish WebSocket connection
@@ -44,7 +100,7 @@ impl SubscriptionClient {
 
         let signature = self
             .signer
-            .sign_subscription_auth_payload(&CONFIG.sender_address);
+            .sign_subscription_auth_payload(&CONFIG.sender_address, expiration);
         let ws_subscription_payload = json!({
             "method": "authenticate",
             "id": 0,
@@ -61,9 +117,24 @@ impl SubscriptionClient {
             .await
             .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
 
+        let product_ids = self.product_ids.lock().unwrap().clone();
+        for (id, stream) in subscription_streams(&product_ids).into_iter().enumerate() {
+            let subscribe = json!({
+                "method": "subscribe",
+                "stream": stream,
+                "id": id + 1
+            });
+            ws_writer
+                .send(Message::Text(subscribe.to_string()))
+                .await
+                .map_err(|e| Box::new(e) as Box<dyn Error + Send>)?;
+        }
+
         // Listen to messages in a separate task
+        let events = self.events.clone();
+        let raw_frames = self.raw_frames.clone();
         tokio::spawn(async move {
-            handle_websocket_messages(ws_reader).await;
+            forward_stream_events(ws_reader, events, raw_frames).await;
         });
 
         // Start the ping task to keep the connection alive
@@ -75,6 +146,11 @@ impl SubscriptionClient {
         Ok(())
     }
 
+    // True once the ping task has seen the connection drop and until it is re-established
+    pub fn is_disconnected(&self) -> bool {
+        self.needs_reconnect.load(Ordering::Relaxed)
+    }
+
     async fn start_ping(
         mut ws_writer: SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>,
         needs_reconnect: Arc<AtomicBool>,
@@ -92,54 +168,63 @@ impl SubscriptionClient {
 
     pub async fn check_and_reconnect(&self) {
         if self.needs_reconnect.load(Ordering::Relaxed) {
-            // Connect to the WebSocket
-            let ws_stream = connect_websocket(&CONFIG.arbitrum_vertex_testnet_subscribe_url)
-                .await
-                .expect("Failed to reconnect to WebSocket");
-
-            // Split the WebSocket stream
-            let (mut ws_writer, ws_reader) = ws_stream.split();
-
-            // Reset the reconnection flag
-            self.needs_reconnect.store(false, Ordering::Relaxed);
-
-            let current_time = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64;
-            let expiration = current_time + 60_000; // Example expiration time set to 1 minute from now
-
-            let signature = self
-                .signer
-                .sign_subscription_auth_payload(&CONFIG.sender_address);
-            let ws_subscription_payload = json!({
-                "method": "authenticate",
-                "id": 0,
-                "tx": {
-                    "sender": CONFIG.sender_address, // Use the sender address from your config
-                    "expiration": expiration, // Use the calculated expiration time
-                },
-                "signature": signature
-            });
-
-            // Resend the authentication payload if necessary
-            ws_writer
-                .send(Message::Text(ws_subscription_payload.to_string()))
-                .await
-                .expect("Failed to send auth payload");
-
-            // Spawn a task to listen to messages
-            tokio::spawn(async move {
-                handle_websocket_messages(ws_reader).await;
-            });
+            match self.open_stream().await {
+                // Reset the reconnection flag
+                Ok(()) => self.needs_reconnect.store(false, Ordering::Relaxed),
+                Err(e) => error!("Failed to reconnect subscription stream: {}", e),
+            }
+        }
+    }
+}
 
-            // Clone `needs_reconnect` for the ping task
-            let needs_reconnect_clone = Arc::clone(&self.needs_reconnect);
+// Market data for every product, plus order and fill updates for each configured subaccount
+fn subscription_streams(product_ids: &[u32]) -> Vec<serde_json::Value> {
+    let mut streams = Vec::new();
+    for product_id in product_ids {
+        for stream_type in ["best_bid_offer", "trade", "book_depth"] {
+            streams.push(json!({ "type": stream_type, "product_id": product_id }));
+        }
+        for subaccount in &CONFIG.subaccounts {
+            let sender = subaccount_sender_hex(&CONFIG.sender_address, subaccount);
+            for stream_type in ["order_update", "fill", "position_change"] {
+                streams.push(json!({
+                    "type": stream_type,
+                    "product_id": product_id,
+                    "subaccount": sender
+                }));
+            }
+        }
+    }
+    streams
+}
 
-            // Spawn a task for sending pings
-            tokio::spawn(async move {
-                SubscriptionClient::start_ping(ws_writer, needs_reconnect_clone).await;
-            });
+// Parse text frames into stream events; anything else (acks, errors) is only logged
+async fn forward_stream_events(
+    mut ws_reader: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+    events: broadcast::Sender<StreamEvent>,
+    raw_frames: broadcast::Sender<RawFrame>,
+) {
+    while let Some(message) = ws_reader.next().await {
+        match message {
+            Ok(Message::Text(text)) => match serde_json::from_str::<StreamEvent>(&text) {
+                // No receivers is fine, nobody is interested yet
+                Ok(event) => {
+                    let _ = raw_frames.send(RawFrame {
+                        received_ms: SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .unwrap()
+                            .as_millis() as u64,
+                        text: Arc::from(text.as_str()),
+                    });
+                    let _ = events.send(event);
+                }
+                Err(_) => info!("Received text message: {}", text),
+            },
+            Ok(_) => info!("Received other message."),
+            Err(e) => {
+                error!("Error receiving message: {:?}", e);
+                break;
+            }
         }
     }
 }
@@ -0,0 +1,235 @@
+use std::time::Duration;
+
+use crate::{
+    domain::models::trading::Side,
+    shared::utils::decimal::{Decimal, RoundingMode},
+};
+
+const SECS_PER_HOUR: u64 = 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlgoKind {
+    // Even slices over the duration
+    Twap,
+    // Slices weighted by the hour-of-day volume profile
+    Vwap,
+    // A fixed share of the volume traded since the start
+    Pov,
+}
+
+#[derive(Debug, Clone)]
+pub struct AlgoParams {
+    pub kind: AlgoKind,
+    pub instrument: String,
+    pub side: Side,
+    pub quantity: Decimal,
+    // No child is sent while the touch is beyond it
+    pub limit_price: Option<Decimal>,
+    // TWAP and VWAP spread the order over it; POV gives up once it has passed
+    pub duration: Duration,
+    pub slice_interval: Duration,
+    // POV only, e.g. 0.1 for 10% of traded volume
+    pub participation_rate: Option<Decimal>,
+}
+
+impl AlgoParams {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.quantity <= Decimal::ZERO {
+            return Err("Quantity must be positive".to_string());
+        }
+        if self.slice_interval.is_zero() {
+            return Err("Slice interval must be positive".to_string());
+        }
+        if self.duration < self.slice_interval {
+            return Err("Duration must be at least one slice interval".to_string());
+        }
+        if self.kind == AlgoKind::Pov {
+            match self.participation_rate {
+                Some(rate) if rate > Decimal::ZERO && rate <= Decimal::ONE => {}
+                _ => return Err("POV needs a participation rate in (0, 1]".to_string()),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn slices(&self) -> usize {
+        let interval = self.slice_interval.as_millis().max(1);
+        self.duration.as_millis().div_ceil(interval) as usize
+    }
+}
+
+// Cumulative share of the parent due by the end of each slice; the last entry is always one
+fn cumulative(weights: &[Decimal]) -> Vec<Decimal> {
+    let total = weights
+        .iter()
+        .try_fold(Decimal::ZERO, |sum, weight| sum.checked_add(*weight))
+        .unwrap_or_default();
+    if total <= Decimal::ZERO && !weights.is_empty() {
+        return cumulative(&vec![Decimal::ONE; weights.len()]);
+    }
+
+    let mut running = Decimal::ZERO;
+    let mut targets: Vec<Decimal> = weights
+        .iter()
+        .map(|weight| {
+            running = running.checked_add(*weight).unwrap_or(running);
+            running.checked_div(total).unwrap_or(Decimal::ONE)
+        })
+        .collect();
+    if let Some(last) = targets.last_mut() {
+        *last = Decimal::ONE;
+    }
+    targets
+}
+
+pub fn twap_targets(slices: usize) -> Vec<Decimal> {
+    cumulative(&vec![Decimal::ONE; slices])
+}
+
+/// Average volume traded in each UTC hour of the day, from `(open unix secs, volume)` candles.
+pub fn hourly_profile(candles: &[(u64, Decimal)]) -> [Decimal; 24] {
+    let mut totals = [Decimal::ZERO; 24];
+    let mut counts = [0i64; 24];
+    for (timestamp, volume) in candles {
+        let hour = ((timestamp / SECS_PER_HOUR) % 24) as usize;
+        totals[hour] = totals[hour].checked_add(*volume).unwrap_or(totals[hour]);
+        counts[hour] += 1;
+    }
+
+    let mut profile = [Decimal::ZERO; 24];
+    for ((average, total), count) in profile.iter_mut().zip(totals).zip(counts) {
+        if count > 0 {
+            *average = total
+                .checked_div(Decimal::from_int(count))
+                .unwrap_or_default();
+        }
+    }
+    profile
+}
+
+// Each slice weighted by the profile's volume for the hour it starts in; TWAP without history
+pub fn vwap_targets(
+    start_secs: u64,
+    params: &AlgoParams,
+    profile: &[Decimal; 24],
+) -> Vec<Decimal> {
+    let interval_secs = params.slice_interval.as_secs();
+    let weights: Vec<Decimal> = (0..params.slices() as u64)
+        .map(|slice| {
+            let slice_start = start_secs + slice * interval_secs;
+            profile[((slice_start / SECS_PER_HOUR) % 24) as usize]
+        })
+        .collect();
+    cumulative(&weights)
+}
+
+// What a POV algo should have filled after `market_volume` traded, capped at the parent. The
+// algo's own `filled` trades are part of that volume but don't count towards it
+pub fn pov_target(params: &AlgoParams, market_volume: Decimal, filled: Decimal) -> Decimal {
+    let volume = market_volume
+        .checked_sub(filled)
+        .unwrap_or_default()
+        .max(Decimal::ZERO);
+    params
+        .participation_rate
+        .and_then(|rate| rate.checked_mul(volume))
+        .unwrap_or_default()
+        .min(params.quantity)
+}
+
+// Child size to catch up from `filled` to `target`; None below the venue's minimum
+pub fn child_quantity(
+    target: Decimal,
+    filled: Decimal,
+    step_size: Decimal,
+    min_size: Decimal,
+) -> Option<Decimal> {
+    let due = target.checked_sub(filled)?;
+    let due = if step_size.is_zero() {
+        due
+    } else {
+        due.round_to_increment(step_size, RoundingMode::TowardZero)?
+    };
+    (due > Decimal::ZERO && due >= min_size).then_some(due)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn params(kind: AlgoKind) -> AlgoParams {
+        AlgoParams {
+            kind,
+            instrument: "BTC-PERP".to_string(),
+            side: Side::Buy,
+            quantity: dec("10"),
+            limit_price: None,
+            duration: Duration::from_secs(3 * SECS_PER_HOUR),
+            slice_interval: Duration::from_secs(SECS_PER_HOUR),
+            participation_rate: Some(dec("0.1")),
+        }
+    }
+
+    #[test]
+    fn hourly_profile_averages_each_hour_across_days() {
+        let day = 24 * SECS_PER_HOUR;
+        let profile = hourly_profile(&[
+            (0, dec("10")),
+            (day, dec("20")),
+            (day + 5 * SECS_PER_HOUR, dec("7")),
+        ]);
+        assert_eq!(profile[0], dec("15"));
+        assert_eq!(profile[5], dec("7"));
+        assert_eq!(profile[1], Decimal::ZERO);
+    }
+
+    #[test]
+    fn vwap_targets_follow_the_profile_from_the_start_hour() {
+        let mut profile = [Decimal::ZERO; 24];
+        profile[22] = dec("1");
+        profile[23] = dec("3");
+        profile[0] = dec("4");
+
+        // Starting at 22:00 the three hourly slices wrap past midnight
+        let targets = vwap_targets(22 * SECS_PER_HOUR, &params(AlgoKind::Vwap), &profile);
+        assert_eq!(targets, vec![dec("0.125"), dec("0.5"), Decimal::ONE]);
+
+        // No history at all falls back to even slices
+        let flat = vwap_targets(0, &params(AlgoKind::Vwap), &[Decimal::ZERO; 24]);
+        assert_eq!(flat, twap_targets(3));
+        assert_eq!(flat.last(), Some(&Decimal::ONE));
+    }
+
+    #[test]
+    fn pov_target_leaves_out_its_own_fills_and_stops_at_the_parent() {
+        let params = params(AlgoKind::Pov);
+        assert_eq!(pov_target(&params, dec("50"), dec("2")), dec("4.8"));
+        assert_eq!(pov_target(&params, dec("1000"), Decimal::ZERO), dec("10"));
+        assert_eq!(pov_target(&params, dec("1"), dec("3")), Decimal::ZERO);
+    }
+
+    #[test]
+    fn child_quantity_rounds_down_and_respects_the_minimum() {
+        assert_eq!(
+            child_quantity(dec("2.57"), dec("1"), dec("0.1"), dec("0.5")),
+            Some(dec("1.5"))
+        );
+        assert_eq!(
+            child_quantity(dec("1.3"), dec("1"), dec("0.1"), dec("0.5")),
+            None
+        );
+        // Ahead of schedule sends nothing
+        assert_eq!(
+            child_quantity(dec("1"), dec("2"), Decimal::ZERO, Decimal::ZERO),
+            None
+        );
+        assert_eq!(
+            child_quantity(dec("1.23"), Decimal::ZERO, Decimal::ZERO, Decimal::ZERO),
+            Some(dec("1.23"))
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use std::io::BufRead;
+
+use crate::{
+    domain::models::trading::{MarketEvent, Side, Venue},
+    shared::utils::decimal::Decimal,
+};
+
+// One OHLCV bar
+#[derive(Debug, Clone)]
+pub struct Candle {
+    pub open_ms: u64,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    // Base quantity traded
+    pub volume: Decimal,
+}
+
+/// Read recorded events, one JSON `MarketEvent` per line, into time order.
+pub fn read_events(reader: impl BufRead) -> Result<Vec<MarketEvent>, String> {
+    let mut events = Vec::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| format!("Failed to read line {}: {}", number + 1, e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event: MarketEvent = serde_json::from_str(&line)
+            .map_err(|e| format!("Invalid event on line {}: {}", number + 1, e))?;
+        // Fills and order updates of the recording session aren't the backtest's to replay
+        if event.is_market_data() {
+            events.push(event);
+        }
+    }
+    // Stable, so events sharing a timestamp keep their recorded order
+    events.sort_by_key(MarketEvent::timestamp_ms);
+    Ok(events)
+}
+
+/// Quotes and trades walking each candle from open to close through its extremes.
+///
+/// An up candle is taken to visit its low before its high and a down candle the reverse. At
+/// each stop the touch is one increment wide around the price with the candle's whole volume
+/// on both sides, and a quarter of the volume trades there, so resting orders see the range.
+pub fn candle_events(
+    venue: Venue,
+    instrument: &str,
+    candles: &[Candle],
+    granularity_ms: u64,
+    price_increment: Decimal,
+) -> Vec<MarketEvent> {
+    let quarter = Decimal::from_int(4);
+    let mut candles = candles.to_vec();
+    candles.sort_by_key(|candle| candle.open_ms);
+
+    let mut events = Vec::with_capacity(candles.len() * 8);
+    let mut last = None;
+    for candle in &candles {
+        let path = if candle.close >= candle.open {
+            [candle.open, candle.low, candle.high, candle.close]
+        } else {
+            [candle.open, candle.high, candle.low, candle.close]
+        };
+        let size = candle.volume.checked_div(quarter).unwrap_or_default();
+        for (step, price) in path.into_iter().enumerate() {
+            let timestamp_ms = candle.open_ms + granularity_ms * step as u64 / 4;
+            let Some(ask) = price.checked_add(price_increment) else {
+                continue;
+            };
+            events.push(MarketEvent::Quote {
+                venue,
+                instrument: instrument.to_string(),
+                bid_price: price,
+                bid_quantity: candle.volume,
+                ask_price: ask,
+                ask_quantity: candle.volume,
+                timestamp_ms,
+            });
+            if size > Decimal::ZERO {
+                events.push(MarketEvent::Trade {
+                    venue,
+                    instrument: instrument.to_string(),
+                    price,
+                    quantity: size,
+                    // Buyers lift a rising price and sellers hit a falling one
+                    taker_side: if last.is_some_and(|last| price < last) {
+                        Side::Sell
+                    } else {
+                        Side::Buy
+                    },
+                    timestamp_ms,
+                });
+            }
+            last = Some(price);
+        }
+    }
+    events
+}
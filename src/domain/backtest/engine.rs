@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+
+use crate::domain::{
+    models::trading::MarketEvent,
+    strategies::{
+        driver::StrategyDriver,
+        strategy::{OrderResponse, StrategyAction},
+    },
+};
+
+use super::{
+    exchange::SimExchange,
+    report::{BacktestReport, Ledger},
+};
+
+/// Replays recorded events through a strategy against the simulated venue.
+///
+/// Time is the recorded timestamps: orders reach the venue `latency_ms` after the callback that
+/// sent them, timers fire between events at their due time, and the venue's answers and fills
+/// reach the strategy as soon as they happen.
+pub struct Backtest {
+    driver: StrategyDriver,
+    exchange: SimExchange,
+    ledger: Ledger,
+    latency_ms: u64,
+    // Actions on their way to the venue, by arrival time then send order
+    in_flight: BTreeMap<(u64, u64), StrategyAction>,
+    sent: u64,
+    next_timer_ms: Option<u64>,
+}
+
+impl Backtest {
+    pub fn new(driver: StrategyDriver, exchange: SimExchange, latency_ms: u64) -> Self {
+        Backtest {
+            driver,
+            exchange,
+            ledger: Ledger::new(),
+            latency_ms,
+            in_flight: BTreeMap::new(),
+            sent: 0,
+            next_timer_ms: None,
+        }
+    }
+
+    pub fn run(mut self, events: impl IntoIterator<Item = MarketEvent>) -> BacktestReport {
+        let mut now_ms = None;
+        for event in events {
+            let timestamp_ms = event.timestamp_ms();
+            if now_ms.is_none() {
+                let actions = self.driver.start(timestamp_ms);
+                self.send(actions, timestamp_ms);
+                let interval = self.driver.timer_interval();
+                self.next_timer_ms =
+                    interval.map(|interval| timestamp_ms + interval.as_millis() as u64);
+            }
+            self.advance(timestamp_ms);
+            now_ms = Some(timestamp_ms);
+            self.on_market(&event, timestamp_ms);
+        }
+
+        // The strategy stops at the last event and its final orders still reach the venue
+        if let Some(now_ms) = now_ms {
+            self.next_timer_ms = None;
+            let actions = self.driver.stop(now_ms);
+            self.send(actions, now_ms);
+            self.advance(u64::MAX);
+        }
+        self.ledger.finish()
+    }
+
+    fn send(&mut self, actions: Vec<StrategyAction>, now_ms: u64) {
+        for action in actions {
+            self.sent += 1;
+            let arrival = now_ms.saturating_add(self.latency_ms);
+            self.in_flight.insert((arrival, self.sent), action);
+        }
+    }
+
+    // Deliver every action and timer due up to the given time, earliest first
+    fn advance(&mut self, until_ms: u64) {
+        loop {
+            let action_at = self.in_flight.keys().next().map(|(at, _)| *at);
+            let timer_at = self.next_timer_ms;
+            match (action_at, timer_at) {
+                (Some(action_at), timer_at)
+                    if action_at <= until_ms && timer_at.is_none_or(|t| action_at <= t) =>
+                {
+                    if let Some(((at, _), action)) = self.in_flight.pop_first() {
+                        self.execute(action, at);
+                    }
+                }
+                (_, Some(timer_at)) if timer_at <= until_ms => {
+                    let interval = self.driver.timer_interval();
+                    self.next_timer_ms =
+                        interval.map(|interval| timer_at + interval.as_millis().max(1) as u64);
+                    let actions = self.driver.timer(timer_at);
+                    self.send(actions, timer_at);
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn on_market(&mut self, event: &MarketEvent, now_ms: u64) {
+        self.ledger.on_event(now_ms);
+        let caused = self.exchange.on_market(event);
+        self.record_fills();
+        if let Some(mid) = self.exchange.mid(event.venue(), event.instrument()) {
+            self.ledger.mark(event.venue(), event.instrument(), mid, now_ms);
+        }
+
+        let mut actions = self.driver.event(event, now_ms);
+        for event in &caused {
+            actions.extend(self.driver.event(event, now_ms));
+        }
+        self.send(actions, now_ms);
+    }
+
+    fn record_fills(&mut self) {
+        for fill in self.exchange.take_fills() {
+            self.ledger.on_fill(&fill);
+        }
+    }
+
+    fn execute(&mut self, action: StrategyAction, now_ms: u64) {
+        let (tag, venue, order, result, events) = match action {
+            StrategyAction::Place { tag, venue, order } => {
+                let (result, events) = self.exchange.place(venue, &order, now_ms);
+                (tag, venue, order, result, events)
+            }
+            StrategyAction::CancelAndPlace {
+                tag,
+                venue,
+                cancel,
+                order,
+            } => {
+                // As on a live venue, the replacement goes in whether or not the cancel found
+                // the order
+                let mut events = self
+                    .exchange
+                    .cancel(venue, &cancel, now_ms)
+                    .unwrap_or_default();
+                let (result, placed) = self.exchange.place(venue, &order, now_ms);
+                events.extend(placed);
+                (tag, venue, order, result, events)
+            }
+            StrategyAction::Cancel { venue, cancel } => {
+                let events = self
+                    .exchange
+                    .cancel(venue, &cancel, now_ms)
+                    .unwrap_or_default();
+                self.deliver(events, now_ms);
+                return;
+            }
+            StrategyAction::CancelAll { venue, instrument } => {
+                let events = self.exchange.cancel_all(venue, instrument.as_deref(), now_ms);
+                self.deliver(events, now_ms);
+                return;
+            }
+        };
+
+        self.ledger.on_order(result.is_ok());
+        self.record_fills();
+        let response = OrderResponse {
+            tag,
+            venue,
+            order,
+            result,
+        };
+        let actions = self.driver.order_response(&response, now_ms);
+        self.send(actions, now_ms);
+        self.deliver(events, now_ms);
+    }
+
+    fn deliver(&mut self, events: Vec<MarketEvent>, now_ms: u64) {
+        for event in &events {
+            let actions = self.driver.event(event, now_ms);
+            self.send(actions, now_ms);
+        }
+    }
+}
@@ -0,0 +1,684 @@
+use std::collections::HashMap;
+
+use crate::{
+    domain::models::{
+        order_book::OrderBook,
+        trading::{
+            BookLevel, CancelRequest, MarketEvent, OrderAck, OrderRequest, Side, TimeInForce,
+            Venue, VenueOrderStatus,
+        },
+    },
+    shared::{errors::connector_error::ConnectorError, utils::decimal::Decimal},
+};
+
+// Fee rates of one instrument as fractions of notional; a negative maker rate is a rebate
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FeeRates {
+    pub maker: Decimal,
+    pub taker: Decimal,
+}
+
+// One execution against the simulated venue
+#[derive(Debug, Clone)]
+pub struct SimFill {
+    pub venue: Venue,
+    pub instrument: String,
+    pub order_id: String,
+    pub side: Side,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub fee: Decimal,
+    // False when the order took liquidity
+    pub maker: bool,
+    pub timestamp_ms: u64,
+}
+
+#[derive(Debug, Clone)]
+struct RestingOrder {
+    order_id: String,
+    side: Side,
+    price: Decimal,
+    remaining: Decimal,
+    // Recorded size at the order's price that has to trade before it does
+    queue_ahead: Decimal,
+}
+
+#[derive(Debug, Default)]
+struct SimMarket {
+    book: OrderBook,
+    best_bid: Option<BookLevel>,
+    best_ask: Option<BookLevel>,
+    fees: FeeRates,
+    // Taken by simulated orders since the recorded level last changed, by taker side and price
+    consumed: HashMap<(Side, Decimal), Decimal>,
+    // Insertion order gives time priority within a price
+    resting: Vec<RestingOrder>,
+}
+
+impl SimMarket {
+    // Recorded levels on one side, best first; the quote stands in on quote-only data
+    fn levels(&self, side: Side) -> Vec<BookLevel> {
+        let levels: Vec<BookLevel> = match side {
+            Side::Buy => self.book.bids().collect(),
+            Side::Sell => self.book.asks().collect(),
+        };
+        if !levels.is_empty() {
+            return levels;
+        }
+        let touch = match side {
+            Side::Buy => &self.best_bid,
+            Side::Sell => &self.best_ask,
+        };
+        touch.iter().cloned().collect()
+    }
+
+    fn size_at(&self, side: Side, price: Decimal) -> Decimal {
+        self.levels(side)
+            .into_iter()
+            .find(|level| level.price == price)
+            .map(|level| level.quantity)
+            .unwrap_or_default()
+    }
+
+    fn crosses(&self, side: Side, price: Decimal) -> bool {
+        match side {
+            Side::Buy => self.best_ask.as_ref().is_some_and(|ask| ask.price <= price),
+            Side::Sell => self.best_bid.as_ref().is_some_and(|bid| bid.price >= price),
+        }
+    }
+
+    // Recorded depth up to the limit, less what earlier orders already took, as (price, size)
+    fn available(&self, side: Side, limit: Decimal, quantity: Decimal) -> Vec<(Decimal, Decimal)> {
+        let mut takes = Vec::new();
+        let mut left = quantity;
+        for level in self.levels(side.opposite()) {
+            let reachable = match side {
+                Side::Buy => level.price <= limit,
+                Side::Sell => level.price >= limit,
+            };
+            if !reachable || left.is_zero() {
+                break;
+            }
+            let taken = self
+                .consumed
+                .get(&(side, level.price))
+                .copied()
+                .unwrap_or_default();
+            let size = level
+                .quantity
+                .checked_sub(taken)
+                .unwrap_or_default()
+                .min(left);
+            if size > Decimal::ZERO {
+                takes.push((level.price, size));
+                left = left.checked_sub(size).unwrap_or_default();
+            }
+        }
+        takes
+    }
+
+    fn consume(&mut self, side: Side, takes: &[(Decimal, Decimal)]) {
+        for (price, quantity) in takes {
+            let taken = self.consumed.entry((side, *price)).or_default();
+            *taken = taken.checked_add(*quantity).unwrap_or(*taken);
+        }
+    }
+
+    // Recorded size behind each price simulated orders took from
+    fn consumed_levels(&self) -> Vec<((Side, Decimal), Decimal)> {
+        self.consumed
+            .keys()
+            .map(|&(side, price)| ((side, price), self.size_at(side.opposite(), price)))
+            .collect()
+    }
+
+    // A level whose recorded size changed no longer holds what simulated orders took from it
+    fn release_changed(&mut self, before: Vec<((Side, Decimal), Decimal)>) {
+        for (key, size) in before {
+            if self.size_at(key.0.opposite(), key.1) != size {
+                self.consumed.remove(&key);
+            }
+        }
+    }
+}
+
+// Quantity of one resting order to fill, at the given price
+struct Execution {
+    index: usize,
+    price: Decimal,
+    quantity: Decimal,
+    maker: bool,
+}
+
+/// Simulated Vertex matching against recorded market data.
+///
+/// Incoming orders take the recorded depth up to their limit at the taker rate, and whatever
+/// rests joins the back of the recorded queue at its price. A resting order fills at the maker
+/// rate once trades at its price have worked through the size ahead of it, or as soon as the
+/// market trades through it. When the book moves through it, it takes the crossed depth at the
+/// taker rate instead. Simulated orders never change the recorded book; size they take is held
+/// back until the recorded level it came from changes.
+#[derive(Debug, Default)]
+pub struct SimExchange {
+    markets: HashMap<(Venue, String), SimMarket>,
+    next_id: u64,
+    fills: Vec<SimFill>,
+}
+
+impl SimExchange {
+    pub fn new(fees: HashMap<(Venue, String), FeeRates>) -> Self {
+        let markets = fees
+            .into_iter()
+            .map(|(key, fees)| {
+                let market = SimMarket {
+                    fees,
+                    ..SimMarket::default()
+                };
+                (key, market)
+            })
+            .collect();
+        SimExchange {
+            markets,
+            next_id: 0,
+            fills: Vec::new(),
+        }
+    }
+
+    // Executions since the last call
+    pub fn take_fills(&mut self) -> Vec<SimFill> {
+        std::mem::take(&mut self.fills)
+    }
+
+    pub fn mid(&self, venue: Venue, instrument: &str) -> Option<Decimal> {
+        let market = self.markets.get(&(venue, instrument.to_string()))?;
+        let (bid, ask) = (market.best_bid.as_ref()?, market.best_ask.as_ref()?);
+        bid.price
+            .checked_add(ask.price)?
+            .checked_div(Decimal::from_int(2))
+    }
+
+    // Charge the fee of the fill's liquidity flag, record it and return its private event
+    fn execute(&mut self, mut fill: SimFill) -> MarketEvent {
+        let fees = self
+            .markets
+            .get(&(fill.venue, fill.instrument.clone()))
+            .map(|market| market.fees)
+            .unwrap_or_default();
+        let rate = if fill.maker { fees.maker } else { fees.taker };
+        fill.fee = fill
+            .price
+            .checked_mul(fill.quantity)
+            .and_then(|notional| notional.checked_mul(rate))
+            .unwrap_or_default();
+        let event = MarketEvent::Fill {
+            venue: fill.venue,
+            instrument: fill.instrument.clone(),
+            order_id: fill.order_id.clone(),
+            side: fill.side,
+            price: fill.price,
+            quantity: fill.quantity,
+            fee: fill.fee,
+            timestamp_ms: fill.timestamp_ms,
+        };
+        self.fills.push(fill);
+        event
+    }
+
+    /// Match a new order; the ack or refusal comes with the events it caused.
+    pub fn place(
+        &mut self,
+        venue: Venue,
+        order: &OrderRequest,
+        now_ms: u64,
+    ) -> (Result<OrderAck, ConnectorError>, Vec<MarketEvent>) {
+        if order.quantity <= Decimal::ZERO || order.price <= Decimal::ZERO {
+            let message = "Price and quantity must be positive".to_string();
+            let error = ConnectorError::InvalidRequest(message);
+            return (Err(error), Vec::new());
+        }
+        let key = (venue, order.instrument.clone());
+        let market = self.markets.entry(key.clone()).or_default();
+        if order.time_in_force == TimeInForce::PostOnly && market.crosses(order.side, order.price) {
+            let error = ConnectorError::Rejected("Post-only order would cross".to_string());
+            return (Err(error), Vec::new());
+        }
+
+        let takes = market.available(order.side, order.price, order.quantity);
+        let left = takes
+            .iter()
+            .try_fold(order.quantity, |left, (_, quantity)| {
+                left.checked_sub(*quantity)
+            })
+            .unwrap_or_default();
+        if order.time_in_force == TimeInForce::Fok && !left.is_zero() {
+            let error = ConnectorError::Rejected("Fill-or-kill order can't fill in full".into());
+            return (Err(error), Vec::new());
+        }
+        market.consume(order.side, &takes);
+
+        let rests = matches!(
+            order.time_in_force,
+            TimeInForce::Gtc | TimeInForce::PostOnly
+        );
+        let status = if left.is_zero() {
+            VenueOrderStatus::Filled
+        } else if !rests {
+            VenueOrderStatus::Cancelled
+        } else if left < order.quantity {
+            VenueOrderStatus::PartiallyFilled
+        } else {
+            VenueOrderStatus::Open
+        };
+        self.next_id += 1;
+        let order_id = format!("sim-{}", self.next_id);
+        if rests && !left.is_zero() {
+            let queue_ahead = market.size_at(order.side, order.price);
+            market.resting.push(RestingOrder {
+                order_id: order_id.clone(),
+                side: order.side,
+                price: order.price,
+                remaining: left,
+                queue_ahead,
+            });
+        }
+
+        let mut events = Vec::with_capacity(takes.len() + 1);
+        for (price, quantity) in takes {
+            let fill = SimFill {
+                venue,
+                instrument: order.instrument.clone(),
+                order_id: order_id.clone(),
+                side: order.side,
+                price,
+                quantity,
+                fee: Decimal::ZERO,
+                maker: false,
+                timestamp_ms: now_ms,
+            };
+            events.push(self.execute(fill));
+        }
+        events.push(MarketEvent::OrderUpdate {
+            venue,
+            instrument: order.instrument.clone(),
+            order_id: order_id.clone(),
+            status,
+            remaining: if rests { left } else { Decimal::ZERO },
+            timestamp_ms: now_ms,
+        });
+
+        let ack = OrderAck {
+            order_id,
+            local_id: None,
+            client_order_id: order.client_order_id.clone(),
+        };
+        (Ok(ack), events)
+    }
+
+    pub fn cancel(
+        &mut self,
+        venue: Venue,
+        cancel: &CancelRequest,
+        now_ms: u64,
+    ) -> Result<Vec<MarketEvent>, ConnectorError> {
+        let not_found = || ConnectorError::NotFound(format!("No open order {}", cancel.order_id));
+        let market = self
+            .markets
+            .get_mut(&(venue, cancel.instrument.clone()))
+            .ok_or_else(not_found)?;
+        let position = market
+            .resting
+            .iter()
+            .position(|order| order.order_id == cancel.order_id)
+            .ok_or_else(not_found)?;
+        market.resting.remove(position);
+        Ok(vec![MarketEvent::OrderUpdate {
+            venue,
+            instrument: cancel.instrument.clone(),
+            order_id: cancel.order_id.clone(),
+            status: VenueOrderStatus::Cancelled,
+            remaining: Decimal::ZERO,
+            timestamp_ms: now_ms,
+        }])
+    }
+
+    // Every resting order on the venue, or on one of its instruments
+    pub fn cancel_all(
+        &mut self,
+        venue: Venue,
+        instrument: Option<&str>,
+        now_ms: u64,
+    ) -> Vec<MarketEvent> {
+        let mut events = Vec::new();
+        for ((market_venue, market_instrument), market) in &mut self.markets {
+            if *market_venue != venue || instrument.is_some_and(|i| i != market_instrument) {
+                continue;
+            }
+            for order in market.resting.drain(..) {
+                events.push(MarketEvent::OrderUpdate {
+                    venue,
+                    instrument: market_instrument.clone(),
+                    order_id: order.order_id,
+                    status: VenueOrderStatus::Cancelled,
+                    remaining: Decimal::ZERO,
+                    timestamp_ms: now_ms,
+                });
+            }
+        }
+        events
+    }
+
+    /// Apply one recorded event and return the fills and updates it caused.
+    pub fn on_market(&mut self, event: &MarketEvent) -> Vec<MarketEvent> {
+        let key = (event.venue(), event.instrument().to_string());
+        let market = self.markets.entry(key.clone()).or_default();
+        // In time priority
+        let mut executions: Vec<Execution> = Vec::new();
+
+        match event {
+            MarketEvent::Quote {
+                bid_price,
+                bid_quantity,
+                ask_price,
+                ask_quantity,
+                ..
+            } => {
+                let before = market.consumed_levels();
+                market.best_bid = Some(BookLevel {
+                    price: *bid_price,
+                    quantity: *bid_quantity,
+                });
+                market.best_ask = Some(BookLevel {
+                    price: *ask_price,
+                    quantity: *ask_quantity,
+                });
+                market.release_changed(before);
+            }
+            MarketEvent::BookUpdate {
+                bids,
+                asks,
+                snapshot,
+                ..
+            } => {
+                let before = market.consumed_levels();
+                market.book.apply(bids, asks, *snapshot);
+                if let Some(bid) = market.book.best_bid() {
+                    market.best_bid = Some(bid);
+                }
+                if let Some(ask) = market.book.best_ask() {
+                    market.best_ask = Some(ask);
+                }
+                market.release_changed(before);
+                // Size leaving a level is assumed to come from ahead of the simulated order
+                let sizes: Vec<Decimal> = market
+                    .resting
+                    .iter()
+                    .map(|order| market.size_at(order.side, order.price))
+                    .collect();
+                for (order, size) in market.resting.iter_mut().zip(sizes) {
+                    order.queue_ahead = order.queue_ahead.min(size);
+                }
+            }
+            MarketEvent::Trade {
+                price,
+                quantity,
+                taker_side,
+                ..
+            } => {
+                let mut volume = *quantity;
+                for (i, order) in market.resting.iter_mut().enumerate() {
+                    if order.side == *taker_side {
+                        continue;
+                    }
+                    let through = match order.side {
+                        Side::Buy => *price < order.price,
+                        Side::Sell => *price > order.price,
+                    };
+                    if through && volume > Decimal::ZERO {
+                        // A print through the order fills it only up to the traded volume
+                        let filled = volume.min(order.remaining);
+                        volume = volume.checked_sub(filled).unwrap_or_default();
+                        executions.push(Execution {
+                            index: i,
+                            price: order.price,
+                            quantity: filled,
+                            maker: true,
+                        });
+                    } else if *price == order.price && volume > Decimal::ZERO {
+                        // The trade works through the queue ahead before reaching the order
+                        let reached = volume
+                            .checked_sub(order.queue_ahead)
+                            .unwrap_or_default()
+                            .max(Decimal::ZERO);
+                        order.queue_ahead = order
+                            .queue_ahead
+                            .checked_sub(volume)
+                            .unwrap_or_default()
+                            .max(Decimal::ZERO);
+                        let filled = reached.min(order.remaining);
+                        volume = volume.checked_sub(filled).unwrap_or_default();
+                        if filled > Decimal::ZERO {
+                            executions.push(Execution {
+                                index: i,
+                                price: order.price,
+                                quantity: filled,
+                                maker: true,
+                            });
+                        }
+                    }
+                }
+            }
+            MarketEvent::Fill { .. } | MarketEvent::OrderUpdate { .. } => return Vec::new(),
+        }
+
+        // A quote or book through a resting order leaves it crossing the recorded depth, which
+        // it takes like an incoming order, short of what simulated orders already took
+        if !matches!(event, MarketEvent::Trade { .. }) {
+            for i in 0..market.resting.len() {
+                let order = &market.resting[i];
+                if !market.crosses(order.side, order.price) {
+                    continue;
+                }
+                let (side, price, remaining) = (order.side, order.price, order.remaining);
+                let takes = market.available(side, price, remaining);
+                market.consume(side, &takes);
+                for (price, quantity) in takes {
+                    executions.push(Execution {
+                        index: i,
+                        price,
+                        quantity,
+                        maker: false,
+                    });
+                }
+            }
+        }
+        self.fill_resting(&key, executions, event.timestamp_ms())
+    }
+
+    fn fill_resting(
+        &mut self,
+        key: &(Venue, String),
+        executions: Vec<Execution>,
+        now_ms: u64,
+    ) -> Vec<MarketEvent> {
+        let Some(market) = self.markets.get_mut(key) else {
+            return Vec::new();
+        };
+        let mut filled = Vec::with_capacity(executions.len());
+        for execution in executions {
+            let order = &mut market.resting[execution.index];
+            order.remaining = order
+                .remaining
+                .checked_sub(execution.quantity)
+                .unwrap_or_default();
+            filled.push((order.clone(), execution));
+        }
+        market
+            .resting
+            .retain(|order| order.remaining > Decimal::ZERO);
+
+        let mut events = Vec::with_capacity(filled.len() * 2);
+        for (order, execution) in filled {
+            let fill = SimFill {
+                venue: key.0,
+                instrument: key.1.clone(),
+                order_id: order.order_id.clone(),
+                side: order.side,
+                price: execution.price,
+                quantity: execution.quantity,
+                fee: Decimal::ZERO,
+                maker: execution.maker,
+                timestamp_ms: now_ms,
+            };
+            events.push(self.execute(fill));
+            events.push(MarketEvent::OrderUpdate {
+                venue: key.0,
+                instrument: key.1.clone(),
+                order_id: order.order_id,
+                status: if order.remaining > Decimal::ZERO {
+                    VenueOrderStatus::PartiallyFilled
+                } else {
+                    VenueOrderStatus::Filled
+                },
+                remaining: order.remaining,
+                timestamp_ms: now_ms,
+            });
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INSTRUMENT: &str = "BTC-PERP";
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn level(price: &str, quantity: &str) -> BookLevel {
+        BookLevel {
+            price: dec(price),
+            quantity: dec(quantity),
+        }
+    }
+
+    fn exchange() -> SimExchange {
+        let fees = FeeRates {
+            maker: dec("-0.0001"),
+            taker: dec("0.001"),
+        };
+        SimExchange::new(HashMap::from([(
+            (Venue::Vertex, INSTRUMENT.to_string()),
+            fees,
+        )]))
+    }
+
+    fn book(bids: Vec<BookLevel>, asks: Vec<BookLevel>, snapshot: bool) -> MarketEvent {
+        MarketEvent::BookUpdate {
+            venue: Venue::Vertex,
+            instrument: INSTRUMENT.to_string(),
+            bids,
+            asks,
+            snapshot,
+            timestamp_ms: 0,
+        }
+    }
+
+    fn quote(bid: &str, ask: &str) -> MarketEvent {
+        MarketEvent::Quote {
+            venue: Venue::Vertex,
+            instrument: INSTRUMENT.to_string(),
+            bid_price: dec(bid),
+            bid_quantity: dec("1"),
+            ask_price: dec(ask),
+            ask_quantity: dec("1"),
+            timestamp_ms: 0,
+        }
+    }
+
+    fn buy(price: &str, quantity: &str) -> OrderRequest {
+        OrderRequest {
+            instrument: INSTRUMENT.to_string(),
+            side: Side::Buy,
+            price: dec(price),
+            quantity: dec(quantity),
+            time_in_force: TimeInForce::Gtc,
+            client_order_id: None,
+        }
+    }
+
+    #[test]
+    fn a_remainder_that_walked_the_book_is_not_refilled_from_the_same_depth() {
+        let mut exchange = exchange();
+        let asks = vec![level("100", "1"), level("101", "1")];
+        exchange.on_market(&book(vec![level("99", "1")], asks, true));
+
+        let (ack, _) = exchange.place(Venue::Vertex, &buy("101", "3"), 0);
+        assert!(ack.is_ok());
+        assert_eq!(exchange.take_fills().len(), 2);
+
+        // The recording still shows the depth the order took
+        exchange.on_market(&quote("99", "100"));
+        exchange.on_market(&book(
+            vec![level("99", "1")],
+            vec![level("100", "1")],
+            false,
+        ));
+        assert!(exchange.take_fills().is_empty());
+    }
+
+    #[test]
+    fn a_resting_order_the_book_moves_through_takes_the_fresh_depth_as_taker() {
+        let mut exchange = exchange();
+        exchange.on_market(&book(vec![level("99", "1")], vec![level("101", "1")], true));
+        let (ack, _) = exchange.place(Venue::Vertex, &buy("100", "2"), 0);
+        let order_id = ack.unwrap().order_id;
+
+        exchange.on_market(&book(vec![], vec![level("99.5", "1.5")], false));
+        let fills = exchange.take_fills();
+        assert_eq!(fills.len(), 1);
+        let fill = &fills[0];
+        assert_eq!(fill.order_id, order_id);
+        assert_eq!((fill.price, fill.quantity), (dec("99.5"), dec("1.5")));
+        assert!(!fill.maker);
+        assert_eq!(fill.fee, dec("0.14925"));
+
+        // Nothing is left at that level until the recording changes it
+        exchange.on_market(&quote("99", "99.5"));
+        assert!(exchange.take_fills().is_empty());
+    }
+
+    #[test]
+    fn a_trade_through_a_resting_order_fills_no_more_than_it_printed() {
+        let mut exchange = exchange();
+        exchange.on_market(&book(vec![level("99", "1")], vec![level("101", "1")], true));
+        let (ack, _) = exchange.place(Venue::Vertex, &buy("100", "2"), 0);
+        let order_id = ack.unwrap().order_id;
+
+        let trade = |quantity: &str| MarketEvent::Trade {
+            venue: Venue::Vertex,
+            instrument: INSTRUMENT.to_string(),
+            price: dec("99.5"),
+            quantity: dec(quantity),
+            taker_side: Side::Sell,
+            timestamp_ms: 0,
+        };
+        exchange.on_market(&trade("0.5"));
+        let fills = exchange.take_fills();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].order_id, order_id);
+        assert_eq!(
+            (fills[0].price, fills[0].quantity),
+            (dec("100"), dec("0.5"))
+        );
+        assert!(fills[0].maker);
+
+        // A larger print takes only what is left of the order
+        exchange.on_market(&trade("3"));
+        let fills = exchange.take_fills();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].quantity, dec("1.5"));
+    }
+}
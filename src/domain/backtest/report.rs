@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+
+use crate::{domain::models::trading::Venue, shared::utils::decimal::Decimal};
+
+use super::exchange::SimFill;
+
+// At most one equity point per this much simulated time; drawdown still sees every change
+const CURVE_INTERVAL_MS: u64 = 60_000;
+
+#[derive(Debug, Clone)]
+pub struct InstrumentResult {
+    pub venue: Venue,
+    pub instrument: String,
+    // Signed, positive long
+    pub position: Decimal,
+    pub entry_price: Decimal,
+    pub mark_price: Option<Decimal>,
+    pub realized_pnl: Decimal,
+    pub fees: Decimal,
+    pub fills: u64,
+    pub volume: Decimal,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EquityPoint {
+    pub timestamp_ms: u64,
+    pub equity: Decimal,
+}
+
+/// Outcome of one backtest, in quote currency.
+#[derive(Debug, Clone, Default)]
+pub struct BacktestReport {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    // Recorded events replayed
+    pub events: u64,
+    pub orders_sent: u64,
+    pub orders_rejected: u64,
+    pub fills: u64,
+    pub maker_fills: u64,
+    pub taker_fills: u64,
+    // Base quantity and quote notional traded
+    pub volume: Decimal,
+    pub notional: Decimal,
+    pub fees: Decimal,
+    pub realized_pnl: Decimal,
+    pub unrealized_pnl: Decimal,
+    // Realized plus unrealized, net of fees
+    pub total_pnl: Decimal,
+    // Largest fall of total PnL from a previous high
+    pub max_drawdown: Decimal,
+    pub instruments: Vec<InstrumentResult>,
+    pub equity_curve: Vec<EquityPoint>,
+}
+
+// Average-price position accounting per instrument plus the running equity curve
+#[derive(Debug, Default)]
+pub struct Ledger {
+    instruments: HashMap<(Venue, String), InstrumentResult>,
+    report: BacktestReport,
+    peak: Decimal,
+    last_point_ms: Option<u64>,
+}
+
+impl InstrumentResult {
+    fn unrealized(&self) -> Decimal {
+        self.mark_price
+            .and_then(|mark| mark.checked_sub(self.entry_price))
+            .and_then(|change| change.checked_mul(self.position))
+            .unwrap_or_default()
+    }
+
+    fn apply(&mut self, signed: Decimal, price: Decimal) {
+        let position = self.position;
+        let next = position.checked_add(signed).unwrap_or(position);
+        let adds = position.is_zero() || position.is_negative() == signed.is_negative();
+        if adds {
+            // Average the entry over the old and new size
+            self.entry_price = self
+                .entry_price
+                .checked_mul(position.abs())
+                .zip(price.checked_mul(signed.abs()))
+                .and_then(|(old, new)| old.checked_add(new))
+                .and_then(|cost| cost.checked_div(next.abs()))
+                .unwrap_or(price);
+        } else {
+            let closed = signed.abs().min(position.abs());
+            let closed = if position.is_negative() {
+                Decimal::ZERO.checked_sub(closed).unwrap_or_default()
+            } else {
+                closed
+            };
+            let pnl = price
+                .checked_sub(self.entry_price)
+                .and_then(|change| change.checked_mul(closed))
+                .unwrap_or_default();
+            self.realized_pnl = self.realized_pnl.checked_add(pnl).unwrap_or(self.realized_pnl);
+            // A fill larger than the position opens the other way at its own price
+            if next.is_zero() {
+                self.entry_price = Decimal::ZERO;
+            } else if next.is_negative() != position.is_negative() {
+                self.entry_price = price;
+            }
+        }
+        self.position = next;
+    }
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Ledger::default()
+    }
+
+    pub fn on_event(&mut self, timestamp_ms: u64) {
+        if self.report.events == 0 {
+            self.report.start_ms = timestamp_ms;
+        }
+        self.report.events += 1;
+        self.report.end_ms = timestamp_ms;
+    }
+
+    pub fn on_order(&mut self, accepted: bool) {
+        self.report.orders_sent += 1;
+        if !accepted {
+            self.report.orders_rejected += 1;
+        }
+    }
+
+    pub fn on_fill(&mut self, fill: &SimFill) {
+        let report = &mut self.report;
+        report.fills += 1;
+        if fill.maker {
+            report.maker_fills += 1;
+        } else {
+            report.taker_fills += 1;
+        }
+        let notional = fill.price.checked_mul(fill.quantity).unwrap_or_default();
+        report.volume = report.volume.checked_add(fill.quantity).unwrap_or(report.volume);
+        report.notional = report.notional.checked_add(notional).unwrap_or(report.notional);
+
+        let instrument = self
+            .instruments
+            .entry((fill.venue, fill.instrument.clone()))
+            .or_insert_with(|| InstrumentResult {
+                venue: fill.venue,
+                instrument: fill.instrument.clone(),
+                position: Decimal::ZERO,
+                entry_price: Decimal::ZERO,
+                mark_price: None,
+                realized_pnl: Decimal::ZERO,
+                fees: Decimal::ZERO,
+                fills: 0,
+                volume: Decimal::ZERO,
+            });
+        instrument.apply(fill.side.signed(fill.quantity), fill.price);
+        instrument.fills += 1;
+        instrument.volume = instrument.volume.checked_add(fill.quantity).unwrap_or_default();
+        instrument.fees = instrument.fees.checked_add(fill.fee).unwrap_or(instrument.fees);
+        self.update_equity(fill.timestamp_ms);
+    }
+
+    // Mark an instrument the strategy has traded to the market's mid
+    pub fn mark(&mut self, venue: Venue, instrument: &str, mid: Decimal, timestamp_ms: u64) {
+        if let Some(result) = self.instruments.get_mut(&(venue, instrument.to_string())) {
+            result.mark_price = Some(mid);
+            self.update_equity(timestamp_ms);
+        }
+    }
+
+    fn totals(&self) -> (Decimal, Decimal, Decimal) {
+        self.instruments.values().fold(
+            (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO),
+            |(realized, unrealized, fees), result| {
+                (
+                    realized.checked_add(result.realized_pnl).unwrap_or(realized),
+                    unrealized.checked_add(result.unrealized()).unwrap_or(unrealized),
+                    fees.checked_add(result.fees).unwrap_or(fees),
+                )
+            },
+        )
+    }
+
+    fn update_equity(&mut self, timestamp_ms: u64) {
+        let (realized, unrealized, fees) = self.totals();
+        let equity = realized
+            .checked_add(unrealized)
+            .and_then(|pnl| pnl.checked_sub(fees))
+            .unwrap_or_default();
+        self.peak = self.peak.max(equity);
+        let drawdown = self.peak.checked_sub(equity).unwrap_or_default();
+        self.report.max_drawdown = self.report.max_drawdown.max(drawdown);
+
+        let recent = self
+            .last_point_ms
+            .is_some_and(|last| timestamp_ms < last.saturating_add(CURVE_INTERVAL_MS));
+        if !recent {
+            self.report.equity_curve.push(EquityPoint {
+                timestamp_ms,
+                equity,
+            });
+            self.last_point_ms = Some(timestamp_ms);
+        }
+    }
+
+    pub fn finish(mut self) -> BacktestReport {
+        let end_ms = self.report.end_ms;
+        if self.report.fills > 0 {
+            self.last_point_ms = None;
+            self.update_equity(end_ms);
+        }
+        let (realized, unrealized, fees) = self.totals();
+        let mut report = self.report;
+        report.realized_pnl = realized;
+        report.unrealized_pnl = unrealized;
+        report.fees = fees;
+        report.total_pnl = realized
+            .checked_add(unrealized)
+            .and_then(|pnl| pnl.checked_sub(fees))
+            .unwrap_or_default();
+        report.instruments = self.instruments.into_values().collect();
+        report
+            .instruments
+            .sort_by_key(|result| (result.venue.to_string(), result.instrument.clone()));
+        report
+    }
+}
@@ -1,2 +1,8 @@
+pub mod algos;
+pub mod backtest;
 pub mod models;
+pub mod oms;
+pub mod positions;
+pub mod risk;
+pub mod routing;
 pub mod strategies;
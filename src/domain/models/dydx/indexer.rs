@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::shared::utils::decimal::Decimal;
+
+// The indexer sends most integers as strings, but not consistently
+fn string_or_number<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Str(String),
+        Num(i64),
+    }
+
+    let raw = match Raw::deserialize(deserializer)? {
+        Raw::Str(s) => s,
+        Raw::Num(n) => n.to_string(),
+    };
+    raw.parse().map_err(serde::de::Error::custom)
+}
+
+// `GET /v4/height`
+#[derive(Debug, Clone, Deserialize)]
+pub struct HeightResponse {
+    #[serde(deserialize_with = "string_or_number")]
+    pub height: u32,
+}
+
+// `GET /v4/perpetualMarkets`, keyed by ticker
+#[derive(Debug, Clone, Deserialize)]
+pub struct PerpetualMarketsResponse {
+    pub markets: HashMap<String, PerpetualMarket>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerpetualMarket {
+    pub ticker: String,
+    #[serde(deserialize_with = "string_or_number")]
+    pub clob_pair_id: u32,
+    pub status: String,
+    pub tick_size: Decimal,
+    pub step_size: Decimal,
+    // Quantums are base units scaled by 10^-atomic_resolution
+    pub atomic_resolution: i32,
+    pub quantum_conversion_exponent: i32,
+    #[serde(deserialize_with = "string_or_number")]
+    pub step_base_quantums: u64,
+    #[serde(deserialize_with = "string_or_number")]
+    pub subticks_per_tick: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum IndexerSide {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum IndexerOrderStatus {
+    Open,
+    Filled,
+    Canceled,
+    BestEffortCanceled,
+    BestEffortOpened,
+    Untriggered,
+}
+
+// `GET /v4/orders` entries and order updates on the subaccounts channel
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexerOrder {
+    // Indexer uuid, only used to match fills back to their order
+    pub id: String,
+    #[serde(deserialize_with = "string_or_number")]
+    pub client_id: u32,
+    #[serde(deserialize_with = "string_or_number")]
+    pub order_flags: u32,
+    pub ticker: String,
+    pub side: IndexerSide,
+    pub size: Decimal,
+    #[serde(default)]
+    pub total_filled: Decimal,
+    pub price: Decimal,
+    pub status: IndexerOrderStatus,
+}
+
+// `GET /v4/addresses/{address}/subaccountNumber/{n}`
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubaccountResponse {
+    pub subaccount: Subaccount,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Subaccount {
+    pub free_collateral: Decimal,
+    #[serde(default)]
+    pub open_perpetual_positions: HashMap<String, PerpetualPosition>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerpetualPosition {
+    pub market: String,
+    // Signed, negative when short
+    pub size: Decimal,
+    pub entry_price: Decimal,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexerFill {
+    pub side: IndexerSide,
+    pub size: Decimal,
+    pub price: Decimal,
+    pub fee: Decimal,
+    // Indexer uuid of the filled order
+    #[serde(default)]
+    pub order_id: Option<String>,
+    #[serde(alias = "market")]
+    pub ticker: String,
+}
+
+// Cosmos SDK `GET /cosmos/auth/v1beta1/accounts/{address}`
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccountResponse {
+    pub account: BaseAccount,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BaseAccount {
+    #[serde(deserialize_with = "string_or_number")]
+    pub account_number: u64,
+    #[serde(deserialize_with = "string_or_number")]
+    pub sequence: u64,
+}
+
+// Cosmos SDK `POST /cosmos/tx/v1beta1/txs`
+#[derive(Debug, Clone, Deserialize)]
+pub struct BroadcastResponse {
+    pub tx_response: TxResponse,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TxResponse {
+    pub txhash: String,
+    // Zero on success
+    pub code: u32,
+    #[serde(default)]
+    pub raw_log: String,
+}
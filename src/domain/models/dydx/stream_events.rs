@@ -0,0 +1,89 @@
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+
+use crate::shared::utils::decimal::Decimal;
+
+use super::indexer::{IndexerFill, IndexerOrder, IndexerSide};
+
+// Every indexer websocket frame; `contents` depends on the channel
+#[derive(Debug, Clone, Deserialize)]
+pub struct StreamMessage {
+    #[serde(rename = "type")]
+    pub message_type: String,
+    #[serde(default)]
+    pub channel: Option<String>,
+    // Ticker for market channels, "address/number" for subaccounts
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub contents: serde_json::Value,
+}
+
+impl StreamMessage {
+    // "subscribed" carries the initial snapshot, "channel_data" the updates after it
+    pub fn is_snapshot(&self) -> bool {
+        self.message_type == "subscribed"
+    }
+}
+
+// Snapshots send levels as objects, updates as [price, size] pairs
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum StreamLevel {
+    Object { price: Decimal, size: Decimal },
+    Pair(Decimal, Decimal),
+}
+
+impl StreamLevel {
+    pub fn price_size(&self) -> (Decimal, Decimal) {
+        match self {
+            StreamLevel::Object { price, size } => (*price, *size),
+            StreamLevel::Pair(price, size) => (*price, *size),
+        }
+    }
+}
+
+// `v4_orderbook` contents
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OrderbookContents {
+    #[serde(default)]
+    pub bids: Vec<StreamLevel>,
+    #[serde(default)]
+    pub asks: Vec<StreamLevel>,
+}
+
+// `v4_trades` contents
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TradesContents {
+    #[serde(default)]
+    pub trades: Vec<StreamTrade>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StreamTrade {
+    // Taker side
+    pub side: IndexerSide,
+    pub size: Decimal,
+    pub price: Decimal,
+}
+
+// `v4_subaccounts` contents; only the parts the connector maps
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SubaccountContents {
+    #[serde(default, deserialize_with = "skip_partial")]
+    pub orders: Vec<IndexerOrder>,
+    #[serde(default, deserialize_with = "skip_partial")]
+    pub fills: Vec<IndexerFill>,
+}
+
+// Some updates only carry the fields that changed; those entries are dropped, not the message
+fn skip_partial<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let values = Vec::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(values
+        .into_iter()
+        .filter_map(|value| serde_json::from_value(value).ok())
+        .collect())
+}
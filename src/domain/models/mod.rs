@@ -1 +1,5 @@
+pub mod dydx;
+pub mod order_book;
+pub mod orderly;
+pub mod trading;
 pub mod vertex;
@@ -0,0 +1,61 @@
+use std::collections::BTreeMap;
+
+use crate::shared::utils::decimal::Decimal;
+
+use super::trading::BookLevel;
+
+/// Price-level book rebuilt from snapshot and incremental depth updates.
+///
+/// For venues that stream depth but no top-of-book, so quotes can be derived locally.
+#[derive(Debug, Clone, Default)]
+pub struct OrderBook {
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+}
+
+impl OrderBook {
+    // A snapshot replaces both sides; zero quantities remove their level
+    pub fn apply(&mut self, bids: &[BookLevel], asks: &[BookLevel], snapshot: bool) {
+        if snapshot {
+            self.bids.clear();
+            self.asks.clear();
+        }
+        apply_levels(&mut self.bids, bids);
+        apply_levels(&mut self.asks, asks);
+    }
+
+    pub fn best_bid(&self) -> Option<BookLevel> {
+        self.bids.iter().next_back().map(to_level)
+    }
+
+    pub fn best_ask(&self) -> Option<BookLevel> {
+        self.asks.iter().next().map(to_level)
+    }
+
+    // Best first
+    pub fn bids(&self) -> impl Iterator<Item = BookLevel> + '_ {
+        self.bids.iter().rev().map(to_level)
+    }
+
+    // Best first
+    pub fn asks(&self) -> impl Iterator<Item = BookLevel> + '_ {
+        self.asks.iter().map(to_level)
+    }
+}
+
+fn apply_levels(side: &mut BTreeMap<Decimal, Decimal>, levels: &[BookLevel]) {
+    for level in levels {
+        if level.quantity.is_zero() {
+            side.remove(&level.price);
+        } else {
+            side.insert(level.price, level.quantity);
+        }
+    }
+}
+
+fn to_level((price, quantity): (&Decimal, &Decimal)) -> BookLevel {
+    BookLevel {
+        price: *price,
+        quantity: *quantity,
+    }
+}
@@ -0,0 +1,2 @@
+pub mod rest;
+pub mod stream_events;
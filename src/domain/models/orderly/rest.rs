@@ -0,0 +1,81 @@
+use serde::Deserialize;
+
+use crate::shared::utils::decimal::Decimal;
+
+// Envelope of every Orderly REST response
+#[derive(Debug, Clone, Deserialize)]
+pub struct OrderlyResponse<T> {
+    pub success: bool,
+    pub data: Option<T>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+// Paged list responses carry their items in `rows`
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rows<T> {
+    pub rows: Vec<T>,
+}
+
+// `GET /v1/public/info`
+#[derive(Debug, Clone, Deserialize)]
+pub struct SymbolInfo {
+    // e.g. "PERP_ETH_USDC"
+    pub symbol: String,
+    pub quote_tick: Decimal,
+    pub base_tick: Decimal,
+    pub base_min: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OrderlySide {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OrderlyOrderStatus {
+    New,
+    PartialFilled,
+    Filled,
+    Cancelled,
+    Rejected,
+}
+
+// `POST /v1/order`
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlacedOrder {
+    pub order_id: u64,
+}
+
+// `GET /v1/orders`
+#[derive(Debug, Clone, Deserialize)]
+pub struct OrderlyOrder {
+    pub order_id: u64,
+    pub symbol: String,
+    pub side: OrderlySide,
+    // Market orders have no price
+    #[serde(default)]
+    pub price: Option<Decimal>,
+    pub quantity: Decimal,
+    #[serde(default)]
+    pub executed: Decimal,
+    pub status: OrderlyOrderStatus,
+}
+
+// `GET /v1/positions`, with the account's margin alongside the rows
+#[derive(Debug, Clone, Deserialize)]
+pub struct PositionsData {
+    pub free_collateral: Decimal,
+    pub rows: Vec<OrderlyPosition>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OrderlyPosition {
+    pub symbol: String,
+    // Signed, negative when short
+    pub position_qty: Decimal,
+    pub average_open_price: Decimal,
+}
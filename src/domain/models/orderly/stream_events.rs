@@ -0,0 +1,70 @@
+use serde::Deserialize;
+
+use crate::shared::utils::decimal::Decimal;
+
+use super::rest::{OrderlyOrderStatus, OrderlySide};
+
+// Every websocket frame; data frames carry a topic, control frames an event
+#[derive(Debug, Clone, Deserialize)]
+pub struct StreamMessage {
+    #[serde(default)]
+    pub topic: Option<String>,
+    #[serde(default)]
+    pub event: Option<String>,
+    #[serde(default)]
+    pub ts: u64,
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+// `{symbol}@orderbook` snapshots and `{symbol}@orderbookupdate` deltas
+#[derive(Debug, Clone, Deserialize)]
+pub struct OrderbookData {
+    pub symbol: String,
+    #[serde(default)]
+    pub bids: Vec<[Decimal; 2]>,
+    #[serde(default)]
+    pub asks: Vec<[Decimal; 2]>,
+}
+
+// `{symbol}@bbo`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BboData {
+    pub symbol: String,
+    pub bid: Decimal,
+    pub bid_size: Decimal,
+    pub ask: Decimal,
+    pub ask_size: Decimal,
+}
+
+// `{symbol}@trade`
+#[derive(Debug, Clone, Deserialize)]
+pub struct TradeData {
+    pub symbol: String,
+    pub price: Decimal,
+    pub size: Decimal,
+    // Taker side
+    pub side: OrderlySide,
+}
+
+// Private `executionreport`: one per order state change or fill
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionReport {
+    pub symbol: String,
+    pub order_id: u64,
+    pub side: OrderlySide,
+    pub quantity: Decimal,
+    #[serde(default)]
+    pub total_executed_quantity: Decimal,
+    // Only non-zero on reports for a fill
+    #[serde(default)]
+    pub executed_quantity: Decimal,
+    #[serde(default)]
+    pub executed_price: Decimal,
+    #[serde(default)]
+    pub fee: Decimal,
+    pub status: OrderlyOrderStatus,
+    pub timestamp: u64,
+}
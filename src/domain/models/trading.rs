@@ -0,0 +1,246 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::shared::utils::decimal::Decimal;
+
+// Venue-neutral trading models shared by every connector
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Venue {
+    Vertex,
+    Dydx,
+    Orderly,
+}
+
+impl fmt::Display for Venue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Venue::Vertex => "vertex",
+            Venue::Dydx => "dydx",
+            Venue::Orderly => "orderly",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Venue {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "vertex" => Ok(Venue::Vertex),
+            "dydx" => Ok(Venue::Dydx),
+            "orderly" => Ok(Venue::Orderly),
+            other => Err(format!("unknown venue: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    // Apply the side to an unsigned quantity, sells negative
+    pub fn signed(self, quantity: Decimal) -> Decimal {
+        match self {
+            Side::Buy => quantity.abs(),
+            Side::Sell => Decimal::ZERO
+                .checked_sub(quantity.abs())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn opposite(self) -> Side {
+        match self {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        }
+    }
+
+    pub fn of(amount: Decimal) -> Side {
+        if amount.is_negative() {
+            Side::Sell
+        } else {
+            Side::Buy
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeInForce {
+    #[default]
+    Gtc,
+    Ioc,
+    Fok,
+    PostOnly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VenueOrderStatus {
+    Open,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Rejected,
+}
+
+// Tradable market as the venue defines it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Instrument {
+    pub venue: Venue,
+    // Venue symbol, e.g. "BTC-PERP" on Vertex or "BTC-USD" on dYdX
+    pub symbol: String,
+    pub tick_size: Decimal,
+    pub step_size: Decimal,
+    pub min_size: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderRequest {
+    pub instrument: String,
+    pub side: Side,
+    pub price: Decimal,
+    // Unsigned, the side gives the direction
+    pub quantity: Decimal,
+    pub time_in_force: TimeInForce,
+    pub client_order_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderAck {
+    // Venue order id (digest on Vertex), used to cancel
+    pub order_id: String,
+    // Local OMS id when the venue's orders are tracked by the order manager
+    pub local_id: Option<u64>,
+    pub client_order_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelRequest {
+    pub instrument: String,
+    pub order_id: String,
+}
+
+// Resting order as reported by the venue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VenueOrder {
+    pub order_id: String,
+    pub instrument: String,
+    pub side: Side,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub remaining: Decimal,
+    pub status: VenueOrderStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VenuePosition {
+    pub instrument: String,
+    // Signed, positive long and negative short
+    pub amount: Decimal,
+    pub entry_price: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookLevel {
+    pub price: Decimal,
+    // Zero removes the level
+    pub quantity: Decimal,
+}
+
+// Market data and private order events, normalized across venues
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MarketEvent {
+    Quote {
+        venue: Venue,
+        instrument: String,
+        bid_price: Decimal,
+        bid_quantity: Decimal,
+        ask_price: Decimal,
+        ask_quantity: Decimal,
+        timestamp_ms: u64,
+    },
+    Trade {
+        venue: Venue,
+        instrument: String,
+        price: Decimal,
+        quantity: Decimal,
+        taker_side: Side,
+        timestamp_ms: u64,
+    },
+    BookUpdate {
+        venue: Venue,
+        instrument: String,
+        bids: Vec<BookLevel>,
+        asks: Vec<BookLevel>,
+        // True when the levels replace the whole book rather than update it
+        snapshot: bool,
+        timestamp_ms: u64,
+    },
+    Fill {
+        venue: Venue,
+        instrument: String,
+        order_id: String,
+        side: Side,
+        price: Decimal,
+        quantity: Decimal,
+        fee: Decimal,
+        timestamp_ms: u64,
+    },
+    OrderUpdate {
+        venue: Venue,
+        instrument: String,
+        order_id: String,
+        status: VenueOrderStatus,
+        remaining: Decimal,
+        timestamp_ms: u64,
+    },
+}
+
+impl MarketEvent {
+    pub fn venue(&self) -> Venue {
+        match self {
+            MarketEvent::Quote { venue, .. }
+            | MarketEvent::Trade { venue, .. }
+            | MarketEvent::BookUpdate { venue, .. }
+            | MarketEvent::Fill { venue, .. }
+            | MarketEvent::OrderUpdate { venue, .. } => *venue,
+        }
+    }
+
+    pub fn instrument(&self) -> &str {
+        match self {
+            MarketEvent::Quote { instrument, .. }
+            | MarketEvent::Trade { instrument, .. }
+            | MarketEvent::BookUpdate { instrument, .. }
+            | MarketEvent::Fill { instrument, .. }
+            | MarketEvent::OrderUpdate { instrument, .. } => instrument,
+        }
+    }
+
+    pub fn timestamp_ms(&self) -> u64 {
+        match self {
+            MarketEvent::Quote { timestamp_ms, .. }
+            | MarketEvent::Trade { timestamp_ms, .. }
+            | MarketEvent::BookUpdate { timestamp_ms, .. }
+            | MarketEvent::Fill { timestamp_ms, .. }
+            | MarketEvent::OrderUpdate { timestamp_ms, .. } => *timestamp_ms,
+        }
+    }
+    // Public data rather than the account's own order events
+    pub fn is_market_data(&self) -> bool {
+        matches!(
+            self,
+            MarketEvent::Quote { .. } | MarketEvent::Trade { .. } | MarketEvent::BookUpdate { .. }
+        )
+    }
+}
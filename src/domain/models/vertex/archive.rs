@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::shared::utils::decimal::{x18, Decimal};
+
+// Archive (indexer) `candlesticks` query, newest candle first
+#[derive(Debug, Clone, Serialize)]
+pub struct CandlesticksQuery {
+    pub candlesticks: CandlesticksParams,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CandlesticksParams {
+    pub product_id: u32,
+    // Candle length in seconds, e.g. 3600
+    pub granularity: u32,
+    pub limit: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CandlesticksResponse {
+    pub candlesticks: Vec<Candlestick>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Candlestick {
+    // Unix seconds of the candle's open, as a string
+    pub timestamp: String,
+    #[serde(with = "x18")]
+    pub open_x18: Decimal,
+    #[serde(with = "x18")]
+    pub high_x18: Decimal,
+    #[serde(with = "x18")]
+    pub low_x18: Decimal,
+    #[serde(with = "x18")]
+    pub close_x18: Decimal,
+    // Base quantity traded, X18
+    #[serde(with = "x18")]
+    pub volume: Decimal,
+}
+
+// Archive `funding_rate` query for one perp
+#[derive(Debug, Clone, Serialize)]
+pub struct FundingRateQuery {
+    pub funding_rate: FundingRateParams,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FundingRateParams {
+    pub product_id: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FundingRate {
+    // Latest 24h funding rate; positive when longs pay shorts
+    #[serde(with = "x18")]
+    pub funding_rate_x18: Decimal,
+}
+
+// Archive `market_snapshots` query: `count` snapshots `granularity` seconds apart up to
+// `max_time`, newest first
+#[derive(Debug, Clone, Serialize)]
+pub struct MarketSnapshotsQuery {
+    pub market_snapshots: MarketSnapshotsParams,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MarketSnapshotsParams {
+    pub interval: SnapshotInterval,
+    pub product_ids: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotInterval {
+    pub count: u32,
+    pub granularity: u32,
+    // Unix seconds
+    pub max_time: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MarketSnapshotsResponse {
+    pub snapshots: Vec<MarketSnapshot>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MarketSnapshot {
+    // Unix seconds, as a string
+    pub timestamp: String,
+    // 24h funding rate by product id, X18; perps only
+    #[serde(default)]
+    pub funding_rates: HashMap<String, String>,
+}
@@ -1 +1,4 @@
+pub mod archive;
 pub mod sol_structs;
+pub mod stream_events;
+pub mod subaccount;
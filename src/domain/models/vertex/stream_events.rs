@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shared::utils::decimal::{x18, Decimal};
+
+// Events pushed by the vertex subscription websocket, tagged by their "type" field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    OrderUpdate(OrderUpdate),
+    Fill(Fill),
+    PositionChange(PositionChange),
+    BestBidOffer(BestBidOffer),
+    Trade(Trade),
+    BookDepth(BookDepth),
+}
+
+impl StreamEvent {
+    pub fn product_id(&self) -> u32 {
+        match self {
+            StreamEvent::OrderUpdate(e) => e.product_id,
+            StreamEvent::Fill(e) => e.product_id,
+            StreamEvent::PositionChange(e) => e.product_id,
+            StreamEvent::BestBidOffer(e) => e.product_id,
+            StreamEvent::Trade(e) => e.product_id,
+            StreamEvent::BookDepth(e) => e.product_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderUpdateReason {
+    Placed,
+    Filled,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderUpdate {
+    pub timestamp: String,
+    pub product_id: u32,
+    pub digest: String,
+    // Unfilled amount left on the book, signed like the order
+    #[serde(with = "x18")]
+    pub amount: Decimal,
+    pub reason: OrderUpdateReason,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+    pub timestamp: String,
+    pub product_id: u32,
+    pub subaccount: String,
+    pub order_digest: String,
+    #[serde(with = "x18")]
+    pub filled_qty: Decimal,
+    #[serde(with = "x18")]
+    pub remaining_qty: Decimal,
+    #[serde(with = "x18")]
+    pub original_qty: Decimal,
+    #[serde(with = "x18")]
+    pub price: Decimal,
+    pub is_taker: bool,
+    pub is_bid: bool,
+    #[serde(default, with = "x18")]
+    pub fee: Decimal,
+}
+
+impl Fill {
+    // Exchange timestamps are unix nanoseconds
+    pub fn timestamp_secs(&self) -> Option<u64> {
+        self.timestamp
+            .parse::<u128>()
+            .ok()
+            .map(|nanos| (nanos / 1_000_000_000) as u64)
+    }
+
+    // Position delta of this fill, positive when we bought
+    pub fn signed_qty(&self) -> Decimal {
+        let qty = self.filled_qty.abs();
+        if self.is_bid {
+            qty
+        } else {
+            Decimal::ZERO.checked_sub(qty).unwrap_or_default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionChange {
+    pub timestamp: String,
+    pub product_id: u32,
+    pub subaccount: String,
+    #[serde(default)]
+    pub is_lp: bool,
+    #[serde(with = "x18")]
+    pub amount: Decimal,
+    #[serde(with = "x18")]
+    pub v_quote_amount: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BestBidOffer {
+    pub timestamp: String,
+    pub product_id: u32,
+    #[serde(with = "x18")]
+    pub bid_price: Decimal,
+    #[serde(with = "x18")]
+    pub bid_qty: Decimal,
+    #[serde(with = "x18")]
+    pub ask_price: Decimal,
+    #[serde(with = "x18")]
+    pub ask_qty: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trade {
+    pub timestamp: String,
+    pub product_id: u32,
+    #[serde(with = "x18")]
+    pub price: Decimal,
+    #[serde(with = "x18")]
+    pub taker_qty: Decimal,
+    #[serde(with = "x18")]
+    pub maker_qty: Decimal,
+    pub is_taker_buyer: bool,
+}
+
+// Incremental book update; a level with zero quantity has been removed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookDepth {
+    pub min_timestamp: String,
+    pub max_timestamp: String,
+    pub last_max_timestamp: String,
+    pub product_id: u32,
+    pub bids: Vec<[String; 2]>,
+    pub asks: Vec<[String; 2]>,
+}
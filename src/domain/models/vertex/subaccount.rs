@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shared::utils::decimal::{x18, Decimal};
+
+// Envelope shared by gateway query responses
+#[derive(Debug, Clone, Deserialize)]
+pub struct GatewayQueryResponse<T> {
+    pub status: String,
+    pub data: Option<T>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+// `subaccount_orders` query: resting orders of one subaccount on one product
+#[derive(Debug, Clone, Serialize)]
+pub struct SubaccountOrdersQuery<'a> {
+    #[serde(rename = "type")]
+    pub query_type: &'static str,
+    pub sender: &'a str,
+    pub product_id: u32,
+}
+
+impl<'a> SubaccountOrdersQuery<'a> {
+    pub fn new(sender: &'a str, product_id: u32) -> Self {
+        SubaccountOrdersQuery {
+            query_type: "subaccount_orders",
+            sender,
+            product_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubaccountOrders {
+    pub orders: Vec<RestingOrder>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RestingOrder {
+    pub product_id: u32,
+    #[serde(with = "x18")]
+    pub price_x18: Decimal,
+    // Signed original size
+    #[serde(with = "x18")]
+    pub amount: Decimal,
+    // Still resting, signed like `amount`
+    #[serde(with = "x18")]
+    pub unfilled_amount: Decimal,
+    pub expiration: String,
+    pub digest: String,
+}
+
+// `subaccount_info` query: balances and health of one subaccount
+#[derive(Debug, Clone, Serialize)]
+pub struct SubaccountInfoQuery<'a> {
+    #[serde(rename = "type")]
+    pub query_type: &'static str,
+    pub subaccount: &'a str,
+}
+
+impl<'a> SubaccountInfoQuery<'a> {
+    pub fn new(subaccount: &'a str) -> Self {
+        SubaccountInfoQuery {
+            query_type: "subaccount_info",
+            subaccount,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubaccountInfo {
+    pub exists: bool,
+    #[serde(default)]
+    pub spot_balances: Vec<ProductBalance>,
+    #[serde(default)]
+    pub perp_balances: Vec<ProductBalance>,
+    // Initial, maintenance and unweighted health, in that order
+    #[serde(default)]
+    pub healths: Vec<Health>,
+}
+
+impl SubaccountInfo {
+    // Initial health is what new orders draw on
+    pub fn initial_health(&self) -> Option<Decimal> {
+        self.healths.first().map(|h| h.health)
+    }
+
+    // Spot and perp balances together, keyed by product id
+    pub fn balances(&self) -> impl Iterator<Item = &ProductBalance> {
+        self.spot_balances.iter().chain(&self.perp_balances)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProductBalance {
+    pub product_id: u32,
+    pub balance: Balance,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Balance {
+    #[serde(with = "x18")]
+    pub amount: Decimal,
+    // Perp only: quote side of the position, negative of the entry cost for longs
+    #[serde(default, with = "x18")]
+    pub v_quote_balance: Decimal,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Health {
+    #[serde(with = "x18")]
+    pub health: Decimal,
+}
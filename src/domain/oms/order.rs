@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shared::utils::decimal::Decimal;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderState {
+    // Signed and sent, waiting for the gateway to acknowledge it
+    PendingNew,
+    Open,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    Rejected,
+    Expired,
+}
+
+impl OrderState {
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            OrderState::Filled | OrderState::Cancelled | OrderState::Rejected | OrderState::Expired
+        )
+    }
+
+    // Transitions allowed by the state machine; terminal states never change again
+    pub fn can_transition_to(self, next: OrderState) -> bool {
+        use OrderState::*;
+        match (self, next) {
+            (from, to) if from == to => false,
+            (from, _) if from.is_terminal() => false,
+            (PendingNew, _) => true,
+            (Open, PendingNew) | (PartiallyFilled, PendingNew) | (PartiallyFilled, Open) => false,
+            (Open, Rejected) | (PartiallyFilled, Rejected) => false,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManagedOrder {
+    // Local id, assigned before the order is sent so rejects are tracked too
+    pub id: u64,
+    pub digest: Option<String>,
+    pub sender: String,
+    pub product_id: u32,
+    pub price: Decimal,
+    // Signed original size, positive buys and negative sells
+    pub amount: Decimal,
+    // Signed filled size, same sign as `amount`
+    pub filled: Decimal,
+    pub state: OrderState,
+    // Unix seconds after which the exchange drops the order
+    pub expiration: u64,
+    pub created_at_ms: u64,
+    pub updated_at_ms: u64,
+    pub error: Option<String>,
+}
+
+impl ManagedOrder {
+    pub fn remaining(&self) -> Decimal {
+        self.amount.checked_sub(self.filled).unwrap_or_default()
+    }
+
+    // Apply a state change if the state machine allows it, returns whether it did
+    pub fn transition(&mut self, next: OrderState, now_ms: u64) -> bool {
+        if !self.state.can_transition_to(next) {
+            return false;
+        }
+        self.state = next;
+        self.updated_at_ms = now_ms;
+        true
+    }
+}
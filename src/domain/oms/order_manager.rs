@@ -0,0 +1,443 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::RwLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    domain::models::vertex::stream_events::{Fill, OrderUpdate, OrderUpdateReason},
+    shared::utils::decimal::Decimal,
+};
+
+use super::order::{ManagedOrder, OrderState};
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_millis() as u64
+}
+
+// Filter for `OrderManager::list`; unset fields match everything
+#[derive(Debug, Default, Clone)]
+pub struct OrderFilter {
+    pub sender: Option<String>,
+    pub product_id: Option<u32>,
+    pub open_only: bool,
+}
+
+#[derive(Debug, Default)]
+struct OrderBook {
+    orders: HashMap<u64, ManagedOrder>,
+    by_digest: HashMap<String, u64>,
+    next_id: u64,
+    // Ids of terminal orders, oldest first, evicted past `max_terminal`
+    terminal: VecDeque<u64>,
+    max_terminal: usize,
+}
+
+impl OrderBook {
+    fn by_digest_mut(&mut self, digest: &str) -> Option<&mut ManagedOrder> {
+        let id = self.by_digest.get(&digest.to_ascii_lowercase())?;
+        self.orders.get_mut(id)
+    }
+
+    // Move an order to `next`; one that ends up terminal joins the retention queue
+    fn transition(&mut self, id: u64, next: OrderState, now: u64) -> bool {
+        let Some(order) = self.orders.get_mut(&id) else {
+            return false;
+        };
+        if !order.transition(next, now) {
+            return false;
+        }
+        if next.is_terminal() {
+            self.retire(id);
+        }
+        true
+    }
+
+    fn retire(&mut self, id: u64) {
+        self.terminal.push_back(id);
+        while self.terminal.len() > self.max_terminal {
+            let Some(evicted) = self.terminal.pop_front() else {
+                break;
+            };
+            let Some(order) = self.orders.remove(&evicted) else {
+                continue;
+            };
+            if let Some(digest) = order.digest {
+                if self.by_digest.get(&digest) == Some(&evicted) {
+                    self.by_digest.remove(&digest);
+                }
+            }
+        }
+    }
+}
+
+// One page of `OrderManager::page`
+#[derive(Debug, Clone)]
+pub struct OrderPage {
+    pub orders: Vec<ManagedOrder>,
+    // Id to continue after, None on the last page
+    pub next_after: Option<u64>,
+}
+
+/// Tracks every order from signing to its terminal state.
+///
+/// Orders enter as `PendingNew` before they are sent, move on gateway responses and are then
+/// driven by `order_update` and `fill` events from the subscription stream. Only the most
+/// recent terminal orders are kept; open orders are never evicted.
+#[derive(Debug)]
+pub struct OrderManager {
+    book: RwLock<OrderBook>,
+}
+
+impl OrderManager {
+    // Keeps at most `max_terminal` orders that reached a terminal state, dropping the oldest
+    pub fn new(max_terminal: usize) -> Self {
+        OrderManager {
+            book: RwLock::new(OrderBook {
+                max_terminal,
+                ..OrderBook::default()
+            }),
+        }
+    }
+
+    // Register an order about to be sent under its locally computed digest and return its
+    // local id; stream events that beat the gateway response find it by that digest
+    pub fn create_pending(
+        &self,
+        sender: &str,
+        product_id: u32,
+        digest: &str,
+        price: Decimal,
+        amount: Decimal,
+        expiration: u64,
+    ) -> u64 {
+        let mut book = self.book.write().unwrap();
+        book.next_id += 1;
+        let id = book.next_id;
+        let now = now_ms();
+        let digest = digest.to_ascii_lowercase();
+        book.by_digest.insert(digest.clone(), id);
+        book.orders.insert(
+            id,
+            ManagedOrder {
+                id,
+                digest: Some(digest),
+                sender: sender.to_ascii_lowercase(),
+                product_id,
+                price,
+                amount,
+                filled: Decimal::ZERO,
+                state: OrderState::PendingNew,
+                expiration,
+                created_at_ms: now,
+                updated_at_ms: now,
+                error: None,
+            },
+        );
+        id
+    }
+
+    // Put back an order rebuilt from the journal, keeping its original id
+    pub fn restore(&self, order: ManagedOrder) {
+        let mut book = self.book.write().unwrap();
+        let id = order.id;
+        let terminal = order.state.is_terminal();
+        book.next_id = book.next_id.max(id);
+        if let Some(digest) = &order.digest {
+            book.by_digest.insert(digest.to_ascii_lowercase(), id);
+        }
+        book.orders.insert(id, order);
+        if terminal {
+            book.retire(id);
+        }
+    }
+
+    // Highest id handed out so far, so a restart never reuses one
+    pub fn last_id(&self) -> u64 {
+        self.book.read().unwrap().next_id
+    }
+
+    pub fn restore_last_id(&self, id: u64) {
+        let mut book = self.book.write().unwrap();
+        book.next_id = book.next_id.max(id);
+    }
+
+    // Gateway acknowledged the order
+    pub fn on_accepted(&self, id: u64, digest: &str) {
+        let mut book = self.book.write().unwrap();
+        let digest = digest.to_ascii_lowercase();
+        let Some(order) = book.orders.get_mut(&id) else {
+            return;
+        };
+        let previous = order.digest.replace(digest.clone());
+        // Events may already have moved it past Open, which the transition refuses
+        book.transition(id, OrderState::Open, now_ms());
+        if let Some(previous) = previous.filter(|previous| *previous != digest) {
+            book.by_digest.remove(&previous);
+        }
+        book.by_digest.insert(digest, id);
+    }
+
+    // Gateway refused the order, or it never reached the gateway
+    pub fn on_rejected(&self, id: u64, error: &str) {
+        let mut book = self.book.write().unwrap();
+        if book.transition(id, OrderState::Rejected, now_ms()) {
+            if let Some(order) = book.orders.get_mut(&id) {
+                order.error = Some(error.to_string());
+            }
+        }
+    }
+
+    pub fn on_cancelled(&self, digest: &str) {
+        let mut book = self.book.write().unwrap();
+        if let Some(order) = book.by_digest_mut(digest) {
+            let (id, next) = (order.id, Self::cancel_state(order));
+            book.transition(id, next, now_ms());
+        }
+    }
+
+    // Cancel-all acknowledged; an empty product list means every product
+    pub fn on_products_cancelled(&self, sender: &str, product_ids: &[u32]) {
+        let sender = sender.to_ascii_lowercase();
+        let now = now_ms();
+        let mut book = self.book.write().unwrap();
+        let cancelled: Vec<(u64, OrderState)> = book
+            .orders
+            .values()
+            .filter(|order| {
+                order.sender == sender
+                    && !order.state.is_terminal()
+                    && order.state != OrderState::PendingNew
+                    && (product_ids.is_empty() || product_ids.contains(&order.product_id))
+            })
+            .map(|order| (order.id, Self::cancel_state(order)))
+            .collect();
+        for (id, next) in cancelled {
+            book.transition(id, next, now);
+        }
+    }
+
+    pub fn on_order_update(&self, update: &OrderUpdate) {
+        let mut book = self.book.write().unwrap();
+        let Some(order) = book.by_digest_mut(&update.digest) else {
+            return;
+        };
+
+        let next = match update.reason {
+            OrderUpdateReason::Placed => OrderState::Open,
+            OrderUpdateReason::Filled => {
+                // `amount` is what is left on the book after the fill
+                order.filled = order
+                    .amount
+                    .checked_sub(update.amount)
+                    .unwrap_or(order.filled);
+                if update.amount.is_zero() {
+                    OrderState::Filled
+                } else {
+                    OrderState::PartiallyFilled
+                }
+            }
+            OrderUpdateReason::Cancelled => Self::cancel_state(order),
+        };
+        let id = order.id;
+        book.transition(id, next, now_ms());
+    }
+
+    pub fn on_fill(&self, fill: &Fill) {
+        let mut book = self.book.write().unwrap();
+        let Some(order) = book.by_digest_mut(&fill.order_digest) else {
+            return;
+        };
+
+        // Derive filled from the remaining quantity so replayed fills stay idempotent
+        let remaining = fill.remaining_qty.abs();
+        let remaining = if order.amount.is_negative() {
+            Decimal::ZERO.checked_sub(remaining).unwrap_or_default()
+        } else {
+            remaining
+        };
+        order.filled = order.amount.checked_sub(remaining).unwrap_or(order.filled);
+
+        let next = if remaining.is_zero() {
+            OrderState::Filled
+        } else {
+            OrderState::PartiallyFilled
+        };
+        let id = order.id;
+        book.transition(id, next, now_ms());
+    }
+
+    // Vertex reports expiries as cancellations, tell them apart by the order's expiration
+    fn cancel_state(order: &ManagedOrder) -> OrderState {
+        if order.expiration != 0 && now_ms() / 1000 >= order.expiration {
+            OrderState::Expired
+        } else {
+            OrderState::Cancelled
+        }
+    }
+
+    pub fn get(&self, id: u64) -> Option<ManagedOrder> {
+        self.book.read().unwrap().orders.get(&id).cloned()
+    }
+
+    pub fn get_by_digest(&self, digest: &str) -> Option<ManagedOrder> {
+        let book = self.book.read().unwrap();
+        book.by_digest
+            .get(&digest.to_ascii_lowercase())
+            .and_then(|id| book.orders.get(id))
+            .cloned()
+    }
+
+    pub fn list(&self, filter: &OrderFilter) -> Vec<ManagedOrder> {
+        self.page(filter, 0, usize::MAX).orders
+    }
+
+    // Up to `limit` matching orders with ids above `after`, in id order
+    pub fn page(&self, filter: &OrderFilter, after: u64, limit: usize) -> OrderPage {
+        let limit = limit.max(1);
+        let sender = filter.sender.as_ref().map(|s| s.to_ascii_lowercase());
+        let book = self.book.read().unwrap();
+        let mut orders: Vec<&ManagedOrder> = book
+            .orders
+            .values()
+            .filter(|o| o.id > after)
+            .filter(|o| sender.as_ref().is_none_or(|s| &o.sender == s))
+            .filter(|o| filter.product_id.is_none_or(|p| o.product_id == p))
+            .filter(|o| !filter.open_only || !o.state.is_terminal())
+            .collect();
+        orders.sort_by_key(|o| o.id);
+        let next_after = (orders.len() > limit).then(|| orders[limit - 1].id);
+        OrderPage {
+            orders: orders.into_iter().take(limit).cloned().collect(),
+            next_after,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SENDER: &str = "0xABC";
+    const DIGEST: &str = "0xD1";
+
+    fn pending(manager: &OrderManager) -> u64 {
+        manager.create_pending(
+            SENDER,
+            2,
+            DIGEST,
+            Decimal::from_int(100),
+            Decimal::from_int(3),
+            0,
+        )
+    }
+
+    fn fill(remaining: i64) -> Fill {
+        Fill {
+            timestamp: "0".to_string(),
+            product_id: 2,
+            subaccount: SENDER.to_string(),
+            order_digest: "0xd1".to_string(),
+            filled_qty: Decimal::from_int(3 - remaining),
+            remaining_qty: Decimal::from_int(remaining),
+            original_qty: Decimal::from_int(3),
+            price: Decimal::from_int(100),
+            is_taker: true,
+            is_bid: true,
+            fee: Decimal::ZERO,
+        }
+    }
+
+    #[test]
+    fn fills_before_the_gateway_response_are_applied() {
+        let manager = OrderManager::new(10);
+        let id = pending(&manager);
+
+        manager.on_fill(&fill(1));
+        let order = manager.get(id).unwrap();
+        assert_eq!(order.state, OrderState::PartiallyFilled);
+        assert_eq!(order.filled, Decimal::from_int(2));
+
+        // The late ack does not move the order back to Open
+        manager.on_accepted(id, DIGEST);
+        assert_eq!(manager.get(id).unwrap().state, OrderState::PartiallyFilled);
+    }
+
+    #[test]
+    fn cancels_before_the_gateway_response_close_the_order() {
+        let manager = OrderManager::new(10);
+        let id = pending(&manager);
+
+        manager.on_order_update(&OrderUpdate {
+            timestamp: "0".to_string(),
+            product_id: 2,
+            digest: DIGEST.to_string(),
+            amount: Decimal::from_int(3),
+            reason: OrderUpdateReason::Cancelled,
+        });
+        manager.on_accepted(id, DIGEST);
+        assert_eq!(manager.get(id).unwrap().state, OrderState::Cancelled);
+    }
+
+    #[test]
+    fn a_different_gateway_digest_replaces_the_local_one() {
+        let manager = OrderManager::new(10);
+        let id = pending(&manager);
+
+        manager.on_accepted(id, "0xD2");
+        assert!(manager.get_by_digest(DIGEST).is_none());
+        assert_eq!(manager.get_by_digest("0xd2").unwrap().id, id);
+    }
+
+    // Pending orders on their own digests, rejected right away when `reject` is set
+    fn orders(manager: &OrderManager, count: u64, reject: bool) -> Vec<u64> {
+        (0..count)
+            .map(|n| {
+                let digest = format!("0x{}{}", if reject { "c" } else { "e" }, n);
+                let id = manager.create_pending(
+                    SENDER,
+                    2,
+                    &digest,
+                    Decimal::from_int(100),
+                    Decimal::from_int(1),
+                    0,
+                );
+                if reject {
+                    manager.on_rejected(id, "test");
+                }
+                id
+            })
+            .collect()
+    }
+
+    #[test]
+    fn only_the_newest_terminal_orders_are_kept() {
+        let manager = OrderManager::new(2);
+        let open = orders(&manager, 3, false);
+        let closed = orders(&manager, 3, true);
+
+        assert!(manager.get(closed[0]).is_none());
+        assert!(manager.get_by_digest("0xc0").is_none());
+        assert!(manager.get(closed[1]).is_some() && manager.get(closed[2]).is_some());
+        // Open orders are never evicted, however many there are
+        assert!(open.iter().all(|id| manager.get(*id).is_some()));
+    }
+
+    #[test]
+    fn pages_follow_the_id_order() {
+        let manager = OrderManager::new(10);
+        let ids = orders(&manager, 5, false);
+        let filter = OrderFilter::default();
+
+        let first = manager.page(&filter, 0, 2);
+        assert_eq!(first.orders.iter().map(|o| o.id).collect::<Vec<_>>(), ids[..2]);
+        assert_eq!(first.next_after, Some(ids[1]));
+
+        let last = manager.page(&filter, ids[3], 2);
+        assert_eq!(last.orders.len(), 1);
+        assert_eq!(last.next_after, None);
+    }
+}
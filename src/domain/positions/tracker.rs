@@ -0,0 +1,389 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, RwLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::shared::utils::decimal::Decimal;
+
+const UPDATE_CHANNEL_CAPACITY: usize = 1024;
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_millis() as u64
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Position {
+    pub sender: String,
+    pub product_id: u32,
+    // Signed, positive long and negative short
+    pub amount: Decimal,
+    pub avg_entry_price: Decimal,
+    pub realized_pnl: Decimal,
+    pub unrealized_pnl: Decimal,
+    // Fees paid, positive is a cost
+    pub fees: Decimal,
+    // Funding received, negative when paid
+    pub funding: Decimal,
+    pub mark_price: Option<Decimal>,
+    pub updated_at_ms: u64,
+}
+
+impl Position {
+    fn new(sender: &str, product_id: u32) -> Self {
+        Position {
+            sender: sender.to_string(),
+            product_id,
+            ..Default::default()
+        }
+    }
+
+    // Apply a fill and return the PnL it realized, before fees
+    fn apply_fill(&mut self, qty: Decimal, price: Decimal) -> Decimal {
+        let mut realized = Decimal::ZERO;
+        let same_side = self.amount.is_zero() || self.amount.is_negative() == qty.is_negative();
+
+        if same_side {
+            // Adding to the position moves the average entry
+            let held = self.amount.abs();
+            let added = qty.abs();
+            let cost = held
+                .checked_mul(self.avg_entry_price)
+                .and_then(|c| c.checked_add(added.checked_mul(price)?));
+            let total = held.checked_add(added);
+            if let (Some(cost), Some(total)) = (cost, total) {
+                self.avg_entry_price = cost.checked_div(total).unwrap_or(price);
+            }
+        } else {
+            // Reducing realizes PnL on the closed part; anything beyond flat opens at `price`
+            let closed = if qty.abs() > self.amount.abs() {
+                self.amount.abs()
+            } else {
+                qty.abs()
+            };
+            let per_unit = if self.amount.is_negative() {
+                self.avg_entry_price.checked_sub(price)
+            } else {
+                price.checked_sub(self.avg_entry_price)
+            };
+            realized = per_unit
+                .and_then(|p| p.checked_mul(closed))
+                .unwrap_or_default();
+            if qty.abs() > self.amount.abs() {
+                self.avg_entry_price = price;
+            }
+        }
+
+        self.amount = self.amount.checked_add(qty).unwrap_or(self.amount);
+        if self.amount.is_zero() {
+            self.avg_entry_price = Decimal::ZERO;
+        }
+        self.realized_pnl = self
+            .realized_pnl
+            .checked_add(realized)
+            .unwrap_or(self.realized_pnl);
+        realized
+    }
+
+    fn remark(&mut self) {
+        self.unrealized_pnl = self
+            .mark_price
+            .and_then(|mark| mark.checked_sub(self.avg_entry_price))
+            .and_then(|diff| diff.checked_mul(self.amount))
+            .unwrap_or_default();
+    }
+}
+
+// Positions as of a journal snapshot, with the last position change seen for each
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrackerSnapshot {
+    positions: Vec<Position>,
+    position_changes: Vec<((String, u32), (Decimal, Decimal))>,
+}
+
+/// Per subaccount and product position keeper fed by fills.
+///
+/// Tracks size, average entry, realized PnL, fees and funding, and marks open positions against
+/// the oracle price for unrealized PnL. Every change is published on a broadcast channel for
+/// streaming subscribers.
+#[derive(Debug)]
+pub struct PositionTracker {
+    positions: RwLock<HashMap<(String, u32), Position>>,
+    // Last (amount, v_quote) reported by a position change, used to spot funding payments
+    last_position_change: Mutex<HashMap<(String, u32), (Decimal, Decimal)>>,
+    updates: broadcast::Sender<Position>,
+}
+
+impl Default for PositionTracker {
+    fn default() -> Self {
+        PositionTracker::new()
+    }
+}
+
+impl PositionTracker {
+    pub fn new() -> Self {
+        let (updates, _) = broadcast::channel(UPDATE_CHANNEL_CAPACITY);
+        PositionTracker {
+            positions: RwLock::new(HashMap::new()),
+            last_position_change: Mutex::new(HashMap::new()),
+            updates,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Position> {
+        self.updates.subscribe()
+    }
+
+    // Run `f` on the position, creating it if needed, then publish the result
+    fn update<R>(&self, sender: &str, product_id: u32, f: impl FnOnce(&mut Position) -> R) -> R {
+        let sender = sender.to_ascii_lowercase();
+        let (result, snapshot) = {
+            let mut positions = self.positions.write().unwrap();
+            let position = positions
+                .entry((sender.clone(), product_id))
+                .or_insert_with(|| Position::new(&sender, product_id));
+            let result = f(position);
+            position.remark();
+            position.updated_at_ms = now_ms();
+            (result, position.clone())
+        };
+        // Nobody listening is fine
+        let _ = self.updates.send(snapshot);
+        result
+    }
+
+    // Apply a fill and return the realized PnL net of its fee
+    pub fn on_fill(
+        &self,
+        sender: &str,
+        product_id: u32,
+        qty: Decimal,
+        price: Decimal,
+        fee: Decimal,
+    ) -> Decimal {
+        self.update(sender, product_id, |position| {
+            let realized = position.apply_fill(qty, price);
+            position.fees = position.fees.checked_add(fee).unwrap_or(position.fees);
+            realized.checked_sub(fee).unwrap_or(realized)
+        })
+    }
+
+    pub fn on_funding(&self, sender: &str, product_id: u32, payment: Decimal) {
+        self.update(sender, product_id, |position| {
+            position.funding = position
+                .funding
+                .checked_add(payment)
+                .unwrap_or(position.funding);
+        });
+    }
+
+    // A perp position change with an unchanged size moved only the quote balance, which is
+    // treated as funding
+    pub fn on_position_change(
+        &self,
+        sender: &str,
+        product_id: u32,
+        amount: Decimal,
+        v_quote: Decimal,
+    ) {
+        let key = (sender.to_ascii_lowercase(), product_id);
+        let previous = self
+            .last_position_change
+            .lock()
+            .unwrap()
+            .insert(key, (amount, v_quote));
+
+        if let Some((last_amount, last_v_quote)) = previous {
+            if last_amount == amount && last_v_quote != v_quote {
+                if let Some(payment) = v_quote.checked_sub(last_v_quote) {
+                    self.on_funding(sender, product_id, payment);
+                }
+            }
+        }
+    }
+
+    // Overwrite size and entry with the exchange's figures, keeping PnL history
+    pub fn reset(&self, sender: &str, product_id: u32, amount: Decimal, avg_entry_price: Decimal) {
+        self.update(sender, product_id, |position| {
+            position.amount = amount;
+            position.avg_entry_price = if amount.is_zero() {
+                Decimal::ZERO
+            } else {
+                avg_entry_price
+            };
+        });
+    }
+
+    // Mark every position on the product at `price`
+    pub fn mark(&self, product_id: u32, price: Decimal) {
+        let snapshots: Vec<Position> = {
+            let mut positions = self.positions.write().unwrap();
+            positions
+                .values_mut()
+                .filter(|p| p.product_id == product_id && p.mark_price != Some(price))
+                .map(|position| {
+                    position.mark_price = Some(price);
+                    position.remark();
+                    position.updated_at_ms = now_ms();
+                    position.clone()
+                })
+                .collect()
+        };
+        for snapshot in snapshots {
+            let _ = self.updates.send(snapshot);
+        }
+    }
+
+    pub fn get(&self, sender: &str, product_id: u32) -> Option<Position> {
+        self.positions
+            .read()
+            .unwrap()
+            .get(&(sender.to_ascii_lowercase(), product_id))
+            .cloned()
+    }
+
+    pub fn product_ids(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = self
+            .positions
+            .read()
+            .unwrap()
+            .keys()
+            .map(|(_, product_id)| *product_id)
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    pub fn list(&self, sender: Option<&str>, product_id: Option<u32>) -> Vec<Position> {
+        let sender = sender.map(|s| s.to_ascii_lowercase());
+        let mut positions: Vec<Position> = self
+            .positions
+            .read()
+            .unwrap()
+            .values()
+            .filter(|p| sender.as_ref().is_none_or(|s| &p.sender == s))
+            .filter(|p| product_id.is_none_or(|id| p.product_id == id))
+            .cloned()
+            .collect();
+        positions.sort_by(|a, b| (&a.sender, a.product_id).cmp(&(&b.sender, b.product_id)));
+        positions
+    }
+
+    pub fn snapshot(&self) -> TrackerSnapshot {
+        TrackerSnapshot {
+            positions: self.positions.read().unwrap().values().cloned().collect(),
+            position_changes: self
+                .last_position_change
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(key, change)| (key.clone(), *change))
+                .collect(),
+        }
+    }
+
+    pub fn restore(&self, snapshot: TrackerSnapshot) {
+        *self.positions.write().unwrap() = snapshot
+            .positions
+            .into_iter()
+            .map(|p| ((p.sender.clone(), p.product_id), p))
+            .collect();
+        *self.last_position_change.lock().unwrap() =
+            snapshot.position_changes.into_iter().collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SENDER: &str = "0xABC";
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn position(tracker: &PositionTracker) -> Position {
+        tracker.get(SENDER, 2).unwrap()
+    }
+
+    #[test]
+    fn adding_to_a_position_averages_the_entry() {
+        let tracker = PositionTracker::new();
+        tracker.on_fill(SENDER, 2, dec("1"), dec("100"), Decimal::ZERO);
+        tracker.on_fill(SENDER, 2, dec("3"), dec("200"), Decimal::ZERO);
+
+        let position = position(&tracker);
+        assert_eq!(position.amount, dec("4"));
+        assert_eq!(position.avg_entry_price, dec("175"));
+        assert_eq!(position.realized_pnl, Decimal::ZERO);
+    }
+
+    #[test]
+    fn a_partial_close_realizes_only_the_closed_part() {
+        let tracker = PositionTracker::new();
+        tracker.on_fill(SENDER, 2, dec("4"), dec("175"), Decimal::ZERO);
+        let realized = tracker.on_fill(SENDER, 2, dec("-1"), dec("195"), dec("0.5"));
+        tracker.mark(2, dec("185"));
+
+        // The returned PnL is net of the fee, the running total is not
+        assert_eq!(realized, dec("19.5"));
+        let position = position(&tracker);
+        assert_eq!(position.amount, dec("3"));
+        assert_eq!(position.avg_entry_price, dec("175"));
+        assert_eq!(position.realized_pnl, dec("20"));
+        assert_eq!(position.fees, dec("0.5"));
+        assert_eq!(position.unrealized_pnl, dec("30"));
+
+        // Shorts gain when the price falls
+        tracker.on_fill(SENDER, 3, dec("-2"), dec("100"), Decimal::ZERO);
+        let realized = tracker.on_fill(SENDER, 3, dec("1"), dec("90"), Decimal::ZERO);
+        assert_eq!(realized, dec("10"));
+        assert_eq!(tracker.get(SENDER, 3).unwrap().amount, dec("-1"));
+    }
+
+    #[test]
+    fn a_flip_closes_the_old_side_and_opens_at_the_fill_price() {
+        let tracker = PositionTracker::new();
+        tracker.on_fill(SENDER, 2, dec("2"), dec("100"), Decimal::ZERO);
+        let realized = tracker.on_fill(SENDER, 2, dec("-5"), dec("90"), Decimal::ZERO);
+        tracker.mark(2, dec("80"));
+
+        assert_eq!(realized, dec("-20"));
+        let position = position(&tracker);
+        assert_eq!(position.amount, dec("-3"));
+        assert_eq!(position.avg_entry_price, dec("90"));
+        assert_eq!(position.unrealized_pnl, dec("30"));
+
+        // Back to flat clears the entry
+        tracker.on_fill(SENDER, 2, dec("3"), dec("85"), Decimal::ZERO);
+        let position = tracker.get(SENDER, 2).unwrap();
+        assert!(position.amount.is_zero());
+        assert_eq!(position.avg_entry_price, Decimal::ZERO);
+        assert_eq!(position.realized_pnl, dec("-5"));
+        assert_eq!(position.unrealized_pnl, Decimal::ZERO);
+    }
+
+    #[test]
+    fn a_quote_move_at_an_unchanged_size_is_funding() {
+        let tracker = PositionTracker::new();
+        // The first change is only a baseline
+        tracker.on_position_change(SENDER, 2, dec("2"), dec("-200"));
+        assert!(tracker.get(SENDER, 2).is_none());
+
+        tracker.on_position_change(SENDER, 2, dec("2"), dec("-195"));
+        assert_eq!(position(&tracker).funding, dec("5"));
+
+        // A size change is a trade, not funding
+        tracker.on_position_change(SENDER, 2, dec("3"), dec("-300"));
+        tracker.on_position_change(SENDER, 2, dec("3"), dec("-302"));
+        assert_eq!(position(&tracker).funding, dec("3"));
+    }
+}
@@ -0,0 +1,523 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::shared::utils::decimal::Decimal;
+
+use super::limits::RiskConfig;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+const RESERVATION_PREFIX: &str = "reservation-";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RiskViolation {
+    OrderNotional {
+        notional: Decimal,
+        limit: Decimal,
+    },
+    Position {
+        product_id: u32,
+        projected: Decimal,
+        limit: Decimal,
+    },
+    OpenOrders {
+        open: usize,
+        limit: usize,
+    },
+    PriceBand {
+        price: Decimal,
+        reference: Decimal,
+        band_bps: u32,
+    },
+    // A price band is set but there is nothing to measure the price against
+    NoReferencePrice {
+        product_id: u32,
+    },
+    DailyLoss {
+        realized: Decimal,
+        limit: Decimal,
+    },
+}
+
+impl std::error::Error for RiskViolation {}
+
+impl fmt::Display for RiskViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskViolation::OrderNotional { notional, limit } => {
+                write!(f, "order notional {} exceeds limit {}", notional, limit)
+            }
+            RiskViolation::Position {
+                product_id,
+                projected,
+                limit,
+            } => write!(
+                f,
+                "projected position {} on product {} exceeds limit {}",
+                projected, product_id, limit
+            ),
+            RiskViolation::OpenOrders { open, limit } => {
+                write!(f, "{} open orders already at limit {}", open, limit)
+            }
+            RiskViolation::PriceBand {
+                price,
+                reference,
+                band_bps,
+            } => write!(
+                f,
+                "price {} is more than {}bps away from reference {}",
+                price, band_bps, reference
+            ),
+            RiskViolation::NoReferencePrice { product_id } => write!(
+                f,
+                "no reference price for product {} to check the price band against",
+                product_id
+            ),
+            RiskViolation::DailyLoss { realized, limit } => write!(
+                f,
+                "daily realized loss {} has reached limit {}",
+                realized, limit
+            ),
+        }
+    }
+}
+
+// Everything the engine needs to know about an order before it is signed
+#[derive(Debug, Clone)]
+pub struct OrderCheck<'a> {
+    pub sender: &'a str,
+    pub product_id: u32,
+    // Venue symbol, set where `product_id` is only a local number; position caps go by it
+    pub instrument: Option<&'a str>,
+    pub price: Decimal,
+    // Signed, positive buys and negative sells
+    pub amount: Decimal,
+    // Oracle or mid price used for the price band check
+    pub reference_price: Option<Decimal>,
+}
+
+// Exposure held for an order from its risk check until the exchange answers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reservation(u64);
+
+impl Reservation {
+    // Open orders are keyed by digest; reservations use a key no digest can take
+    fn key(self) -> String {
+        format!("{}{}", RESERVATION_PREFIX, self.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OpenOrder {
+    product_id: u32,
+    remaining: Decimal,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Exposure {
+    positions: HashMap<u32, Decimal>,
+    open_orders: HashMap<String, OpenOrder>,
+    realized_pnl: Decimal,
+    day: u64,
+}
+
+impl Exposure {
+    // Daily loss accounting restarts at UTC midnight
+    fn roll_day(&mut self, today: u64) {
+        if self.day != today {
+            self.day = today;
+            self.realized_pnl = Decimal::ZERO;
+        }
+    }
+
+    // Position if every resting order on the same side of `amount` filled, before and after
+    // `amount` fills as well
+    fn projected_position(&self, product_id: u32, amount: Decimal) -> Option<(Decimal, Decimal)> {
+        let position = self.positions.get(&product_id).copied().unwrap_or_default();
+        let resting = self
+            .open_orders
+            .values()
+            .filter(|o| {
+                o.product_id == product_id && o.remaining.is_negative() == amount.is_negative()
+            })
+            .try_fold(Decimal::ZERO, |acc, o| acc.checked_add(o.remaining))?;
+        let before = position.checked_add(resting)?;
+        Some((before, before.checked_add(amount)?))
+    }
+}
+
+// Exposures as of a journal snapshot, reservations left out
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RiskSnapshot(HashMap<String, Exposure>);
+
+fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs()
+        / SECONDS_PER_DAY
+}
+
+/// Pre-trade risk checks, run before an order is signed.
+///
+/// The engine keeps its own view of positions, resting orders and realized PnL per subaccount,
+/// fed by the execute path and by fills, so checks never wait on the exchange. An order that
+/// passes is counted as resting right away, so concurrent orders cannot all pass the same limit.
+#[derive(Debug, Default)]
+pub struct RiskEngine {
+    config: RiskConfig,
+    exposures: Mutex<HashMap<String, Exposure>>,
+    next_reservation: AtomicU64,
+}
+
+impl RiskEngine {
+    pub fn new(config: RiskConfig) -> Self {
+        RiskEngine {
+            config,
+            exposures: Mutex::new(HashMap::new()),
+            next_reservation: AtomicU64::new(0),
+        }
+    }
+
+    // Check an order and, when it passes, reserve its exposure under the same lock; the
+    // reservation must be confirmed or released once the exchange answers
+    pub fn check_order(&self, order: &OrderCheck) -> Result<Reservation, RiskViolation> {
+        let limits = self.config.limits_for(order.sender);
+        let mut exposures = self.exposures.lock().unwrap();
+        let exposure = exposures
+            .entry(order.sender.to_ascii_lowercase())
+            .or_default();
+        exposure.roll_day(today());
+
+        if let Some(limit) = limits.daily_loss_limit {
+            if exposure.realized_pnl.checked_add(limit).unwrap_or_default() <= Decimal::ZERO {
+                return Err(RiskViolation::DailyLoss {
+                    realized: exposure.realized_pnl,
+                    limit,
+                });
+            }
+        }
+
+        if let Some(limit) = limits.max_order_notional {
+            let notional = order
+                .amount
+                .abs()
+                .checked_mul(order.price)
+                .unwrap_or(Decimal::from_x18(i128::MAX));
+            if notional > limit {
+                return Err(RiskViolation::OrderNotional { notional, limit });
+            }
+        }
+
+        if let Some(limit) = limits.max_open_orders {
+            let open = exposure.open_orders.len();
+            if open >= limit {
+                return Err(RiskViolation::OpenOrders { open, limit });
+            }
+        }
+
+        // Orders that don't grow the position are let through even beyond the limit, so an
+        // oversized position can always be worked down
+        if let Some(limit) = limits.max_position_for(order.product_id, order.instrument) {
+            let max = Decimal::from_x18(i128::MAX);
+            let (before, projected) = exposure
+                .projected_position(order.product_id, order.amount)
+                .unwrap_or((Decimal::ZERO, max));
+            if projected.abs() > limit && projected.abs() > before.abs() {
+                return Err(RiskViolation::Position {
+                    product_id: order.product_id,
+                    projected,
+                    limit,
+                });
+            }
+        }
+
+        // Without a reference the band can't be checked, so the order fails closed
+        if let Some(band_bps) = limits.price_band_bps {
+            let reference = order
+                .reference_price
+                .filter(|reference| *reference > Decimal::ZERO)
+                .ok_or(RiskViolation::NoReferencePrice {
+                    product_id: order.product_id,
+                })?;
+            let band = reference
+                .checked_mul(Decimal::from_int(band_bps as i64))
+                .and_then(|b| b.checked_div(Decimal::from_int(10_000)))
+                .unwrap_or_default();
+            let distance = order.price.checked_sub(reference).unwrap_or_default().abs();
+            if distance > band {
+                return Err(RiskViolation::PriceBand {
+                    price: order.price,
+                    reference,
+                    band_bps,
+                });
+            }
+        }
+
+        let reservation = Reservation(self.next_reservation.fetch_add(1, Ordering::Relaxed));
+        exposure.open_orders.insert(
+            reservation.key(),
+            OpenOrder {
+                product_id: order.product_id,
+                remaining: order.amount,
+            },
+        );
+        Ok(reservation)
+    }
+
+    // A checked order was sent or is about to be; its reservation now rests under the order's
+    // digest or venue id, which closes it like any other order on reject or cancel
+    pub fn confirm_reservation(&self, sender: &str, reservation: Reservation, digest: &str) {
+        let mut exposures = self.exposures.lock().unwrap();
+        if let Some(exposure) = exposures.get_mut(&sender.to_ascii_lowercase()) {
+            if let Some(order) = exposure.open_orders.remove(&reservation.key()) {
+                exposure
+                    .open_orders
+                    .insert(digest.to_ascii_lowercase(), order);
+            }
+        }
+    }
+
+    // A checked order was rejected or never reached the exchange
+    pub fn release_reservation(&self, sender: &str, reservation: Reservation) {
+        let mut exposures = self.exposures.lock().unwrap();
+        if let Some(exposure) = exposures.get_mut(&sender.to_ascii_lowercase()) {
+            exposure.open_orders.remove(&reservation.key());
+        }
+    }
+
+    // A resting order restored from the journal or the exchange
+    pub fn on_order_accepted(&self, sender: &str, digest: &str, product_id: u32, amount: Decimal) {
+        let mut exposures = self.exposures.lock().unwrap();
+        exposures
+            .entry(sender.to_ascii_lowercase())
+            .or_default()
+            .open_orders
+            .insert(
+                digest.to_ascii_lowercase(),
+                OpenOrder {
+                    product_id,
+                    remaining: amount,
+                },
+            );
+    }
+
+    // An order left the book (cancelled, expired or fully filled)
+    pub fn on_order_closed(&self, sender: &str, digest: &str) {
+        let mut exposures = self.exposures.lock().unwrap();
+        if let Some(exposure) = exposures.get_mut(&sender.to_ascii_lowercase()) {
+            exposure.open_orders.remove(&digest.to_ascii_lowercase());
+        }
+    }
+
+    // All resting orders for the given products were cancelled; an empty list means every product
+    pub fn on_products_cancelled(&self, sender: &str, product_ids: &[u32]) {
+        let mut exposures = self.exposures.lock().unwrap();
+        if let Some(exposure) = exposures.get_mut(&sender.to_ascii_lowercase()) {
+            exposure
+                .open_orders
+                .retain(|_, o| !product_ids.is_empty() && !product_ids.contains(&o.product_id));
+        }
+    }
+
+    // A fill moves the position and shrinks the resting order it came from
+    pub fn on_fill(&self, sender: &str, digest: &str, product_id: u32, filled_amount: Decimal) {
+        let mut exposures = self.exposures.lock().unwrap();
+        let exposure = exposures.entry(sender.to_ascii_lowercase()).or_default();
+
+        let position = exposure.positions.entry(product_id).or_default();
+        *position = position.checked_add(filled_amount).unwrap_or(*position);
+
+        let digest = digest.to_ascii_lowercase();
+        if let Some(order) = exposure.open_orders.get_mut(&digest) {
+            order.remaining = order
+                .remaining
+                .checked_sub(filled_amount)
+                .unwrap_or_default();
+            if order.remaining.is_zero()
+                || order.remaining.is_negative() != filled_amount.is_negative()
+            {
+                exposure.open_orders.remove(&digest);
+            }
+        }
+    }
+
+    pub fn position(&self, sender: &str, product_id: u32) -> Decimal {
+        self.exposures
+            .lock()
+            .unwrap()
+            .get(&sender.to_ascii_lowercase())
+            .and_then(|e| e.positions.get(&product_id).copied())
+            .unwrap_or_default()
+    }
+
+    // Overwrite a position with the exchange's figure after reconciliation
+    pub fn set_position(&self, sender: &str, product_id: u32, amount: Decimal) {
+        let mut exposures = self.exposures.lock().unwrap();
+        exposures
+            .entry(sender.to_ascii_lowercase())
+            .or_default()
+            .positions
+            .insert(product_id, amount);
+    }
+
+    pub fn snapshot(&self) -> RiskSnapshot {
+        let mut exposures = self.exposures.lock().unwrap().clone();
+        // A reservation only lives as long as the execute call that made it
+        for exposure in exposures.values_mut() {
+            exposure
+                .open_orders
+                .retain(|key, _| !key.starts_with(RESERVATION_PREFIX));
+        }
+        RiskSnapshot(exposures)
+    }
+
+    pub fn restore(&self, snapshot: RiskSnapshot) {
+        *self.exposures.lock().unwrap() = snapshot.0;
+    }
+
+    // Count realized PnL towards the daily loss limit; PnL from an earlier day (e.g. journal
+    // replay) is ignored
+    pub fn record_realized_pnl(&self, sender: &str, pnl: Decimal, timestamp_secs: Option<u64>) {
+        let today = today();
+        if timestamp_secs.is_some_and(|ts| ts / SECONDS_PER_DAY != today) {
+            return;
+        }
+        let mut exposures = self.exposures.lock().unwrap();
+        let exposure = exposures.entry(sender.to_ascii_lowercase()).or_default();
+        exposure.roll_day(today);
+        exposure.realized_pnl = exposure
+            .realized_pnl
+            .checked_add(pnl)
+            .unwrap_or(exposure.realized_pnl);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::risk::limits::RiskLimits;
+
+    const SENDER: &str = "0xABC";
+
+    fn engine_with(limits: RiskLimits) -> RiskEngine {
+        RiskEngine::new(RiskConfig {
+            default: limits,
+            ..RiskConfig::default()
+        })
+    }
+
+    fn order(amount: i64) -> OrderCheck<'static> {
+        OrderCheck {
+            sender: SENDER,
+            product_id: 2,
+            instrument: None,
+            price: Decimal::from_int(100),
+            amount: Decimal::from_int(amount),
+            reference_price: None,
+        }
+    }
+
+    #[test]
+    fn passing_orders_count_against_limits_before_the_exchange_answers() {
+        let engine = engine_with(RiskLimits {
+            max_open_orders: Some(1),
+            default_max_position: Some(Decimal::from_int(3)),
+            ..RiskLimits::default()
+        });
+        engine.check_order(&order(2)).unwrap();
+        assert_eq!(
+            engine.check_order(&order(1)),
+            Err(RiskViolation::OpenOrders { open: 1, limit: 1 })
+        );
+
+        let engine = engine_with(RiskLimits {
+            default_max_position: Some(Decimal::from_int(3)),
+            ..RiskLimits::default()
+        });
+        engine.check_order(&order(2)).unwrap();
+        assert!(matches!(
+            engine.check_order(&order(2)),
+            Err(RiskViolation::Position { .. })
+        ));
+    }
+
+    #[test]
+    fn released_reservations_free_their_exposure() {
+        let engine = engine_with(RiskLimits {
+            max_open_orders: Some(1),
+            ..RiskLimits::default()
+        });
+        let reservation = engine.check_order(&order(1)).unwrap();
+        engine.release_reservation(SENDER, reservation);
+        engine.check_order(&order(1)).unwrap();
+    }
+
+    #[test]
+    fn confirmed_reservations_rest_under_their_digest() {
+        let engine = engine_with(RiskLimits {
+            max_open_orders: Some(1),
+            ..RiskLimits::default()
+        });
+        let reservation = engine.check_order(&order(1)).unwrap();
+        engine.confirm_reservation(SENDER, reservation, "0xDIGEST");
+        assert!(engine.check_order(&order(1)).is_err());
+
+        engine.on_order_closed(SENDER, "0xdigest");
+        engine.check_order(&order(1)).unwrap();
+    }
+
+    #[test]
+    fn orders_that_shrink_a_position_pass_beyond_the_limit() {
+        let engine = engine_with(RiskLimits {
+            default_max_position: Some(Decimal::from_int(3)),
+            ..RiskLimits::default()
+        });
+        engine.set_position(SENDER, 2, Decimal::from_int(5));
+
+        assert!(matches!(
+            engine.check_order(&order(1)),
+            Err(RiskViolation::Position { .. })
+        ));
+        engine.check_order(&order(-2)).unwrap();
+        // Flipping through zero to a position beyond the old one still counts as growing it
+        assert!(matches!(
+            engine.check_order(&order(-9)),
+            Err(RiskViolation::Position { .. })
+        ));
+    }
+
+    #[test]
+    fn price_band_fails_closed_without_a_reference() {
+        let engine = engine_with(RiskLimits {
+            price_band_bps: Some(100),
+            ..RiskLimits::default()
+        });
+        assert_eq!(
+            engine.check_order(&order(1)),
+            Err(RiskViolation::NoReferencePrice { product_id: 2 })
+        );
+
+        let with_reference = |reference: i64| OrderCheck {
+            reference_price: Some(Decimal::from_int(reference)),
+            ..order(1)
+        };
+        engine.check_order(&with_reference(101)).unwrap();
+        assert!(matches!(
+            engine.check_order(&with_reference(102)),
+            Err(RiskViolation::PriceBand { .. })
+        ));
+        assert!(matches!(
+            engine.check_order(&with_reference(0)),
+            Err(RiskViolation::NoReferencePrice { .. })
+        ));
+    }
+}
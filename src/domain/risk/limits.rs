@@ -0,0 +1,75 @@
+use serde::Deserialize;
+use std::{collections::HashMap, error::Error, fs};
+
+use crate::shared::utils::decimal::Decimal;
+
+// Limits for one subaccount; an unset field means the check is disabled
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct RiskLimits {
+    // Absolute position cap per product id
+    pub max_position: HashMap<u32, Decimal>,
+    // Absolute position cap per instrument symbol, for venues other than Vertex
+    pub max_instrument_position: HashMap<String, Decimal>,
+    // Cap for products without an explicit max_position entry
+    pub default_max_position: Option<Decimal>,
+    pub max_order_notional: Option<Decimal>,
+    pub max_open_orders: Option<usize>,
+    // Maximum distance of the order price from the reference (oracle or mid) price; orders are
+    // refused while no reference is known
+    pub price_band_bps: Option<u32>,
+    // Trading halts once the realized loss for the UTC day reaches this amount
+    pub daily_loss_limit: Option<Decimal>,
+}
+
+impl RiskLimits {
+    // Orders naming an instrument symbol are only held to the symbol caps; their product id is
+    // a local number that means nothing in a limits file
+    pub fn max_position_for(&self, product_id: u32, instrument: Option<&str>) -> Option<Decimal> {
+        match instrument {
+            Some(instrument) => self.max_instrument_position.get(instrument),
+            None => self.max_position.get(&product_id),
+        }
+        .copied()
+        .or(self.default_max_position)
+    }
+}
+
+/// Risk limits file, keyed by the full subaccount sender hex, or by the venue name for venues
+/// other than Vertex.
+///
+/// ```json
+/// {
+///   "default": { "max_order_notional": "50000", "max_open_orders": 20 },
+///   "subaccounts": {
+///     "0x...64656661756c740000000000": { "max_position": { "2": "1.5" }, "price_band_bps": 200 },
+///     "dydx": { "max_instrument_position": { "BTC-USD": "0.5" } }
+///   }
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct RiskConfig {
+    pub default: RiskLimits,
+    pub subaccounts: HashMap<String, RiskLimits>,
+}
+
+impl RiskConfig {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)?;
+        let mut config: RiskConfig = serde_json::from_str(&contents)?;
+        // Senders are compared case-insensitively
+        config.subaccounts = config
+            .subaccounts
+            .into_iter()
+            .map(|(sender, limits)| (sender.to_ascii_lowercase(), limits))
+            .collect();
+        Ok(config)
+    }
+
+    pub fn limits_for(&self, sender: &str) -> &RiskLimits {
+        self.subaccounts
+            .get(&sender.to_ascii_lowercase())
+            .unwrap_or(&self.default)
+    }
+}
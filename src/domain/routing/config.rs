@@ -0,0 +1,80 @@
+use serde::Deserialize;
+use std::{collections::HashMap, error::Error, fs};
+
+use crate::domain::models::trading::Venue;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutingPolicy {
+    // Best fee-adjusted price first
+    #[default]
+    BestPrice,
+    // Cheapest venue first, price breaks ties
+    LowestFee,
+    // Configured venue order, regardless of price
+    VenuePriority,
+}
+
+/// Smart order router settings, keyed by venue name.
+///
+/// ```json
+/// {
+///   "policy": "best_price",
+///   "venue_priority": ["vertex", "dydx", "orderly"],
+///   "taker_fee_bps": { "vertex": 2, "dydx": 5, "orderly": 3 },
+///   "leverage": { "dydx": 5 },
+///   "instruments": {
+///     "BTC": { "vertex": "BTC-PERP", "dydx": "BTC-USD", "orderly": "PERP_BTC_USDC" }
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RouterConfig {
+    pub policy: RoutingPolicy,
+    // Earlier venues win ties, and order the venues under `venue_priority`
+    pub venue_priority: Vec<Venue>,
+    pub taker_fee_bps: HashMap<Venue, u32>,
+    // Notional allowed per unit of free margin; venues without an entry use 1
+    pub leverage: HashMap<Venue, u32>,
+    // Router instrument name to each venue's symbol for it
+    pub instruments: HashMap<String, HashMap<Venue, String>>,
+    // Quotes older than this are ignored
+    pub max_quote_age_ms: u64,
+}
+
+impl Default for RouterConfig {
+    fn default() -> Self {
+        RouterConfig {
+            policy: RoutingPolicy::default(),
+            venue_priority: vec![Venue::Vertex, Venue::Dydx, Venue::Orderly],
+            taker_fee_bps: HashMap::new(),
+            leverage: HashMap::new(),
+            instruments: HashMap::new(),
+            max_quote_age_ms: 2_000,
+        }
+    }
+}
+
+impl RouterConfig {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn fee_bps(&self, venue: Venue) -> u32 {
+        self.taker_fee_bps.get(&venue).copied().unwrap_or(0)
+    }
+
+    pub fn leverage_for(&self, venue: Venue) -> u32 {
+        self.leverage.get(&venue).copied().unwrap_or(1)
+    }
+
+    // Venues not listed rank after every listed one
+    pub fn priority_of(&self, venue: Venue) -> usize {
+        self.venue_priority
+            .iter()
+            .position(|v| *v == venue)
+            .unwrap_or(self.venue_priority.len())
+    }
+}
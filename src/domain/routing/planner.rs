@@ -0,0 +1,333 @@
+use std::cmp::Ordering;
+
+use crate::{
+    domain::models::trading::{Side, Venue},
+    shared::utils::decimal::{Decimal, RoundingMode},
+};
+
+use super::config::{RouterConfig, RoutingPolicy};
+
+const BPS: i64 = 10_000;
+
+// What one venue can take of the parent order right now
+#[derive(Debug, Clone)]
+pub struct VenueLiquidity {
+    pub venue: Venue,
+    pub instrument: String,
+    // Touch on the side the parent order trades against
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub step_size: Decimal,
+    pub min_size: Decimal,
+    // Free margin in quote units; None when the venue doesn't report it
+    pub available_margin: Option<Decimal>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChildOrder {
+    pub venue: Venue,
+    pub instrument: String,
+    pub price: Decimal,
+    pub quantity: Decimal,
+}
+
+#[derive(Debug, Clone)]
+pub struct RoutePlan {
+    pub children: Vec<ChildOrder>,
+    // Left over once every venue's touch or margin is used up
+    pub unrouted: Decimal,
+}
+
+// Price after taker fees: what a buy really pays, or a sell really receives
+fn fee_adjusted(price: Decimal, fee_bps: u32, side: Side) -> Decimal {
+    let fee = Decimal::from_int(fee_bps as i64)
+        .checked_div(Decimal::from_int(BPS))
+        .unwrap_or_default();
+    let factor = match side {
+        Side::Buy => Decimal::ONE.checked_add(fee),
+        Side::Sell => Decimal::ONE.checked_sub(fee),
+    };
+    factor
+        .and_then(|factor| price.checked_mul(factor))
+        .unwrap_or(price)
+}
+
+// Ordering of two venues by price for the parent side, best first
+fn by_price(side: Side, a: Decimal, b: Decimal) -> Ordering {
+    match side {
+        Side::Buy => a.cmp(&b),
+        Side::Sell => b.cmp(&a),
+    }
+}
+
+fn crosses_limit(side: Side, price: Decimal, limit: Option<Decimal>) -> bool {
+    match (side, limit) {
+        (Side::Buy, Some(limit)) => price > limit,
+        (Side::Sell, Some(limit)) => price < limit,
+        (_, None) => false,
+    }
+}
+
+/// Split a parent order across venues against their current touch.
+///
+/// Venues are ranked by `policy`; each takes as much as its touch, its margin and its step
+/// size allow before the next one is considered. Children are priced at the venue's touch.
+pub fn plan(
+    config: &RouterConfig,
+    policy: RoutingPolicy,
+    side: Side,
+    quantity: Decimal,
+    limit_price: Option<Decimal>,
+    mut venues: Vec<VenueLiquidity>,
+) -> RoutePlan {
+    venues.retain(|venue| {
+        !venue.quantity.is_zero() && !crosses_limit(side, venue.price, limit_price)
+    });
+    venues.sort_by(|a, b| {
+        let effective_a = fee_adjusted(a.price, config.fee_bps(a.venue), side);
+        let effective_b = fee_adjusted(b.price, config.fee_bps(b.venue), side);
+        let priority = config.priority_of(a.venue).cmp(&config.priority_of(b.venue));
+        match policy {
+            RoutingPolicy::BestPrice => by_price(side, effective_a, effective_b).then(priority),
+            RoutingPolicy::LowestFee => config
+                .fee_bps(a.venue)
+                .cmp(&config.fee_bps(b.venue))
+                .then(by_price(side, effective_a, effective_b))
+                .then(priority),
+            RoutingPolicy::VenuePriority => priority,
+        }
+    });
+
+    let mut remaining = quantity;
+    let mut children = Vec::new();
+    for venue in venues {
+        if remaining.is_zero() {
+            break;
+        }
+
+        // Margin caps the notional, leverage scales it
+        let margin_cap = venue.available_margin.and_then(|margin| {
+            margin
+                .checked_mul(Decimal::from_int(config.leverage_for(venue.venue) as i64))
+                .and_then(|notional| notional.checked_div(venue.price))
+        });
+        let capacity = match margin_cap {
+            Some(cap) => venue.quantity.min(cap),
+            None => venue.quantity,
+        };
+        let available = remaining.min(capacity);
+        let child_quantity = available
+            .round_to_increment(venue.step_size, RoundingMode::Floor)
+            .unwrap_or(available);
+        if child_quantity <= Decimal::ZERO || child_quantity < venue.min_size {
+            continue;
+        }
+
+        remaining = remaining.checked_sub(child_quantity).unwrap_or_default();
+        children.push(ChildOrder {
+            venue: venue.venue,
+            instrument: venue.instrument,
+            price: venue.price,
+            quantity: child_quantity,
+        });
+    }
+
+    RoutePlan {
+        children,
+        unrouted: remaining,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn venue(venue: Venue, price: &str, quantity: &str) -> VenueLiquidity {
+        VenueLiquidity {
+            venue,
+            instrument: venue.to_string(),
+            price: dec(price),
+            quantity: dec(quantity),
+            step_size: dec("0.1"),
+            min_size: dec("0.1"),
+            available_margin: None,
+        }
+    }
+
+    fn config() -> RouterConfig {
+        RouterConfig {
+            taker_fee_bps: HashMap::from([
+                (Venue::Vertex, 10),
+                (Venue::Dydx, 5),
+                (Venue::Orderly, 0),
+            ]),
+            ..RouterConfig::default()
+        }
+    }
+
+    fn split(plan: &RoutePlan) -> Vec<(Venue, Decimal)> {
+        plan.children
+            .iter()
+            .map(|child| (child.venue, child.quantity))
+            .collect()
+    }
+
+    #[test]
+    fn best_price_takes_the_cheapest_fee_adjusted_touch_first() {
+        // Vertex's lower ask loses to dYdX once Vertex's higher fee is added
+        let venues = vec![
+            venue(Venue::Vertex, "100.00", "1"),
+            venue(Venue::Dydx, "100.04", "1"),
+            venue(Venue::Orderly, "100.20", "5"),
+        ];
+        let plan = plan(
+            &config(),
+            RoutingPolicy::BestPrice,
+            Side::Buy,
+            dec("2.5"),
+            None,
+            venues,
+        );
+        assert_eq!(
+            split(&plan),
+            vec![
+                (Venue::Dydx, dec("1")),
+                (Venue::Vertex, dec("1")),
+                (Venue::Orderly, dec("0.5"))
+            ]
+        );
+        assert!(plan.unrouted.is_zero());
+    }
+
+    #[test]
+    fn best_price_sells_into_the_highest_bid() {
+        let venues = vec![
+            venue(Venue::Vertex, "99", "1"),
+            venue(Venue::Dydx, "101", "1"),
+        ];
+        let plan = plan(
+            &config(),
+            RoutingPolicy::BestPrice,
+            Side::Sell,
+            dec("1"),
+            None,
+            venues,
+        );
+        assert_eq!(split(&plan), vec![(Venue::Dydx, dec("1"))]);
+    }
+
+    #[test]
+    fn lowest_fee_ranks_by_fee_before_price() {
+        let venues = vec![
+            venue(Venue::Vertex, "90", "1"),
+            venue(Venue::Orderly, "110", "1"),
+        ];
+        let plan = plan(
+            &config(),
+            RoutingPolicy::LowestFee,
+            Side::Buy,
+            dec("1"),
+            None,
+            venues,
+        );
+        assert_eq!(split(&plan), vec![(Venue::Orderly, dec("1"))]);
+    }
+
+    #[test]
+    fn venue_priority_follows_the_configured_order() {
+        let config = RouterConfig {
+            venue_priority: vec![Venue::Orderly, Venue::Vertex],
+            ..config()
+        };
+        let venues = vec![
+            venue(Venue::Dydx, "90", "1"),
+            venue(Venue::Vertex, "100", "1"),
+            venue(Venue::Orderly, "110", "1"),
+        ];
+        let plan = plan(
+            &config,
+            RoutingPolicy::VenuePriority,
+            Side::Buy,
+            dec("3"),
+            None,
+            venues,
+        );
+        // Unlisted venues come last
+        assert_eq!(
+            split(&plan),
+            vec![
+                (Venue::Orderly, dec("1")),
+                (Venue::Vertex, dec("1")),
+                (Venue::Dydx, dec("1"))
+            ]
+        );
+    }
+
+    #[test]
+    fn limit_price_skips_venues_beyond_it() {
+        let venues = vec![
+            venue(Venue::Vertex, "100", "1"),
+            venue(Venue::Dydx, "105", "1"),
+        ];
+        let plan = plan(
+            &config(),
+            RoutingPolicy::BestPrice,
+            Side::Buy,
+            dec("2"),
+            Some(dec("101")),
+            venues,
+        );
+        assert_eq!(split(&plan), vec![(Venue::Vertex, dec("1"))]);
+        assert_eq!(plan.unrouted, dec("1"));
+    }
+
+    #[test]
+    fn margin_caps_the_child_with_leverage() {
+        let config = RouterConfig {
+            leverage: HashMap::from([(Venue::Dydx, 5)]),
+            ..config()
+        };
+        let mut vertex = venue(Venue::Vertex, "100", "10");
+        vertex.available_margin = Some(dec("150"));
+        let mut dydx = venue(Venue::Dydx, "100", "10");
+        dydx.available_margin = Some(dec("50"));
+        let plan = plan(
+            &config,
+            RoutingPolicy::VenuePriority,
+            Side::Buy,
+            dec("10"),
+            None,
+            vec![vertex, dydx],
+        );
+        // 150 of margin buys 1.5 at 100; 50 at 5x leverage buys 2.5
+        assert_eq!(
+            split(&plan),
+            vec![(Venue::Vertex, dec("1.5")), (Venue::Dydx, dec("2.5"))]
+        );
+        assert_eq!(plan.unrouted, dec("6"));
+    }
+
+    #[test]
+    fn children_round_down_to_the_step_and_respect_the_minimum() {
+        let mut vertex = venue(Venue::Vertex, "100", "0.37");
+        vertex.step_size = dec("0.1");
+        let mut dydx = venue(Venue::Dydx, "100", "0.05");
+        dydx.min_size = dec("0.1");
+        let plan = plan(
+            &config(),
+            RoutingPolicy::VenuePriority,
+            Side::Buy,
+            dec("1"),
+            None,
+            vec![vertex, dydx],
+        );
+        assert_eq!(split(&plan), vec![(Venue::Vertex, dec("0.3"))]);
+        assert_eq!(plan.unrouted, dec("0.7"));
+    }
+}
@@ -0,0 +1,587 @@
+use std::collections::{HashMap, HashSet};
+
+use log::{info, warn};
+use serde::Deserialize;
+
+use crate::{
+    domain::models::trading::{
+        MarketEvent, OrderRequest, Side, TimeInForce, Venue, VenueOrderStatus,
+    },
+    shared::utils::decimal::{Decimal, RoundingMode},
+};
+
+use super::strategy::{BookView, OrderResponse, Strategy, StrategyContext};
+
+const BPS: i64 = 10_000;
+
+// One venue's listing of the traded instrument
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArbLegParams {
+    pub venue: Venue,
+    // Venue symbol, which may differ between venues for the same market
+    pub instrument: String,
+    pub tick_size: Decimal,
+    // Sizes are rounded down to it when set
+    #[serde(default)]
+    pub step_size: Option<Decimal>,
+    #[serde(default)]
+    pub min_size: Decimal,
+    pub taker_fee_bps: Decimal,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArbitrageParams {
+    // Exactly two legs on different venues
+    pub legs: Vec<ArbLegParams>,
+    // Edge left after both taker fees that a cross must offer, in bps of the buy price
+    pub min_edge_bps: Decimal,
+    // Largest quantity sent on each leg per cross
+    pub max_order_size: Decimal,
+    // Absolute position either venue may build up; crosses adding past it are skipped
+    pub max_position: Decimal,
+    // How far through the touch a hedge IOC may fill
+    #[serde(default = "default_hedge_slippage_bps")]
+    pub hedge_slippage_bps: Decimal,
+    // Quiet time after a round is answered so its late fills land before the next decision
+    #[serde(default = "default_settle_ms")]
+    pub settle_ms: u64,
+}
+
+fn default_hedge_slippage_bps() -> Decimal {
+    Decimal::from_int(10)
+}
+
+fn default_settle_ms() -> u64 {
+    250
+}
+
+#[derive(Debug, Default)]
+struct LegState {
+    // Best bid and ask with their sizes
+    bid: Option<(Decimal, Decimal)>,
+    ask: Option<(Decimal, Decimal)>,
+    position: Decimal,
+}
+
+/// Takes both sides of a cross between two venues listing the same instrument.
+///
+/// When one venue's bid is above the other's ask by more than both taker fees plus
+/// `min_edge_bps`, the strategy buys the ask and sells the bid with a pair of IOC orders
+/// sized to the smaller touch. Each cross leaves the two venues' positions offsetting;
+/// a leg that fills short of the other is hedged on whichever venue prices it best, and
+/// crosses that would push a venue past `max_position` are skipped, so the positions are
+/// only worked back down by crosses in the other direction.
+#[derive(Debug)]
+pub struct Arbitrage {
+    params: ArbitrageParams,
+    legs: [LegState; 2],
+    // Orders sent but not yet answered, by tag
+    pending: HashMap<u64, usize>,
+    // Accepted orders until the venue closes them
+    orders: HashMap<String, usize>,
+    // IOC fills and closing updates can beat their ack; they are held by order id until it
+    // arrives
+    unclaimed: HashMap<String, Vec<Decimal>>,
+    closed: HashSet<String>,
+    // When the last answer of the previous round arrived
+    settled_at_ms: u64,
+}
+
+pub fn factory(params: &serde_json::Value) -> Result<Box<dyn Strategy>, String> {
+    Ok(Box::new(Arbitrage::new(parse_params(params)?)))
+}
+
+fn parse_params(params: &serde_json::Value) -> Result<ArbitrageParams, String> {
+    let params: ArbitrageParams =
+        serde_json::from_value(params.clone()).map_err(|e| e.to_string())?;
+    let [first, second] = params.legs.as_slice() else {
+        return Err("Arbitrage needs exactly two legs".to_string());
+    };
+    if first.venue == second.venue {
+        return Err("Legs must be on different venues".to_string());
+    }
+    if params
+        .legs
+        .iter()
+        .any(|leg| leg.tick_size <= Decimal::ZERO || leg.taker_fee_bps.is_negative())
+    {
+        return Err("tick_size must be positive and taker_fee_bps not negative".to_string());
+    }
+    if params.max_order_size <= Decimal::ZERO || params.max_position <= Decimal::ZERO {
+        return Err("max_order_size and max_position must be positive".to_string());
+    }
+    Ok(params)
+}
+
+fn bps(value: Decimal) -> Decimal {
+    value
+        .checked_div(Decimal::from_int(BPS))
+        .unwrap_or_default()
+}
+
+// Price after the taker fee: what a buy really pays, or a sell really receives
+fn fee_adjusted(price: Decimal, fee_bps: Decimal, side: Side) -> Option<Decimal> {
+    let factor = match side {
+        Side::Buy => Decimal::ONE.checked_add(bps(fee_bps))?,
+        Side::Sell => Decimal::ONE.checked_sub(bps(fee_bps))?,
+    };
+    price.checked_mul(factor)
+}
+
+impl Arbitrage {
+    pub fn new(params: ArbitrageParams) -> Self {
+        Arbitrage {
+            params,
+            legs: Default::default(),
+            pending: HashMap::new(),
+            orders: HashMap::new(),
+            unclaimed: HashMap::new(),
+            closed: HashSet::new(),
+            settled_at_ms: 0,
+        }
+    }
+
+    fn leg_of(&self, venue: Venue, instrument: &str) -> Option<usize> {
+        self.params
+            .legs
+            .iter()
+            .position(|leg| leg.venue == venue && leg.instrument == instrument)
+    }
+
+    // Rounded down to the leg's step, or None when below its minimum
+    fn round_size(&self, leg: usize, quantity: Decimal) -> Option<Decimal> {
+        let spec = &self.params.legs[leg];
+        let quantity = match spec.step_size {
+            Some(step) => quantity.round_to_increment(step, RoundingMode::TowardZero)?,
+            None => quantity,
+        };
+        (quantity > Decimal::ZERO && quantity >= spec.min_size).then_some(quantity)
+    }
+
+    // Quantity both legs can trade: rounded to each leg's step in turn
+    fn pair_size(&self, quantity: Decimal) -> Option<Decimal> {
+        let quantity = self.round_size(0, quantity)?;
+        let quantity = self.round_size(1, quantity)?;
+        self.round_size(0, quantity)
+    }
+
+    // Room left on a leg's position for a trade on the given side
+    fn room(&self, leg: usize, side: Side) -> Decimal {
+        let position = self.legs[leg].position;
+        let room = match side {
+            Side::Buy => self.params.max_position.checked_sub(position),
+            Side::Sell => self.params.max_position.checked_add(position),
+        };
+        room.unwrap_or_default().max(Decimal::ZERO)
+    }
+
+    // Edge in bps of buying on one leg and selling on the other, after both fees
+    fn edge_bps(&self, buy: usize, sell: usize) -> Option<Decimal> {
+        let (ask, _) = self.legs[buy].ask?;
+        let (bid, _) = self.legs[sell].bid?;
+        let cost = fee_adjusted(ask, self.params.legs[buy].taker_fee_bps, Side::Buy)?;
+        let proceeds = fee_adjusted(bid, self.params.legs[sell].taker_fee_bps, Side::Sell)?;
+        proceeds
+            .checked_sub(cost)?
+            .checked_mul(Decimal::from_int(BPS))?
+            .checked_div(ask)
+    }
+
+    fn send(
+        &mut self,
+        ctx: &mut StrategyContext,
+        leg: usize,
+        side: Side,
+        price: Decimal,
+        quantity: Decimal,
+    ) {
+        let spec = &self.params.legs[leg];
+        let order = OrderRequest {
+            instrument: spec.instrument.clone(),
+            side,
+            price,
+            quantity,
+            time_in_force: TimeInForce::Ioc,
+            client_order_id: None,
+        };
+        let tag = ctx.place(spec.venue, order);
+        self.pending.insert(tag, leg);
+    }
+
+    // Fire both legs of a cross when it clears the fees and the margin
+    fn try_cross(&mut self, ctx: &mut StrategyContext, buy: usize, sell: usize) -> bool {
+        let Some(edge) = self.edge_bps(buy, sell) else {
+            return false;
+        };
+        if edge < self.params.min_edge_bps {
+            return false;
+        }
+        let (Some((ask, ask_size)), Some((bid, bid_size))) =
+            (self.legs[buy].ask, self.legs[sell].bid)
+        else {
+            return false;
+        };
+        let quantity = ask_size
+            .min(bid_size)
+            .min(self.params.max_order_size)
+            .min(self.room(buy, Side::Buy))
+            .min(self.room(sell, Side::Sell));
+        let Some(quantity) = self.pair_size(quantity) else {
+            return false;
+        };
+
+        info!(
+            "Crossing {} {} on {} at {} against {} on {} at {}, edge {} bps",
+            quantity,
+            self.params.legs[buy].instrument,
+            self.params.legs[buy].venue,
+            ask,
+            self.params.legs[sell].instrument,
+            self.params.legs[sell].venue,
+            bid,
+            edge
+        );
+        self.send(ctx, buy, Side::Buy, ask, quantity);
+        self.send(ctx, sell, Side::Sell, bid, quantity);
+        true
+    }
+
+    // IOC through the touch by at most the slippage, kept on the leg's tick
+    fn hedge_price(&self, leg: usize, side: Side) -> Option<Decimal> {
+        let slippage = bps(self.params.hedge_slippage_bps);
+        let tick = self.params.legs[leg].tick_size;
+        match side {
+            Side::Buy => {
+                let (ask, _) = self.legs[leg].ask?;
+                let limit = ask
+                    .checked_mul(Decimal::ONE.checked_add(slippage)?)?
+                    .round_to_increment(tick, RoundingMode::Floor)?;
+                Some(limit.max(ask))
+            }
+            Side::Sell => {
+                let (bid, _) = self.legs[leg].bid?;
+                let limit = bid
+                    .checked_mul(Decimal::ONE.checked_sub(slippage)?)?
+                    .round_to_increment(tick, RoundingMode::Ceil)?;
+                Some(limit.min(bid))
+            }
+        }
+    }
+
+    // Flatten the net position left by a leg that filled short of the other, on whichever
+    // venue gives the better fee-adjusted price
+    fn hedge(&mut self, ctx: &mut StrategyContext, net: Decimal) -> bool {
+        let side = Side::of(net).opposite();
+        let best = (0..self.legs.len())
+            .filter_map(|leg| {
+                let quantity = self.round_size(leg, net.abs())?;
+                let touch = match side {
+                    Side::Buy => self.legs[leg].ask?.0,
+                    Side::Sell => self.legs[leg].bid?.0,
+                };
+                let price = fee_adjusted(touch, self.params.legs[leg].taker_fee_bps, side)?;
+                Some((leg, quantity, price))
+            })
+            .min_by(|a, b| match side {
+                Side::Buy => a.2.cmp(&b.2),
+                Side::Sell => b.2.cmp(&a.2),
+            });
+        let Some((leg, quantity, _)) = best else {
+            return false;
+        };
+        let Some(price) = self.hedge_price(leg, side) else {
+            return false;
+        };
+
+        warn!(
+            "Arbitrage on {} is off by {}, hedging on {}",
+            self.params.legs[leg].instrument, net, self.params.legs[leg].venue
+        );
+        self.send(ctx, leg, side, price, quantity);
+        true
+    }
+
+    fn check(&mut self, ctx: &mut StrategyContext) {
+        // One round at a time, and only once the last one has settled
+        if !self.pending.is_empty()
+            || ctx.now_ms() < self.settled_at_ms.saturating_add(self.params.settle_ms)
+        {
+            return;
+        }
+        let net = self.legs[0]
+            .position
+            .checked_add(self.legs[1].position)
+            .unwrap_or_default();
+        if !net.is_zero() && self.hedge(ctx, net) {
+            return;
+        }
+        if !self.try_cross(ctx, 0, 1) {
+            self.try_cross(ctx, 1, 0);
+        }
+    }
+
+    fn apply_fill(&mut self, leg: usize, signed: Decimal) {
+        let position = &mut self.legs[leg].position;
+        if let Some(updated) = position.checked_add(signed) {
+            *position = updated;
+        }
+    }
+}
+
+impl Strategy for Arbitrage {
+    fn subscriptions(&self) -> Vec<(Venue, String)> {
+        self.params
+            .legs
+            .iter()
+            .map(|leg| (leg.venue, leg.instrument.clone()))
+            .collect()
+    }
+
+    // Thresholds and sizes apply from the next cross; the legs need a restart
+    fn configure(&mut self, params: &serde_json::Value) -> Result<(), String> {
+        let params = parse_params(params)?;
+        let same_legs = params
+            .legs
+            .iter()
+            .zip(&self.params.legs)
+            .all(|(new, old)| new.venue == old.venue && new.instrument == old.instrument);
+        if !same_legs {
+            return Err("Legs can't change while running".to_string());
+        }
+        self.params = params;
+        Ok(())
+    }
+
+    fn on_book(&mut self, ctx: &mut StrategyContext, book: &BookView) {
+        let Some(leg) = self.leg_of(book.venue, book.instrument) else {
+            return;
+        };
+        let state = &mut self.legs[leg];
+        state.bid = book
+            .best_bid
+            .as_ref()
+            .map(|level| (level.price, level.quantity));
+        state.ask = book
+            .best_ask
+            .as_ref()
+            .map(|level| (level.price, level.quantity));
+        self.check(ctx);
+    }
+
+    fn on_fill(&mut self, _ctx: &mut StrategyContext, fill: &MarketEvent) {
+        let MarketEvent::Fill {
+            venue,
+            instrument,
+            order_id,
+            side,
+            quantity,
+            ..
+        } = fill
+        else {
+            return;
+        };
+        let Some(leg) = self.leg_of(*venue, instrument) else {
+            return;
+        };
+        let signed = side.signed(*quantity);
+        if self.orders.get(order_id) == Some(&leg) {
+            self.apply_fill(leg, signed);
+        } else if !self.pending.is_empty() {
+            self.unclaimed
+                .entry(order_id.clone())
+                .or_default()
+                .push(signed);
+        }
+    }
+
+    fn on_order_update(&mut self, _ctx: &mut StrategyContext, update: &MarketEvent) {
+        let MarketEvent::OrderUpdate {
+            order_id, status, ..
+        } = update
+        else {
+            return;
+        };
+        if matches!(
+            status,
+            VenueOrderStatus::Filled | VenueOrderStatus::Cancelled | VenueOrderStatus::Rejected
+        ) && self.orders.remove(order_id).is_none()
+            && !self.pending.is_empty()
+        {
+            self.closed.insert(order_id.clone());
+        }
+    }
+
+    fn on_order_ack(&mut self, ctx: &mut StrategyContext, response: &OrderResponse) {
+        let Some(leg) = self.pending.remove(&response.tag) else {
+            return;
+        };
+        match &response.result {
+            Ok(ack) => {
+                if !self.closed.remove(&ack.order_id) {
+                    self.orders.insert(ack.order_id.clone(), leg);
+                }
+                for signed in self.unclaimed.remove(&ack.order_id).unwrap_or_default() {
+                    self.apply_fill(leg, signed);
+                }
+            }
+            Err(e) => warn!(
+                "Arbitrage order on {} {} failed: {}",
+                self.params.legs[leg].venue, self.params.legs[leg].instrument, e
+            ),
+        }
+        // Whatever is left belongs to other orders on these instruments
+        if self.pending.is_empty() {
+            self.unclaimed.clear();
+            self.closed.clear();
+            self.settled_at_ms = ctx.now_ms();
+        }
+    }
+
+    // IOC orders never rest, so there is nothing to cancel
+    fn on_stop(&mut self, _ctx: &mut StrategyContext) {
+        info!(
+            "Arbitrage stopped holding {} on {} and {} on {}",
+            self.legs[0].position,
+            self.params.legs[0].venue,
+            self.legs[1].position,
+            self.params.legs[1].venue
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::strategies::strategy::StrategyAction;
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn leg(venue: Venue, instrument: &str, step: &str, fee_bps: &str) -> ArbLegParams {
+        ArbLegParams {
+            venue,
+            instrument: instrument.to_string(),
+            tick_size: Decimal::ONE,
+            step_size: Some(dec(step)),
+            min_size: dec(step),
+            taker_fee_bps: dec(fee_bps),
+        }
+    }
+
+    fn arbitrage() -> Arbitrage {
+        Arbitrage::new(ArbitrageParams {
+            legs: vec![
+                leg(Venue::Vertex, "BTC-PERP", "0.001", "2"),
+                leg(Venue::Dydx, "BTC-USD", "0.01", "5"),
+            ],
+            min_edge_bps: dec("1"),
+            max_order_size: dec("1"),
+            max_position: dec("2"),
+            hedge_slippage_bps: dec("10"),
+            settle_ms: 0,
+        })
+    }
+
+    fn touch(arb: &mut Arbitrage, leg: usize, bid: (&str, &str), ask: (&str, &str)) {
+        arb.legs[leg].bid = Some((dec(bid.0), dec(bid.1)));
+        arb.legs[leg].ask = Some((dec(ask.0), dec(ask.1)));
+    }
+
+    // Venue, side and quantity of every order a check sends
+    fn check(arb: &mut Arbitrage) -> Vec<(Venue, Side, Decimal)> {
+        let mut ctx = StrategyContext::new();
+        arb.check(&mut ctx);
+        ctx.take_actions()
+            .into_iter()
+            .filter_map(|action| match action {
+                StrategyAction::Place { venue, order, .. } => {
+                    Some((venue, order.side, order.quantity))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn the_edge_is_what_is_left_after_both_taker_fees() {
+        let mut arb = arbitrage();
+        touch(&mut arb, 0, ("99990", "1"), ("100000", "1"));
+        touch(&mut arb, 1, ("100100", "1"), ("100110", "1"));
+
+        // 100100 * 0.9995 - 100000 * 1.0002 = 29.95, on a 100000 ask
+        assert_eq!(arb.edge_bps(0, 1), Some(dec("2.995")));
+        assert_eq!(
+            check(&mut arb),
+            vec![
+                (Venue::Vertex, Side::Buy, dec("1")),
+                (Venue::Dydx, Side::Sell, dec("1")),
+            ]
+        );
+
+        // Six bps of raw spread do not cover seven bps of fees
+        let mut arb = arbitrage();
+        touch(&mut arb, 0, ("99990", "1"), ("100000", "1"));
+        touch(&mut arb, 1, ("100060", "1"), ("100070", "1"));
+        assert!(arb.edge_bps(0, 1).unwrap().is_negative());
+        assert!(check(&mut arb).is_empty());
+    }
+
+    #[test]
+    fn both_legs_trade_a_size_valid_on_either_step() {
+        let mut arb = arbitrage();
+        assert_eq!(arb.pair_size(dec("0.4567")), Some(dec("0.45")));
+        assert_eq!(arb.pair_size(dec("0.009")), None);
+
+        touch(&mut arb, 0, ("99990", "1"), ("100000", "0.4567"));
+        touch(&mut arb, 1, ("100100", "1"), ("100110", "1"));
+        assert_eq!(
+            check(&mut arb),
+            vec![
+                (Venue::Vertex, Side::Buy, dec("0.45")),
+                (Venue::Dydx, Side::Sell, dec("0.45")),
+            ]
+        );
+    }
+
+    #[test]
+    fn crosses_only_use_the_room_left_under_max_position() {
+        let mut arb = arbitrage();
+        touch(&mut arb, 0, ("99990", "1"), ("100000", "1"));
+        touch(&mut arb, 1, ("100100", "1"), ("100110", "1"));
+        arb.legs[0].position = dec("1.8");
+        arb.legs[1].position = dec("-1.8");
+        assert_eq!(
+            check(&mut arb),
+            vec![
+                (Venue::Vertex, Side::Buy, dec("0.2")),
+                (Venue::Dydx, Side::Sell, dec("0.2")),
+            ]
+        );
+
+        arb.pending.clear();
+        arb.legs[0].position = dec("2");
+        arb.legs[1].position = dec("-2");
+        assert!(check(&mut arb).is_empty());
+    }
+
+    #[test]
+    fn a_short_leg_is_hedged_where_it_nets_the_most() {
+        // Selling 0.5 nets 100000 * 0.9998 = 99980 on Vertex against 99960.005 on dYdX
+        let mut arb = arbitrage();
+        touch(&mut arb, 0, ("100000", "1"), ("100010", "1"));
+        touch(&mut arb, 1, ("100010", "1"), ("100020", "1"));
+        arb.legs[0].position = dec("0.5");
+        assert_eq!(
+            check(&mut arb),
+            vec![(Venue::Vertex, Side::Sell, dec("0.5"))]
+        );
+
+        // A better dYdX bid outweighs its higher fee
+        let mut arb = arbitrage();
+        touch(&mut arb, 0, ("100000", "1"), ("100010", "1"));
+        touch(&mut arb, 1, ("100050", "1"), ("100060", "1"));
+        arb.legs[0].position = dec("0.5");
+        assert_eq!(check(&mut arb), vec![(Venue::Dydx, Side::Sell, dec("0.5"))]);
+    }
+}
@@ -0,0 +1,736 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use log::{info, warn};
+use serde::Deserialize;
+
+use crate::{
+    domain::models::trading::{
+        MarketEvent, OrderRequest, Side, TimeInForce, Venue, VenueOrderStatus,
+    },
+    shared::utils::decimal::{Decimal, RoundingMode},
+};
+
+use super::strategy::{BookView, OrderResponse, Strategy, StrategyContext};
+
+// Spot and perp of one underlying both trade on Vertex
+const VENUE: Venue = Venue::Vertex;
+const BPS: i64 = 10_000;
+
+// 24h funding rate of the perp as of a time, kept outside the strategy
+pub trait FundingSource: Debug + Send + Sync {
+    fn rate_at(&self, now_ms: u64) -> Option<Decimal>;
+}
+
+pub type FundingHandle = Arc<dyn FundingSource>;
+
+// Latest polled rate, whatever the time
+impl FundingSource for RwLock<Option<Decimal>> {
+    fn rate_at(&self, _now_ms: u64) -> Option<Decimal> {
+        *self.read().unwrap()
+    }
+}
+
+// Recorded rates as (unix ms, rate), oldest first; each holds until the next
+#[derive(Debug, Clone, Default)]
+pub struct FundingHistory(pub Vec<(u64, Decimal)>);
+
+impl FundingSource for FundingHistory {
+    fn rate_at(&self, now_ms: u64) -> Option<Decimal> {
+        let known = self.0.partition_point(|(at_ms, _)| *at_ms <= now_ms);
+        let (_, rate) = self.0.get(known.checked_sub(1)?)?;
+        Some(*rate)
+    }
+}
+
+// Tick and lot rules of one leg
+#[derive(Debug, Clone)]
+pub struct LegSpec {
+    pub instrument: String,
+    pub price_increment: Decimal,
+    pub size_increment: Decimal,
+    pub min_size: Decimal,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BasisParams {
+    pub spot_instrument: String,
+    pub perp_instrument: String,
+    // Base size of each leg once fully in
+    pub quantity: Decimal,
+    // Largest pair of child orders sent per check
+    pub slice_size: Decimal,
+    // |24h funding| that opens a position, in bps
+    pub entry_funding_bps: Decimal,
+    // The position unwinds once the perp's premium over spot is back within this, in bps
+    pub exit_basis_bps: Decimal,
+    // Base units the legs may drift apart before the hedge trade fires
+    #[serde(default)]
+    pub hedge_tolerance: Decimal,
+    // Base units of imbalance at which the whole position is unwound
+    pub max_imbalance: Decimal,
+    // How far through the touch child IOCs may fill
+    #[serde(default = "default_slippage_bps")]
+    pub slippage_bps: Decimal,
+    // Allow spot short / perp long when funding is negative; needs spot borrowing
+    #[serde(default)]
+    pub allow_reverse: bool,
+    #[serde(default = "default_check_interval_secs")]
+    pub check_interval_secs: u64,
+    // Set through ConfigureStrategy to flatten both legs and stay flat
+    #[serde(default)]
+    pub unwind: bool,
+}
+
+fn default_slippage_bps() -> Decimal {
+    Decimal::from_int(10)
+}
+
+fn default_check_interval_secs() -> u64 {
+    5
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    // Spot long, perp short: collects positive funding
+    LongSpot,
+    // Spot short, perp long: collects negative funding
+    ShortSpot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Flat,
+    Entering(Direction),
+    Holding(Direction),
+    Exiting,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Leg {
+    Spot,
+    Perp,
+}
+
+/// Delta-neutral spot/perp position opened when funding pays and closed when basis converges.
+///
+/// Both legs are traded with IOC pairs on a timer. Fills update each leg's position as they
+/// arrive and the next check trades the perp to bring the legs back in line; an imbalance past
+/// `max_imbalance` unwinds everything. The strategy assumes both legs are flat when it starts.
+#[derive(Debug)]
+pub struct BasisCapture {
+    params: BasisParams,
+    spot: LegSpec,
+    perp: LegSpec,
+    funding: FundingHandle,
+    phase: Phase,
+    spot_position: Decimal,
+    perp_position: Decimal,
+    spot_touch: Option<(Decimal, Decimal)>,
+    perp_touch: Option<(Decimal, Decimal)>,
+    // Children sent but not yet answered
+    pending: HashMap<u64, Leg>,
+    // Accepted children until the venue closes them
+    orders: HashMap<String, Leg>,
+    // IOC fills and closing updates can beat their ack; they are held by order id until it
+    // arrives
+    unclaimed: HashMap<String, Vec<(Leg, Decimal)>>,
+    closed: HashSet<String>,
+}
+
+pub fn parse_params(params: &serde_json::Value) -> Result<BasisParams, String> {
+    let params: BasisParams = serde_json::from_value(params.clone()).map_err(|e| e.to_string())?;
+    if params.quantity <= Decimal::ZERO || params.slice_size <= Decimal::ZERO {
+        return Err("quantity and slice_size must be positive".to_string());
+    }
+    if params.max_imbalance <= params.hedge_tolerance {
+        return Err("max_imbalance must exceed hedge_tolerance".to_string());
+    }
+    if params.check_interval_secs == 0 {
+        return Err("check_interval_secs must be positive".to_string());
+    }
+    Ok(params)
+}
+
+fn bps(value: Decimal) -> Decimal {
+    value
+        .checked_div(Decimal::from_int(BPS))
+        .unwrap_or_default()
+}
+
+fn mid((bid, ask): (Decimal, Decimal)) -> Option<Decimal> {
+    bid.checked_add(ask)?.checked_div(Decimal::from_int(2))
+}
+
+impl BasisCapture {
+    pub fn new(params: BasisParams, spot: LegSpec, perp: LegSpec, funding: FundingHandle) -> Self {
+        BasisCapture {
+            params,
+            spot,
+            perp,
+            funding,
+            phase: Phase::Flat,
+            spot_position: Decimal::ZERO,
+            perp_position: Decimal::ZERO,
+            spot_touch: None,
+            perp_touch: None,
+            pending: HashMap::new(),
+            orders: HashMap::new(),
+            unclaimed: HashMap::new(),
+            closed: HashSet::new(),
+        }
+    }
+
+    fn leg(&self, leg: Leg) -> &LegSpec {
+        match leg {
+            Leg::Spot => &self.spot,
+            Leg::Perp => &self.perp,
+        }
+    }
+
+    fn leg_of(&self, instrument: &str) -> Option<Leg> {
+        if instrument == self.spot.instrument {
+            Some(Leg::Spot)
+        } else if instrument == self.perp.instrument {
+            Some(Leg::Perp)
+        } else {
+            None
+        }
+    }
+
+    // Perp premium over spot in bps
+    fn basis_bps(&self) -> Option<Decimal> {
+        let spot = mid(self.spot_touch?)?;
+        let perp = mid(self.perp_touch?)?;
+        perp.checked_sub(spot)?
+            .checked_mul(Decimal::from_int(BPS))?
+            .checked_div(spot)
+    }
+
+    fn entry_direction(&self, funding: Decimal, basis_bps: Decimal) -> Option<Direction> {
+        let threshold = bps(self.params.entry_funding_bps);
+        let exit = self.params.exit_basis_bps;
+        if funding >= threshold && basis_bps > exit {
+            return Some(Direction::LongSpot);
+        }
+        let short_threshold = Decimal::ZERO.checked_sub(threshold)?;
+        let short_exit = Decimal::ZERO.checked_sub(exit)?;
+        if self.params.allow_reverse && funding <= short_threshold && basis_bps < short_exit {
+            return Some(Direction::ShortSpot);
+        }
+        None
+    }
+
+    // Basis converged, or funding now charges the position instead of paying it
+    fn should_exit(&self, direction: Direction, funding: Decimal, basis_bps: Decimal) -> bool {
+        match direction {
+            Direction::LongSpot => basis_bps <= self.params.exit_basis_bps || funding.is_negative(),
+            Direction::ShortSpot => {
+                let exit = Decimal::ZERO
+                    .checked_sub(self.params.exit_basis_bps)
+                    .unwrap_or_default();
+                basis_bps >= exit || funding > Decimal::ZERO
+            }
+        }
+    }
+
+    fn round_size(&self, leg: Leg, quantity: Decimal) -> Option<Decimal> {
+        let spec = self.leg(leg);
+        let quantity = if spec.size_increment.is_zero() {
+            quantity
+        } else {
+            quantity.round_to_increment(spec.size_increment, RoundingMode::TowardZero)?
+        };
+        (quantity > Decimal::ZERO && quantity >= spec.min_size).then_some(quantity)
+    }
+
+    // IOC through the touch by at most the slippage, kept on the price increment
+    fn ioc_price(&self, leg: Leg, side: Side) -> Option<Decimal> {
+        let (bid, ask) = match leg {
+            Leg::Spot => self.spot_touch?,
+            Leg::Perp => self.perp_touch?,
+        };
+        let slippage = bps(self.params.slippage_bps);
+        let increment = self.leg(leg).price_increment;
+        match side {
+            Side::Buy => {
+                let limit = ask
+                    .checked_mul(Decimal::ONE.checked_add(slippage)?)?
+                    .round_to_increment(increment, RoundingMode::Floor)?;
+                Some(limit.max(ask))
+            }
+            Side::Sell => {
+                let limit = bid
+                    .checked_mul(Decimal::ONE.checked_sub(slippage)?)?
+                    .round_to_increment(increment, RoundingMode::Ceil)?;
+                Some(limit.min(bid))
+            }
+        }
+    }
+
+    fn send(&mut self, ctx: &mut StrategyContext, leg: Leg, side: Side, quantity: Decimal) {
+        let (Some(quantity), Some(price)) =
+            (self.round_size(leg, quantity), self.ioc_price(leg, side))
+        else {
+            return;
+        };
+        let order = OrderRequest {
+            instrument: self.leg(leg).instrument.clone(),
+            side,
+            price,
+            quantity,
+            time_in_force: TimeInForce::Ioc,
+            client_order_id: None,
+        };
+        let tag = ctx.place(VENUE, order);
+        self.pending.insert(tag, leg);
+    }
+
+    fn apply_fill(&mut self, leg: Leg, signed: Decimal) {
+        let position = match leg {
+            Leg::Spot => &mut self.spot_position,
+            Leg::Perp => &mut self.perp_position,
+        };
+        if let Some(updated) = position.checked_add(signed) {
+            *position = updated;
+        }
+    }
+
+    // Trade the perp back to the spot leg's size
+    fn hedge(&mut self, ctx: &mut StrategyContext, imbalance: Decimal) {
+        let side = if imbalance > Decimal::ZERO {
+            Side::Sell
+        } else {
+            Side::Buy
+        };
+        info!(
+            "Basis legs {} / {} off by {}, hedging on the perp",
+            self.spot.instrument, self.perp.instrument, imbalance
+        );
+        self.send(ctx, Leg::Perp, side, imbalance.abs());
+    }
+
+    // Send one pair of children moving both legs toward their targets
+    fn slice(&mut self, ctx: &mut StrategyContext, spot_target: Decimal) {
+        let spot_delta = spot_target
+            .checked_sub(self.spot_position)
+            .unwrap_or_default();
+        let quantity = spot_delta.abs().min(self.params.slice_size);
+        let spot_side = Side::of(spot_delta);
+        self.send(ctx, Leg::Spot, spot_side, quantity);
+        self.send(ctx, Leg::Perp, spot_side.opposite(), quantity);
+    }
+
+    fn check(&mut self, ctx: &mut StrategyContext) {
+        // One round of children at a time
+        if !self.pending.is_empty() {
+            return;
+        }
+
+        let imbalance = self
+            .spot_position
+            .checked_add(self.perp_position)
+            .unwrap_or_default();
+        if imbalance.abs() > self.params.max_imbalance && self.phase != Phase::Exiting {
+            warn!(
+                "Basis legs {} / {} off by {}, past the limit; unwinding",
+                self.spot.instrument, self.perp.instrument, imbalance
+            );
+            self.phase = Phase::Exiting;
+        }
+        let tolerance = self.params.hedge_tolerance.max(self.perp.min_size);
+        if imbalance.abs() >= tolerance && self.phase != Phase::Exiting {
+            self.hedge(ctx, imbalance);
+            return;
+        }
+
+        if self.params.unwind && self.phase != Phase::Flat {
+            self.phase = Phase::Exiting;
+        }
+        // Entering and holding decisions need both the funding rate and the basis
+        let funding = self.funding.rate_at(ctx.now_ms());
+        let signals = funding.zip(self.basis_bps());
+        match self.phase {
+            Phase::Flat => {
+                let Some((funding, basis_bps)) = signals.filter(|_| !self.params.unwind) else {
+                    return;
+                };
+                if let Some(direction) = self.entry_direction(funding, basis_bps) {
+                    info!(
+                        "Entering {:?} on {} at funding {} and basis {} bps",
+                        direction, self.spot.instrument, funding, basis_bps
+                    );
+                    self.phase = Phase::Entering(direction);
+                    self.check(ctx);
+                }
+            }
+            Phase::Entering(direction) => {
+                let target = match direction {
+                    Direction::LongSpot => self.params.quantity,
+                    Direction::ShortSpot => {
+                        Decimal::ZERO.checked_sub(self.params.quantity).unwrap_or_default()
+                    }
+                };
+                let remaining = target.checked_sub(self.spot_position).unwrap_or_default();
+                if self.round_size(Leg::Spot, remaining.abs()).is_none() {
+                    self.phase = Phase::Holding(direction);
+                } else {
+                    self.slice(ctx, target);
+                }
+            }
+            Phase::Holding(direction) => {
+                let Some((funding, basis_bps)) = signals else {
+                    return;
+                };
+                if self.should_exit(direction, funding, basis_bps) {
+                    info!(
+                        "Unwinding {} at funding {} and basis {} bps",
+                        self.spot.instrument, funding, basis_bps
+                    );
+                    self.phase = Phase::Exiting;
+                    self.check(ctx);
+                }
+            }
+            Phase::Exiting => {
+                let spot_open = self.round_size(Leg::Spot, self.spot_position.abs()).is_some();
+                let perp_open = self.round_size(Leg::Perp, self.perp_position.abs()).is_some();
+                if spot_open {
+                    self.slice(ctx, Decimal::ZERO);
+                } else if perp_open {
+                    // Spot is gone; close whatever the perp still holds on its own
+                    let side = Side::of(self.perp_position).opposite();
+                    self.send(ctx, Leg::Perp, side, self.perp_position.abs());
+                } else {
+                    self.phase = Phase::Flat;
+                }
+            }
+        }
+    }
+}
+
+impl Strategy for BasisCapture {
+    fn subscriptions(&self) -> Vec<(Venue, String)> {
+        vec![
+            (VENUE, self.spot.instrument.clone()),
+            (VENUE, self.perp.instrument.clone()),
+        ]
+    }
+
+    fn timer_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(self.params.check_interval_secs))
+    }
+
+    fn configure(&mut self, params: &serde_json::Value) -> Result<(), String> {
+        let params = parse_params(params)?;
+        if params.spot_instrument != self.params.spot_instrument
+            || params.perp_instrument != self.params.perp_instrument
+        {
+            return Err("Instruments can't change while running".to_string());
+        }
+        self.params = params;
+        Ok(())
+    }
+
+    fn on_book(&mut self, _ctx: &mut StrategyContext, book: &BookView) {
+        let (Some(bid), Some(ask)) = (&book.best_bid, &book.best_ask) else {
+            return;
+        };
+        match self.leg_of(book.instrument) {
+            Some(Leg::Spot) => self.spot_touch = Some((bid.price, ask.price)),
+            Some(Leg::Perp) => self.perp_touch = Some((bid.price, ask.price)),
+            None => {}
+        }
+    }
+
+    // Positions move here; the hedge trade waits for the next check so late fills of the
+    // same round are counted first
+    fn on_fill(&mut self, _ctx: &mut StrategyContext, fill: &MarketEvent) {
+        let MarketEvent::Fill {
+            instrument,
+            order_id,
+            side,
+            quantity,
+            ..
+        } = fill
+        else {
+            return;
+        };
+        let Some(leg) = self.leg_of(instrument) else {
+            return;
+        };
+        let signed = side.signed(*quantity);
+        if self.orders.contains_key(order_id) {
+            self.apply_fill(leg, signed);
+        } else if !self.pending.is_empty() {
+            self.unclaimed
+                .entry(order_id.clone())
+                .or_default()
+                .push((leg, signed));
+        }
+    }
+
+    fn on_order_update(&mut self, _ctx: &mut StrategyContext, update: &MarketEvent) {
+        let MarketEvent::OrderUpdate {
+            order_id, status, ..
+        } = update
+        else {
+            return;
+        };
+        if matches!(
+            status,
+            VenueOrderStatus::Filled | VenueOrderStatus::Cancelled | VenueOrderStatus::Rejected
+        ) && self.orders.remove(order_id).is_none()
+            && !self.pending.is_empty()
+        {
+            self.closed.insert(order_id.clone());
+        }
+    }
+
+    fn on_order_ack(&mut self, _ctx: &mut StrategyContext, response: &OrderResponse) {
+        let Some(leg) = self.pending.remove(&response.tag) else {
+            return;
+        };
+        match &response.result {
+            Ok(ack) => {
+                if !self.closed.remove(&ack.order_id) {
+                    self.orders.insert(ack.order_id.clone(), leg);
+                }
+                for (leg, signed) in self.unclaimed.remove(&ack.order_id).unwrap_or_default() {
+                    self.apply_fill(leg, signed);
+                }
+            }
+            Err(e) => warn!(
+                "Basis {:?} child on {} failed: {}",
+                leg,
+                self.leg(leg).instrument,
+                e
+            ),
+        }
+        // Whatever is left belongs to other orders on these instruments
+        if self.pending.is_empty() {
+            self.unclaimed.clear();
+            self.closed.clear();
+        }
+    }
+
+    fn on_timer(&mut self, ctx: &mut StrategyContext) {
+        self.check(ctx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        models::trading::{BookLevel, OrderAck},
+        strategies::strategy::StrategyAction,
+    };
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn leg(instrument: &str) -> LegSpec {
+        LegSpec {
+            instrument: instrument.to_string(),
+            price_increment: dec("0.01"),
+            size_increment: dec("0.01"),
+            min_size: dec("0.01"),
+        }
+    }
+
+    fn strategy(funding: &str) -> BasisCapture {
+        let params = BasisParams {
+            spot_instrument: "BTC".to_string(),
+            perp_instrument: "BTC-PERP".to_string(),
+            quantity: dec("2"),
+            slice_size: dec("1"),
+            entry_funding_bps: dec("1"),
+            exit_basis_bps: dec("5"),
+            hedge_tolerance: dec("0.1"),
+            max_imbalance: dec("1"),
+            slippage_bps: dec("10"),
+            allow_reverse: false,
+            check_interval_secs: 5,
+            unwind: false,
+        };
+        let funding: FundingHandle = Arc::new(RwLock::new(Some(dec(funding))));
+        BasisCapture::new(params, leg("BTC"), leg("BTC-PERP"), funding)
+    }
+
+    fn quote(strategy: &mut BasisCapture, instrument: &'static str, bid: &str, ask: &str) {
+        let level = |price: &str| BookLevel {
+            price: dec(price),
+            quantity: Decimal::ONE,
+        };
+        strategy.on_book(
+            &mut StrategyContext::new(),
+            &BookView {
+                venue: VENUE,
+                instrument,
+                best_bid: Some(level(bid)),
+                best_ask: Some(level(ask)),
+            },
+        );
+    }
+
+    // Run a check and return what it sent
+    fn check(strategy: &mut BasisCapture, ctx: &mut StrategyContext) -> Vec<StrategyAction> {
+        strategy.on_timer(ctx);
+        ctx.take_actions()
+    }
+
+    fn orders(actions: &[StrategyAction]) -> Vec<(String, Side, Decimal)> {
+        actions
+            .iter()
+            .filter_map(|action| match action {
+                StrategyAction::Place { order, .. } => {
+                    Some((order.instrument.clone(), order.side, order.quantity))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Accept every child and fill it completely
+    fn fill_all(
+        strategy: &mut BasisCapture,
+        ctx: &mut StrategyContext,
+        actions: &[StrategyAction],
+    ) {
+        for action in actions {
+            let StrategyAction::Place { tag, venue, order } = action else {
+                continue;
+            };
+            let order_id = format!("order-{}", tag);
+            strategy.on_order_ack(
+                ctx,
+                &OrderResponse {
+                    tag: *tag,
+                    venue: *venue,
+                    order: order.clone(),
+                    result: Ok(OrderAck {
+                        order_id: order_id.clone(),
+                        local_id: None,
+                        client_order_id: None,
+                    }),
+                },
+            );
+            strategy.on_fill(
+                ctx,
+                &MarketEvent::Fill {
+                    venue: *venue,
+                    instrument: order.instrument.clone(),
+                    order_id: order_id.clone(),
+                    side: order.side,
+                    price: order.price,
+                    quantity: order.quantity,
+                    fee: Decimal::ZERO,
+                    timestamp_ms: 0,
+                },
+            );
+            strategy.on_order_update(
+                ctx,
+                &MarketEvent::OrderUpdate {
+                    venue: *venue,
+                    instrument: order.instrument.clone(),
+                    order_id,
+                    status: VenueOrderStatus::Filled,
+                    remaining: Decimal::ZERO,
+                    timestamp_ms: 0,
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn enters_holds_and_exits_as_funding_and_basis_move() {
+        let mut strategy = strategy("0.001");
+        let mut ctx = StrategyContext::new();
+        quote(&mut strategy, "BTC", "100", "100.2");
+        quote(&mut strategy, "BTC-PERP", "100.5", "100.7");
+
+        // About 50bps of premium with funding paying the short perp
+        let actions = check(&mut strategy, &mut ctx);
+        assert_eq!(strategy.phase, Phase::Entering(Direction::LongSpot));
+        assert_eq!(
+            orders(&actions),
+            vec![
+                ("BTC".to_string(), Side::Buy, dec("1")),
+                ("BTC-PERP".to_string(), Side::Sell, dec("1")),
+            ]
+        );
+        fill_all(&mut strategy, &mut ctx, &actions);
+        let actions = check(&mut strategy, &mut ctx);
+        assert_eq!(orders(&actions).len(), 2);
+        fill_all(&mut strategy, &mut ctx, &actions);
+
+        assert!(check(&mut strategy, &mut ctx).is_empty());
+        assert_eq!(strategy.phase, Phase::Holding(Direction::LongSpot));
+        assert_eq!(
+            (strategy.spot_position, strategy.perp_position),
+            (dec("2"), dec("-2"))
+        );
+
+        // Basis converged
+        quote(&mut strategy, "BTC-PERP", "100", "100.2");
+        let actions = check(&mut strategy, &mut ctx);
+        assert_eq!(strategy.phase, Phase::Exiting);
+        assert_eq!(
+            orders(&actions),
+            vec![
+                ("BTC".to_string(), Side::Sell, dec("1")),
+                ("BTC-PERP".to_string(), Side::Buy, dec("1")),
+            ]
+        );
+        fill_all(&mut strategy, &mut ctx, &actions);
+        let actions = check(&mut strategy, &mut ctx);
+        fill_all(&mut strategy, &mut ctx, &actions);
+
+        assert!(check(&mut strategy, &mut ctx).is_empty());
+        assert_eq!(strategy.phase, Phase::Flat);
+        // Closed children are no longer tracked
+        assert!(strategy.orders.is_empty());
+    }
+
+    #[test]
+    fn a_small_imbalance_is_hedged_on_the_perp() {
+        let mut strategy = strategy("0");
+        let mut ctx = StrategyContext::new();
+        quote(&mut strategy, "BTC", "100", "100.2");
+        quote(&mut strategy, "BTC-PERP", "100", "100.2");
+        strategy.phase = Phase::Holding(Direction::LongSpot);
+        strategy.spot_position = dec("2");
+        strategy.perp_position = dec("-1.5");
+
+        let actions = check(&mut strategy, &mut ctx);
+        assert_eq!(
+            orders(&actions),
+            vec![("BTC-PERP".to_string(), Side::Sell, dec("0.5"))]
+        );
+        assert_eq!(strategy.phase, Phase::Holding(Direction::LongSpot));
+    }
+
+    #[test]
+    fn an_imbalance_past_the_limit_unwinds_everything() {
+        let mut strategy = strategy("0.001");
+        let mut ctx = StrategyContext::new();
+        quote(&mut strategy, "BTC", "100", "100.2");
+        quote(&mut strategy, "BTC-PERP", "100.5", "100.7");
+        strategy.phase = Phase::Holding(Direction::LongSpot);
+        strategy.spot_position = dec("2");
+        strategy.perp_position = dec("-0.5");
+
+        // Still worth holding, but the legs are too far apart to hedge
+        let actions = check(&mut strategy, &mut ctx);
+        assert_eq!(strategy.phase, Phase::Exiting);
+        assert_eq!(
+            orders(&actions)[0],
+            ("BTC".to_string(), Side::Sell, dec("1"))
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use crate::domain::models::{
+    order_book::OrderBook,
+    trading::{BookLevel, MarketEvent, Venue},
+};
+
+use super::strategy::{BookView, OrderResponse, Strategy, StrategyAction, StrategyContext};
+
+// Book and touch of one subscribed instrument
+#[derive(Debug, Default)]
+struct MarketState {
+    book: OrderBook,
+    best_bid: Option<BookLevel>,
+    best_ask: Option<BookLevel>,
+}
+
+/// Turns market events into strategy callbacks and collects the actions they queue.
+///
+/// Knows nothing about where events come from or where actions go, so the live runtime and
+/// the backtester drive strategies the same way.
+pub struct StrategyDriver {
+    strategy: Box<dyn Strategy>,
+    subscriptions: HashSet<(Venue, String)>,
+    markets: HashMap<(Venue, String), MarketState>,
+    ctx: StrategyContext,
+}
+
+impl StrategyDriver {
+    pub fn new(strategy: Box<dyn Strategy>) -> Self {
+        let subscriptions = strategy.subscriptions().into_iter().collect();
+        StrategyDriver {
+            strategy,
+            subscriptions,
+            markets: HashMap::new(),
+            ctx: StrategyContext::new(),
+        }
+    }
+
+    pub fn venues(&self) -> HashSet<Venue> {
+        self.subscriptions.iter().map(|(venue, _)| *venue).collect()
+    }
+
+    pub fn timer_interval(&self) -> Option<Duration> {
+        self.strategy.timer_interval()
+    }
+
+    pub fn configure(&mut self, params: &serde_json::Value) -> Result<(), String> {
+        self.strategy.configure(params)?;
+        // Parameters may change what the strategy trades
+        self.subscriptions = self.strategy.subscriptions().into_iter().collect();
+        Ok(())
+    }
+
+    fn run(
+        &mut self,
+        now_ms: u64,
+        callback: impl FnOnce(&mut dyn Strategy, &mut StrategyContext),
+    ) -> Vec<StrategyAction> {
+        self.ctx.set_now_ms(now_ms);
+        callback(self.strategy.as_mut(), &mut self.ctx);
+        self.ctx.take_actions()
+    }
+
+    pub fn start(&mut self, now_ms: u64) -> Vec<StrategyAction> {
+        self.run(now_ms, |strategy, ctx| strategy.on_start(ctx))
+    }
+
+    pub fn stop(&mut self, now_ms: u64) -> Vec<StrategyAction> {
+        self.run(now_ms, |strategy, ctx| strategy.on_stop(ctx))
+    }
+
+    pub fn timer(&mut self, now_ms: u64) -> Vec<StrategyAction> {
+        self.run(now_ms, |strategy, ctx| strategy.on_timer(ctx))
+    }
+
+    pub fn order_response(&mut self, response: &OrderResponse, now_ms: u64) -> Vec<StrategyAction> {
+        self.run(now_ms, |strategy, ctx| strategy.on_order_ack(ctx, response))
+    }
+
+    // Events on instruments the strategy didn't subscribe to are ignored
+    pub fn event(&mut self, event: &MarketEvent, now_ms: u64) -> Vec<StrategyAction> {
+        let key = (event.venue(), event.instrument().to_string());
+        if !self.subscriptions.contains(&key) {
+            return Vec::new();
+        }
+
+        self.ctx.set_now_ms(now_ms);
+        let strategy = self.strategy.as_mut();
+        let ctx = &mut self.ctx;
+        match event {
+            MarketEvent::Quote {
+                bid_price,
+                bid_quantity,
+                ask_price,
+                ask_quantity,
+                ..
+            } => {
+                let market = self.markets.entry(key.clone()).or_default();
+                market.best_bid = Some(BookLevel {
+                    price: *bid_price,
+                    quantity: *bid_quantity,
+                });
+                market.best_ask = Some(BookLevel {
+                    price: *ask_price,
+                    quantity: *ask_quantity,
+                });
+                strategy.on_book(ctx, &book_view(&key, market));
+            }
+            MarketEvent::BookUpdate {
+                bids,
+                asks,
+                snapshot,
+                ..
+            } => {
+                let market = self.markets.entry(key.clone()).or_default();
+                market.book.apply(bids, asks, *snapshot);
+                // Depth wins over the last quote once there is any
+                if let Some(bid) = market.book.best_bid() {
+                    market.best_bid = Some(bid);
+                }
+                if let Some(ask) = market.book.best_ask() {
+                    market.best_ask = Some(ask);
+                }
+                strategy.on_book(ctx, &book_view(&key, market));
+            }
+            MarketEvent::Trade { .. } => strategy.on_trade(ctx, event),
+            MarketEvent::Fill { .. } => strategy.on_fill(ctx, event),
+            MarketEvent::OrderUpdate { .. } => strategy.on_order_update(ctx, event),
+        }
+        self.ctx.take_actions()
+    }
+}
+
+fn book_view<'a>(
+    (venue, instrument): &'a (Venue, String),
+    market: &MarketState,
+) -> BookView<'a> {
+    BookView {
+        venue: *venue,
+        instrument,
+        best_bid: market.best_bid.clone(),
+        best_ask: market.best_ask.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::models::trading::{OrderRequest, Side, TimeInForce},
+        shared::utils::decimal::Decimal,
+    };
+
+    // Joins the best bid on every book callback
+    struct JoinBid;
+
+    impl Strategy for JoinBid {
+        fn subscriptions(&self) -> Vec<(Venue, String)> {
+            vec![(Venue::Dydx, "BTC-USD".to_string())]
+        }
+
+        fn configure(&mut self, _params: &serde_json::Value) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn on_book(&mut self, ctx: &mut StrategyContext, book: &BookView) {
+            if let Some(bid) = &book.best_bid {
+                ctx.place(
+                    book.venue,
+                    OrderRequest {
+                        instrument: book.instrument.to_string(),
+                        side: Side::Buy,
+                        price: bid.price,
+                        quantity: Decimal::from_int(1),
+                        time_in_force: TimeInForce::PostOnly,
+                        client_order_id: None,
+                    },
+                );
+            }
+        }
+    }
+
+    fn level(price: i64) -> BookLevel {
+        BookLevel {
+            price: Decimal::from_int(price),
+            quantity: Decimal::from_int(1),
+        }
+    }
+
+    fn quote(instrument: &str, bid: i64, ask: i64) -> MarketEvent {
+        MarketEvent::Quote {
+            venue: Venue::Dydx,
+            instrument: instrument.to_string(),
+            bid_price: Decimal::from_int(bid),
+            bid_quantity: Decimal::from_int(1),
+            ask_price: Decimal::from_int(ask),
+            ask_quantity: Decimal::from_int(1),
+            timestamp_ms: 0,
+        }
+    }
+
+    // Price and tag of the single order an event produced
+    fn placed(actions: Vec<StrategyAction>) -> (Decimal, u64) {
+        match actions.as_slice() {
+            [StrategyAction::Place { tag, order, .. }] => (order.price, *tag),
+            other => panic!("expected one order, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn ignores_instruments_the_strategy_did_not_subscribe_to() {
+        let mut driver = StrategyDriver::new(Box::new(JoinBid));
+        assert!(driver.event(&quote("ETH-USD", 99, 101), 1).is_empty());
+        assert_eq!(
+            placed(driver.event(&quote("BTC-USD", 99, 101), 2)),
+            (Decimal::from_int(99), 1)
+        );
+    }
+
+    #[test]
+    fn depth_replaces_the_last_quote_once_there_is_any() {
+        let mut driver = StrategyDriver::new(Box::new(JoinBid));
+        driver.event(&quote("BTC-USD", 99, 101), 1);
+
+        let depth = MarketEvent::BookUpdate {
+            venue: Venue::Dydx,
+            instrument: "BTC-USD".to_string(),
+            bids: vec![level(97), level(98)],
+            asks: vec![level(102)],
+            snapshot: true,
+            timestamp_ms: 2,
+        };
+        assert_eq!(placed(driver.event(&depth, 2)), (Decimal::from_int(98), 2));
+    }
+}
@@ -0,0 +1,517 @@
+use std::collections::HashMap;
+
+use log::warn;
+use serde::Deserialize;
+
+use crate::{
+    domain::models::trading::{
+        MarketEvent, OrderRequest, Side, TimeInForce, Venue, VenueOrderStatus,
+    },
+    shared::utils::decimal::{Decimal, RoundingMode},
+};
+
+use super::strategy::{BookView, OrderResponse, Strategy, StrategyContext};
+
+// Grids run on Vertex, where the levels are snapped to the product's price increment
+const VENUE: Venue = Venue::Vertex;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GridParams {
+    pub instrument: String,
+    pub lower_price: Decimal,
+    pub upper_price: Decimal,
+    // Price lines from lower to upper inclusive, evenly spaced before snapping
+    pub levels: usize,
+    pub order_size: Decimal,
+    #[serde(default = "default_post_only")]
+    pub post_only: bool,
+}
+
+fn default_post_only() -> bool {
+    true
+}
+
+// Product increments the grid snaps to
+#[derive(Debug, Clone, Copy)]
+pub struct GridIncrements {
+    pub price: Decimal,
+    pub size: Decimal,
+    pub min_size: Decimal,
+}
+
+#[derive(Debug, Clone)]
+struct GridOrder {
+    side: Side,
+    order_id: Option<String>,
+    // Tag of the placement until the venue answers
+    pending: Option<u64>,
+}
+
+/// Ladder of resting buys below the market and sells above it between two bounds.
+///
+/// A filled buy is replaced by a sell one level up and a filled sell by a buy one level down,
+/// so the grid keeps one empty level between its bids and asks and earns a level's spacing on
+/// every round trip.
+#[derive(Debug)]
+pub struct Grid {
+    params: GridParams,
+    increments: GridIncrements,
+    prices: Vec<Decimal>,
+    orders: Vec<Option<GridOrder>>,
+    // Placements to retry on the next book after the venue refused them
+    retries: Vec<(usize, Side)>,
+    // Updates can beat their ack; they are held by order id until it arrives
+    unclaimed: HashMap<String, VenueOrderStatus>,
+    started: bool,
+}
+
+pub fn parse_params(params: &serde_json::Value) -> Result<GridParams, String> {
+    let params: GridParams = serde_json::from_value(params.clone()).map_err(|e| e.to_string())?;
+    if params.lower_price <= Decimal::ZERO || params.upper_price <= params.lower_price {
+        return Err("Bounds must satisfy 0 < lower_price < upper_price".to_string());
+    }
+    if params.levels < 2 {
+        return Err("A grid needs at least two levels".to_string());
+    }
+    Ok(params)
+}
+
+// Evenly spaced lines snapped to the price increment; lines that snap together are merged
+fn grid_prices(params: &GridParams, increment: Decimal) -> Result<Vec<Decimal>, String> {
+    let span = params
+        .upper_price
+        .checked_sub(params.lower_price)
+        .ok_or("Bounds overflow")?;
+    let step = span
+        .checked_div(Decimal::from_int(params.levels as i64 - 1))
+        .ok_or("Bounds overflow")?;
+
+    let mut prices = Vec::with_capacity(params.levels);
+    for level in 0..params.levels {
+        let price = step
+            .checked_mul(Decimal::from_int(level as i64))
+            .and_then(|offset| params.lower_price.checked_add(offset))
+            .and_then(|price| price.round_to_increment(increment, RoundingMode::Nearest))
+            .ok_or("Invalid price increment")?;
+        if prices.last() != Some(&price) && price > Decimal::ZERO {
+            prices.push(price);
+        }
+    }
+    if prices.len() < 2 {
+        return Err("Bounds are too tight for the price increment".to_string());
+    }
+    Ok(prices)
+}
+
+impl Grid {
+    pub fn new(params: GridParams, increments: GridIncrements) -> Result<Self, String> {
+        let prices = grid_prices(&params, increments.price)?;
+        Grid::order_size(&params, &increments)?;
+        Ok(Grid {
+            orders: vec![None; prices.len()],
+            params,
+            increments,
+            prices,
+            retries: Vec::new(),
+            unclaimed: HashMap::new(),
+            started: false,
+        })
+    }
+
+    fn order_size(params: &GridParams, increments: &GridIncrements) -> Result<Decimal, String> {
+        let size = if increments.size.is_zero() {
+            params.order_size
+        } else {
+            params
+                .order_size
+                .round_to_increment(increments.size, RoundingMode::TowardZero)
+                .ok_or("Invalid size increment")?
+        };
+        if size <= Decimal::ZERO || size < increments.min_size {
+            return Err(format!(
+                "order_size is below the minimum of {}",
+                increments.min_size
+            ));
+        }
+        Ok(size)
+    }
+
+    fn place(&mut self, ctx: &mut StrategyContext, level: usize, side: Side) {
+        let Ok(quantity) = Grid::order_size(&self.params, &self.increments) else {
+            return;
+        };
+        let order = OrderRequest {
+            instrument: self.params.instrument.clone(),
+            side,
+            price: self.prices[level],
+            quantity,
+            time_in_force: if self.params.post_only {
+                TimeInForce::PostOnly
+            } else {
+                TimeInForce::Gtc
+            },
+            client_order_id: None,
+        };
+        let tag = ctx.place(VENUE, order);
+        self.orders[level] = Some(GridOrder {
+            side,
+            order_id: None,
+            pending: Some(tag),
+        });
+    }
+
+    // Buys below the mid, sells above it, and the line closest to the mid left empty
+    fn setup(&mut self, ctx: &mut StrategyContext, mid: Decimal) {
+        let gap = self
+            .prices
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, price)| price.checked_sub(mid).map(Decimal::abs))
+            .map(|(level, _)| level)
+            .unwrap_or_default();
+        let sides: Vec<(usize, Side)> = self
+            .prices
+            .iter()
+            .enumerate()
+            .filter(|(level, _)| *level != gap)
+            .map(|(level, price)| (level, if *price < mid { Side::Buy } else { Side::Sell }))
+            .collect();
+        for (level, side) in sides {
+            self.place(ctx, level, side);
+        }
+        self.started = true;
+    }
+
+    // Mirror a filled level onto the opposite side, one line further on
+    fn on_filled(&mut self, ctx: &mut StrategyContext, level: usize, side: Side) {
+        self.orders[level] = None;
+        let next = match side {
+            Side::Buy => level.checked_add(1).filter(|next| *next < self.prices.len()),
+            Side::Sell => level.checked_sub(1),
+        };
+        match next {
+            Some(next) if self.orders[next].is_none() => self.place(ctx, next, side.opposite()),
+            Some(_) => {}
+            None => warn!(
+                "Grid on {} filled its {:?} edge at {}",
+                self.params.instrument, side, self.prices[level]
+            ),
+        }
+    }
+
+    fn on_status(
+        &mut self,
+        ctx: &mut StrategyContext,
+        level: usize,
+        side: Side,
+        status: VenueOrderStatus,
+    ) {
+        match status {
+            VenueOrderStatus::Filled => self.on_filled(ctx, level, side),
+            // Pulled from outside the grid; put it back on the next book
+            VenueOrderStatus::Cancelled | VenueOrderStatus::Rejected => {
+                self.orders[level] = None;
+                self.retries.push((level, side));
+            }
+            VenueOrderStatus::Open | VenueOrderStatus::PartiallyFilled => {}
+        }
+    }
+
+    fn any_pending(&self) -> bool {
+        self.orders
+            .iter()
+            .flatten()
+            .any(|order| order.pending.is_some())
+    }
+}
+
+impl Strategy for Grid {
+    fn subscriptions(&self) -> Vec<(Venue, String)> {
+        vec![(VENUE, self.params.instrument.clone())]
+    }
+
+    // Size and post-only apply to new orders; the lines themselves need a restart
+    fn configure(&mut self, params: &serde_json::Value) -> Result<(), String> {
+        let params = parse_params(params)?;
+        if params.instrument != self.params.instrument
+            || params.lower_price != self.params.lower_price
+            || params.upper_price != self.params.upper_price
+            || params.levels != self.params.levels
+        {
+            return Err("Instrument, bounds and levels can't change while running".to_string());
+        }
+        Grid::order_size(&params, &self.increments)?;
+        self.params = params;
+        Ok(())
+    }
+
+    fn on_book(&mut self, ctx: &mut StrategyContext, book: &BookView) {
+        let (Some(bid), Some(ask)) = (&book.best_bid, &book.best_ask) else {
+            return;
+        };
+        if !self.started {
+            if let Some(mid) = bid
+                .price
+                .checked_add(ask.price)
+                .and_then(|sum| sum.checked_div(Decimal::from_int(2)))
+            {
+                self.setup(ctx, mid);
+            }
+            return;
+        }
+        // A line the market has moved through waits until it would rest again
+        for (level, side) in std::mem::take(&mut self.retries) {
+            let rests = match side {
+                Side::Buy => self.prices[level] < ask.price,
+                Side::Sell => self.prices[level] > bid.price,
+            };
+            if !rests {
+                self.retries.push((level, side));
+            } else if self.orders[level].is_none() {
+                self.place(ctx, level, side);
+            }
+        }
+    }
+
+    fn on_order_update(&mut self, ctx: &mut StrategyContext, update: &MarketEvent) {
+        let MarketEvent::OrderUpdate {
+            order_id, status, ..
+        } = update
+        else {
+            return;
+        };
+        let found = self.orders.iter().enumerate().find_map(|(level, order)| {
+            let order = order.as_ref()?;
+            (order.order_id.as_deref() == Some(order_id)).then_some((level, order.side))
+        });
+        match found {
+            Some((level, side)) => self.on_status(ctx, level, side, *status),
+            // Only a closing status changes the grid
+            None if self.any_pending()
+                && matches!(
+                    status,
+                    VenueOrderStatus::Filled
+                        | VenueOrderStatus::Cancelled
+                        | VenueOrderStatus::Rejected
+                ) =>
+            {
+                self.unclaimed.insert(order_id.clone(), *status);
+            }
+            None => {}
+        }
+    }
+
+    fn on_order_ack(&mut self, ctx: &mut StrategyContext, response: &OrderResponse) {
+        let Some(level) = self.orders.iter().position(|order| {
+            order
+                .as_ref()
+                .is_some_and(|order| order.pending == Some(response.tag))
+        }) else {
+            return;
+        };
+        match &response.result {
+            Ok(ack) => {
+                if let Some(order) = self.orders[level].as_mut() {
+                    order.pending = None;
+                    order.order_id = Some(ack.order_id.clone());
+                }
+                if let Some(status) = self.unclaimed.remove(&ack.order_id) {
+                    self.on_status(ctx, level, response.order.side, status);
+                }
+            }
+            Err(e) => {
+                warn!(
+                    "Grid order at {} on {} failed: {}",
+                    self.prices[level], self.params.instrument, e
+                );
+                self.retries.push((level, response.order.side));
+                self.orders[level] = None;
+            }
+        }
+        // Whatever is left belongs to other orders on this instrument
+        if !self.any_pending() {
+            self.unclaimed.clear();
+        }
+    }
+
+    fn on_stop(&mut self, ctx: &mut StrategyContext) {
+        ctx.cancel_all(VENUE, Some(&self.params.instrument));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{
+            models::trading::{BookLevel, OrderAck},
+            strategies::strategy::StrategyAction,
+        },
+        shared::errors::connector_error::ConnectorError,
+    };
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    // Lines at 90, 95, 100, 105 and 110
+    fn grid() -> Grid {
+        Grid::new(
+            GridParams {
+                instrument: "BTC-PERP".to_string(),
+                lower_price: dec("90"),
+                upper_price: dec("110"),
+                levels: 5,
+                order_size: dec("0.15"),
+                post_only: true,
+            },
+            GridIncrements {
+                price: dec("1"),
+                size: dec("0.1"),
+                min_size: dec("0.1"),
+            },
+        )
+        .unwrap()
+    }
+
+    fn book(bid: &str, ask: &str) -> BookView<'static> {
+        let level = |price: &str| BookLevel {
+            price: dec(price),
+            quantity: Decimal::ONE,
+        };
+        BookView {
+            venue: VENUE,
+            instrument: "BTC-PERP",
+            best_bid: Some(level(bid)),
+            best_ask: Some(level(ask)),
+        }
+    }
+
+    // Every order placed, with its tag
+    fn placed(ctx: &mut StrategyContext) -> Vec<(u64, OrderRequest)> {
+        ctx.take_actions()
+            .into_iter()
+            .filter_map(|action| match action {
+                StrategyAction::Place { tag, order, .. } => Some((tag, order)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn lines(placed: &[(u64, OrderRequest)]) -> Vec<(Side, Decimal)> {
+        placed
+            .iter()
+            .map(|(_, order)| (order.side, order.price))
+            .collect()
+    }
+
+    fn answer(
+        grid: &mut Grid,
+        ctx: &mut StrategyContext,
+        (tag, order): &(u64, OrderRequest),
+        result: Result<OrderAck, ConnectorError>,
+    ) {
+        grid.on_order_ack(
+            ctx,
+            &OrderResponse {
+                tag: *tag,
+                venue: VENUE,
+                order: order.clone(),
+                result,
+            },
+        );
+    }
+
+    fn accepted(tag: u64) -> Result<OrderAck, ConnectorError> {
+        Ok(OrderAck {
+            order_id: format!("order-{}", tag),
+            local_id: None,
+            client_order_id: None,
+        })
+    }
+
+    fn filled(tag: u64) -> MarketEvent {
+        MarketEvent::OrderUpdate {
+            venue: VENUE,
+            instrument: "BTC-PERP".to_string(),
+            order_id: format!("order-{}", tag),
+            status: VenueOrderStatus::Filled,
+            remaining: Decimal::ZERO,
+            timestamp_ms: 0,
+        }
+    }
+
+    #[test]
+    fn setup_leaves_the_line_nearest_the_mid_empty() {
+        let mut grid = grid();
+        let mut ctx = StrategyContext::new();
+        grid.on_book(&mut ctx, &book("102.5", "103"));
+
+        let placed = placed(&mut ctx);
+        assert_eq!(
+            lines(&placed),
+            vec![
+                (Side::Buy, dec("90")),
+                (Side::Buy, dec("95")),
+                (Side::Buy, dec("100")),
+                (Side::Sell, dec("110")),
+            ]
+        );
+        // Sizes are snapped down to the increment
+        assert!(placed.iter().all(|(_, order)| order.quantity == dec("0.1")));
+    }
+
+    #[test]
+    fn a_fill_is_replaced_on_the_opposite_side_one_line_on() {
+        let mut grid = grid();
+        let mut ctx = StrategyContext::new();
+        grid.on_book(&mut ctx, &book("100", "101"));
+        let placed_orders = placed(&mut ctx);
+        for order in &placed_orders {
+            answer(&mut grid, &mut ctx, order, accepted(order.0));
+        }
+
+        // The buy at 95 filled, so a sell goes up at the empty 100 line
+        let (buy_tag, _) = placed_orders[1];
+        grid.on_order_update(&mut ctx, &filled(buy_tag));
+        assert_eq!(lines(&placed(&mut ctx)), vec![(Side::Sell, dec("100"))]);
+    }
+
+    #[test]
+    fn updates_that_beat_the_ack_apply_once_it_arrives() {
+        let mut grid = grid();
+        let mut ctx = StrategyContext::new();
+        grid.on_book(&mut ctx, &book("100", "101"));
+        let placed_orders = placed(&mut ctx);
+
+        let sell = &placed_orders[2];
+        grid.on_order_update(&mut ctx, &filled(sell.0));
+        assert!(placed(&mut ctx).is_empty());
+
+        answer(&mut grid, &mut ctx, sell, accepted(sell.0));
+        assert_eq!(lines(&placed(&mut ctx)), vec![(Side::Buy, dec("100"))]);
+    }
+
+    #[test]
+    fn refused_orders_are_retried_once_they_would_rest() {
+        let mut grid = grid();
+        let mut ctx = StrategyContext::new();
+        grid.on_book(&mut ctx, &book("100", "101"));
+        let placed_orders = placed(&mut ctx);
+
+        let sell = &placed_orders[2];
+        answer(
+            &mut grid,
+            &mut ctx,
+            sell,
+            Err(ConnectorError::Rejected("would cross".to_string())),
+        );
+
+        // The bid is through the 105 sell line, so it waits
+        grid.on_book(&mut ctx, &book("106", "107"));
+        assert!(placed(&mut ctx).is_empty());
+
+        grid.on_book(&mut ctx, &book("103", "104"));
+        assert_eq!(lines(&placed(&mut ctx)), vec![(Side::Sell, dec("105"))]);
+    }
+}
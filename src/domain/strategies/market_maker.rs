@@ -0,0 +1,641 @@
+use std::collections::HashMap;
+
+use log::warn;
+use serde::Deserialize;
+
+use crate::{
+    domain::models::trading::{
+        CancelRequest, MarketEvent, OrderRequest, Side, TimeInForce, Venue, VenueOrderStatus,
+    },
+    shared::utils::decimal::{Decimal, RoundingMode},
+};
+
+use super::strategy::{BookView, OrderResponse, Strategy, StrategyContext};
+
+const BPS: i64 = 10_000;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MarketMakerParams {
+    pub venue: Venue,
+    pub instrument: String,
+    pub tick_size: Decimal,
+    // Sizes are rounded down to it when set
+    #[serde(default)]
+    pub step_size: Option<Decimal>,
+    // Distance between the innermost bid and ask, around the skewed mid
+    pub spread_bps: Decimal,
+    // Extra distance from the mid for each level past the first
+    #[serde(default)]
+    pub level_spacing_bps: Decimal,
+    // One entry per level on each side, innermost first
+    pub sizes: Vec<Decimal>,
+    // Absolute position at which the skew is full and the side adding to it stops quoting
+    pub max_inventory: Decimal,
+    // How far the quotes shift away from the position at max inventory
+    #[serde(default)]
+    pub skew_bps: Decimal,
+    // A resting quote is replaced once its target price moved further than this
+    pub requote_threshold_bps: Decimal,
+    #[serde(default = "default_post_only")]
+    pub post_only: bool,
+    // Position held when the strategy starts
+    #[serde(default)]
+    pub initial_inventory: Decimal,
+}
+
+fn default_post_only() -> bool {
+    true
+}
+
+// One rung of the ladder on one side
+#[derive(Debug, Default)]
+struct Slot {
+    // Resting order as last acknowledged
+    order_id: Option<String>,
+    price: Decimal,
+    quantity: Decimal,
+    // Tag of the place or replace in flight; the slot is left alone until it resolves
+    pending: Option<u64>,
+}
+
+/// Two-sided market maker quoting a ladder around the mid, skewed against its inventory.
+///
+/// Quotes are only touched when their target moves past the requote threshold, and are
+/// replaced with one cancel-and-place rather than a cancel followed by a later place.
+#[derive(Debug)]
+pub struct MarketMaker {
+    params: MarketMakerParams,
+    inventory: Decimal,
+    slots: HashMap<(Side, usize), Slot>,
+    // Orders cancelled or replaced out of their slot can still fill until the venue reports
+    // them done, so they are kept with the slot they came from until then
+    retired: HashMap<String, (Side, usize)>,
+    // Fills can beat their ack; they are held by order id until it arrives
+    unclaimed: HashMap<String, Vec<Decimal>>,
+}
+
+pub fn factory(params: &serde_json::Value) -> Result<Box<dyn Strategy>, String> {
+    Ok(Box::new(MarketMaker::new(parse_params(params)?)))
+}
+
+fn parse_params(params: &serde_json::Value) -> Result<MarketMakerParams, String> {
+    let params: MarketMakerParams =
+        serde_json::from_value(params.clone()).map_err(|e| e.to_string())?;
+    if params.tick_size <= Decimal::ZERO {
+        return Err("tick_size must be positive".to_string());
+    }
+    if params.max_inventory <= Decimal::ZERO {
+        return Err("max_inventory must be positive".to_string());
+    }
+    if params.sizes.iter().any(|size| *size <= Decimal::ZERO) {
+        return Err("sizes must be positive".to_string());
+    }
+    Ok(params)
+}
+
+fn bps(value: Decimal) -> Decimal {
+    value
+        .checked_div(Decimal::from_int(BPS))
+        .unwrap_or_default()
+}
+
+impl MarketMaker {
+    pub fn new(params: MarketMakerParams) -> Self {
+        MarketMaker {
+            inventory: params.initial_inventory,
+            params,
+            slots: HashMap::new(),
+            retired: HashMap::new(),
+            unclaimed: HashMap::new(),
+        }
+    }
+
+    // Mid shifted against the position: long inventory lowers both quotes so asks fill first
+    fn center(&self, mid: Decimal) -> Option<Decimal> {
+        let ratio = self
+            .inventory
+            .checked_div(self.params.max_inventory)?
+            .clamp(Decimal::from_int(-1), Decimal::ONE);
+        let shift = bps(self.params.skew_bps).checked_mul(ratio)?;
+        mid.checked_mul(Decimal::ONE.checked_sub(shift)?)
+    }
+
+    // Price and size a level should rest at, or None when the side shouldn't quote it
+    fn target(
+        &self,
+        side: Side,
+        level: usize,
+        center: Decimal,
+        book: &BookView,
+    ) -> Option<(Decimal, Decimal)> {
+        let size = *self.params.sizes.get(level)?;
+        let full = match side {
+            Side::Buy => self.inventory >= self.params.max_inventory,
+            Side::Sell => self.inventory.checked_add(self.params.max_inventory)? <= Decimal::ZERO,
+        };
+        if full {
+            return None;
+        }
+
+        let size = match self.params.step_size {
+            Some(step) => size.round_to_increment(step, RoundingMode::TowardZero)?,
+            None => size,
+        };
+        if size.is_zero() {
+            return None;
+        }
+
+        let spacing =
+            bps(self.params.level_spacing_bps).checked_mul(Decimal::from_int(level as i64))?;
+        let offset = bps(self.params.spread_bps)
+            .checked_div(Decimal::from_int(2))?
+            .checked_add(spacing)?;
+        let tick = self.params.tick_size;
+        let price = match side {
+            Side::Buy => {
+                let price = center
+                    .checked_mul(Decimal::ONE.checked_sub(offset)?)?
+                    .round_to_increment(tick, RoundingMode::Floor)?;
+                // A post-only bid at or through the ask would be rejected
+                match &book.best_ask {
+                    Some(ask) if self.params.post_only && price >= ask.price => {
+                        ask.price.checked_sub(tick)?
+                    }
+                    _ => price,
+                }
+            }
+            Side::Sell => {
+                let price = center
+                    .checked_mul(Decimal::ONE.checked_add(offset)?)?
+                    .round_to_increment(tick, RoundingMode::Ceil)?;
+                match &book.best_bid {
+                    Some(bid) if self.params.post_only && price <= bid.price => {
+                        bid.price.checked_add(tick)?
+                    }
+                    _ => price,
+                }
+            }
+        };
+        if price <= Decimal::ZERO {
+            return None;
+        }
+        Some((price, size))
+    }
+
+    // Whether a quote resting at `resting` is far enough from its target to replace
+    fn moved(&self, resting: (Decimal, Decimal), target: (Decimal, Decimal)) -> bool {
+        let ((resting_price, resting_quantity), (price, quantity)) = (resting, target);
+        if resting_quantity != quantity {
+            return true;
+        }
+        let distance = price
+            .checked_sub(resting_price)
+            .map(Decimal::abs)
+            .and_then(|d| d.checked_mul(Decimal::from_int(BPS)))
+            .and_then(|d| d.checked_div(resting_price));
+        match distance {
+            Some(distance) => distance > self.params.requote_threshold_bps,
+            None => true,
+        }
+    }
+
+    fn order(&self, side: Side, price: Decimal, quantity: Decimal) -> OrderRequest {
+        OrderRequest {
+            instrument: self.params.instrument.clone(),
+            side,
+            price,
+            quantity,
+            time_in_force: if self.params.post_only {
+                TimeInForce::PostOnly
+            } else {
+                TimeInForce::Gtc
+            },
+            client_order_id: None,
+        }
+    }
+
+    fn cancel_request(&self, order_id: &str) -> CancelRequest {
+        CancelRequest {
+            instrument: self.params.instrument.clone(),
+            order_id: order_id.to_string(),
+        }
+    }
+
+    fn requote(&mut self, ctx: &mut StrategyContext, book: &BookView) {
+        let (Some(bid), Some(ask)) = (&book.best_bid, &book.best_ask) else {
+            return;
+        };
+        let Some(center) = bid
+            .price
+            .checked_add(ask.price)
+            .and_then(|sum| sum.checked_div(Decimal::from_int(2)))
+            .and_then(|mid| self.center(mid))
+        else {
+            return;
+        };
+
+        // Levels dropped by a reconfigure still need their orders pulled
+        let levels = self
+            .slots
+            .keys()
+            .map(|(_, level)| level + 1)
+            .chain([self.params.sizes.len()])
+            .max()
+            .unwrap_or_default();
+        let venue = self.params.venue;
+
+        for side in [Side::Buy, Side::Sell] {
+            for level in 0..levels {
+                let target = self.target(side, level, center, book);
+                let slot = self.slots.entry((side, level)).or_default();
+                if slot.pending.is_some() {
+                    continue;
+                }
+                let order_id = slot.order_id.clone();
+                let resting = (slot.price, slot.quantity);
+
+                let tag = match (order_id, target) {
+                    (None, Some((price, quantity))) => {
+                        let tag = ctx.place(venue, self.order(side, price, quantity));
+                        Some((tag, price, quantity))
+                    }
+                    (order_id, None) => {
+                        if let Some(order_id) = order_id {
+                            ctx.cancel(venue, self.cancel_request(&order_id));
+                            self.retired.insert(order_id, (side, level));
+                        }
+                        self.slots.remove(&(side, level));
+                        None
+                    }
+                    (Some(order_id), Some((price, quantity))) => {
+                        if !self.moved(resting, (price, quantity)) {
+                            continue;
+                        }
+                        let tag = ctx.cancel_and_place(
+                            venue,
+                            self.cancel_request(&order_id),
+                            self.order(side, price, quantity),
+                        );
+                        self.retired.insert(order_id, (side, level));
+                        Some((tag, price, quantity))
+                    }
+                };
+
+                if let Some((tag, price, quantity)) = tag {
+                    let slot = self.slots.entry((side, level)).or_default();
+                    slot.pending = Some(tag);
+                    slot.price = price;
+                    slot.quantity = quantity;
+                }
+            }
+        }
+    }
+
+    fn apply_fill(&mut self, signed: Decimal) {
+        if let Some(inventory) = self.inventory.checked_add(signed) {
+            self.inventory = inventory;
+        }
+    }
+
+    fn slot_for_order(&mut self, order_id: &str) -> Option<&mut Slot> {
+        self.slots
+            .values_mut()
+            .find(|slot| slot.order_id.as_deref() == Some(order_id))
+    }
+}
+
+impl Strategy for MarketMaker {
+    fn subscriptions(&self) -> Vec<(Venue, String)> {
+        vec![(self.params.venue, self.params.instrument.clone())]
+    }
+
+    fn configure(&mut self, params: &serde_json::Value) -> Result<(), String> {
+        let params = parse_params(params)?;
+        if params.venue != self.params.venue || params.instrument != self.params.instrument {
+            return Err("venue and instrument can't change while running".to_string());
+        }
+        // The tracked position carries over; initial_inventory only applies at start
+        self.params = params;
+        Ok(())
+    }
+
+    fn on_book(&mut self, ctx: &mut StrategyContext, book: &BookView) {
+        self.requote(ctx, book);
+    }
+
+    fn on_fill(&mut self, _ctx: &mut StrategyContext, fill: &MarketEvent) {
+        let MarketEvent::Fill {
+            order_id,
+            side,
+            quantity,
+            ..
+        } = fill
+        else {
+            return;
+        };
+        let signed = side.signed(*quantity);
+        if self.slot_for_order(order_id).is_some() || self.retired.contains_key(order_id) {
+            self.apply_fill(signed);
+        } else if self.slots.values().any(|slot| slot.pending.is_some()) {
+            self.unclaimed
+                .entry(order_id.clone())
+                .or_default()
+                .push(signed);
+        }
+    }
+
+    fn on_order_update(&mut self, _ctx: &mut StrategyContext, update: &MarketEvent) {
+        let MarketEvent::OrderUpdate {
+            order_id, status, ..
+        } = update
+        else {
+            return;
+        };
+        let done = matches!(
+            status,
+            VenueOrderStatus::Filled | VenueOrderStatus::Cancelled | VenueOrderStatus::Rejected
+        );
+        // The next book update quotes the level again
+        if done {
+            self.retired.remove(order_id);
+            if let Some(slot) = self.slot_for_order(order_id) {
+                slot.order_id = None;
+            }
+        }
+    }
+
+    fn on_order_ack(&mut self, ctx: &mut StrategyContext, response: &OrderResponse) {
+        let Some((&key, slot)) = self
+            .slots
+            .iter_mut()
+            .find(|(_, slot)| slot.pending == Some(response.tag))
+        else {
+            return;
+        };
+        slot.pending = None;
+        let stale = match &response.result {
+            Ok(ack) => {
+                slot.order_id = Some(ack.order_id.clone());
+                for signed in self.unclaimed.remove(&ack.order_id).unwrap_or_default() {
+                    self.apply_fill(signed);
+                }
+                None
+            }
+            Err(e) => {
+                warn!("Market maker order on {} failed: {}", response.venue, e);
+                slot.order_id.take()
+            }
+        };
+        // Whatever is left belongs to other orders on this instrument
+        if self.slots.values().all(|slot| slot.pending.is_none()) {
+            self.unclaimed.clear();
+        }
+        // A failed replace may have left the old order resting
+        if let Some(order_id) = stale {
+            ctx.cancel(response.venue, self.cancel_request(&order_id));
+            self.retired.insert(order_id, key);
+        }
+    }
+
+    fn on_stop(&mut self, ctx: &mut StrategyContext) {
+        ctx.cancel_all(self.params.venue, Some(&self.params.instrument));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        models::trading::{BookLevel, OrderAck},
+        strategies::strategy::StrategyAction,
+    };
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn params() -> MarketMakerParams {
+        MarketMakerParams {
+            venue: Venue::Dydx,
+            instrument: "BTC-USD".to_string(),
+            tick_size: dec("0.01"),
+            step_size: None,
+            spread_bps: dec("20"),
+            level_spacing_bps: dec("10"),
+            sizes: vec![dec("1"), dec("2")],
+            max_inventory: dec("10"),
+            skew_bps: dec("50"),
+            requote_threshold_bps: dec("5"),
+            post_only: true,
+            initial_inventory: Decimal::ZERO,
+        }
+    }
+
+    fn book(bid: &str, ask: &str) -> BookView<'static> {
+        let level = |price: &str| BookLevel {
+            price: dec(price),
+            quantity: Decimal::ONE,
+        };
+        BookView {
+            venue: Venue::Dydx,
+            instrument: "BTC-USD",
+            best_bid: Some(level(bid)),
+            best_ask: Some(level(ask)),
+        }
+    }
+
+    // Side, price and size of every order the actions send, bids first
+    fn quotes(actions: &[StrategyAction]) -> Vec<(Side, Decimal, Decimal)> {
+        let mut quotes: Vec<(Side, Decimal, Decimal)> = actions
+            .iter()
+            .filter_map(|action| match action {
+                StrategyAction::Place { order, .. }
+                | StrategyAction::CancelAndPlace { order, .. } => {
+                    Some((order.side, order.price, order.quantity))
+                }
+                _ => None,
+            })
+            .collect();
+        quotes.sort_by_key(|(side, price, _)| (*side == Side::Sell, *price));
+        quotes
+    }
+
+    // Accept every order the actions sent as `order-<tag>`
+    fn ack_all(mm: &mut MarketMaker, ctx: &mut StrategyContext, actions: &[StrategyAction]) {
+        for action in actions {
+            let (StrategyAction::Place { tag, venue, order }
+            | StrategyAction::CancelAndPlace {
+                tag, venue, order, ..
+            }) = action
+            else {
+                continue;
+            };
+            mm.on_order_ack(
+                ctx,
+                &OrderResponse {
+                    tag: *tag,
+                    venue: *venue,
+                    order: order.clone(),
+                    result: Ok(OrderAck {
+                        order_id: format!("order-{}", tag),
+                        local_id: None,
+                        client_order_id: None,
+                    }),
+                },
+            );
+        }
+    }
+
+    fn fill(order_id: &str, side: Side, quantity: &str) -> MarketEvent {
+        MarketEvent::Fill {
+            venue: Venue::Dydx,
+            instrument: "BTC-USD".to_string(),
+            order_id: order_id.to_string(),
+            side,
+            price: dec("100"),
+            quantity: dec(quantity),
+            fee: Decimal::ZERO,
+            timestamp_ms: 0,
+        }
+    }
+
+    #[test]
+    fn quotes_a_ladder_around_the_mid() {
+        let mut mm = MarketMaker::new(params());
+        let mut ctx = StrategyContext::new();
+        mm.on_book(&mut ctx, &book("99", "101"));
+
+        assert_eq!(
+            quotes(&ctx.take_actions()),
+            vec![
+                (Side::Buy, dec("99.8"), dec("2")),
+                (Side::Buy, dec("99.9"), dec("1")),
+                (Side::Sell, dec("100.1"), dec("1")),
+                (Side::Sell, dec("100.2"), dec("2")),
+            ]
+        );
+    }
+
+    #[test]
+    fn inventory_skews_the_quotes_and_stops_the_side_adding_to_it() {
+        let mut mm = MarketMaker::new(MarketMakerParams {
+            sizes: vec![dec("1")],
+            initial_inventory: dec("5"),
+            ..params()
+        });
+        let mut ctx = StrategyContext::new();
+        // Half the max inventory shifts the center down by half of 50bps
+        mm.on_book(&mut ctx, &book("99", "101"));
+        assert_eq!(
+            quotes(&ctx.take_actions()),
+            vec![
+                (Side::Buy, dec("99.65"), dec("1")),
+                (Side::Sell, dec("99.85"), dec("1")),
+            ]
+        );
+
+        let mut mm = MarketMaker::new(MarketMakerParams {
+            sizes: vec![dec("1")],
+            initial_inventory: dec("10"),
+            ..params()
+        });
+        mm.on_book(&mut ctx, &book("99", "101"));
+        let quotes = quotes(&ctx.take_actions());
+        assert_eq!(quotes.len(), 1);
+        assert_eq!(quotes[0].0, Side::Sell);
+    }
+
+    #[test]
+    fn post_only_quotes_stay_off_the_other_side() {
+        let skewed = MarketMakerParams {
+            sizes: vec![dec("1")],
+            initial_inventory: dec("10"),
+            ..params()
+        };
+        let mut ctx = StrategyContext::new();
+
+        // The skewed ask would cross the 99.9 bid, so it rests one tick above it
+        let mut mm = MarketMaker::new(skewed.clone());
+        mm.on_book(&mut ctx, &book("99.9", "100.1"));
+        assert_eq!(
+            quotes(&ctx.take_actions()),
+            vec![(Side::Sell, dec("99.91"), dec("1"))]
+        );
+
+        let mut mm = MarketMaker::new(MarketMakerParams {
+            post_only: false,
+            ..skewed
+        });
+        mm.on_book(&mut ctx, &book("99.9", "100.1"));
+        assert_eq!(
+            quotes(&ctx.take_actions()),
+            vec![(Side::Sell, dec("99.6"), dec("1"))]
+        );
+    }
+
+    #[test]
+    fn quotes_are_replaced_only_past_the_threshold() {
+        let mut mm = MarketMaker::new(MarketMakerParams {
+            sizes: vec![dec("1")],
+            ..params()
+        });
+        let mut ctx = StrategyContext::new();
+        mm.on_book(&mut ctx, &book("99", "101"));
+        let actions = ctx.take_actions();
+        ack_all(&mut mm, &mut ctx, &actions);
+
+        // About 3bps away from the resting quotes
+        mm.on_book(&mut ctx, &book("99.04", "101.04"));
+        assert!(ctx.take_actions().is_empty());
+
+        // About 9bps away
+        mm.on_book(&mut ctx, &book("99.1", "101.1"));
+        let actions = ctx.take_actions();
+        assert!(actions
+            .iter()
+            .all(|a| matches!(a, StrategyAction::CancelAndPlace { .. })));
+        assert_eq!(
+            quotes(&actions),
+            vec![
+                (Side::Buy, dec("99.99"), dec("1")),
+                (Side::Sell, dec("100.21"), dec("1")),
+            ]
+        );
+    }
+
+    #[test]
+    fn replaced_orders_count_fills_until_they_are_done() {
+        let mut mm = MarketMaker::new(MarketMakerParams {
+            sizes: vec![dec("1")],
+            ..params()
+        });
+        let mut ctx = StrategyContext::new();
+        mm.on_book(&mut ctx, &book("99", "101"));
+        let actions = ctx.take_actions();
+        ack_all(&mut mm, &mut ctx, &actions);
+        let StrategyAction::Place { tag, .. } = &actions[0] else {
+            panic!("expected a place, got {:?}", actions[0]);
+        };
+        let (old_id, side) = (format!("order-{}", tag), quotes(&actions[..1])[0].0);
+
+        mm.on_book(&mut ctx, &book("99.1", "101.1"));
+        let actions = ctx.take_actions();
+        ack_all(&mut mm, &mut ctx, &actions);
+
+        // The replaced order filled before the cancel reached it
+        mm.on_fill(&mut ctx, &fill(&old_id, side, "0.5"));
+        assert_eq!(mm.inventory.abs(), dec("0.5"));
+
+        let done = MarketEvent::OrderUpdate {
+            venue: Venue::Dydx,
+            instrument: "BTC-USD".to_string(),
+            order_id: old_id.clone(),
+            status: VenueOrderStatus::Cancelled,
+            remaining: Decimal::ZERO,
+            timestamp_ms: 0,
+        };
+        mm.on_order_update(&mut ctx, &done);
+        mm.on_fill(&mut ctx, &fill(&old_id, side, "0.5"));
+        assert_eq!(mm.inventory.abs(), dec("0.5"));
+    }
+}
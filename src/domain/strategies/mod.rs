@@ -0,0 +1,6 @@
+pub mod arbitrage;
+pub mod basis;
+pub mod driver;
+pub mod grid;
+pub mod market_maker;
+pub mod strategy;
@@ -0,0 +1,153 @@
+use std::time::Duration;
+
+use crate::{
+    domain::models::trading::{
+        BookLevel, CancelRequest, MarketEvent, OrderAck, OrderRequest, Venue,
+    },
+    shared::errors::connector_error::ConnectorError,
+};
+
+// Order instruction a strategy hands back to the runtime
+#[derive(Debug, Clone)]
+pub enum StrategyAction {
+    Place {
+        // Echoed on the matching `OrderResponse`
+        tag: u64,
+        venue: Venue,
+        order: OrderRequest,
+    },
+    Cancel {
+        venue: Venue,
+        cancel: CancelRequest,
+    },
+    // Cancel then place; answered with an `OrderResponse` like `Place`
+    CancelAndPlace {
+        tag: u64,
+        venue: Venue,
+        cancel: CancelRequest,
+        order: OrderRequest,
+    },
+    CancelAll {
+        venue: Venue,
+        instrument: Option<String>,
+    },
+}
+
+/// Collects a callback's orders; the runtime sends them once the callback returns.
+#[derive(Debug, Default)]
+pub struct StrategyContext {
+    actions: Vec<StrategyAction>,
+    next_tag: u64,
+    now_ms: u64,
+}
+
+impl StrategyContext {
+    pub fn new() -> Self {
+        StrategyContext::default()
+    }
+
+    // Wall clock at the start of the callback; replayed time when backtesting
+    pub fn now_ms(&self) -> u64 {
+        self.now_ms
+    }
+
+    pub fn set_now_ms(&mut self, now_ms: u64) {
+        self.now_ms = now_ms;
+    }
+
+    // Queue an order and return the tag its response will carry
+    pub fn place(&mut self, venue: Venue, order: OrderRequest) -> u64 {
+        self.next_tag += 1;
+        let tag = self.next_tag;
+        self.actions.push(StrategyAction::Place { tag, venue, order });
+        tag
+    }
+
+    pub fn cancel(&mut self, venue: Venue, cancel: CancelRequest) {
+        self.actions.push(StrategyAction::Cancel { venue, cancel });
+    }
+
+    // Queue a replace and return the tag the new order's response will carry
+    pub fn cancel_and_place(
+        &mut self,
+        venue: Venue,
+        cancel: CancelRequest,
+        order: OrderRequest,
+    ) -> u64 {
+        self.next_tag += 1;
+        let tag = self.next_tag;
+        self.actions.push(StrategyAction::CancelAndPlace {
+            tag,
+            venue,
+            cancel,
+            order,
+        });
+        tag
+    }
+
+    pub fn cancel_all(&mut self, venue: Venue, instrument: Option<&str>) {
+        self.actions.push(StrategyAction::CancelAll {
+            venue,
+            instrument: instrument.map(str::to_string),
+        });
+    }
+
+    pub fn take_actions(&mut self) -> Vec<StrategyAction> {
+        std::mem::take(&mut self.actions)
+    }
+}
+
+// Book of one instrument after a depth or top-of-book update
+#[derive(Debug)]
+pub struct BookView<'a> {
+    pub venue: Venue,
+    pub instrument: &'a str,
+    pub best_bid: Option<BookLevel>,
+    pub best_ask: Option<BookLevel>,
+}
+
+// Outcome of a `StrategyContext::place` or `cancel_and_place`
+#[derive(Debug, Clone)]
+pub struct OrderResponse {
+    pub tag: u64,
+    pub venue: Venue,
+    pub order: OrderRequest,
+    pub result: Result<OrderAck, ConnectorError>,
+}
+
+/// A trading strategy driven by the strategy runtime.
+///
+/// Callbacks run one at a time on the strategy's own task and never block on the network:
+/// orders are queued on the context and their outcome arrives later through `on_order_ack`.
+pub trait Strategy: Send {
+    // Venue and instrument pairs whose events reach this strategy
+    fn subscriptions(&self) -> Vec<(Venue, String)>;
+
+    // How often `on_timer` fires; None for never
+    fn timer_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    // Apply new parameters while running
+    fn configure(&mut self, params: &serde_json::Value) -> Result<(), String>;
+
+    fn on_start(&mut self, _ctx: &mut StrategyContext) {}
+
+    fn on_book(&mut self, _ctx: &mut StrategyContext, _book: &BookView) {}
+
+    // `MarketEvent::Trade`
+    fn on_trade(&mut self, _ctx: &mut StrategyContext, _trade: &MarketEvent) {}
+
+    // `MarketEvent::Fill` on a subscribed instrument, whichever strategy placed the order
+    fn on_fill(&mut self, _ctx: &mut StrategyContext, _fill: &MarketEvent) {}
+
+    // `MarketEvent::OrderUpdate` on a subscribed instrument
+    fn on_order_update(&mut self, _ctx: &mut StrategyContext, _update: &MarketEvent) {}
+
+    fn on_order_ack(&mut self, _ctx: &mut StrategyContext, _response: &OrderResponse) {}
+
+    fn on_timer(&mut self, _ctx: &mut StrategyContext) {}
+
+    // Last chance to queue cancels before the strategy is dropped
+    fn on_stop(&mut self, _ctx: &mut StrategyContext) {}
+}
@@ -4,11 +4,12 @@ mod connectors;
 mod domain;
 mod services;
 mod shared;
+mod storage;
 
 // Include the generated protobuf code
 // Include the generated protobuf modules
 pub mod trading_service {
-    tonic::include_proto!("trading");
+    tonic::include_proto!("trading.v1");
 }
 pub mod vertex_products {
     tonic::include_proto!("vertex_products");
@@ -22,16 +23,50 @@ pub mod vertex_query {
 pub mod vertex_execute {
     tonic::include_proto!("vertex_execute");
 }
+pub mod oms {
+    tonic::include_proto!("oms");
+}
+pub mod positions {
+    tonic::include_proto!("positions");
+}
+pub mod strategies {
+    tonic::include_proto!("strategies");
+}
+pub mod algos {
+    tonic::include_proto!("algos");
+}
+pub mod backtest {
+    tonic::include_proto!("backtest");
+}
 
 use crate::api::router as api_router;
-use config::Config;
-use connectors::vertex::{gateway_client::GatewayClient, subscription_client::SubscriptionClient};
-use std::sync::Arc;
+use config::{Config, CONFIG};
+use connectors::vertex::{
+    archive_client::ArchiveClient, gateway_client::GatewayClient,
+    subscription_client::SubscriptionClient,
+};
+use std::{path::PathBuf, sync::Arc, time::Duration};
 use tonic::transport::Server;
 use tonic_web::GrpcWebLayer;
 use tower_http::cors::{Any, CorsLayer};
 
-use crate::services::vertex::client::VertexClient;
+use crate::connectors::{
+    connector::Connector, dydx::connector::DydxConnector, orderly::connector::OrderlyConnector,
+    registry::ConnectorRegistry,
+};
+use crate::domain::models::trading::Venue;
+use crate::domain::oms::order_manager::OrderManager;
+use crate::domain::positions::tracker::PositionTracker;
+use crate::domain::risk::{engine::RiskEngine, limits::RiskConfig};
+use crate::domain::routing::config::RouterConfig;
+use crate::services::algos::{executor::AlgoExecutor, service::AlgoControl};
+use crate::services::backtest::service::BacktestControl;
+use crate::services::strategies::{kinds, runtime::StrategyRuntime, service::StrategyControl};
+use crate::services::trading::{gateway::TradingGateway, router::SmartOrderRouter};
+use crate::services::vertex::{
+    client::VertexClient, kill_switch::KillSwitch, paper::PaperExchange, registry::ProductRegistry,
+};
+use crate::storage::{journal::Journal, recorder::Recorder};
 use tracing_subscriber::FmtSubscriber;
 
 #[tokio::main]
@@ -45,19 +80,149 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Create a new instance of the SubscriptionClient
     let subscription_client = Arc::new(SubscriptionClient::new());
 
+    // Subscribe the recorder before anything connects so it sees the first message
+    if let Some(dir) = &CONFIG.recorder_dir {
+        let recorder = Recorder::open(
+            dir,
+            CONFIG.recorder_max_file_bytes,
+            CONFIG.recorder_rotate_secs,
+        )?;
+        recorder.spawn(subscription_client.subscribe_raw_frames())?;
+    }
+
     // Create a new instance of the GatewayClient
     let gateway_client = Arc::new(GatewayClient::new());
-    let trading_service = VertexClient {
-        subscription_client: Arc::clone(&subscription_client),
-        gateway_client: Arc::clone(&gateway_client),
+
+    // Load contracts, products and symbols once up front and keep them refreshed. Orders cannot
+    // be validated, signed or reconciled without them, so there is no starting without them
+    let registry = Arc::new(ProductRegistry::new(Arc::clone(&gateway_client)));
+    registry
+        .load()
+        .await
+        .map_err(|e| format!("Failed to load product registry: {}", e))?;
+    registry.spawn_refresh();
+
+    // Without a limits file every check is disabled, so make that loud
+    let risk_config = match &CONFIG.risk_limits_path {
+        Some(path) => RiskConfig::load(path)?,
+        None => {
+            log::warn!("RISK_LIMITS_PATH not set, pre-trade risk limits are disabled");
+            RiskConfig::default()
+        }
     };
+    let risk_engine = Arc::new(RiskEngine::new(risk_config));
 
-    // Create a new instance of the VertexQueryService
-    let vertex_query_service = VertexClient {
+    let (journal, journal_replay) = Journal::open(
+        &CONFIG.journal_dir,
+        CONFIG.journal_segment_bytes,
+        CONFIG.journal_fsync,
+    )?;
+
+    // Paper trading matches executes against the live feed instead of sending them
+    let paper = if CONFIG.paper_trading {
+        log::warn!("PAPER_TRADING is on, Vertex orders are simulated and never sent");
+        let paper = PaperExchange::new(Arc::clone(&registry), Arc::clone(&subscription_client))
+            .map_err(|e| format!("Failed to start paper trading: {}", e))?;
+        let paper = Arc::new(paper);
+        paper.spawn();
+        Some(paper)
+    } else {
+        None
+    };
+
+    // One kill switch halts every venue
+    let kill_switch = Arc::new(KillSwitch::new());
+
+    // Every service shares the same underlying clients and state
+    let vertex_client = VertexClient {
         subscription_client: Arc::clone(&subscription_client),
         gateway_client: Arc::clone(&gateway_client),
+        registry: Arc::clone(&registry),
+        risk_engine: Arc::clone(&risk_engine),
+        kill_switch: Arc::clone(&kill_switch),
+        order_manager: Arc::new(OrderManager::new(CONFIG.oms_max_terminal_orders)),
+        positions: Arc::new(PositionTracker::new()),
+        journal: Arc::new(journal),
+        paper,
     };
-    let vertex_query_service_arc = Arc::new(vertex_query_service);
+    // Rebuild order and exposure state from before the last shutdown or crash
+    vertex_client.replay_journal(journal_replay);
+    // Line that state up with the exchange before accepting any traffic. Paper orders exist
+    // nowhere to line up with and died with the last session's simulator
+    if CONFIG.paper_trading {
+        vertex_client.close_paper_orders();
+    } else {
+        // Trading on state that could not be checked against the exchange is worse than not
+        // starting
+        let report = vertex_client
+            .reconcile(CONFIG.reconcile_cancel_orphans)
+            .await;
+        if !report.errors.is_empty() {
+            return Err(format!("Reconciliation failed: {}", report.errors.join("; ")).into());
+        }
+    }
+    // Nothing journals until the services start, so this is a consistent point for the next
+    // start to replay from
+    vertex_client.snapshot_journal();
+
+    // A replay only simulates Vertex, orders on any other venue would be real
+    if CONFIG.replay_path.is_some() && (CONFIG.dydx.is_some() || CONFIG.orderly.is_some()) {
+        return Err("REPLAY_PATH only simulates Vertex; unset the dYdX and Orderly settings".into());
+    }
+
+    // Every venue is reached through its connector, behind the kill switch and risk checks
+    let mut connectors = ConnectorRegistry::new(kill_switch, Arc::clone(&risk_engine));
+    connectors.register(Arc::new(vertex_client.clone()) as Arc<dyn Connector>);
+    if let Some(dydx_config) = &CONFIG.dydx {
+        match DydxConnector::new(dydx_config) {
+            Ok(dydx) => connectors.register(Arc::new(dydx) as Arc<dyn Connector>),
+            Err(e) => log::error!("Failed to set up dYdX connector: {}", e),
+        }
+    }
+    if let Some(orderly_config) = &CONFIG.orderly {
+        match OrderlyConnector::new(orderly_config) {
+            Ok(orderly) => connectors.register(Arc::new(orderly) as Arc<dyn Connector>),
+            Err(e) => log::error!("Failed to set up Orderly connector: {}", e),
+        }
+    }
+    let vertex_connector = connectors.get(Venue::Vertex)?;
+    let connectors = Arc::new(connectors);
+
+    // Without a router config there are no cross-venue instruments to route
+    let router_config = match &CONFIG.router_config_path {
+        Some(path) => RouterConfig::load(path)?,
+        None => RouterConfig::default(),
+    };
+    let router = Arc::new(SmartOrderRouter::new(Arc::clone(&connectors), router_config));
+    router.spawn_quote_listener();
+    let archive = ArchiveClient::new(&CONFIG.vertex_archive_url);
+    // Strategies trade through the same connectors, and so the same risk checks, as clients
+    let mut strategy_runtime = StrategyRuntime::new(Arc::clone(&connectors));
+    kinds::register_builtin(&mut strategy_runtime, Arc::clone(&registry), archive.clone());
+    let strategy_runtime = Arc::new(strategy_runtime);
+    let strategy_control = StrategyControl::new(Arc::clone(&strategy_runtime));
+    // Backtests build strategies from the same registered kinds
+    let backtest_control = BacktestControl::new(
+        strategy_runtime,
+        Arc::clone(&registry),
+        archive.clone(),
+        CONFIG.backtest_data_dir.as_ref().map(PathBuf::from),
+    );
+    let trading_gateway = Arc::new(TradingGateway::new(connectors, router));
+
+    // Execution algos slice on Vertex only, through the same risk checks
+    let algo_executor = AlgoExecutor::new(vertex_connector, Arc::clone(&registry), archive);
+    let algo_control = AlgoControl::new(Arc::new(algo_executor));
+    let trading_service = trading_gateway.as_ref().clone();
+
+    // Create a new instance of the VertexQueryService
+    let vertex_query_service_arc = Arc::new(vertex_client.clone());
+    vertex_query_service_arc.spawn_event_pump();
+    vertex_query_service_arc.spawn_position_marker();
+
+    if let Some(timeout_secs) = CONFIG.dead_man_switch_timeout_secs {
+        vertex_query_service_arc.spawn_dead_man_switch(Duration::from_secs(timeout_secs));
+    }
 
     let cors = CorsLayer::new().allow_origin(Any);
     let addr = "[::1]:1321".parse()?;
@@ -73,18 +238,31 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             ))
             .add_service(tonic_web::enable(
                 vertex_query::vertex_query_service_server::VertexQueryServiceServer::new(
-                    VertexClient {
-                        subscription_client: Arc::clone(&subscription_client),
-                        gateway_client: Arc::clone(&gateway_client),
-                    },
+                    vertex_client.clone(),
                 ),
             ))
+            .add_service(tonic_web::enable(
+                oms::order_management_service_server::OrderManagementServiceServer::new(
+                    vertex_client.clone(),
+                ),
+            ))
+            .add_service(tonic_web::enable(
+                positions::position_service_server::PositionServiceServer::new(
+                    vertex_client.clone(),
+                ),
+            ))
+            .add_service(tonic_web::enable(
+                strategies::strategy_service_server::StrategyServiceServer::new(strategy_control),
+            ))
+            .add_service(tonic_web::enable(
+                algos::algo_service_server::AlgoServiceServer::new(algo_control),
+            ))
+            .add_service(tonic_web::enable(
+                backtest::backtest_service_server::BacktestServiceServer::new(backtest_control),
+            ))
             .add_service(tonic_web::enable(
                 vertex_execute::vertex_execute_service_server::VertexExecuteServiceServer::new(
-                    VertexClient {
-                        subscription_client: Arc::clone(&subscription_client),
-                        gateway_client: Arc::clone(&gateway_client),
-                    },
+                    vertex_client,
                 ),
             ))
             .serve(addr)
@@ -93,7 +271,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     });
 
     // Start the HTTP server
-    let http_app = api_router(vertex_query_service_arc.clone());
+    let http_app = api_router(vertex_query_service_arc.clone(), trading_gateway);
     let listener = tokio::net::TcpListener::bind("0.0.0.0:1322").await.unwrap();
     let http_server = tokio::spawn(async move {
         axum::serve(listener, http_app)
@@ -0,0 +1,412 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use futures::StreamExt;
+use log::{info, warn};
+use tokio::sync::watch;
+
+use crate::{
+    connectors::{connector::Connector, vertex::archive_client::ArchiveClient},
+    domain::{
+        algos::schedule::{self, AlgoKind, AlgoParams},
+        models::trading::{
+            Instrument, MarketEvent, OrderRequest, Side, TimeInForce, VenueOrderStatus,
+        },
+    },
+    services::vertex::registry::ProductRegistry,
+    shared::{errors::connector_error::ConnectorError, utils::decimal::Decimal},
+};
+
+// A week of hourly candles feeds the VWAP profile
+const PROFILE_GRANULARITY_SECS: u32 = 60 * 60;
+const PROFILE_CANDLES: u32 = 7 * 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlgoState {
+    Running,
+    Completed,
+    Cancelled,
+    // The duration ran out before the parent was filled
+    Expired,
+}
+
+#[derive(Debug, Clone)]
+pub struct AlgoProgress {
+    pub algo_id: u64,
+    pub state: AlgoState,
+    pub filled: Decimal,
+    pub remaining: Decimal,
+    pub average_price: Option<Decimal>,
+    pub child_orders: u32,
+    // Why the last slice was skipped or refused
+    pub message: Option<String>,
+    pub updated_at_ms: u64,
+}
+
+struct RunningAlgo {
+    cancel: watch::Sender<bool>,
+}
+
+/// Slices parent orders into IOC children on Vertex, one task per algo.
+///
+/// Children go through the Vertex connector, so every slice passes the same risk checks and
+/// kill switch as a manual order. Progress is published on a watch channel that always holds
+/// the latest state, so slow readers skip straight to it.
+pub struct AlgoExecutor {
+    vertex: Arc<dyn Connector>,
+    registry: Arc<ProductRegistry>,
+    archive: ArchiveClient,
+    next_id: AtomicU64,
+    running: Arc<Mutex<HashMap<u64, RunningAlgo>>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+impl AlgoExecutor {
+    pub fn new(
+        vertex: Arc<dyn Connector>,
+        registry: Arc<ProductRegistry>,
+        archive: ArchiveClient,
+    ) -> Self {
+        AlgoExecutor {
+            vertex,
+            registry,
+            archive,
+            next_id: AtomicU64::new(1),
+            running: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    async fn instrument(&self, symbol: &str) -> Result<Instrument, ConnectorError> {
+        self.vertex
+            .instruments()
+            .await?
+            .into_iter()
+            .find(|instrument| instrument.symbol == symbol)
+            .ok_or_else(|| ConnectorError::InvalidRequest(format!("Unknown symbol: {}", symbol)))
+    }
+
+    // Cumulative share due per slice; POV has no schedule
+    async fn targets(&self, params: &AlgoParams) -> Result<Vec<Decimal>, ConnectorError> {
+        match params.kind {
+            AlgoKind::Twap => Ok(schedule::twap_targets(params.slices())),
+            AlgoKind::Vwap => {
+                let product_id = self
+                    .registry
+                    .product_id_for_symbol(&params.instrument)
+                    .ok_or_else(|| {
+                        ConnectorError::InvalidRequest(format!(
+                            "Unknown symbol: {}",
+                            params.instrument
+                        ))
+                    })?;
+                let candles: Vec<(u64, Decimal)> = self
+                    .archive
+                    .candlesticks(product_id, PROFILE_GRANULARITY_SECS, PROFILE_CANDLES)
+                    .await?
+                    .into_iter()
+                    .filter_map(|candle| Some((candle.timestamp.parse().ok()?, candle.volume)))
+                    .collect();
+                let profile = schedule::hourly_profile(&candles);
+                Ok(schedule::vwap_targets(now_ms() / 1000, params, &profile))
+            }
+            AlgoKind::Pov => Ok(Vec::new()),
+        }
+    }
+
+    /// Start an algo and return a receiver of its progress, which carries its id.
+    pub async fn start(
+        &self,
+        params: AlgoParams,
+    ) -> Result<watch::Receiver<AlgoProgress>, ConnectorError> {
+        params.validate().map_err(ConnectorError::InvalidRequest)?;
+        let instrument = self.instrument(&params.instrument).await?;
+        let targets = self.targets(&params).await?;
+
+        let algo_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (progress_tx, progress) = watch::channel(AlgoProgress {
+            algo_id,
+            state: AlgoState::Running,
+            filled: Decimal::ZERO,
+            remaining: params.quantity,
+            average_price: None,
+            child_orders: 0,
+            message: None,
+            updated_at_ms: now_ms(),
+        });
+        let (cancel, cancelled) = watch::channel(false);
+        self.running
+            .lock()
+            .unwrap()
+            .insert(algo_id, RunningAlgo { cancel });
+
+        info!(
+            "Starting {:?} algo {}: {:?} {} {}",
+            params.kind, algo_id, params.side, params.quantity, params.instrument
+        );
+        let run = AlgoRun {
+            algo_id,
+            params,
+            instrument,
+            targets,
+            vertex: Arc::clone(&self.vertex),
+            progress: progress_tx,
+            child_ids: HashSet::new(),
+            fills: HashMap::new(),
+            in_flight: HashMap::new(),
+            market_volume: Decimal::ZERO,
+            touch: None,
+            child_orders: 0,
+            message: None,
+        };
+        let running = Arc::clone(&self.running);
+        tokio::spawn(async move {
+            run.run(cancelled).await;
+            running.lock().unwrap().remove(&algo_id);
+        });
+        Ok(progress)
+    }
+
+    // IOC children never rest, so a cancelled algo leaves nothing on the book
+    pub fn cancel(&self, algo_id: u64) -> Result<(), ConnectorError> {
+        let running = self.running.lock().unwrap();
+        let algo = running
+            .get(&algo_id)
+            .ok_or_else(|| ConnectorError::NotFound(format!("No running algo {}", algo_id)))?;
+        let _ = algo.cancel.send(true);
+        Ok(())
+    }
+}
+
+// State of one algo, owned by its task
+struct AlgoRun {
+    algo_id: u64,
+    params: AlgoParams,
+    instrument: Instrument,
+    targets: Vec<Decimal>,
+    vertex: Arc<dyn Connector>,
+    progress: watch::Sender<AlgoProgress>,
+    // Venue ids of accepted children
+    child_ids: HashSet<String>,
+    // Filled quantity and notional per accepted child
+    fills: HashMap<String, (Decimal, Decimal)>,
+    // Size of each accepted child the venue hasn't closed yet. Events wait in the stream while
+    // a child is being placed, so its closing update always comes after the ack
+    in_flight: HashMap<String, Decimal>,
+    // Traded on the instrument since the start, for POV
+    market_volume: Decimal,
+    // Best bid and ask
+    touch: Option<(Decimal, Decimal)>,
+    child_orders: u32,
+    message: Option<String>,
+}
+
+impl AlgoRun {
+    fn filled(&self) -> (Decimal, Decimal) {
+        self.child_ids
+            .iter()
+            .filter_map(|id| self.fills.get(id))
+            .fold((Decimal::ZERO, Decimal::ZERO), |(quantity, notional), fill| {
+                (
+                    quantity.checked_add(fill.0).unwrap_or(quantity),
+                    notional.checked_add(fill.1).unwrap_or(notional),
+                )
+            })
+    }
+
+    // Filled plus whatever open children may still fill
+    fn committed(&self) -> Decimal {
+        let (filled, _) = self.filled();
+        self.in_flight
+            .iter()
+            .fold(filled, |committed, (id, quantity)| {
+                let child_filled = self.fills.get(id).map(|fill| fill.0).unwrap_or_default();
+                quantity
+                    .checked_sub(child_filled)
+                    .map(|open| open.max(Decimal::ZERO))
+                    .and_then(|open| committed.checked_add(open))
+                    .unwrap_or(committed)
+            })
+    }
+
+    fn publish(&self, state: AlgoState) {
+        let (filled, notional) = self.filled();
+        let _ = self.progress.send(AlgoProgress {
+            algo_id: self.algo_id,
+            state,
+            filled,
+            remaining: self
+                .params
+                .quantity
+                .checked_sub(filled)
+                .unwrap_or_default()
+                .max(Decimal::ZERO),
+            average_price: notional.checked_div(filled),
+            child_orders: self.child_orders,
+            message: self.message.clone(),
+            updated_at_ms: now_ms(),
+        });
+    }
+
+    fn on_event(&mut self, event: MarketEvent) {
+        if event.instrument() != self.params.instrument {
+            return;
+        }
+        match event {
+            MarketEvent::Quote {
+                bid_price,
+                ask_price,
+                ..
+            } => self.touch = Some((bid_price, ask_price)),
+            MarketEvent::Trade { quantity, .. } => {
+                if let Some(volume) = self.market_volume.checked_add(quantity) {
+                    self.market_volume = volume;
+                }
+            }
+            // Like its closing update, a child's fills come after its ack; anything else is
+            // another order's
+            MarketEvent::Fill {
+                order_id,
+                price,
+                quantity,
+                ..
+            } if self.child_ids.contains(&order_id) => {
+                let fill = self.fills.entry(order_id).or_default();
+                fill.0 = fill.0.checked_add(quantity).unwrap_or(fill.0);
+                fill.1 = price
+                    .checked_mul(quantity)
+                    .and_then(|notional| fill.1.checked_add(notional))
+                    .unwrap_or(fill.1);
+                self.publish(AlgoState::Running);
+            }
+            MarketEvent::OrderUpdate {
+                order_id, status, ..
+            } => {
+                let done = matches!(
+                    status,
+                    VenueOrderStatus::Filled
+                        | VenueOrderStatus::Cancelled
+                        | VenueOrderStatus::Rejected
+                );
+                if done {
+                    self.in_flight.remove(&order_id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Touch to cross, unless it is beyond the limit
+    fn child_price(&mut self) -> Option<Decimal> {
+        let Some((bid, ask)) = self.touch else {
+            self.message = Some("No quote yet".to_string());
+            return None;
+        };
+        let price = match self.params.side {
+            Side::Buy => ask,
+            Side::Sell => bid,
+        };
+        let beyond = match (self.params.limit_price, self.params.side) {
+            (Some(limit), Side::Buy) => price > limit,
+            (Some(limit), Side::Sell) => price < limit,
+            (None, _) => false,
+        };
+        if beyond {
+            self.message = Some(format!("Touch {} is beyond the limit", price));
+            return None;
+        }
+        Some(price)
+    }
+
+    // Send one IOC child catching up with the schedule, counting children still in flight
+    async fn slice(&mut self, target: Decimal) {
+        let Some(quantity) = schedule::child_quantity(
+            target.min(self.params.quantity),
+            self.committed(),
+            self.instrument.step_size,
+            self.instrument.min_size,
+        ) else {
+            return;
+        };
+        let Some(price) = self.child_price() else {
+            self.publish(AlgoState::Running);
+            return;
+        };
+
+        let order = OrderRequest {
+            instrument: self.params.instrument.clone(),
+            side: self.params.side,
+            price,
+            quantity,
+            time_in_force: TimeInForce::Ioc,
+            client_order_id: None,
+        };
+        match self.vertex.place_order(&order).await {
+            Ok(ack) => {
+                self.in_flight.insert(ack.order_id.clone(), quantity);
+                self.child_ids.insert(ack.order_id);
+                self.child_orders += 1;
+                self.message = None;
+            }
+            Err(e) => {
+                warn!("Algo {} child refused: {}", self.algo_id, e);
+                self.message = Some(e.to_string());
+            }
+        }
+        self.publish(AlgoState::Running);
+    }
+
+    async fn run(mut self, mut cancelled: watch::Receiver<bool>) {
+        let mut events = self.vertex.market_events();
+        let started = Instant::now();
+        let mut ticker = tokio::time::interval(self.params.slice_interval);
+        let mut next_slice = 0;
+
+        let state = loop {
+            if self.filled().0 >= self.params.quantity {
+                break AlgoState::Completed;
+            }
+            tokio::select! {
+                changed = cancelled.changed() => {
+                    if changed.is_err() || *cancelled.borrow() {
+                        break AlgoState::Cancelled;
+                    }
+                }
+                Some(event) = events.next() => self.on_event(event),
+                _ = ticker.tick() => {
+                    let target = match self.params.kind {
+                        AlgoKind::Pov => {
+                            if started.elapsed() >= self.params.duration {
+                                break AlgoState::Expired;
+                            }
+                            schedule::pov_target(&self.params, self.market_volume, self.filled().0)
+                        }
+                        // One interval past the last slice lets its fills arrive
+                        AlgoKind::Twap | AlgoKind::Vwap => match self.targets.get(next_slice) {
+                            Some(share) => share
+                                .checked_mul(self.params.quantity)
+                                .unwrap_or(self.params.quantity),
+                            None => break AlgoState::Expired,
+                        },
+                    };
+                    next_slice += 1;
+                    self.slice(target).await;
+                }
+            }
+        };
+
+        info!("Algo {} finished: {:?}", self.algo_id, state);
+        self.publish(state);
+    }
+}
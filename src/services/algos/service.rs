@@ -0,0 +1,126 @@
+use std::{pin::Pin, sync::Arc, time::Duration};
+
+use futures::{stream, Stream};
+use tokio::sync::watch;
+use tonic::{Request, Response, Status};
+
+use crate::{
+    algos::{
+        self as proto, algo_service_server::AlgoService, AlgoType, CancelAlgoRequest,
+        CancelAlgoResponse, ExecuteAlgoRequest,
+    },
+    domain::{
+        algos::schedule::{AlgoKind, AlgoParams},
+        models::trading::Side,
+    },
+    shared::utils::decimal::Decimal,
+};
+
+use super::executor::{AlgoExecutor, AlgoProgress, AlgoState};
+
+/// gRPC front of the algo executor.
+#[derive(Clone)]
+pub struct AlgoControl {
+    pub executor: Arc<AlgoExecutor>,
+}
+
+impl AlgoControl {
+    pub fn new(executor: Arc<AlgoExecutor>) -> Self {
+        AlgoControl { executor }
+    }
+}
+
+fn parse_decimal(name: &str, value: &str) -> Result<Decimal, String> {
+    value
+        .parse()
+        .map_err(|e| format!("Invalid {}: {}", name, e))
+}
+
+// Errors are invalid arguments
+fn to_params(request: ExecuteAlgoRequest) -> Result<AlgoParams, String> {
+    Ok(AlgoParams {
+        kind: match request.algo() {
+            AlgoType::Twap => AlgoKind::Twap,
+            AlgoType::Vwap => AlgoKind::Vwap,
+            AlgoType::Pov => AlgoKind::Pov,
+        },
+        side: match request.side() {
+            proto::Side::Buy => Side::Buy,
+            proto::Side::Sell => Side::Sell,
+            proto::Side::Unspecified => return Err("Order side is missing".to_string()),
+        },
+        quantity: parse_decimal("quantity", &request.quantity)?,
+        limit_price: request
+            .limit_price
+            .as_deref()
+            .map(|price| parse_decimal("limit_price", price))
+            .transpose()?,
+        duration: Duration::from_secs(request.duration_secs),
+        slice_interval: Duration::from_secs(request.slice_interval_secs),
+        participation_rate: request
+            .participation_rate
+            .as_deref()
+            .map(|rate| parse_decimal("participation_rate", rate))
+            .transpose()?,
+        instrument: request.instrument,
+    })
+}
+
+impl From<AlgoProgress> for proto::AlgoProgress {
+    fn from(progress: AlgoProgress) -> Self {
+        let state = match progress.state {
+            AlgoState::Running => proto::AlgoState::Running,
+            AlgoState::Completed => proto::AlgoState::Completed,
+            AlgoState::Cancelled => proto::AlgoState::Cancelled,
+            AlgoState::Expired => proto::AlgoState::Expired,
+        };
+        proto::AlgoProgress {
+            algo_id: progress.algo_id,
+            state: state as i32,
+            filled_quantity: progress.filled.to_string(),
+            remaining_quantity: progress.remaining.to_string(),
+            average_price: progress.average_price.map(|price| price.to_string()),
+            child_orders: progress.child_orders,
+            message: progress.message,
+            updated_at_ms: progress.updated_at_ms,
+        }
+    }
+}
+
+// The current progress, then every change until the algo is done
+fn progress_stream(
+    progress: watch::Receiver<AlgoProgress>,
+) -> impl Stream<Item = Result<proto::AlgoProgress, Status>> {
+    stream::unfold(Some((progress, true)), |state| async move {
+        let (mut progress, first) = state?;
+        if !first && progress.changed().await.is_err() {
+            return None;
+        }
+        let current = progress.borrow_and_update().clone();
+        let next = (current.state == AlgoState::Running).then_some((progress, false));
+        Some((Ok(current.into()), next))
+    })
+}
+
+#[tonic::async_trait]
+impl AlgoService for AlgoControl {
+    type ExecuteAlgoStream =
+        Pin<Box<dyn Stream<Item = Result<proto::AlgoProgress, Status>> + Send + 'static>>;
+
+    async fn execute_algo(
+        &self,
+        request: Request<ExecuteAlgoRequest>,
+    ) -> Result<Response<Self::ExecuteAlgoStream>, Status> {
+        let params = to_params(request.into_inner()).map_err(Status::invalid_argument)?;
+        let progress = self.executor.start(params).await?;
+        Ok(Response::new(Box::pin(progress_stream(progress))))
+    }
+
+    async fn cancel_algo(
+        &self,
+        request: Request<CancelAlgoRequest>,
+    ) -> Result<Response<CancelAlgoResponse>, Status> {
+        self.executor.cancel(request.into_inner().algo_id)?;
+        Ok(Response::new(CancelAlgoResponse {}))
+    }
+}
@@ -0,0 +1,298 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use log::warn;
+use tonic::{Request, Response, Status};
+
+use crate::{
+    backtest::{
+        self as proto, backtest_service_server::BacktestService, run_backtest_request::Source,
+        ArchiveSource, RunBacktestRequest,
+    },
+    connectors::vertex::archive_client::ArchiveClient,
+    domain::{
+        backtest::{
+            data::{self, Candle},
+            engine::Backtest,
+            exchange::{FeeRates, SimExchange},
+            report::{BacktestReport, EquityPoint, InstrumentResult},
+        },
+        models::trading::{MarketEvent, Venue},
+        strategies::{basis::FundingHistory, driver::StrategyDriver},
+    },
+    services::{
+        strategies::{
+            runtime::{StrategyEnv, StrategyRuntime},
+            service::parse_params,
+        },
+        vertex::{connector::to_market_event, registry::ProductRegistry},
+    },
+    shared::{errors::connector_error::ConnectorError, utils::decimal::Decimal},
+    storage::recorder,
+};
+
+/// gRPC front of the backtester, building strategies from the runtime's registered kinds.
+#[derive(Clone)]
+pub struct BacktestControl {
+    pub runtime: Arc<StrategyRuntime>,
+    pub registry: Arc<ProductRegistry>,
+    pub archive: ArchiveClient,
+    // Event files are only read from inside it; none are without it
+    pub data_dir: Option<PathBuf>,
+}
+
+impl BacktestControl {
+    pub fn new(
+        runtime: Arc<StrategyRuntime>,
+        registry: Arc<ProductRegistry>,
+        archive: ArchiveClient,
+        data_dir: Option<PathBuf>,
+    ) -> Self {
+        BacktestControl {
+            runtime,
+            registry,
+            archive,
+            data_dir,
+        }
+    }
+
+    // An events_path relative to the data directory, refused once resolved outside it
+    fn data_file(&self, path: &str) -> Result<PathBuf, ConnectorError> {
+        let dir = self.data_dir.as_ref().ok_or_else(|| {
+            ConnectorError::Rejected(
+                "BACKTEST_DATA_DIR is not set, event files are disabled".into(),
+            )
+        })?;
+        let dir = dir
+            .canonicalize()
+            .map_err(|e| ConnectorError::Internal(format!("Invalid data directory: {}", e)))?;
+        let file = dir
+            .join(path)
+            .canonicalize()
+            .map_err(|e| ConnectorError::NotFound(format!("Failed to open {}: {}", path, e)))?;
+        if !file.starts_with(&dir) {
+            return Err(ConnectorError::InvalidRequest(format!(
+                "{} is outside the data directory",
+                path
+            )));
+        }
+        Ok(file)
+    }
+
+    // Recorded funding of the Vertex perps among the subscriptions, over the replayed span
+    async fn funding_history(
+        &self,
+        subscriptions: &[(Venue, String)],
+        events: &[MarketEvent],
+    ) -> HashMap<String, FundingHistory> {
+        let mut funding = HashMap::new();
+        let (Some(first), Some(last)) = (events.first(), events.last()) else {
+            return funding;
+        };
+        for (venue, instrument) in subscriptions {
+            let Some(symbol) = self.registry.symbol(instrument) else {
+                continue;
+            };
+            if *venue != Venue::Vertex || symbol.r#type != "perp" {
+                continue;
+            }
+            let rates = self
+                .archive
+                .funding_history(
+                    symbol.product_id as u32,
+                    first.timestamp_ms() / 1000,
+                    last.timestamp_ms() / 1000,
+                )
+                .await;
+            match rates {
+                Ok(rates) => {
+                    funding.insert(instrument.clone(), FundingHistory(rates));
+                }
+                Err(e) => warn!("Backtesting without funding of {}: {}", instrument, e),
+            }
+        }
+        funding
+    }
+
+    // Vertex fees of the subscribed instruments; other venues trade free
+    fn fee_rates(&self, subscriptions: &[(Venue, String)]) -> HashMap<(Venue, String), FeeRates> {
+        subscriptions
+            .iter()
+            .filter(|(venue, _)| *venue == Venue::Vertex)
+            .filter_map(|(venue, instrument)| {
+                let symbol = self.registry.symbol(instrument)?;
+                let rates = FeeRates {
+                    maker: Decimal::from_x18_str(&symbol.maker_fee_rate_x18).ok()?,
+                    taker: Decimal::from_x18_str(&symbol.taker_fee_rate_x18).ok()?,
+                };
+                Some(((*venue, instrument.clone()), rates))
+            })
+            .collect()
+    }
+
+    async fn archive_events(&self, source: ArchiveSource) -> Result<Vec<MarketEvent>, Status> {
+        let symbol = self.registry.symbol(&source.instrument).ok_or_else(|| {
+            Status::invalid_argument(format!("Unknown symbol: {}", source.instrument))
+        })?;
+        let increment = Decimal::from_x18_str(&symbol.price_increment_x18)
+            .map_err(|e| Status::internal(format!("Invalid price increment: {}", e)))?;
+        let candles: Vec<Candle> = self
+            .archive
+            .candlesticks(
+                symbol.product_id as u32,
+                source.granularity_secs,
+                source.limit,
+            )
+            .await?
+            .into_iter()
+            .filter_map(|candle| {
+                let open_secs: u64 = candle.timestamp.parse().ok()?;
+                Some(Candle {
+                    open_ms: open_secs * 1000,
+                    open: candle.open_x18,
+                    high: candle.high_x18,
+                    low: candle.low_x18,
+                    close: candle.close_x18,
+                    volume: candle.volume,
+                })
+            })
+            .collect();
+        Ok(data::candle_events(
+            Venue::Vertex,
+            &source.instrument,
+            &candles,
+            source.granularity_secs as u64 * 1000,
+            increment,
+        ))
+    }
+}
+
+// Market data from event files, or recorder output (`.gz`) mapped through the product registry
+fn read_events_file(
+    registry: &ProductRegistry,
+    path: &Path,
+) -> Result<Vec<MarketEvent>, ConnectorError> {
+    let name = path.display();
+    if path.extension().is_some_and(|extension| extension == "gz") {
+        let recording = recorder::read_recording(path)
+            .map_err(|e| ConnectorError::NotFound(format!("Failed to read {}: {}", name, e)))?;
+        let mut events: Vec<MarketEvent> = recording
+            .filter_map(|recorded| to_market_event(registry, recorded.event))
+            .filter(MarketEvent::is_market_data)
+            .collect();
+        events.sort_by_key(MarketEvent::timestamp_ms);
+        return Ok(events);
+    }
+    let file = File::open(path)
+        .map_err(|e| ConnectorError::NotFound(format!("Failed to open {}: {}", name, e)))?;
+    data::read_events(BufReader::new(file)).map_err(ConnectorError::InvalidRequest)
+}
+
+async fn recorded_events(
+    registry: Arc<ProductRegistry>,
+    path: PathBuf,
+) -> Result<Vec<MarketEvent>, Status> {
+    let events = tokio::task::spawn_blocking(move || read_events_file(&registry, &path))
+        .await
+        .map_err(|e| Status::internal(format!("Event reader failed: {}", e)))??;
+    Ok(events)
+}
+
+impl From<InstrumentResult> for proto::InstrumentResult {
+    fn from(result: InstrumentResult) -> Self {
+        proto::InstrumentResult {
+            venue: result.venue.to_string(),
+            instrument: result.instrument,
+            position: result.position.to_string(),
+            entry_price: result.entry_price.to_string(),
+            mark_price: result.mark_price.map(|price| price.to_string()),
+            realized_pnl: result.realized_pnl.to_string(),
+            fees: result.fees.to_string(),
+            fills: result.fills,
+            volume: result.volume.to_string(),
+        }
+    }
+}
+
+impl From<EquityPoint> for proto::EquityPoint {
+    fn from(point: EquityPoint) -> Self {
+        proto::EquityPoint {
+            timestamp_ms: point.timestamp_ms,
+            equity: point.equity.to_string(),
+        }
+    }
+}
+
+impl From<BacktestReport> for proto::BacktestReport {
+    fn from(report: BacktestReport) -> Self {
+        proto::BacktestReport {
+            start_ms: report.start_ms,
+            end_ms: report.end_ms,
+            events: report.events,
+            orders_sent: report.orders_sent,
+            orders_rejected: report.orders_rejected,
+            fills: report.fills,
+            maker_fills: report.maker_fills,
+            taker_fills: report.taker_fills,
+            volume: report.volume.to_string(),
+            notional: report.notional.to_string(),
+            fees: report.fees.to_string(),
+            realized_pnl: report.realized_pnl.to_string(),
+            unrealized_pnl: report.unrealized_pnl.to_string(),
+            total_pnl: report.total_pnl.to_string(),
+            max_drawdown: report.max_drawdown.to_string(),
+            instruments: report.instruments.into_iter().map(Into::into).collect(),
+            equity_curve: report.equity_curve.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl BacktestService for BacktestControl {
+    async fn run_backtest(
+        &self,
+        request: Request<RunBacktestRequest>,
+    ) -> Result<Response<proto::BacktestReport>, Status> {
+        let request = request.into_inner();
+        let params = parse_params(&request.params_json).map_err(Status::invalid_argument)?;
+        // Built once without funding only to learn what it trades
+        let env = StrategyEnv::Backtest {
+            funding: HashMap::new(),
+        };
+        let subscriptions = self
+            .runtime
+            .create(&request.kind, &params, &env)?
+            .subscriptions();
+        let fees = self.fee_rates(&subscriptions);
+        let events = match request.source {
+            Some(Source::EventsPath(path)) => {
+                recorded_events(Arc::clone(&self.registry), self.data_file(&path)?).await?
+            }
+            Some(Source::Archive(source)) => self.archive_events(source).await?,
+            None => return Err(Status::invalid_argument("A data source is required")),
+        };
+        let env = StrategyEnv::Backtest {
+            funding: self.funding_history(&subscriptions, &events).await,
+        };
+        let strategy = self.runtime.create(&request.kind, &params, &env)?;
+
+        // Replays are CPU-bound; keep them off the async workers
+        let latency_ms = request.latency_ms;
+        let report = tokio::task::spawn_blocking(move || {
+            let backtest = Backtest::new(
+                StrategyDriver::new(strategy),
+                SimExchange::new(fees),
+                latency_ms,
+            );
+            backtest.run(events)
+        })
+        .await
+        .map_err(|e| Status::internal(format!("Backtest failed: {}", e)))?;
+        Ok(Response::new(report.into()))
+    }
+}
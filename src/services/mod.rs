@@ -1 +1,5 @@
+pub mod algos;
+pub mod backtest;
+pub mod strategies;
+pub mod trading;
 pub mod vertex;
@@ -0,0 +1,128 @@
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use log::warn;
+
+use crate::{
+    connectors::vertex::archive_client::ArchiveClient,
+    domain::strategies::{
+        arbitrage,
+        basis::{self, BasisCapture, FundingHandle, LegSpec},
+        grid::{self, Grid, GridIncrements},
+        market_maker,
+        strategy::Strategy,
+    },
+    services::vertex::registry::ProductRegistry,
+    shared::utils::decimal::Decimal,
+    vertex_symbols::Symbol,
+};
+
+use super::runtime::{StrategyEnv, StrategyRuntime};
+
+// Vertex updates funding hourly; polling more often only keeps the value fresh after restarts
+const FUNDING_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+fn x18(value: &str) -> Result<Decimal, String> {
+    Decimal::from_x18_str(value).map_err(|e| e.to_string())
+}
+
+fn symbol(registry: &ProductRegistry, name: &str) -> Result<Symbol, String> {
+    registry
+        .symbol(name)
+        .ok_or_else(|| format!("Unknown symbol: {}", name))
+}
+
+fn leg_spec(symbol: &Symbol) -> Result<LegSpec, String> {
+    Ok(LegSpec {
+        instrument: symbol.symbol.clone(),
+        price_increment: x18(&symbol.price_increment_x18)?,
+        size_increment: x18(&symbol.size_increment)?,
+        min_size: x18(&symbol.min_size)?,
+    })
+}
+
+// Keep the perp's funding rate current for as long as the strategy holding it is alive
+fn spawn_funding_poller(
+    archive: ArchiveClient,
+    product_id: u32,
+    funding: &Arc<RwLock<Option<Decimal>>>,
+) {
+    let funding = Arc::downgrade(funding);
+    tokio::spawn(async move {
+        loop {
+            let rate = archive.funding_rate(product_id).await;
+            let Some(funding) = funding.upgrade() else {
+                return;
+            };
+            match rate {
+                Ok(rate) => *funding.write().unwrap() = Some(rate.funding_rate_x18),
+                Err(e) => warn!("Failed to poll funding of product {}: {}", product_id, e),
+            }
+            drop(funding);
+            tokio::time::sleep(FUNDING_POLL_INTERVAL).await;
+        }
+    });
+}
+
+/// Register every strategy kind that ships with the server.
+pub fn register_builtin(
+    runtime: &mut StrategyRuntime,
+    registry: Arc<ProductRegistry>,
+    archive: ArchiveClient,
+) {
+    runtime.register(
+        "market_maker",
+        Box::new(|params, _: &StrategyEnv| market_maker::factory(params)),
+    );
+    runtime.register(
+        "arbitrage",
+        Box::new(|params, _: &StrategyEnv| arbitrage::factory(params)),
+    );
+
+    let grid_registry = Arc::clone(&registry);
+    runtime.register(
+        "grid",
+        Box::new(move |params: &serde_json::Value, _: &StrategyEnv| {
+            let params = grid::parse_params(params)?;
+            let symbol = symbol(&grid_registry, &params.instrument)?;
+            let increments = GridIncrements {
+                price: x18(&symbol.price_increment_x18)?,
+                size: x18(&symbol.size_increment)?,
+                min_size: x18(&symbol.min_size)?,
+            };
+            Ok(Box::new(Grid::new(params, increments)?) as Box<dyn Strategy>)
+        }),
+    );
+
+    runtime.register(
+        "basis",
+        Box::new(move |params: &serde_json::Value, env: &StrategyEnv| {
+            let params = basis::parse_params(params)?;
+            let spot = symbol(&registry, &params.spot_instrument)?;
+            let perp = symbol(&registry, &params.perp_instrument)?;
+            if spot.r#type != "spot" || perp.r#type != "perp" {
+                return Err("Expected a spot and a perp instrument".to_string());
+            }
+
+            // Backtests replay recorded funding instead of polling the current rate
+            let funding: FundingHandle = match env {
+                StrategyEnv::Live => {
+                    let funding = Arc::new(RwLock::new(None));
+                    spawn_funding_poller(archive.clone(), perp.product_id as u32, &funding);
+                    funding
+                }
+                StrategyEnv::Backtest { funding } => {
+                    Arc::new(funding.get(&perp.symbol).cloned().unwrap_or_default())
+                }
+            };
+            Ok(Box::new(BasisCapture::new(
+                params,
+                leg_spec(&spot)?,
+                leg_spec(&perp)?,
+                funding,
+            )))
+        }),
+    );
+}
@@ -0,0 +1,3 @@
+pub mod kinds;
+pub mod runtime;
+pub mod service;
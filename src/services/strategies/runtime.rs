@@ -0,0 +1,368 @@
+use std::{
+    collections::HashMap,
+    future,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use futures::{stream, StreamExt};
+use log::{error, warn};
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+    time::Interval,
+};
+
+use crate::{
+    connectors::registry::ConnectorRegistry,
+    domain::strategies::{
+        basis::FundingHistory,
+        driver::StrategyDriver,
+        strategy::{OrderResponse, Strategy, StrategyAction},
+    },
+    shared::errors::strategy_error::StrategyError,
+};
+
+// Builds a strategy of one kind from its start parameters
+pub type StrategyFactory = Box<
+    dyn Fn(&serde_json::Value, &StrategyEnv) -> Result<Box<dyn Strategy>, String> + Send + Sync,
+>;
+
+// Where a strategy gets the data that doesn't come through the connectors
+#[derive(Debug, Clone)]
+pub enum StrategyEnv {
+    // Polled from the venues while the strategy runs
+    Live,
+    // Recorded perp funding by instrument, for replays; nothing may be polled
+    Backtest {
+        funding: HashMap<String, FundingHistory>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct StrategyInfo {
+    pub id: String,
+    pub kind: String,
+    pub params: serde_json::Value,
+    pub started_at_ms: u64,
+    // False once the strategy's task has ended on its own
+    pub running: bool,
+}
+
+enum Command {
+    Configure(serde_json::Value, oneshot::Sender<Result<(), String>>),
+    Stop(oneshot::Sender<()>),
+}
+
+struct RunningStrategy {
+    info: StrategyInfo,
+    commands: mpsc::UnboundedSender<Command>,
+    handle: JoinHandle<()>,
+}
+
+/// Runs strategies on their own tasks, feeding them the connectors' market events and sending
+/// their orders through the same guarded connectors, so every venue's orders pass the kill switch
+/// and the risk checks.
+pub struct StrategyRuntime {
+    connectors: Arc<ConnectorRegistry>,
+    factories: HashMap<String, StrategyFactory>,
+    running: Mutex<HashMap<String, RunningStrategy>>,
+}
+
+impl StrategyRuntime {
+    pub fn new(connectors: Arc<ConnectorRegistry>) -> Self {
+        StrategyRuntime {
+            connectors,
+            factories: HashMap::new(),
+            running: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn register(&mut self, kind: &str, factory: StrategyFactory) {
+        self.factories.insert(kind.to_string(), factory);
+    }
+
+    pub fn kinds(&self) -> Vec<String> {
+        let mut kinds: Vec<String> = self.factories.keys().cloned().collect();
+        kinds.sort();
+        kinds
+    }
+
+    // A fresh strategy of one kind, for the runtime or the backtester
+    pub fn create(
+        &self,
+        kind: &str,
+        params: &serde_json::Value,
+        env: &StrategyEnv,
+    ) -> Result<Box<dyn Strategy>, StrategyError> {
+        let factory = self
+            .factories
+            .get(kind)
+            .ok_or_else(|| StrategyError::UnknownKind(kind.to_string()))?;
+        factory(params, env).map_err(StrategyError::InvalidParams)
+    }
+
+    pub fn start(
+        &self,
+        id: &str,
+        kind: &str,
+        params: serde_json::Value,
+    ) -> Result<StrategyInfo, StrategyError> {
+        if !self.factories.contains_key(kind) {
+            return Err(StrategyError::UnknownKind(kind.to_string()));
+        }
+
+        let mut running = self.running.lock().unwrap();
+        // A strategy that ended on its own frees its id
+        if running
+            .get(id)
+            .is_some_and(|strategy| !strategy.handle.is_finished())
+        {
+            return Err(StrategyError::AlreadyRunning(id.to_string()));
+        }
+
+        let strategy = self.create(kind, &params, &StrategyEnv::Live)?;
+        let (commands, command_rx) = mpsc::unbounded_channel();
+        let handle = tokio::spawn(run(
+            id.to_string(),
+            StrategyDriver::new(strategy),
+            Arc::clone(&self.connectors),
+            command_rx,
+        ));
+
+        let info = StrategyInfo {
+            id: id.to_string(),
+            kind: kind.to_string(),
+            params,
+            started_at_ms: now_ms(),
+            running: true,
+        };
+        running.insert(
+            id.to_string(),
+            RunningStrategy {
+                info: info.clone(),
+                commands,
+                handle,
+            },
+        );
+        Ok(info)
+    }
+
+    /// Stop a strategy once its `on_stop` callback has run and every action it returned was sent.
+    pub async fn stop(&self, id: &str) -> Result<StrategyInfo, StrategyError> {
+        let strategy = self
+            .running
+            .lock()
+            .unwrap()
+            .remove(id)
+            .ok_or_else(|| StrategyError::NotRunning(id.to_string()))?;
+
+        let (reply, done) = oneshot::channel();
+        if strategy.commands.send(Command::Stop(reply)).is_ok() {
+            let _ = done.await;
+        }
+        let mut info = strategy.info;
+        info.running = false;
+        Ok(info)
+    }
+
+    pub async fn configure(
+        &self,
+        id: &str,
+        params: serde_json::Value,
+    ) -> Result<StrategyInfo, StrategyError> {
+        let commands = self
+            .running
+            .lock()
+            .unwrap()
+            .get(id)
+            .map(|strategy| strategy.commands.clone())
+            .ok_or_else(|| StrategyError::NotRunning(id.to_string()))?;
+
+        let (reply, result) = oneshot::channel();
+        commands
+            .send(Command::Configure(params.clone(), reply))
+            .map_err(|_| StrategyError::NotRunning(id.to_string()))?;
+        result
+            .await
+            .map_err(|_| StrategyError::NotRunning(id.to_string()))?
+            .map_err(StrategyError::InvalidParams)?;
+
+        let mut running = self.running.lock().unwrap();
+        let strategy = running
+            .get_mut(id)
+            .ok_or_else(|| StrategyError::NotRunning(id.to_string()))?;
+        strategy.info.params = params;
+        Ok(strategy.info.clone())
+    }
+
+    pub fn list(&self) -> Vec<StrategyInfo> {
+        let mut strategies: Vec<StrategyInfo> = self
+            .running
+            .lock()
+            .unwrap()
+            .values()
+            .map(|strategy| StrategyInfo {
+                running: !strategy.handle.is_finished(),
+                ..strategy.info.clone()
+            })
+            .collect();
+        strategies.sort_by(|a, b| a.id.cmp(&b.id));
+        strategies
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+async fn tick(timer: &mut Option<Interval>) {
+    match timer {
+        Some(timer) => {
+            timer.tick().await;
+        }
+        None => future::pending().await,
+    }
+}
+
+// Send one action and wait for the venue's answer; order outcomes go back through `responses`
+async fn send_action(
+    id: &str,
+    connectors: &ConnectorRegistry,
+    responses: &mpsc::UnboundedSender<OrderResponse>,
+    action: StrategyAction,
+) {
+    match action {
+        StrategyAction::Place { tag, venue, order } => {
+            let result = match connectors.get(venue) {
+                Ok(connector) => connector.place_order(&order).await,
+                Err(e) => Err(e),
+            };
+            let _ = responses.send(OrderResponse {
+                tag,
+                venue,
+                order,
+                result,
+            });
+        }
+        StrategyAction::CancelAndPlace {
+            tag,
+            venue,
+            cancel,
+            order,
+        } => {
+            let result = match connectors.get(venue) {
+                Ok(connector) => connector.cancel_and_place(&cancel, &order).await,
+                Err(e) => Err(e),
+            };
+            let _ = responses.send(OrderResponse {
+                tag,
+                venue,
+                order,
+                result,
+            });
+        }
+        StrategyAction::Cancel { venue, cancel } => {
+            let result = match connectors.get(venue) {
+                Ok(connector) => connector.cancel_order(&cancel).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                warn!(
+                    "Strategy {} failed to cancel {}: {}",
+                    id, cancel.order_id, e
+                );
+            }
+        }
+        StrategyAction::CancelAll { venue, instrument } => {
+            let result = match connectors.get(venue) {
+                Ok(connector) => connector.cancel_all(instrument.as_deref()).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                warn!("Strategy {} failed to cancel all on {}: {}", id, venue, e);
+            }
+        }
+    }
+}
+
+// The strategy's sender: actions go out one at a time in the order the strategy returned them,
+// so a cancel-all on stop cannot overtake a place still in flight
+fn spawn_sender(
+    id: String,
+    connectors: Arc<ConnectorRegistry>,
+    responses: mpsc::UnboundedSender<OrderResponse>,
+) -> (mpsc::UnboundedSender<Vec<StrategyAction>>, JoinHandle<()>) {
+    let (actions_tx, mut actions) = mpsc::unbounded_channel::<Vec<StrategyAction>>();
+    let handle = tokio::spawn(async move {
+        while let Some(batch) = actions.recv().await {
+            for action in batch {
+                send_action(&id, &connectors, &responses, action).await;
+            }
+        }
+    });
+    (actions_tx, handle)
+}
+
+// Wait until the sender has sent everything queued so far
+async fn drain(actions: mpsc::UnboundedSender<Vec<StrategyAction>>, sender: JoinHandle<()>) {
+    drop(actions);
+    let _ = sender.await;
+}
+
+// The strategy's task: one callback at a time until stopped
+async fn run(
+    id: String,
+    mut driver: StrategyDriver,
+    connectors: Arc<ConnectorRegistry>,
+    mut commands: mpsc::UnboundedReceiver<Command>,
+) {
+    let streams: Vec<_> = driver
+        .venues()
+        .into_iter()
+        .filter_map(|venue| match connectors.get(venue) {
+            Ok(connector) => Some(connector.market_events()),
+            Err(e) => {
+                error!("Strategy {} subscribes to {}: {}", id, venue, e);
+                None
+            }
+        })
+        .collect();
+    let mut events = stream::select_all(streams);
+    let (responses_tx, mut responses) = mpsc::unbounded_channel();
+    let (actions, sender) = spawn_sender(id.clone(), Arc::clone(&connectors), responses_tx);
+    let mut timer = driver.timer_interval().map(tokio::time::interval);
+
+    let _ = actions.send(driver.start(now_ms()));
+    loop {
+        let batch = tokio::select! {
+            Some(event) = events.next() => driver.event(&event, now_ms()),
+            Some(response) = responses.recv() => driver.order_response(&response, now_ms()),
+            _ = tick(&mut timer) => driver.timer(now_ms()),
+            command = commands.recv() => match command {
+                Some(Command::Configure(params, reply)) => {
+                    let _ = reply.send(driver.configure(&params));
+                    // The new interval applies from now; venues not streamed at start need a
+                    // restart
+                    timer = driver.timer_interval().map(tokio::time::interval);
+                    Vec::new()
+                }
+                Some(Command::Stop(reply)) => {
+                    let _ = actions.send(driver.stop(now_ms()));
+                    drain(actions, sender).await;
+                    let _ = reply.send(());
+                    return;
+                }
+                None => {
+                    let _ = actions.send(driver.stop(now_ms()));
+                    drain(actions, sender).await;
+                    return;
+                }
+            },
+        };
+        let _ = actions.send(batch);
+    }
+}
@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+
+use crate::strategies::{
+    strategy_service_server::StrategyService, ConfigureStrategyRequest, ListStrategiesRequest,
+    ListStrategiesResponse, StartStrategyRequest, StopStrategyRequest, StrategyStatus,
+};
+
+use super::runtime::{StrategyInfo, StrategyRuntime};
+
+/// gRPC controls over the strategy runtime.
+#[derive(Clone)]
+pub struct StrategyControl {
+    pub runtime: Arc<StrategyRuntime>,
+}
+
+impl StrategyControl {
+    pub fn new(runtime: Arc<StrategyRuntime>) -> Self {
+        StrategyControl { runtime }
+    }
+}
+
+// An empty string means no parameters
+pub fn parse_params(params_json: &str) -> Result<serde_json::Value, String> {
+    if params_json.trim().is_empty() {
+        return Ok(serde_json::Value::Object(Default::default()));
+    }
+    serde_json::from_str(params_json).map_err(|e| format!("Invalid params_json: {}", e))
+}
+
+impl From<StrategyInfo> for StrategyStatus {
+    fn from(info: StrategyInfo) -> Self {
+        StrategyStatus {
+            id: info.id,
+            kind: info.kind,
+            params_json: info.params.to_string(),
+            started_at_ms: info.started_at_ms,
+            running: info.running,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl StrategyService for StrategyControl {
+    async fn start_strategy(
+        &self,
+        request: Request<StartStrategyRequest>,
+    ) -> Result<Response<StrategyStatus>, Status> {
+        let start = request.into_inner();
+        if start.id.is_empty() {
+            return Err(Status::invalid_argument("Strategy id is required"));
+        }
+        let params = parse_params(&start.params_json).map_err(Status::invalid_argument)?;
+        let info = self.runtime.start(&start.id, &start.kind, params)?;
+        Ok(Response::new(info.into()))
+    }
+
+    async fn stop_strategy(
+        &self,
+        request: Request<StopStrategyRequest>,
+    ) -> Result<Response<StrategyStatus>, Status> {
+        let info = self.runtime.stop(&request.into_inner().id).await?;
+        Ok(Response::new(info.into()))
+    }
+
+    async fn configure_strategy(
+        &self,
+        request: Request<ConfigureStrategyRequest>,
+    ) -> Result<Response<StrategyStatus>, Status> {
+        let configure = request.into_inner();
+        let params = parse_params(&configure.params_json).map_err(Status::invalid_argument)?;
+        let info = self.runtime.configure(&configure.id, params).await?;
+        Ok(Response::new(info.into()))
+    }
+
+    async fn list_strategies(
+        &self,
+        _request: Request<ListStrategiesRequest>,
+    ) -> Result<Response<ListStrategiesResponse>, Status> {
+        Ok(Response::new(ListStrategiesResponse {
+            strategies: self
+                .runtime
+                .list()
+                .into_iter()
+                .map(StrategyStatus::from)
+                .collect(),
+            kinds: self.runtime.kinds(),
+        }))
+    }
+}
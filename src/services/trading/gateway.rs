@@ -0,0 +1,505 @@
+use std::{pin::Pin, sync::Arc};
+
+use futures::{stream, Stream, StreamExt};
+use tonic::{Request, Response, Status};
+
+use crate::{
+    connectors::{connector::Connector, registry::ConnectorRegistry},
+    domain::{
+        models::trading::{
+            BookLevel, CancelRequest, Instrument, MarketEvent, OrderRequest, Side, TimeInForce,
+            Venue, VenueOrder, VenueOrderStatus, VenuePosition,
+        },
+        routing::config::RoutingPolicy,
+    },
+    shared::{errors::connector_error::ConnectorError, utils::decimal::Decimal},
+    trading_service::{
+        self as proto, market_event::Event, trading_service_server::TradingService,
+        CancelAllRequest, CancelOrderRequest, CancelResponse, ConnectionRequest,
+        ConnectionResponse, ListInstrumentsRequest, ListInstrumentsResponse,
+        ListOpenOrdersRequest, ListOpenOrdersResponse, ListPositionsRequest,
+        ListPositionsResponse, PlaceOrderRequest, PlaceOrderResponse, RouteOrderRequest,
+        RouteOrderResponse, StreamMarketEventsRequest,
+    },
+};
+
+use super::router::{ParentOrder, SmartOrderRouter};
+
+/// Venue-neutral trading service, dispatching every call to the requested venue's connector.
+#[derive(Debug, Clone)]
+pub struct TradingGateway {
+    pub connectors: Arc<ConnectorRegistry>,
+    pub router: Arc<SmartOrderRouter>,
+}
+
+impl TradingGateway {
+    pub fn new(connectors: Arc<ConnectorRegistry>, router: Arc<SmartOrderRouter>) -> Self {
+        TradingGateway { connectors, router }
+    }
+
+    // One venue when given, otherwise every configured venue
+    fn connectors_for(
+        &self,
+        venue: Option<&str>,
+    ) -> Result<Vec<Arc<dyn Connector>>, ConnectorError> {
+        match venue {
+            Some(_) => Ok(vec![self.connectors.get(parse_venue(venue)?)?]),
+            None => Ok(self
+                .connectors
+                .venues()
+                .into_iter()
+                .filter_map(|venue| self.connectors.get(venue).ok())
+                .collect()),
+        }
+    }
+}
+
+// Requests without a venue go to Vertex, the venue this service started with
+pub fn parse_venue(venue: Option<&str>) -> Result<Venue, ConnectorError> {
+    venue
+        .unwrap_or("vertex")
+        .parse()
+        .map_err(ConnectorError::InvalidRequest)
+}
+
+fn parse_decimal(name: &str, value: &str) -> Result<Decimal, ConnectorError> {
+    value
+        .parse()
+        .map_err(|e| ConnectorError::InvalidRequest(format!("Invalid {}: {}", name, e)))
+}
+
+fn parse_side(side: proto::Side) -> Result<Side, ConnectorError> {
+    match side {
+        proto::Side::Buy => Ok(Side::Buy),
+        proto::Side::Sell => Ok(Side::Sell),
+        proto::Side::Unspecified => Err(ConnectorError::InvalidRequest(
+            "Order side is missing".to_string(),
+        )),
+    }
+}
+
+fn to_proto_side(side: Side) -> proto::Side {
+    match side {
+        Side::Buy => proto::Side::Buy,
+        Side::Sell => proto::Side::Sell,
+    }
+}
+
+fn to_time_in_force(time_in_force: proto::TimeInForce) -> TimeInForce {
+    match time_in_force {
+        proto::TimeInForce::Gtc => TimeInForce::Gtc,
+        proto::TimeInForce::Ioc => TimeInForce::Ioc,
+        proto::TimeInForce::Fok => TimeInForce::Fok,
+        proto::TimeInForce::PostOnly => TimeInForce::PostOnly,
+    }
+}
+
+// Unset means the router's configured policy
+fn to_routing_policy(policy: proto::RoutingPolicy) -> Option<RoutingPolicy> {
+    match policy {
+        proto::RoutingPolicy::Configured => None,
+        proto::RoutingPolicy::BestPrice => Some(RoutingPolicy::BestPrice),
+        proto::RoutingPolicy::LowestFee => Some(RoutingPolicy::LowestFee),
+        proto::RoutingPolicy::VenuePriority => Some(RoutingPolicy::VenuePriority),
+    }
+}
+
+fn to_proto_status(status: VenueOrderStatus) -> proto::OrderStatus {
+    match status {
+        VenueOrderStatus::Open => proto::OrderStatus::Open,
+        VenueOrderStatus::PartiallyFilled => proto::OrderStatus::PartiallyFilled,
+        VenueOrderStatus::Filled => proto::OrderStatus::Filled,
+        VenueOrderStatus::Cancelled => proto::OrderStatus::Cancelled,
+        VenueOrderStatus::Rejected => proto::OrderStatus::Rejected,
+    }
+}
+
+fn to_proto_levels(levels: Vec<BookLevel>) -> Vec<proto::BookLevel> {
+    levels
+        .into_iter()
+        .map(|level| proto::BookLevel {
+            price: level.price.to_string(),
+            quantity: level.quantity.to_string(),
+        })
+        .collect()
+}
+
+impl From<Instrument> for proto::Instrument {
+    fn from(instrument: Instrument) -> Self {
+        proto::Instrument {
+            venue: instrument.venue.to_string(),
+            symbol: instrument.symbol,
+            tick_size: instrument.tick_size.to_string(),
+            step_size: instrument.step_size.to_string(),
+            min_size: instrument.min_size.to_string(),
+        }
+    }
+}
+
+fn to_proto_order(venue: Venue, order: VenueOrder) -> proto::VenueOrder {
+    proto::VenueOrder {
+        venue: venue.to_string(),
+        order_id: order.order_id,
+        instrument: order.instrument,
+        side: to_proto_side(order.side) as i32,
+        price: order.price.to_string(),
+        quantity: order.quantity.to_string(),
+        remaining: order.remaining.to_string(),
+        status: to_proto_status(order.status) as i32,
+    }
+}
+
+fn to_proto_position(venue: Venue, position: VenuePosition) -> proto::Position {
+    proto::Position {
+        venue: venue.to_string(),
+        instrument: position.instrument,
+        amount: position.amount.to_string(),
+        entry_price: position.entry_price.map(|p| p.to_string()),
+    }
+}
+
+impl From<MarketEvent> for proto::MarketEvent {
+    fn from(event: MarketEvent) -> Self {
+        let venue = event.venue().to_string();
+        let instrument = event.instrument().to_string();
+        let (timestamp_ms, event) = match event {
+            MarketEvent::Quote {
+                bid_price,
+                bid_quantity,
+                ask_price,
+                ask_quantity,
+                timestamp_ms,
+                ..
+            } => (
+                timestamp_ms,
+                Event::Quote(proto::Quote {
+                    bid_price: bid_price.to_string(),
+                    bid_quantity: bid_quantity.to_string(),
+                    ask_price: ask_price.to_string(),
+                    ask_quantity: ask_quantity.to_string(),
+                }),
+            ),
+            MarketEvent::Trade {
+                price,
+                quantity,
+                taker_side,
+                timestamp_ms,
+                ..
+            } => (
+                timestamp_ms,
+                Event::Trade(proto::Trade {
+                    price: price.to_string(),
+                    quantity: quantity.to_string(),
+                    taker_side: to_proto_side(taker_side) as i32,
+                }),
+            ),
+            MarketEvent::BookUpdate {
+                bids,
+                asks,
+                snapshot,
+                timestamp_ms,
+                ..
+            } => (
+                timestamp_ms,
+                Event::BookUpdate(proto::BookUpdate {
+                    bids: to_proto_levels(bids),
+                    asks: to_proto_levels(asks),
+                    snapshot,
+                }),
+            ),
+            MarketEvent::Fill {
+                order_id,
+                side,
+                price,
+                quantity,
+                fee,
+                timestamp_ms,
+                ..
+            } => (
+                timestamp_ms,
+                Event::Fill(proto::Fill {
+                    order_id,
+                    side: to_proto_side(side) as i32,
+                    price: price.to_string(),
+                    quantity: quantity.to_string(),
+                    fee: fee.to_string(),
+                }),
+            ),
+            MarketEvent::OrderUpdate {
+                order_id,
+                status,
+                remaining,
+                timestamp_ms,
+                ..
+            } => (
+                timestamp_ms,
+                Event::OrderUpdate(proto::OrderUpdate {
+                    order_id,
+                    status: to_proto_status(status) as i32,
+                    remaining: remaining.to_string(),
+                }),
+            ),
+        };
+        proto::MarketEvent {
+            venue,
+            instrument,
+            timestamp_ms,
+            event: Some(event),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl TradingService for TradingGateway {
+    type StreamMarketEventsStream =
+        Pin<Box<dyn Stream<Item = Result<proto::MarketEvent, Status>> + Send + 'static>>;
+
+    async fn initiate_connection(
+        &self,
+        request: Request<ConnectionRequest>,
+    ) -> Result<Response<ConnectionResponse>, Status> {
+        let connection_request = request.into_inner();
+        let venue = parse_venue(connection_request.venue.as_deref())?;
+        let connector = self.connectors.get(venue)?;
+
+        let response = match connector.connect().await {
+            Ok(()) => ConnectionResponse {
+                success: true,
+                message: format!("Connection initiated and {} subscription started", venue),
+            },
+            Err(e) => ConnectionResponse {
+                success: false,
+                message: format!("Failed to start {} subscription: {}", venue, e),
+            },
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn place_order(
+        &self,
+        request: Request<PlaceOrderRequest>,
+    ) -> Result<Response<PlaceOrderResponse>, Status> {
+        let place_request = request.into_inner();
+        let venue = parse_venue(place_request.venue.as_deref())?;
+        let order = OrderRequest {
+            side: parse_side(place_request.side())?,
+            time_in_force: to_time_in_force(place_request.time_in_force()),
+            price: parse_decimal("price", &place_request.price)?,
+            quantity: parse_decimal("quantity", &place_request.quantity)?,
+            instrument: place_request.instrument,
+            client_order_id: place_request.client_order_id,
+        };
+
+        let ack = self.connectors.get(venue)?.place_order(&order).await?;
+        Ok(Response::new(PlaceOrderResponse {
+            venue: venue.to_string(),
+            order_id: ack.order_id,
+            local_id: ack.local_id,
+            client_order_id: ack.client_order_id,
+        }))
+    }
+
+    async fn cancel_order(
+        &self,
+        request: Request<CancelOrderRequest>,
+    ) -> Result<Response<CancelResponse>, Status> {
+        let cancel_request = request.into_inner();
+        let venue = parse_venue(cancel_request.venue.as_deref())?;
+        let cancel = CancelRequest {
+            instrument: cancel_request.instrument,
+            order_id: cancel_request.order_id,
+        };
+
+        self.connectors.get(venue)?.cancel_order(&cancel).await?;
+        Ok(Response::new(CancelResponse {}))
+    }
+
+    async fn cancel_all(
+        &self,
+        request: Request<CancelAllRequest>,
+    ) -> Result<Response<CancelResponse>, Status> {
+        let cancel_request = request.into_inner();
+        let venue = parse_venue(cancel_request.venue.as_deref())?;
+
+        self.connectors
+            .get(venue)?
+            .cancel_all(cancel_request.instrument.as_deref())
+            .await?;
+        Ok(Response::new(CancelResponse {}))
+    }
+
+    async fn list_instruments(
+        &self,
+        request: Request<ListInstrumentsRequest>,
+    ) -> Result<Response<ListInstrumentsResponse>, Status> {
+        let list_request = request.into_inner();
+        let mut instruments = Vec::new();
+        for connector in self.connectors_for(list_request.venue.as_deref())? {
+            instruments.extend(
+                connector
+                    .instruments()
+                    .await?
+                    .into_iter()
+                    .map(proto::Instrument::from),
+            );
+        }
+        Ok(Response::new(ListInstrumentsResponse { instruments }))
+    }
+
+    async fn list_open_orders(
+        &self,
+        request: Request<ListOpenOrdersRequest>,
+    ) -> Result<Response<ListOpenOrdersResponse>, Status> {
+        let list_request = request.into_inner();
+        let mut orders = Vec::new();
+        for connector in self.connectors_for(list_request.venue.as_deref())? {
+            let venue = connector.venue();
+            orders.extend(
+                connector
+                    .open_orders(list_request.instrument.as_deref())
+                    .await?
+                    .into_iter()
+                    .map(|order| to_proto_order(venue, order)),
+            );
+        }
+        Ok(Response::new(ListOpenOrdersResponse { orders }))
+    }
+
+    async fn list_positions(
+        &self,
+        request: Request<ListPositionsRequest>,
+    ) -> Result<Response<ListPositionsResponse>, Status> {
+        let list_request = request.into_inner();
+        let mut positions = Vec::new();
+        for connector in self.connectors_for(list_request.venue.as_deref())? {
+            let venue = connector.venue();
+            positions.extend(
+                connector
+                    .positions()
+                    .await?
+                    .into_iter()
+                    .map(|position| to_proto_position(venue, position)),
+            );
+        }
+        Ok(Response::new(ListPositionsResponse { positions }))
+    }
+
+    async fn stream_market_events(
+        &self,
+        request: Request<StreamMarketEventsRequest>,
+    ) -> Result<Response<Self::StreamMarketEventsStream>, Status> {
+        let stream_request = request.into_inner();
+        let instrument = stream_request.instrument;
+        let streams = self
+            .connectors_for(stream_request.venue.as_deref())?
+            .into_iter()
+            .map(|connector| connector.market_events());
+
+        let events = stream::select_all(streams)
+            .filter(move |event| {
+                let keep = instrument
+                    .as_deref()
+                    .is_none_or(|instrument| event.instrument() == instrument);
+                async move { keep }
+            })
+            .map(proto::MarketEvent::from)
+            .map(Ok);
+        Ok(Response::new(Box::pin(events)))
+    }
+
+    async fn route_order(
+        &self,
+        request: Request<RouteOrderRequest>,
+    ) -> Result<Response<RouteOrderResponse>, Status> {
+        let route_request = request.into_inner();
+        let parent = ParentOrder {
+            side: parse_side(route_request.side())?,
+            time_in_force: to_time_in_force(route_request.time_in_force()),
+            policy: to_routing_policy(route_request.policy()),
+            quantity: parse_decimal("quantity", &route_request.quantity)?,
+            limit_price: route_request
+                .limit_price
+                .as_deref()
+                .map(|price| parse_decimal("limit_price", price))
+                .transpose()?,
+            instrument: route_request.instrument,
+        };
+
+        let result = self.router.route(&parent).await?;
+        let children = result
+            .children
+            .into_iter()
+            .map(|child_result| {
+                let child = child_result.child;
+                let (order_id, error) = match child_result.result {
+                    Ok(order_id) => (Some(order_id), None),
+                    Err(e) => (None, Some(e.to_string())),
+                };
+                proto::ChildOrder {
+                    venue: child.venue.to_string(),
+                    instrument: child.instrument,
+                    price: child.price.to_string(),
+                    quantity: child.quantity.to_string(),
+                    order_id,
+                    error,
+                }
+            })
+            .collect();
+        Ok(Response::new(RouteOrderResponse {
+            children,
+            unrouted_quantity: result.unrouted.to_string(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_without_a_venue_go_to_vertex() {
+        assert_eq!(parse_venue(None).unwrap(), Venue::Vertex);
+        assert_eq!(parse_venue(Some("dYdX")).unwrap(), Venue::Dydx);
+        assert!(matches!(
+            parse_venue(Some("binance")),
+            Err(ConnectorError::InvalidRequest(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_a_missing_side_and_malformed_numbers() {
+        assert_eq!(parse_side(proto::Side::Sell).unwrap(), Side::Sell);
+        assert!(matches!(
+            parse_side(proto::Side::Unspecified),
+            Err(ConnectorError::InvalidRequest(_))
+        ));
+        assert!(matches!(
+            parse_decimal("price", "1.2.3"),
+            Err(ConnectorError::InvalidRequest(_))
+        ));
+    }
+
+    #[test]
+    fn market_events_carry_their_venue_and_instrument() {
+        let event = proto::MarketEvent::from(MarketEvent::Fill {
+            venue: Venue::Orderly,
+            instrument: "PERP_ETH_USDC".to_string(),
+            order_id: "42".to_string(),
+            side: Side::Sell,
+            price: "3000.5".parse().unwrap(),
+            quantity: "0.25".parse().unwrap(),
+            fee: "0.1".parse().unwrap(),
+            timestamp_ms: 7,
+        });
+
+        assert_eq!(event.venue, "orderly");
+        assert_eq!(event.instrument, "PERP_ETH_USDC");
+        assert_eq!(event.timestamp_ms, 7);
+        let Some(Event::Fill(fill)) = event.event else {
+            panic!("expected a fill, got {:?}", event.event);
+        };
+        assert_eq!(fill.order_id, "42");
+        assert_eq!(fill.side, proto::Side::Sell as i32);
+        assert_eq!((fill.price.as_str(), fill.quantity.as_str()), ("3000.5", "0.25"));
+    }
+}
@@ -0,0 +1,243 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use futures::StreamExt;
+use log::warn;
+
+use crate::{
+    connectors::registry::ConnectorRegistry,
+    domain::{
+        models::trading::{Instrument, MarketEvent, OrderRequest, Side, TimeInForce, Venue},
+        routing::{
+            config::{RouterConfig, RoutingPolicy},
+            planner::{self, ChildOrder, VenueLiquidity},
+        },
+    },
+    shared::{errors::connector_error::ConnectorError, utils::decimal::Decimal},
+};
+
+// Touch of one venue's book as last streamed
+#[derive(Debug, Clone, Copy)]
+struct Touch {
+    bid_price: Decimal,
+    bid_quantity: Decimal,
+    ask_price: Decimal,
+    ask_quantity: Decimal,
+    received_at: Instant,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParentOrder {
+    // Router instrument name from the config, not a venue symbol
+    pub instrument: String,
+    pub side: Side,
+    pub quantity: Decimal,
+    // Venues whose touch is beyond this are skipped
+    pub limit_price: Option<Decimal>,
+    pub time_in_force: TimeInForce,
+    // Overrides the configured policy
+    pub policy: Option<RoutingPolicy>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChildResult {
+    pub child: ChildOrder,
+    // Venue order id, or why the venue refused the child
+    pub result: Result<String, ConnectorError>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RouteResult {
+    pub children: Vec<ChildResult>,
+    // Left over after planning plus whatever the venues refused
+    pub unrouted: Decimal,
+}
+
+/// Splits parent orders across venues from live top of book, fees and free margin.
+///
+/// Touches come from every connector's quote stream; instrument metadata is loaded on first
+/// use and kept for the life of the process.
+#[derive(Debug)]
+pub struct SmartOrderRouter {
+    connectors: Arc<ConnectorRegistry>,
+    config: RouterConfig,
+    touches: Arc<RwLock<HashMap<(Venue, String), Touch>>>,
+    instruments: RwLock<HashMap<(Venue, String), Instrument>>,
+}
+
+impl SmartOrderRouter {
+    pub fn new(connectors: Arc<ConnectorRegistry>, config: RouterConfig) -> Self {
+        SmartOrderRouter {
+            connectors,
+            config,
+            touches: Arc::new(RwLock::new(HashMap::new())),
+            instruments: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Keeps the touch of every venue and instrument current from the connectors' quotes.
+    pub fn spawn_quote_listener(&self) {
+        for venue in self.connectors.venues() {
+            let Ok(connector) = self.connectors.get(venue) else {
+                continue;
+            };
+            let touches = Arc::clone(&self.touches);
+            tokio::spawn(async move {
+                let mut events = connector.market_events();
+                while let Some(event) = events.next().await {
+                    if let MarketEvent::Quote {
+                        venue,
+                        instrument,
+                        bid_price,
+                        bid_quantity,
+                        ask_price,
+                        ask_quantity,
+                        ..
+                    } = event
+                    {
+                        let touch = Touch {
+                            bid_price,
+                            bid_quantity,
+                            ask_price,
+                            ask_quantity,
+                            received_at: Instant::now(),
+                        };
+                        touches.write().unwrap().insert((venue, instrument), touch);
+                    }
+                }
+                warn!("{} quote stream ended, router stops seeing its book", venue);
+            });
+        }
+    }
+
+    async fn instrument(
+        &self,
+        venue: Venue,
+        symbol: &str,
+    ) -> Result<Instrument, ConnectorError> {
+        let key = (venue, symbol.to_string());
+        if let Some(instrument) = self.instruments.read().unwrap().get(&key) {
+            return Ok(instrument.clone());
+        }
+
+        let loaded = self.connectors.get(venue)?.instruments().await?;
+        let mut instruments = self.instruments.write().unwrap();
+        for instrument in loaded {
+            instruments.insert((venue, instrument.symbol.clone()), instrument);
+        }
+        instruments
+            .get(&key)
+            .cloned()
+            .ok_or_else(|| ConnectorError::NotFound(format!("{} has no {}", venue, symbol)))
+    }
+
+    // What each configured venue offers against the parent side right now
+    async fn liquidity(
+        &self,
+        parent: &ParentOrder,
+    ) -> Result<Vec<VenueLiquidity>, ConnectorError> {
+        let symbols = self.config.instruments.get(&parent.instrument).ok_or_else(|| {
+            ConnectorError::NotFound(format!("No routes for {}", parent.instrument))
+        })?;
+        let max_age = Duration::from_millis(self.config.max_quote_age_ms);
+
+        let mut venues = Vec::new();
+        for (venue, symbol) in symbols {
+            let touch = self
+                .touches
+                .read()
+                .unwrap()
+                .get(&(*venue, symbol.clone()))
+                .copied()
+                .filter(|touch| touch.received_at.elapsed() <= max_age);
+            let Some(touch) = touch else {
+                continue;
+            };
+            let Ok(connector) = self.connectors.get(*venue) else {
+                continue;
+            };
+            let instrument = match self.instrument(*venue, symbol).await {
+                Ok(instrument) => instrument,
+                Err(e) => {
+                    warn!("Skipping {} for routing: {}", venue, e);
+                    continue;
+                }
+            };
+            // A venue that can't report margin is still routed to, uncapped
+            let available_margin = connector.available_margin().await.unwrap_or_else(|e| {
+                warn!("Failed to read {} margin: {}", venue, e);
+                None
+            });
+
+            let (price, quantity) = match parent.side {
+                Side::Buy => (touch.ask_price, touch.ask_quantity),
+                Side::Sell => (touch.bid_price, touch.bid_quantity),
+            };
+            venues.push(VenueLiquidity {
+                venue: *venue,
+                instrument: symbol.clone(),
+                price,
+                quantity,
+                step_size: instrument.step_size,
+                min_size: instrument.min_size,
+                available_margin,
+            });
+        }
+        Ok(venues)
+    }
+
+    /// Plan the split and send every child order to its venue concurrently.
+    pub async fn route(&self, parent: &ParentOrder) -> Result<RouteResult, ConnectorError> {
+        if parent.quantity <= Decimal::ZERO {
+            return Err(ConnectorError::InvalidRequest(
+                "Quantity must be positive".to_string(),
+            ));
+        }
+
+        // Without a live quote every child would be skipped; say so rather than route nothing
+        let venues = self.liquidity(parent).await?;
+        if venues.is_empty() {
+            return Err(ConnectorError::Rejected(format!(
+                "No venue has a fresh quote for {}; are the connectors connected?",
+                parent.instrument
+            )));
+        }
+        let plan = planner::plan(
+            &self.config,
+            parent.policy.unwrap_or(self.config.policy),
+            parent.side,
+            parent.quantity,
+            parent.limit_price,
+            venues,
+        );
+
+        let sends = plan.children.into_iter().map(|child| async move {
+            let order = OrderRequest {
+                instrument: child.instrument.clone(),
+                side: parent.side,
+                price: child.price,
+                quantity: child.quantity,
+                time_in_force: parent.time_in_force,
+                client_order_id: None,
+            };
+            let result = match self.connectors.get(child.venue) {
+                Ok(connector) => connector.place_order(&order).await.map(|ack| ack.order_id),
+                Err(e) => Err(e),
+            };
+            ChildResult { child, result }
+        });
+        let children = futures::future::join_all(sends).await;
+
+        // A child the venue refused is as unrouted as one that was never planned
+        let unrouted = children
+            .iter()
+            .filter(|c| c.result.is_err())
+            .try_fold(plan.unrouted, |acc, c| acc.checked_add(c.child.quantity))
+            .unwrap_or(parent.quantity);
+
+        Ok(RouteResult { children, unrouted })
+    }
+}
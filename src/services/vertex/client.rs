@@ -1,22 +1,32 @@
 use std::{sync::Arc, time::Duration};
-use tonic::{Request, Response, Status};
 
 use crate::{
     connectors::vertex::{gateway_client::GatewayClient, subscription_client::SubscriptionClient},
-    trading_service::{
-        trading_service_server::TradingService, ConnectionRequest, ConnectionResponse,
+    domain::{
+        oms::order_manager::OrderManager, positions::tracker::PositionTracker,
+        risk::engine::RiskEngine,
     },
+    services::vertex::{kill_switch::KillSwitch, paper::PaperExchange, registry::ProductRegistry},
+    storage::journal::Journal,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct VertexClient {
     // You might want to include shared state here
     pub subscription_client: Arc<SubscriptionClient>,
     pub gateway_client: Arc<GatewayClient>,
+    pub registry: Arc<ProductRegistry>,
+    pub risk_engine: Arc<RiskEngine>,
+    pub kill_switch: Arc<KillSwitch>,
+    pub order_manager: Arc<OrderManager>,
+    pub positions: Arc<PositionTracker>,
+    pub journal: Arc<Journal>,
+    // Set in paper-trading mode, where executes never reach the gateway
+    pub paper: Option<Arc<PaperExchange>>,
 }
 
 impl VertexClient {
-    async fn check_and_reconnect(vertex_client: Arc<SubscriptionClient>) {
+    pub async fn check_and_reconnect(vertex_client: Arc<SubscriptionClient>) {
         // Continuously check and reconnect in another task
         tokio::spawn(async move {
             loop {
@@ -26,37 +36,3 @@ impl VertexClient {
         });
     }
 }
-
-#[tonic::async_trait]
-impl TradingService for VertexClient {
-    async fn initiate_connection(
-        &self,
-        request: Request<ConnectionRequest>,
-    ) -> Result<Response<ConnectionResponse>, Status> {
-        println!("InitiateConnection request: {:?}", request);
-        // Call start_subscription here
-        match self.subscription_client.start_subscription().await {
-            Ok(_) => {
-                // Upon successful subscription, spawn the check_and_reconnect task
-                let subscription_client_clone = self.subscription_client.clone();
-                tokio::spawn(async move {
-                    VertexClient::check_and_reconnect(subscription_client_clone).await;
-                });
-
-                let response = ConnectionResponse {
-                    success: true,
-                    message: "Connection initiated and subscription started".into(),
-                };
-                Ok(Response::new(response))
-            }
-            Err(e) => {
-                // Handle subscription failure
-                let response = ConnectionResponse {
-                    success: false,
-                    message: format!("Failed to start subscription: {}", e),
-                };
-                Ok(Response::new(response))
-            }
-        }
-    }
-}
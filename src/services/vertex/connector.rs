@@ -0,0 +1,404 @@
+use std::sync::Arc;
+
+use futures::stream;
+use log::warn;
+use tokio::sync::broadcast::error::RecvError;
+use tonic::Request;
+
+use crate::{
+    config::CONFIG,
+    connectors::connector::{Connector, MarketEventStream},
+    domain::models::{
+        trading::{
+            BookLevel, CancelRequest, Instrument, MarketEvent, OrderAck, OrderRequest, Side,
+            TimeInForce, Venue, VenueOrder, VenueOrderStatus, VenuePosition,
+        },
+        vertex::{
+            stream_events::{OrderUpdateReason, StreamEvent},
+            subaccount::{
+                SubaccountInfo, SubaccountInfoQuery, SubaccountOrders, SubaccountOrdersQuery,
+            },
+        },
+    },
+    services::vertex::registry::ProductRegistry,
+    shared::{
+        errors::connector_error::ConnectorError,
+        utils::{decimal::Decimal, type_conv},
+    },
+    vertex_execute::{
+        vertex_execute_service_server::VertexExecuteService, CancelAndPlaceRequest,
+        CancelOrderRequest, Order, PlaceOrderRequest, PlaceOrderResponse,
+    },
+};
+
+use super::client::VertexClient;
+
+// Connector orders go through the default subaccount
+const DEFAULT_SUBACCOUNT: &str = "default";
+
+// Vertex order types carried in the top bits of the expiration
+fn order_type(time_in_force: TimeInForce) -> u32 {
+    match time_in_force {
+        TimeInForce::Gtc => 0,
+        TimeInForce::Ioc => 1,
+        TimeInForce::Fok => 2,
+        TimeInForce::PostOnly => 3,
+    }
+}
+
+// Exchange timestamps are unix nanoseconds
+fn timestamp_ms(nanos: &str) -> u64 {
+    nanos
+        .parse::<u128>()
+        .map(|n| (n / 1_000_000) as u64)
+        .unwrap_or_default()
+}
+
+fn x18_levels(levels: &[[String; 2]]) -> Vec<BookLevel> {
+    levels
+        .iter()
+        .filter_map(|[price, quantity]| {
+            Some(BookLevel {
+                price: Decimal::from_x18_str(price).ok()?,
+                quantity: Decimal::from_x18_str(quantity).ok()?,
+            })
+        })
+        .collect()
+}
+
+// Map a subscription event onto the venue-neutral model; events for unknown products are dropped
+pub fn to_market_event(registry: &ProductRegistry, event: StreamEvent) -> Option<MarketEvent> {
+    let instrument = registry.symbol_for_product(event.product_id())?.symbol;
+    let venue = Venue::Vertex;
+    let market_event = match event {
+        StreamEvent::BestBidOffer(bbo) => MarketEvent::Quote {
+            venue,
+            instrument,
+            bid_price: bbo.bid_price,
+            bid_quantity: bbo.bid_qty,
+            ask_price: bbo.ask_price,
+            ask_quantity: bbo.ask_qty,
+            timestamp_ms: timestamp_ms(&bbo.timestamp),
+        },
+        StreamEvent::Trade(trade) => MarketEvent::Trade {
+            venue,
+            instrument,
+            price: trade.price,
+            quantity: trade.taker_qty.abs(),
+            taker_side: if trade.is_taker_buyer {
+                Side::Buy
+            } else {
+                Side::Sell
+            },
+            timestamp_ms: timestamp_ms(&trade.timestamp),
+        },
+        StreamEvent::BookDepth(depth) => MarketEvent::BookUpdate {
+            venue,
+            instrument,
+            bids: x18_levels(&depth.bids),
+            asks: x18_levels(&depth.asks),
+            snapshot: false,
+            timestamp_ms: timestamp_ms(&depth.max_timestamp),
+        },
+        StreamEvent::Fill(fill) => MarketEvent::Fill {
+            venue,
+            instrument,
+            order_id: fill.order_digest.clone(),
+            side: if fill.is_bid { Side::Buy } else { Side::Sell },
+            price: fill.price,
+            quantity: fill.filled_qty.abs(),
+            fee: fill.fee,
+            timestamp_ms: timestamp_ms(&fill.timestamp),
+        },
+        StreamEvent::OrderUpdate(update) => MarketEvent::OrderUpdate {
+            venue,
+            instrument,
+            order_id: update.digest,
+            status: match update.reason {
+                OrderUpdateReason::Placed => VenueOrderStatus::Open,
+                OrderUpdateReason::Filled if update.amount.is_zero() => VenueOrderStatus::Filled,
+                OrderUpdateReason::Filled => VenueOrderStatus::PartiallyFilled,
+                OrderUpdateReason::Cancelled => VenueOrderStatus::Cancelled,
+            },
+            remaining: update.amount.abs(),
+            timestamp_ms: timestamp_ms(&update.timestamp),
+        },
+        StreamEvent::PositionChange(_) => return None,
+    };
+    Some(market_event)
+}
+
+fn place_request(order: &OrderRequest) -> PlaceOrderRequest {
+    PlaceOrderRequest {
+        order: Some(Order {
+            sender: CONFIG.sender_address.clone(),
+            price: Some(order.price.to_string()),
+            size: Some(order.side.signed(order.quantity).to_string()),
+            ..Default::default()
+        }),
+        symbol: Some(order.instrument.clone()),
+        order_type: Some(order_type(order.time_in_force)),
+        // Vertex echoes numeric client ids back on the response
+        id: order
+            .client_order_id
+            .as_deref()
+            .and_then(|id| id.parse().ok()),
+        ..Default::default()
+    }
+}
+
+fn order_ack(
+    response: PlaceOrderResponse,
+    order: &OrderRequest,
+) -> Result<OrderAck, ConnectorError> {
+    match response.data {
+        Some(data) if response.status == "success" => Ok(OrderAck {
+            order_id: data.digest,
+            local_id: Some(response.order_id),
+            client_order_id: order.client_order_id.clone(),
+        }),
+        _ => Err(ConnectorError::Rejected(
+            response.error.unwrap_or(response.status),
+        )),
+    }
+}
+
+impl VertexClient {
+    fn default_sender(&self) -> String {
+        type_conv::subaccount_sender_hex(&CONFIG.sender_address, DEFAULT_SUBACCOUNT)
+    }
+
+    fn product_id_for(&self, instrument: &str) -> Result<u32, ConnectorError> {
+        self.registry
+            .product_id_for_symbol(instrument)
+            .ok_or_else(|| {
+                ConnectorError::InvalidRequest(format!("Unknown symbol: {}", instrument))
+            })
+    }
+
+    // One instrument, or every product when none is given
+    fn product_ids_for(&self, instrument: Option<&str>) -> Result<Vec<u32>, ConnectorError> {
+        match instrument {
+            Some(instrument) => Ok(vec![self.product_id_for(instrument)?]),
+            None => Ok(self.registry.product_ids()),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Connector for VertexClient {
+    fn venue(&self) -> Venue {
+        Venue::Vertex
+    }
+
+    // The execute path checks every order against the subaccount's limits
+    fn checks_risk(&self) -> bool {
+        true
+    }
+
+    async fn connect(&self) -> Result<(), ConnectorError> {
+        let product_ids = self.registry.product_ids();
+        self.subscription_client
+            .start_subscription(&product_ids)
+            .await
+            .map_err(|e| {
+                ConnectorError::Unavailable(format!("Failed to start subscription: {}", e))
+            })?;
+
+        // Upon successful subscription, spawn the check_and_reconnect task
+        let subscription_client_clone = self.subscription_client.clone();
+        tokio::spawn(async move {
+            VertexClient::check_and_reconnect(subscription_client_clone).await;
+        });
+        Ok(())
+    }
+
+    async fn place_order(&self, order: &OrderRequest) -> Result<OrderAck, ConnectorError> {
+        let response = VertexExecuteService::place_order(self, Request::new(place_request(order)))
+            .await?
+            .into_inner();
+        order_ack(response, order)
+    }
+
+    // One signed cancel_and_place, so the old quote and the new one are never both resting
+    async fn cancel_and_place(
+        &self,
+        cancel: &CancelRequest,
+        order: &OrderRequest,
+    ) -> Result<OrderAck, ConnectorError> {
+        let product_id = self.product_id_for(&cancel.instrument)?;
+        let sender = type_conv::hex_to_fixed_bytes32(&self.default_sender())
+            .map_err(|e| ConnectorError::Internal(format!("Invalid sender: {}", e)))?;
+        let digest = type_conv::hex_to_fixed_bytes32(&cancel.order_id)
+            .map_err(|e| ConnectorError::InvalidRequest(format!("Invalid order id: {}", e)))?;
+
+        let request = CancelAndPlaceRequest {
+            cancel_order_request: Some(CancelOrderRequest {
+                product_ids: vec![product_id],
+                digests: vec![digest.to_vec()],
+                sender: sender.to_vec(),
+                ..Default::default()
+            }),
+            place_order_request: Some(place_request(order)),
+        };
+        let response = VertexExecuteService::cancel_and_place(self, Request::new(request))
+            .await?
+            .into_inner();
+        order_ack(response, order)
+    }
+
+    async fn cancel_order(&self, cancel: &CancelRequest) -> Result<(), ConnectorError> {
+        let product_id = self.product_id_for(&cancel.instrument)?;
+        let sender = type_conv::hex_to_fixed_bytes32(&self.default_sender())
+            .map_err(|e| ConnectorError::Internal(format!("Invalid sender: {}", e)))?;
+        let digest = type_conv::hex_to_fixed_bytes32(&cancel.order_id)
+            .map_err(|e| ConnectorError::InvalidRequest(format!("Invalid order id: {}", e)))?;
+
+        let response = self
+            .cancel_orders(sender, vec![product_id], vec![digest])
+            .await?;
+        if response.status != "success" {
+            return Err(ConnectorError::Rejected(response.status));
+        }
+        Ok(())
+    }
+
+    async fn cancel_all(&self, instrument: Option<&str>) -> Result<(), ConnectorError> {
+        let product_ids = self.product_ids_for(instrument)?;
+        let response = self
+            .cancel_product_orders(&self.default_sender(), product_ids)
+            .await?;
+        if response.status != "success" {
+            return Err(ConnectorError::Rejected(response.status));
+        }
+        Ok(())
+    }
+
+    async fn instruments(&self) -> Result<Vec<Instrument>, ConnectorError> {
+        let x18 = |value: &str| Decimal::from_x18_str(value).unwrap_or_default();
+        Ok(self
+            .registry
+            .symbols()
+            .into_iter()
+            .map(|symbol| Instrument {
+                venue: Venue::Vertex,
+                tick_size: x18(&symbol.price_increment_x18),
+                step_size: x18(&symbol.size_increment),
+                min_size: x18(&symbol.min_size),
+                symbol: symbol.symbol,
+            })
+            .collect())
+    }
+
+    async fn open_orders(
+        &self,
+        instrument: Option<&str>,
+    ) -> Result<Vec<VenueOrder>, ConnectorError> {
+        let sender = self.default_sender();
+        if let Some(paper) = &self.paper {
+            return Ok(paper.open_orders(&sender, &self.product_ids_for(instrument)?));
+        }
+        let mut open_orders = Vec::new();
+        for product_id in self.product_ids_for(instrument)? {
+            let Some(symbol) = self.registry.symbol_for_product(product_id) else {
+                continue;
+            };
+            let orders: SubaccountOrders = self
+                .query_gateway(&SubaccountOrdersQuery::new(&sender, product_id))
+                .await?;
+            for order in orders.orders {
+                let partially_filled = order.unfilled_amount != order.amount;
+                open_orders.push(VenueOrder {
+                    order_id: order.digest,
+                    instrument: symbol.symbol.clone(),
+                    side: Side::of(order.amount),
+                    price: order.price_x18,
+                    quantity: order.amount.abs(),
+                    remaining: order.unfilled_amount.abs(),
+                    status: if partially_filled {
+                        VenueOrderStatus::PartiallyFilled
+                    } else {
+                        VenueOrderStatus::Open
+                    },
+                });
+            }
+        }
+        Ok(open_orders)
+    }
+
+    async fn positions(&self) -> Result<Vec<VenuePosition>, ConnectorError> {
+        // Paper positions only exist in the tracker, built from the simulated fills
+        if self.paper.is_some() {
+            return Ok(self
+                .positions
+                .list(Some(&self.default_sender()), None)
+                .into_iter()
+                .filter(|position| !position.amount.is_zero())
+                .filter_map(|position| {
+                    let symbol = self.registry.symbol_for_product(position.product_id)?;
+                    Some(VenuePosition {
+                        instrument: symbol.symbol,
+                        amount: position.amount,
+                        entry_price: Some(position.avg_entry_price)
+                            .filter(|price| !price.is_zero()),
+                    })
+                })
+                .collect());
+        }
+        let info: SubaccountInfo = self
+            .query_gateway(&SubaccountInfoQuery::new(&self.default_sender()))
+            .await?;
+
+        Ok(info
+            .balances()
+            .filter(|b| !b.balance.amount.is_zero())
+            .filter_map(|b| {
+                let symbol = self.registry.symbol_for_product(b.product_id)?;
+                // Perp entry price is implied by the quote balance; spot balances carry none
+                let entry_price = Decimal::ZERO
+                    .checked_sub(b.balance.v_quote_balance)
+                    .and_then(|cost| cost.checked_div(b.balance.amount))
+                    .filter(|price| !price.is_zero());
+                Some(VenuePosition {
+                    instrument: symbol.symbol,
+                    amount: b.balance.amount,
+                    entry_price,
+                })
+            })
+            .collect())
+    }
+
+    async fn available_margin(&self) -> Result<Option<Decimal>, ConnectorError> {
+        // The simulator keeps no collateral
+        if self.paper.is_some() {
+            return Ok(None);
+        }
+        let info: SubaccountInfo = self
+            .query_gateway(&SubaccountInfoQuery::new(&self.default_sender()))
+            .await?;
+        Ok(info.initial_health())
+    }
+
+    fn market_events(&self) -> MarketEventStream {
+        let events = self.subscription_client.subscribe_events();
+        let registry = Arc::clone(&self.registry);
+        Box::pin(stream::unfold(
+            (events, registry),
+            |(mut events, registry)| async move {
+                loop {
+                    match events.recv().await {
+                        Ok(event) => {
+                            if let Some(market_event) = to_market_event(&registry, event) {
+                                return Some((market_event, (events, registry)));
+                            }
+                        }
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("Vertex market event stream lagged, skipped {}", skipped)
+                        }
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            },
+        ))
+    }
+}
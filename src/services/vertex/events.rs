@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use log::{info, warn};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    domain::models::vertex::stream_events::{OrderUpdateReason, StreamEvent},
+    storage::journal::JournalEntry,
+};
+
+use super::client::VertexClient;
+
+impl VertexClient {
+    /// Feeds subscription events into the order manager and the risk engine.
+    pub fn spawn_event_pump(self: &Arc<Self>) {
+        let client = Arc::clone(self);
+        let mut events = client.subscription_client.subscribe_events();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        client.journal_stream_event(&event);
+                        client.apply_stream_event(&event);
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Event pump lagged, skipped {} stream events", skipped)
+                    }
+                    Err(RecvError::Closed) => {
+                        info!("Subscription event channel closed, stopping event pump");
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    // Only order lifecycle and position events are journaled, market data is not
+    fn journal_stream_event(&self, event: &StreamEvent) {
+        match event {
+            StreamEvent::OrderUpdate(update) => {
+                self.journal.append(JournalEntry::OrderUpdate(update.clone()))
+            }
+            StreamEvent::Fill(fill) => self.journal.append(JournalEntry::Fill(fill.clone())),
+            StreamEvent::PositionChange(change) => self
+                .journal
+                .append(JournalEntry::PositionChange(change.clone())),
+            _ => {}
+        }
+    }
+
+    pub fn apply_stream_event(&self, event: &StreamEvent) {
+        match event {
+            StreamEvent::OrderUpdate(update) => {
+                self.order_manager.on_order_update(update);
+                if update.reason == OrderUpdateReason::Cancelled || update.amount.is_zero() {
+                    if let Some(order) = self.order_manager.get_by_digest(&update.digest) {
+                        self.risk_engine
+                            .on_order_closed(&order.sender, &update.digest);
+                    }
+                }
+            }
+            StreamEvent::Fill(fill) => {
+                self.order_manager.on_fill(fill);
+                self.risk_engine.on_fill(
+                    &fill.subaccount,
+                    &fill.order_digest,
+                    fill.product_id,
+                    fill.signed_qty(),
+                );
+                let realized = self.positions.on_fill(
+                    &fill.subaccount,
+                    fill.product_id,
+                    fill.signed_qty(),
+                    fill.price,
+                    fill.fee,
+                );
+                self.risk_engine.record_realized_pnl(
+                    &fill.subaccount,
+                    realized,
+                    fill.timestamp_secs(),
+                );
+            }
+            StreamEvent::PositionChange(change) if !change.is_lp => {
+                self.positions.on_position_change(
+                    &change.subaccount,
+                    change.product_id,
+                    change.amount,
+                    change.v_quote_amount,
+                );
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,168 +1,422 @@
 use alloy_primitives::FixedBytes;
-use log::{error, info};
+use log::{error, info, warn};
 use serde_json::json;
 use tonic::{Request, Response, Status};
 
 use crate::{
+    config::CONFIG,
     connectors::vertex::payload_signer::Signer,
-    domain::models::vertex::sol_structs::Order,
-    services::vertex::helper::VertexHelper,
-    shared::utils::type_conv::{self, fixed_bytes_to_hex, vec_to_fixed_bytes32},
+    domain::{
+        models::vertex::sol_structs::{Cancellation, CancellationProducts, Order},
+        risk::engine::OrderCheck,
+    },
+    services::vertex::{helper::VertexHelper, validation::validate_order},
+    storage::journal::JournalEntry,
+    shared::{
+        errors::{connect_error::ConnectError, connector_error::ConnectorError},
+        utils::{
+            decimal::Decimal,
+            type_conv::{self, fixed_bytes_to_hex, vec_to_fixed_bytes32},
+        },
+    },
     vertex_execute::{
         vertex_execute_service_server::VertexExecuteService, CancelAllForProductRequest,
-        CancelAndPlaceRequest, CancelOrderRequest, CancelOrderResponse, PlaceOrderRequest,
+        CancelAndPlaceRequest, CancelOrderRequest, CancelOrderResponse, HeartbeatRequest,
+        HeartbeatResponse, KillSwitchRequest, KillSwitchResponse, PlaceOrderRequest,
         PlaceOrderResponse,
     },
 };
 
 use super::client::VertexClient;
 
-#[tonic::async_trait]
-impl VertexExecuteService for VertexClient {
-    // places order on vertex
-    async fn place_order(
+// The top two bits of an order expiration carry the order type
+pub const EXPIRATION_MASK: u64 = (1 << 62) - 1;
+
+// Prefer the human decimal field and fall back to the raw X18 integer string
+fn x18_field(decimal: Option<&str>, raw_x18: &str, name: &str) -> Result<i128, ConnectorError> {
+    match decimal {
+        Some(value) => value
+            .parse::<Decimal>()
+            .map(Decimal::to_x18)
+            .map_err(|e| ConnectorError::InvalidRequest(format!("Invalid {}: {}", name, e))),
+        None => type_conv::string_and_i128(raw_x18).map_err(|e| {
+            ConnectorError::InvalidRequest(format!("Invalid {} X18 value: {}", name, e))
+        }),
+    }
+}
+
+impl VertexClient {
+    // Signer for cancellations, which verify against the endpoint contract
+    fn cancel_signer(&self) -> Signer {
+        Signer::new(self.registry.endpoint_addr())
+    }
+
+    // Executes go to the gateway, or to the simulator in paper-trading mode
+    async fn send_execute(&self, payload: String) -> Result<String, ConnectError> {
+        match &self.paper {
+            Some(paper) => Ok(paper.execute(&payload)),
+            None => self.gateway_client.send_message(payload).await,
+        }
+    }
+
+    // Signed cancellation tx of specific orders, with the sender and digests as hex
+    fn sign_cancellation(
         &self,
-        request: Request<PlaceOrderRequest>,
-    ) -> Result<Response<PlaceOrderResponse>, Status> {
-        let place_order_request = request.into_inner();
+        sender: FixedBytes<32>,
+        product_ids: Vec<u32>,
+        digests: Vec<FixedBytes<32>>,
+    ) -> (serde_json::Value, String, String, Vec<String>) {
+        let cancellation = Cancellation {
+            sender,
+            productIds: product_ids,
+            digests,
+            nonce: self.generate_nonce(),
+        };
+        let signature = self
+            .cancel_signer()
+            .sign_cancel_orders_payload(&cancellation);
+
+        let sender_hex = format!("0x{}", fixed_bytes_to_hex(&cancellation.sender));
+        let digests_hex: Vec<String> = cancellation
+            .digests
+            .iter()
+            .map(|digest| format!("0x{}", fixed_bytes_to_hex(digest)))
+            .collect();
+        let tx = json!({
+            "sender": sender_hex,
+            "productIds": cancellation.productIds,
+            "digests": digests_hex,
+            "nonce": cancellation.nonce.to_string()
+        });
+        (tx, signature, sender_hex, digests_hex)
+    }
+
+    // Book-keeping once the gateway confirmed a cancel of specific orders
+    fn on_orders_cancelled(&self, sender_hex: &str, digests_hex: &[String]) {
+        self.journal.append(JournalEntry::OrdersCancelled {
+            sender: sender_hex.to_string(),
+            digests: digests_hex.to_vec(),
+        });
+        for digest in digests_hex {
+            self.order_manager.on_cancelled(digest);
+            self.risk_engine.on_order_closed(sender_hex, digest);
+        }
+    }
+
+    fn reject_order(&self, order_id: u64, sender: &str, digest: &str, error: &str) {
+        self.risk_engine.on_order_closed(sender, digest);
+        self.journal.append(JournalEntry::OrderRejected {
+            order_id,
+            error: error.to_string(),
+        });
+        self.order_manager.on_rejected(order_id, error);
+    }
+
+    // Signed cancel of specific orders by digest
+    pub async fn cancel_orders(
+        &self,
+        sender: FixedBytes<32>,
+        product_ids: Vec<u32>,
+        digests: Vec<FixedBytes<32>>,
+    ) -> Result<CancelOrderResponse, Status> {
+        let (tx, signature, sender_hex, digests_hex) =
+            self.sign_cancellation(sender, product_ids, digests);
+        let cancel_payload = json!({
+            "cancel_orders": {
+                "tx": tx,
+                "signature": signature
+            }
+        });
+
+        let response = self.send_cancel("cancel_orders", cancel_payload).await?;
+        self.on_orders_cancelled(&sender_hex, &digests_hex);
+        Ok(response)
+    }
+
+    // Signed cancel of every resting order of `sender` on the given products
+    pub async fn cancel_product_orders(
+        &self,
+        sender: &str,
+        product_ids: Vec<u32>,
+    ) -> Result<CancelOrderResponse, Status> {
+        let cancellation = CancellationProducts {
+            sender: type_conv::hex_to_fixed_bytes32(sender)
+                .map_err(|e| Status::invalid_argument(format!("Invalid sender: {}", e)))?,
+            productIds: product_ids,
+            nonce: self.generate_nonce(),
+        };
+        let signature = self
+            .cancel_signer()
+            .sign_cancel_product_orders_payload(&cancellation);
+
+        let cancel_all_payload = json!({
+            "cancel_product_orders": {
+                "tx": {
+                    "sender": sender,
+                    "productIds": cancellation.productIds,
+                    "nonce": cancellation.nonce.to_string()
+                },
+                "signature": signature,
+                "digest": null
+            }
+        });
+
+        let response = self
+            .send_cancel("cancel_product_orders", cancel_all_payload)
+            .await?;
+        self.journal.append(JournalEntry::ProductsCancelled {
+            sender: sender.to_string(),
+            product_ids: cancellation.productIds.clone(),
+        });
+        self.order_manager
+            .on_products_cancelled(sender, &cancellation.productIds);
+        self.risk_engine
+            .on_products_cancelled(sender, &cancellation.productIds);
+        Ok(response)
+    }
+
+    // Kill switch, book rules and pre-trade risk, then a signed place_order. With
+    // `cancel_order_request` the place goes out as one cancel_and_place.
+    async fn submit_order(
+        &self,
+        place_order_request: PlaceOrderRequest,
+        cancel_order_request: Option<CancelOrderRequest>,
+    ) -> Result<PlaceOrderResponse, Status> {
+        if self.kill_switch.is_engaged() {
+            return Err(Status::unavailable(format!(
+                "Kill switch engaged: {}",
+                self.kill_switch.reason()
+            )));
+        }
+
+        // The cancel leg is signed up front so a bad digest fails before anything is reserved
+        let cancel = match cancel_order_request {
+            Some(cancel_order_request) => {
+                let sender = vec_to_fixed_bytes32(cancel_order_request.sender)
+                    .map_err(|e| Status::invalid_argument(format!("Invalid sender: {}", e)))?;
+                let digests: Vec<FixedBytes<32>> = cancel_order_request
+                    .digests
+                    .into_iter()
+                    .map(vec_to_fixed_bytes32)
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| Status::invalid_argument(format!("Invalid digest: {}", e)))?;
+                Some(self.sign_cancellation(sender, cancel_order_request.product_ids, digests))
+            }
+            None => None,
+        };
+
+        let product_id = self.resolve_product_id(&place_order_request)?;
         let order_request = place_order_request
             .order
             .ok_or_else(|| Status::invalid_argument("Order is missing in the request"))?;
 
-        const DEFAULT_SUBACCOUNT: &str = "64656661756c740000000000";
-        let sender_full_hex = format!(
-            "{:0<64}",
-            format!(
-                "{}{}",
-                &order_request.sender,
-                DEFAULT_SUBACCOUNT // default subaccount - replace when we create our own subacc
-            )
-        );
+        // default subaccount - replace when we create our own subacc
+        let sender_full_hex = type_conv::subaccount_sender_hex(&order_request.sender, "default");
+        let sender = type_conv::hex_to_bytes(sender_full_hex.trim_start_matches("0x"))
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| vec_to_fixed_bytes32(bytes).map_err(str::to_string))
+            .map_err(|e| {
+                Status::invalid_argument(format!("Invalid sender {}: {}", order_request.sender, e))
+            })?;
 
-        // Multiply with 1e18 to real life pricing standard. Src: vertex doc
-        let price_x18 = type_conv::string_and_i128(&order_request.price_x18);
-        let amount_x18 = type_conv::string_and_i128(&order_request.amount);
+        // Decimal prices/sizes are scaled by 1e18 into the X18 integers vertex expects. Src: vertex doc
+        let price_x18 = x18_field(order_request.price.as_deref(), &order_request.price_x18, "price")?;
+        let amount_x18 = x18_field(order_request.size.as_deref(), &order_request.amount, "size")?;
 
-        let clean_sender_hex = sender_full_hex.trim_start_matches("0x");
-        let address_bytes = type_conv::hex_to_bytes(&clean_sender_hex);
-        let expiration_time = self.generate_expiration_time(1000, 0);
+        // Reject (or round) against the cached book rules before paying for a signature and round-trip
+        let symbol = self.registry.symbol_for_product(product_id).ok_or_else(|| {
+            Status::unavailable(format!("No symbol metadata for product {}", product_id))
+        })?;
+        let validated = validate_order(
+            &symbol,
+            price_x18,
+            amount_x18,
+            CONFIG.order_rounding_policy,
+        )?;
+
+        // Pre-trade risk, with the oracle price as the reference for the price band
+        let reference_price = self
+            .registry
+            .product(product_id)
+            .and_then(|p| Decimal::from_x18_str(&p.oracle_price_x18).ok());
+        let order_type = place_order_request.order_type.unwrap_or(0);
+        if order_type > 3 {
+            return Err(Status::invalid_argument(format!(
+                "Invalid order type: {}",
+                order_type
+            )));
+        }
+        let ordr_addrs = self.get_contract_addr(product_id).ok_or_else(|| {
+            Status::not_found(format!("No book contract known for product {}", product_id))
+        })?;
+        // Exposure stays reserved from here until the gateway answers
+        let reservation = self
+            .risk_engine
+            .check_order(&OrderCheck {
+                sender: &sender_full_hex,
+                product_id,
+                instrument: None,
+                price: Decimal::from_x18(validated.price_x18),
+                amount: Decimal::from_x18(validated.amount_x18),
+                reference_price,
+            })
+            .map_err(|v| Status::failed_precondition(format!("Risk check failed: {}", v)))?;
+
+        let expiration_time = self.generate_expiration_time(1000, order_type as u8);
 
         // Construct the Order struct from the request to Order Request from alloy Sol
         let order = Order {
-            sender: vec_to_fixed_bytes32(address_bytes).unwrap(),
-            priceX18: price_x18,
-            amount: amount_x18,
+            sender,
+            priceX18: validated.price_x18,
+            amount: validated.amount_x18,
             expiration: expiration_time,
             nonce: self.generate_nonce(),
         };
 
-        let ordr_addrs: Option<String> =
-            self.get_contract_addr(place_order_request.product_id).await;
-
         // With Verifying Contract
-        let signer = Signer::new(ordr_addrs);
+        let signer = Signer::new(Some(ordr_addrs));
         let signature = signer.sign_place_order_payload(&order);
+        let digest = signer.order_digest(&order);
 
-        let payload = json!({
-            "place_order": {
-                "product_id": place_order_request.product_id,
-                "order": {
-                    "sender": sender_full_hex, // Assuming sender is a String
-                    "priceX18": &order.priceX18.to_string(),
-                    "amount": &order.amount.to_string(),
-                    "expiration": &order.expiration.to_string(),
-                    "nonce": &order.nonce.to_string()
-                },
-                "signature": signature,
-                "id": place_order_request.id,
-            }
-        })
-        .to_string();
-
-        match self.gateway_client.send_message(payload).await {
-            Ok(response_data) => match serde_json::from_str::<PlaceOrderResponse>(&response_data) {
-                Ok(response) => Ok(Response::new(response)),
-                Err(e) => Err(Status::internal(format!(
-                    "Failed to parse gateway response: {}",
-                    e
-                ))),
+        let place = json!({
+            "product_id": product_id,
+            "order": {
+                "sender": sender_full_hex, // Assuming sender is a String
+                "priceX18": &order.priceX18.to_string(),
+                "amount": &order.amount.to_string(),
+                "expiration": &order.expiration.to_string(),
+                "nonce": &order.nonce.to_string()
             },
-            Err(e) => Err(Status::internal(format!(
-                "Failed to send order to gateway: {}",
-                e
-            ))),
-        }
-    }
-
-    async fn cancel_order(
-        &self,
-        request: Request<CancelOrderRequest>,
-    ) -> Result<Response<CancelOrderResponse>, Status> {
-        let cancel_order_request = request.into_inner();
+            "signature": signature,
+            "id": place_order_request.id,
+        });
+        // With a cancel leg both go out as one atomic cancel_and_place
+        let tx_type = if cancel.is_some() { "cancel_and_place" } else { "place_order" };
+        let payload = match &cancel {
+            Some((cancel_tx, cancel_signature, _, _)) => json!({
+                "cancel_and_place": {
+                    "cancel_tx": cancel_tx,
+                    "cancel_signature": cancel_signature,
+                    "place_order": place
+                }
+            }),
+            None => json!({ "place_order": place }),
+        };
 
-        let cancel_payload = json!({
-            "cancel_orders": {
-                "tx": {
-                    "sender": cancel_order_request.sender,
-                    "productIds": cancel_order_request.product_ids,
-                    "digests": cancel_order_request.digests,
-                    "nonce": self.generate_nonce()
-                },
-                "signature": "" // You'll need to generate a signature based on your protocol
-            }
+        // Track the order from here on, including rejects. The exposure moves to the digest
+        // before sending, so fills that beat the response find both the order and its exposure
+        let order_id = self.order_manager.create_pending(
+            &sender_full_hex,
+            product_id,
+            &digest,
+            Decimal::from_x18(order.priceX18),
+            Decimal::from_x18(order.amount),
+            order.expiration & EXPIRATION_MASK,
+        );
+        self.journal.append(JournalEntry::OrderCreated {
+            order_id,
+            sender: sender_full_hex.clone(),
+            product_id,
+            digest: Some(digest.clone()),
+            price: Decimal::from_x18(order.priceX18),
+            amount: Decimal::from_x18(order.amount),
+            expiration: order.expiration & EXPIRATION_MASK,
         });
+        self.risk_engine
+            .confirm_reservation(&sender_full_hex, reservation, &digest);
+        // Records are written in order, so once the signed tx is on disk the creation is too. An
+        // order the journal could not record is never sent: a restart would not know about it
+        if let Err(e) = self.journal_signed_tx(tx_type, Some(order_id), &payload).await {
+            error!("Failed to journal order {}: {}", order_id, e);
+            self.reject_order(order_id, &sender_full_hex, &digest, "journal write failed");
+            return Err(Status::unavailable(format!(
+                "Failed to journal order before sending: {}",
+                e
+            )));
+        }
 
-        let payload_str = cancel_payload.to_string();
-        match self.gateway_client.send_message(payload_str).await {
+        match self.send_execute(payload.to_string()).await {
             Ok(response_data) => {
-                // Log the raw response data for debugging
-                info!("Raw gateway response: {}", response_data);
-
-                match serde_json::from_str::<CancelOrderResponse>(&response_data) {
-                    Ok(response) => {
-                        info!("Order placed successfully");
-                        Ok(Response::new(response))
+                self.journal_gateway_response(tx_type, Some(order_id), &response_data);
+                match serde_json::from_str::<PlaceOrderResponse>(&response_data) {
+                    Ok(mut response) => {
+                        response.order_id = order_id;
+                        match response.data.as_ref() {
+                            Some(data) if response.status == "success" => {
+                                self.journal.append(JournalEntry::OrderAccepted {
+                                    order_id,
+                                    digest: data.digest.clone(),
+                                });
+                                self.order_manager.on_accepted(order_id, &data.digest);
+                                if !data.digest.eq_ignore_ascii_case(&digest) {
+                                    warn!(
+                                        "Gateway digest {} differs from local digest {}",
+                                        data.digest, digest
+                                    );
+                                    self.risk_engine.on_order_closed(&sender_full_hex, &digest);
+                                    self.risk_engine.on_order_accepted(
+                                        &sender_full_hex,
+                                        &data.digest,
+                                        product_id,
+                                        Decimal::from_x18(order.amount),
+                                    );
+                                }
+                                if let Some((_, _, sender_hex, digests_hex)) = &cancel {
+                                    self.on_orders_cancelled(sender_hex, digests_hex);
+                                }
+                            }
+                            _ => self.reject_order(
+                                order_id,
+                                &sender_full_hex,
+                                &digest,
+                                response.error.as_deref().unwrap_or("rejected by gateway"),
+                            ),
+                        }
+                        Ok(response)
                     }
                     Err(e) => {
-                        error!("Failed to parse response: {}", e);
-                        Err(Status::internal("Failed to parse gateway response"))
+                        self.reject_order(
+                            order_id,
+                            &sender_full_hex,
+                            &digest,
+                            "unparseable gateway response",
+                        );
+                        Err(Status::internal(format!(
+                            "Failed to parse gateway response: {}",
+                            e
+                        )))
                     }
                 }
             }
             Err(e) => {
-                error!("Failed to send order to gateway: {}", e);
-                Err(Status::internal("Failed to send order to gateway"))
+                self.reject_order(order_id, &sender_full_hex, &digest, "gateway unreachable");
+                Err(Status::internal(format!(
+                    "Failed to send order to gateway: {}",
+                    e
+                )))
             }
         }
     }
 
-    async fn cancel_all_for_product(
+    async fn send_cancel(
         &self,
-        request: Request<CancelAllForProductRequest>,
-    ) -> Result<Response<CancelOrderResponse>, Status> {
-        let cancel_order_request = request.into_inner();
-        let cancel_all_payload = json!({
-            "cancel_product_orders": {
-                "tx": {
-                    "sender": cancel_order_request.sender,
-                    "productIds": cancel_order_request.product_ids,
-                    "nonce": self.generate_nonce()
-                },
-                "signature": "", // Generate signature
-                "digest": null
-            }
-        });
-
-        let payload_str = cancel_all_payload.to_string();
-        match self.gateway_client.send_message(payload_str).await {
+        tx_type: &str,
+        payload: serde_json::Value,
+    ) -> Result<CancelOrderResponse, Status> {
+        // A cancel only lowers exposure, so it still goes out when the journal cannot take it
+        if let Err(e) = self.journal_signed_tx(tx_type, None, &payload).await {
+            error!("Failed to journal {} before sending: {}", tx_type, e);
+        }
+        match self.send_execute(payload.to_string()).await {
             Ok(response_data) => {
                 // Log the raw response data for debugging
                 info!("Raw gateway response: {}", response_data);
+                self.journal_gateway_response(tx_type, None, &response_data);
 
                 match serde_json::from_str::<CancelOrderResponse>(&response_data) {
                     Ok(response) => {
-                        info!("Order placed successfully");
-                        Ok(Response::new(response))
+                        info!("Cancel request processed");
+                        Ok(response)
                     }
                     Err(e) => {
                         error!("Failed to parse response: {}", e);
@@ -171,11 +425,55 @@ impl VertexExecuteService for VertexClient {
                 }
             }
             Err(e) => {
-                error!("Failed to send order to gateway: {}", e);
-                Err(Status::internal("Failed to send order to gateway"))
+                error!("Failed to send cancel to gateway: {}", e);
+                Err(Status::internal("Failed to send cancel to gateway"))
             }
         }
     }
+}
+
+#[tonic::async_trait]
+impl VertexExecuteService for VertexClient {
+    // places order on vertex
+    async fn place_order(
+        &self,
+        request: Request<PlaceOrderRequest>,
+    ) -> Result<Response<PlaceOrderResponse>, Status> {
+        let response = self.submit_order(request.into_inner(), None).await?;
+        Ok(Response::new(response))
+    }
+
+    async fn cancel_order(
+        &self,
+        request: Request<CancelOrderRequest>,
+    ) -> Result<Response<CancelOrderResponse>, Status> {
+        let cancel_order_request = request.into_inner();
+
+        let sender = vec_to_fixed_bytes32(cancel_order_request.sender)
+            .map_err(|e| Status::invalid_argument(format!("Invalid sender: {}", e)))?;
+        let digests: Vec<FixedBytes<32>> = cancel_order_request
+            .digests
+            .into_iter()
+            .map(vec_to_fixed_bytes32)
+            .collect::<Result<_, _>>()
+            .map_err(|e| Status::invalid_argument(format!("Invalid digest: {}", e)))?;
+
+        let response = self
+            .cancel_orders(sender, cancel_order_request.product_ids, digests)
+            .await?;
+        Ok(Response::new(response))
+    }
+
+    async fn cancel_all_for_product(
+        &self,
+        request: Request<CancelAllForProductRequest>,
+    ) -> Result<Response<CancelOrderResponse>, Status> {
+        let cancel_order_request = request.into_inner();
+        let response = self
+            .cancel_product_orders(&cancel_order_request.sender, cancel_order_request.product_ids)
+            .await?;
+        Ok(Response::new(response))
+    }
 
     async fn cancel_and_place(
         &self,
@@ -184,65 +482,44 @@ impl VertexExecuteService for VertexClient {
         let inner = request.into_inner();
         let cancel_order_request = inner
             .cancel_order_request
+            .ok_or_else(|| Status::invalid_argument("Cancel is missing in the request"))?;
+        let place_order_request = inner
+            .place_order_request
             .ok_or_else(|| Status::invalid_argument("Order is missing in the request"))?;
-        let place_order_request = inner.place_order_request.unwrap();
-
-        let digests_fixed: Result<Vec<FixedBytes<32>>, _> = cancel_order_request
-            .digests
-            .into_iter()
-            .map(|digest| vec_to_fixed_bytes32(digest)) // Use the conversion utility function
-            .collect(); // Collect into a Result<Vec<FixedBytes<32>>, Error>
-
-        // Check if the conversion was successful
-        let digests_fixed = digests_fixed
-            .map_err(|e| Status::internal(format!("Error converting digests: {}", e)))?;
-
-        // let cancel_order = Cancellation {
-        //     sender: pad_to_fixed_bytes32(&cancel_order_request.sender)
-        //         .map_err(|e| Status::internal(format!("Sender conversion error: {}", e)))?,
-        //     productIds: cancel_order_request.product_ids.clone(),
-        //     digests: digests_fixed.clone(),
-        //     nonce: self.generate_nonce(),
-        // };
-
-        let digests_hex: Vec<String> = digests_fixed
-            .iter()
-            .map(|fixed_bytes| fixed_bytes_to_hex(fixed_bytes))
-            .collect();
 
-        let cancel_and_place_payload = json!({
-            "cancel_and_place": {
-                "cancel_tx": {
-                    "sender": cancel_order_request.sender.clone(), // Assuming this is already a string
-                    "productIds": cancel_order_request.product_ids.clone(),
-                    "digests": digests_hex, // Use the hex string vector
-                    "nonce": self.generate_nonce()
-                },
-                "cancel_signature": "", // Placeholder for cancel signature
-                "place_order": place_order_request
-            }
-        });
-        let payload_str = cancel_and_place_payload.to_string();
-        match self.gateway_client.send_message(payload_str).await {
-            Ok(response_data) => {
-                // Log the raw response data for debugging
-                info!("Raw gateway response: {}", response_data);
+        let response = self
+            .submit_order(place_order_request, Some(cancel_order_request))
+            .await?;
+        Ok(Response::new(response))
+    }
 
-                match serde_json::from_str::<PlaceOrderResponse>(&response_data) {
-                    Ok(response) => {
-                        info!("Order placed successfully");
-                        Ok(Response::new(response))
-                    }
-                    Err(e) => {
-                        error!("Failed to parse response: {}", e);
-                        Err(Status::internal("Failed to parse gateway response"))
-                    }
-                }
-            }
-            Err(e) => {
-                error!("Failed to send order to gateway: {}", e);
-                Err(Status::internal("Failed to send order to gateway"))
-            }
+    async fn kill_switch(
+        &self,
+        request: Request<KillSwitchRequest>,
+    ) -> Result<Response<KillSwitchResponse>, Status> {
+        let kill_switch_request = request.into_inner();
+        if !kill_switch_request.engage {
+            info!("Kill switch released");
+            self.kill_switch.release();
+            return Ok(Response::new(KillSwitchResponse::default()));
         }
+
+        let reason = if kill_switch_request.reason.is_empty() {
+            "manual kill switch"
+        } else {
+            &kill_switch_request.reason
+        };
+        Ok(Response::new(self.trigger_kill_switch(reason).await))
+    }
+
+    async fn heartbeat(
+        &self,
+        _request: Request<HeartbeatRequest>,
+    ) -> Result<Response<HeartbeatResponse>, Status> {
+        self.kill_switch.heartbeat();
+        Ok(Response::new(HeartbeatResponse {
+            engaged: self.kill_switch.is_engaged(),
+            timeout_secs: CONFIG.dead_man_switch_timeout_secs.unwrap_or(0),
+        }))
     }
 }
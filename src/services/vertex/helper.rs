@@ -1,26 +1,31 @@
-use std::{
-    error::Error,
-    time::{SystemTime, UNIX_EPOCH},
-};
-
-use tonic::Status;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::{shared::errors::connect_error::ConnectError, vertex_query::ContractsResponse};
+use crate::{
+    shared::errors::{connect_error::ConnectError, connector_error::ConnectorError},
+    vertex_execute::PlaceOrderRequest,
+};
 
 use super::client::VertexClient;
 
 pub trait VertexHelper {
     fn generate_expiration_time(&self, seconds_from_now: u64, order_type: u8) -> u64;
-    fn construct_query_message<T: serde::Serialize>(&self, request: &T) -> Result<String, Status>;
+    fn construct_query_message<T: serde::Serialize>(
+        &self,
+        request: &T,
+    ) -> Result<String, ConnectorError>;
     async fn send_message_to_gateway(&self, query_message: String) -> Result<String, ConnectError>;
     fn generate_nonce(&self) -> u64;
-    async fn get_contract_addr(&self, product_id: u32) -> Option<String>;
+    fn get_contract_addr(&self, product_id: u32) -> Option<String>;
+    fn resolve_product_id(&self, request: &PlaceOrderRequest) -> Result<u32, ConnectorError>;
 }
 
 impl VertexHelper for VertexClient {
-    fn construct_query_message<T: serde::Serialize>(&self, request: &T) -> Result<String, Status> {
+    fn construct_query_message<T: serde::Serialize>(
+        &self,
+        request: &T,
+    ) -> Result<String, ConnectorError> {
         serde_json::to_string(request)
-            .map_err(|e| Status::internal(format!("Failed to serialize request: {}", e)))
+            .map_err(|e| ConnectorError::Internal(format!("Failed to serialize request: {}", e)))
     }
 
     async fn send_message_to_gateway(&self, query_message: String) -> Result<String, ConnectError> {
@@ -54,21 +59,18 @@ impl VertexHelper for VertexClient {
         expiration | order_type_bits // Combine expiration with order_type_bits
     }
 
-    // get verifying contract order address for signing place order
-    async fn get_contract_addr(&self, product_id: u32) -> Option<String> {
-        const MSG: &str = "{
-            \"type\":\"contracts\"
-        }";
-
-        let response_data = self
-            .send_message_to_gateway(MSG.to_string())
-            .await
-            .expect("Failed to send message to gateway");
-
-        let contracts: ContractsResponse = serde_json::from_str(&response_data).ok()?; // Convert Result to Option, discarding the error
+    // get verifying contract order address for signing place order, served from the registry cache
+    fn get_contract_addr(&self, product_id: u32) -> Option<String> {
+        self.registry.book_addr(product_id)
+    }
 
-        contracts
-            .data
-            .map(|d| d.book_addrs[product_id as usize].clone())
+    // symbol takes precedence over product_id so clients don't need per-network product ids
+    fn resolve_product_id(&self, request: &PlaceOrderRequest) -> Result<u32, ConnectorError> {
+        match request.symbol.as_deref() {
+            Some(symbol) => self.registry.product_id_for_symbol(symbol).ok_or_else(|| {
+                ConnectorError::InvalidRequest(format!("Unknown symbol: {}", symbol))
+            }),
+            None => Ok(request.product_id),
+        }
     }
 }
@@ -0,0 +1,159 @@
+use std::io;
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    domain::{
+        models::vertex::stream_events::StreamEvent,
+        oms::{
+            order::{ManagedOrder, OrderState},
+            order_manager::OrderFilter,
+        },
+        positions::tracker::TrackerSnapshot,
+        risk::engine::RiskSnapshot,
+    },
+    shared::utils::decimal::Decimal,
+    storage::journal::{JournalEntry, JournalReplay},
+};
+
+use super::client::VertexClient;
+
+/// Order, position and risk state written as a journal snapshot.
+///
+/// Only open orders are kept; finished ones are history the next start does not need.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct JournalState {
+    last_order_id: u64,
+    orders: Vec<ManagedOrder>,
+    positions: TrackerSnapshot,
+    risk: RiskSnapshot,
+}
+
+impl VertexClient {
+    // Audit copy of a signed payload; returns once it is on disk, so callers can hold the send
+    pub async fn journal_signed_tx(
+        &self,
+        tx_type: &str,
+        order_id: Option<u64>,
+        payload: &Value,
+    ) -> io::Result<()> {
+        self.journal
+            .append_acked(JournalEntry::SignedTx {
+                tx_type: tx_type.to_string(),
+                order_id,
+                payload: payload.clone(),
+            })
+            .await
+    }
+
+    pub fn journal_gateway_response(&self, tx_type: &str, order_id: Option<u64>, response: &str) {
+        self.journal.append(JournalEntry::GatewayResponse {
+            tx_type: tx_type.to_string(),
+            order_id,
+            response: response.to_string(),
+        });
+    }
+
+    /// Rebuilds the order manager, positions and risk exposures from the journal's snapshot and
+    /// the records after it, oldest first.
+    ///
+    /// Runs once on startup before any service is exposed; nothing is written back to the journal.
+    pub fn replay_journal(&self, replay: JournalReplay<JournalState>) {
+        if let Some(state) = replay.state {
+            info!(
+                "Restoring {} open orders from the journal snapshot",
+                state.orders.len()
+            );
+            self.order_manager.restore_last_id(state.last_order_id);
+            for order in state.orders {
+                self.order_manager.restore(order);
+            }
+            self.positions.restore(state.positions);
+            self.risk_engine.restore(state.risk);
+        }
+
+        let records = replay.records;
+        for record in &records {
+            match &record.entry {
+                JournalEntry::OrderCreated {
+                    order_id,
+                    sender,
+                    product_id,
+                    digest,
+                    price,
+                    amount,
+                    expiration,
+                } => self.order_manager.restore(ManagedOrder {
+                    id: *order_id,
+                    digest: digest.as_ref().map(|d| d.to_ascii_lowercase()),
+                    sender: sender.to_ascii_lowercase(),
+                    product_id: *product_id,
+                    price: *price,
+                    amount: *amount,
+                    filled: Decimal::ZERO,
+                    state: OrderState::PendingNew,
+                    expiration: *expiration,
+                    created_at_ms: record.ts_ms,
+                    updated_at_ms: record.ts_ms,
+                    error: None,
+                }),
+                JournalEntry::OrderAccepted { order_id, digest } => {
+                    self.order_manager.on_accepted(*order_id, digest);
+                    if let Some(order) = self.order_manager.get(*order_id) {
+                        self.risk_engine.on_order_accepted(
+                            &order.sender,
+                            digest,
+                            order.product_id,
+                            order.amount,
+                        );
+                    }
+                }
+                JournalEntry::OrderRejected { order_id, error } => {
+                    self.order_manager.on_rejected(*order_id, error)
+                }
+                JournalEntry::OrdersCancelled { sender, digests } => {
+                    for digest in digests {
+                        self.order_manager.on_cancelled(digest);
+                        self.risk_engine.on_order_closed(sender, digest);
+                    }
+                }
+                JournalEntry::ProductsCancelled {
+                    sender,
+                    product_ids,
+                } => {
+                    self.order_manager
+                        .on_products_cancelled(sender, product_ids);
+                    self.risk_engine.on_products_cancelled(sender, product_ids);
+                }
+                JournalEntry::OrderUpdate(update) => {
+                    self.apply_stream_event(&StreamEvent::OrderUpdate(update.clone()))
+                }
+                JournalEntry::Fill(fill) => {
+                    self.apply_stream_event(&StreamEvent::Fill(fill.clone()))
+                }
+                JournalEntry::PositionChange(change) => {
+                    self.apply_stream_event(&StreamEvent::PositionChange(change.clone()))
+                }
+                // Audit only
+                JournalEntry::SignedTx { .. } | JournalEntry::GatewayResponse { .. } => {}
+            }
+        }
+        info!("Replayed {} journal records", records.len());
+    }
+    // Snapshot the current state so the next start replays only what follows. Call it while
+    // nothing else is journaling and applying events, or the two could disagree
+    pub fn snapshot_journal(&self) {
+        let orders = self.order_manager.list(&OrderFilter {
+            open_only: true,
+            ..OrderFilter::default()
+        });
+        self.journal.snapshot(&JournalState {
+            last_order_id: self.order_manager.last_id(),
+            orders,
+            positions: self.positions.snapshot(),
+            risk: self.risk_engine.snapshot(),
+        });
+    }
+}
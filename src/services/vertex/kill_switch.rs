@@ -0,0 +1,143 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use log::{error, warn};
+
+use crate::{
+    config::CONFIG, connectors::connector::Connector, domain::models::trading::Venue,
+    shared::utils::type_conv::subaccount_sender_hex, vertex_execute::KillSwitchResponse,
+};
+
+use super::client::VertexClient;
+
+/// Global trading halt shared by every order entry path.
+///
+/// Once engaged no new orders are accepted until it is explicitly released, and everything
+/// resting on every registered venue is cancelled. The optional dead man's switch engages it when
+/// the controlling client stops sending heartbeats or the subscription feed drops.
+#[derive(Debug, Default)]
+pub struct KillSwitch {
+    engaged: AtomicBool,
+    reason: Mutex<String>,
+    // None until the first heartbeat, so the watchdog only arms once a controller is attached
+    last_heartbeat: Mutex<Option<Instant>>,
+    // Every registered venue, for the cancel-all when the switch fires
+    connectors: Mutex<Vec<Arc<dyn Connector>>>,
+}
+
+impl KillSwitch {
+    pub fn new() -> Self {
+        KillSwitch::default()
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.engaged.load(Ordering::SeqCst)
+    }
+
+    pub fn reason(&self) -> String {
+        self.reason.lock().unwrap().clone()
+    }
+
+    pub fn engage(&self, reason: &str) {
+        *self.reason.lock().unwrap() = reason.to_string();
+        self.engaged.store(true, Ordering::SeqCst);
+    }
+
+    pub fn release(&self) {
+        self.engaged.store(false, Ordering::SeqCst);
+        self.reason.lock().unwrap().clear();
+        // Re-arm from scratch so a stale heartbeat can't fire the switch right away
+        *self.last_heartbeat.lock().unwrap() = None;
+    }
+
+    pub fn watch(&self, connector: Arc<dyn Connector>) {
+        self.connectors.lock().unwrap().push(connector);
+    }
+
+    pub fn heartbeat(&self) {
+        *self.last_heartbeat.lock().unwrap() = Some(Instant::now());
+    }
+
+    fn heartbeat_expired(&self, timeout: Duration) -> bool {
+        self.last_heartbeat
+            .lock()
+            .unwrap()
+            .is_some_and(|last| last.elapsed() > timeout)
+    }
+}
+
+impl VertexClient {
+    // Block new orders, then cancel everything resting on every product for every subaccount,
+    // and on every other registered venue
+    pub async fn trigger_kill_switch(&self, reason: &str) -> KillSwitchResponse {
+        warn!("Kill switch engaged: {}", reason);
+        self.kill_switch.engage(reason);
+
+        let product_ids = self.registry.product_ids();
+        let mut response = KillSwitchResponse {
+            engaged: true,
+            reason: reason.to_string(),
+            ..Default::default()
+        };
+
+        for subaccount in &CONFIG.subaccounts {
+            let sender = subaccount_sender_hex(&CONFIG.sender_address, subaccount);
+            match self
+                .cancel_product_orders(&sender, product_ids.clone())
+                .await
+            {
+                Ok(cancellation) => response.cancellations.push(cancellation),
+                Err(status) => {
+                    error!("Kill switch cancel-all failed for {}: {}", sender, status);
+                    response
+                        .errors
+                        .push(format!("{}: {}", sender, status.message()));
+                }
+            }
+        }
+
+        // Vertex was swept above for every subaccount, not only the connector's default one
+        let connectors: Vec<Arc<dyn Connector>> =
+            self.kill_switch.connectors.lock().unwrap().clone();
+        for connector in connectors {
+            let venue = connector.venue();
+            if venue == Venue::Vertex {
+                continue;
+            }
+            if let Err(e) = connector.cancel_all(None).await {
+                error!("Kill switch cancel-all failed on {}: {}", venue, e);
+                response.errors.push(format!("{}: {}", venue, e));
+            }
+        }
+
+        response
+    }
+
+    /// Watches heartbeats and the subscription feed and fires the kill switch when either goes away.
+    pub fn spawn_dead_man_switch(self: &Arc<Self>, timeout: Duration) {
+        let client = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(1));
+            loop {
+                interval.tick().await;
+                if client.kill_switch.is_engaged() {
+                    continue;
+                }
+
+                let reason = if client.kill_switch.heartbeat_expired(timeout) {
+                    "controlling client heartbeat timed out"
+                } else if client.subscription_client.is_disconnected() {
+                    "subscription feed disconnected"
+                } else {
+                    continue;
+                };
+                client.trigger_kill_switch(reason).await;
+            }
+        });
+    }
+}
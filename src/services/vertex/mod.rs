@@ -1,4 +1,14 @@
 pub mod client;
+pub mod connector;
+pub mod events;
 pub mod execute;
 pub mod helper;
+pub mod journal;
+pub mod kill_switch;
+pub mod orders;
+pub mod paper;
+pub mod positions;
 pub mod query;
+pub mod reconcile;
+pub mod registry;
+pub mod validation;
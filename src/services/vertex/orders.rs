@@ -0,0 +1,99 @@
+use tonic::{Request, Response, Status};
+
+use crate::{
+    domain::oms::{
+        order::{ManagedOrder, OrderState},
+        order_manager::OrderFilter,
+    },
+    oms::{
+        get_order_request::Key, order_management_service_server::OrderManagementService,
+        GetOrderRequest, ListOrdersRequest, ListOrdersResponse, OrderRecord,
+        OrderState as ProtoOrderState,
+    },
+};
+
+use super::client::VertexClient;
+
+const DEFAULT_PAGE_SIZE: u32 = 100;
+const MAX_PAGE_SIZE: u32 = 1000;
+
+fn to_proto_state(state: OrderState) -> ProtoOrderState {
+    match state {
+        OrderState::PendingNew => ProtoOrderState::PendingNew,
+        OrderState::Open => ProtoOrderState::Open,
+        OrderState::PartiallyFilled => ProtoOrderState::PartiallyFilled,
+        OrderState::Filled => ProtoOrderState::Filled,
+        OrderState::Cancelled => ProtoOrderState::Cancelled,
+        OrderState::Rejected => ProtoOrderState::Rejected,
+        OrderState::Expired => ProtoOrderState::Expired,
+    }
+}
+
+impl From<ManagedOrder> for OrderRecord {
+    fn from(order: ManagedOrder) -> Self {
+        OrderRecord {
+            id: order.id,
+            digest: order.digest.unwrap_or_default(),
+            sender: order.sender,
+            product_id: order.product_id,
+            price: order.price.to_string(),
+            amount: order.amount.to_string(),
+            filled: order.filled.to_string(),
+            state: to_proto_state(order.state) as i32,
+            expiration: order.expiration,
+            created_at_ms: order.created_at_ms,
+            updated_at_ms: order.updated_at_ms,
+            error: order.error,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl OrderManagementService for VertexClient {
+    async fn list_orders(
+        &self,
+        request: Request<ListOrdersRequest>,
+    ) -> Result<Response<ListOrdersResponse>, Status> {
+        let list_request = request.into_inner();
+        let filter = OrderFilter {
+            sender: list_request.sender,
+            product_id: list_request.product_id,
+            open_only: list_request.open_only,
+        };
+        // Page tokens are the id of the last order on the previous page
+        let after = match list_request.page_token.as_str() {
+            "" => 0,
+            token => token
+                .parse()
+                .map_err(|_| Status::invalid_argument(format!("Invalid page token: {}", token)))?,
+        };
+        let page_size = match list_request.page_size {
+            0 => DEFAULT_PAGE_SIZE,
+            size => size.min(MAX_PAGE_SIZE),
+        };
+
+        let page = self.order_manager.page(&filter, after, page_size as usize);
+        Ok(Response::new(ListOrdersResponse {
+            orders: page.orders.into_iter().map(OrderRecord::from).collect(),
+            next_page_token: page
+                .next_after
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+        }))
+    }
+
+    async fn get_order(
+        &self,
+        request: Request<GetOrderRequest>,
+    ) -> Result<Response<OrderRecord>, Status> {
+        let order = match request.into_inner().key {
+            Some(Key::Id(id)) => self.order_manager.get(id),
+            Some(Key::Digest(digest)) => self.order_manager.get_by_digest(&digest),
+            None => return Err(Status::invalid_argument("Either id or digest is required")),
+        };
+
+        order
+            .map(|o| Response::new(OrderRecord::from(o)))
+            .ok_or_else(|| Status::not_found("Order not found"))
+    }
+}
@@ -0,0 +1,637 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use log::{info, warn};
+use serde_json::{json, Value};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    connectors::vertex::{payload_signer, subscription_client::SubscriptionClient},
+    domain::{
+        backtest::exchange::{FeeRates, SimExchange},
+        models::{
+            trading::{
+                CancelRequest, MarketEvent, OrderRequest, Side, TimeInForce, Venue, VenueOrder,
+                VenueOrderStatus,
+            },
+            vertex::{
+                sol_structs::Order,
+                stream_events::{Fill, OrderUpdate, OrderUpdateReason, StreamEvent},
+            },
+        },
+    },
+    services::vertex::{connector::to_market_event, registry::ProductRegistry},
+    shared::utils::{decimal::Decimal, type_conv},
+    vertex_symbols::Symbol,
+};
+
+const PUBLISH_INTERVAL: Duration = Duration::from_millis(5);
+
+// A paper order as the OMS knows it
+#[derive(Debug, Clone)]
+struct PaperOrder {
+    digest: String,
+    sender: String,
+    product_id: u32,
+    instrument: String,
+    price: Decimal,
+    // Signed like the Vertex order, positive to buy
+    amount: Decimal,
+    remaining: Decimal,
+}
+
+#[derive(Debug, Default)]
+struct PaperBook {
+    exchange: SimExchange,
+    // By simulated order id
+    orders: HashMap<String, PaperOrder>,
+    // Digest to simulated order id
+    by_digest: HashMap<String, String>,
+    // Events waiting to be published, in order
+    pending: VecDeque<StreamEvent>,
+}
+
+/// In-process stand-in for the Vertex gateway in paper-trading mode.
+///
+/// Takes the same execute payloads the gateway would, matches them on a `SimExchange` driven by
+/// the live subscription feed and answers in the gateway's format, so validation, risk checks,
+/// the OMS and the journal run unchanged. Fills and order updates are pushed into the
+/// subscription client's event channel as if Vertex had streamed them.
+#[derive(Debug)]
+pub struct PaperExchange {
+    registry: Arc<ProductRegistry>,
+    subscription_client: Arc<SubscriptionClient>,
+    book: Mutex<PaperBook>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+// Stream timestamps are unix nanoseconds
+fn timestamp_nanos(timestamp_ms: u64) -> String {
+    (timestamp_ms as u128 * 1_000_000).to_string()
+}
+
+fn normalize_digest(digest: &str) -> String {
+    format!("0x{}", digest.trim_start_matches("0x").to_ascii_lowercase())
+}
+
+// The order as it was signed, from the strings of a `place_order` payload
+fn signed_order(order: &Value) -> Option<Order> {
+    let field = |name: &str| order[name].as_str();
+    Some(Order {
+        sender: type_conv::hex_to_fixed_bytes32(field("sender")?).ok()?,
+        priceX18: field("priceX18")?.parse().ok()?,
+        amount: field("amount")?.parse().ok()?,
+        expiration: field("expiration")?.parse().ok()?,
+        nonce: field("nonce")?.parse().ok()?,
+    })
+}
+
+// What a signed order asks the simulator for; the order type sits in the top two expiration bits
+fn order_request(instrument: String, signed: &Order) -> OrderRequest {
+    let amount = Decimal::from_x18(signed.amount);
+    OrderRequest {
+        instrument,
+        side: Side::of(amount),
+        price: Decimal::from_x18(signed.priceX18),
+        quantity: amount.abs(),
+        time_in_force: match signed.expiration >> 62 {
+            1 => TimeInForce::Ioc,
+            2 => TimeInForce::Fok,
+            3 => TimeInForce::PostOnly,
+            _ => TimeInForce::Gtc,
+        },
+        client_order_id: None,
+    }
+}
+
+// Fees of every symbol as loaded at startup; without them paper fills would trade free
+fn fee_rates(symbols: Vec<Symbol>) -> Result<HashMap<(Venue, String), FeeRates>, String> {
+    if symbols.is_empty() {
+        return Err("No symbols in the product registry to take fees from".to_string());
+    }
+    symbols
+        .into_iter()
+        .map(|symbol| {
+            let x18 = |value: &str| {
+                Decimal::from_x18_str(value)
+                    .map_err(|e| format!("Invalid fee rate for {}: {}", symbol.symbol, e))
+            };
+            let rates = FeeRates {
+                maker: x18(&symbol.maker_fee_rate_x18)?,
+                taker: x18(&symbol.taker_fee_rate_x18)?,
+            };
+            Ok(((Venue::Vertex, symbol.symbol), rates))
+        })
+        .collect()
+}
+
+fn failure(request_type: &str, error: &str) -> String {
+    json!({
+        "status": "failure",
+        "signature": "",
+        "request_type": request_type,
+        "id": 0,
+        "order_id": 0,
+        "error": error,
+    })
+    .to_string()
+}
+
+fn success(request_type: &str) -> String {
+    json!({
+        "status": "success",
+        "signature": "",
+        "request_type": request_type,
+    })
+    .to_string()
+}
+
+impl PaperBook {
+    // Match an order and remember it under its digest while it rests
+    fn place(
+        &mut self,
+        order: PaperOrder,
+        request: &OrderRequest,
+        now_ms: u64,
+    ) -> Result<(), String> {
+        let (result, events) = self.exchange.place(Venue::Vertex, request, now_ms);
+        let ack = result.map_err(|e| e.to_string())?;
+        self.by_digest
+            .insert(order.digest.clone(), ack.order_id.clone());
+        self.orders.insert(ack.order_id, order);
+        self.translate(events);
+        Ok(())
+    }
+
+    // Turn what the simulator did into the events Vertex would have streamed
+    fn translate(&mut self, events: Vec<MarketEvent>) {
+        for fill in self.exchange.take_fills() {
+            let Some(order) = self.orders.get_mut(&fill.order_id) else {
+                continue;
+            };
+            order.remaining = order
+                .remaining
+                .checked_sub(fill.quantity)
+                .unwrap_or_default()
+                .max(Decimal::ZERO);
+            let fill = StreamEvent::Fill(Fill {
+                timestamp: timestamp_nanos(fill.timestamp_ms),
+                product_id: order.product_id,
+                subaccount: order.sender.clone(),
+                order_digest: order.digest.clone(),
+                filled_qty: fill.quantity,
+                remaining_qty: order.remaining,
+                original_qty: order.amount.abs(),
+                price: fill.price,
+                is_taker: !fill.maker,
+                is_bid: fill.side == Side::Buy,
+                fee: fill.fee,
+            });
+            self.pending.push_back(fill);
+        }
+
+        for event in events {
+            let MarketEvent::OrderUpdate {
+                order_id,
+                status,
+                remaining,
+                timestamp_ms,
+                ..
+            } = event
+            else {
+                continue;
+            };
+            let Some(order) = self.orders.get(&order_id) else {
+                continue;
+            };
+            let reason = match status {
+                VenueOrderStatus::Open => OrderUpdateReason::Placed,
+                VenueOrderStatus::PartiallyFilled | VenueOrderStatus::Filled => {
+                    OrderUpdateReason::Filled
+                }
+                VenueOrderStatus::Cancelled | VenueOrderStatus::Rejected => {
+                    OrderUpdateReason::Cancelled
+                }
+            };
+            let remaining = if order.amount.is_negative() {
+                Decimal::ZERO.checked_sub(remaining).unwrap_or_default()
+            } else {
+                remaining
+            };
+            let update = StreamEvent::OrderUpdate(OrderUpdate {
+                timestamp: timestamp_nanos(timestamp_ms),
+                product_id: order.product_id,
+                digest: order.digest.clone(),
+                amount: remaining,
+                reason,
+            });
+            self.pending.push_back(update);
+            // Done orders are forgotten
+            if remaining.is_zero() || reason == OrderUpdateReason::Cancelled {
+                if let Some(order) = self.orders.remove(&order_id) {
+                    self.by_digest.remove(&order.digest);
+                }
+            }
+        }
+    }
+
+    // Unknown digests are ignored, as the gateway does for orders already gone
+    fn cancel(&mut self, digest: &str, now_ms: u64) {
+        let Some(order_id) = self.by_digest.get(&normalize_digest(digest)).cloned() else {
+            return;
+        };
+        let Some(order) = self.orders.get(&order_id) else {
+            return;
+        };
+        let cancel = CancelRequest {
+            instrument: order.instrument.clone(),
+            order_id,
+        };
+        if let Ok(events) = self.exchange.cancel(Venue::Vertex, &cancel, now_ms) {
+            self.translate(events);
+        }
+    }
+}
+
+impl PaperExchange {
+    /// Fails unless the registry is loaded with the fee rates of every symbol.
+    pub fn new(
+        registry: Arc<ProductRegistry>,
+        subscription_client: Arc<SubscriptionClient>,
+    ) -> Result<Self, String> {
+        let fees = fee_rates(registry.symbols())?;
+        Ok(PaperExchange {
+            registry,
+            subscription_client,
+            book: Mutex::new(PaperBook {
+                exchange: SimExchange::new(fees),
+                ..PaperBook::default()
+            }),
+        })
+    }
+
+    /// Match the live market data and publish paper fills for as long as the feed runs.
+    pub fn spawn(self: &Arc<Self>) {
+        let paper = Arc::clone(self);
+        let mut events = paper.subscription_client.subscribe_events();
+        tokio::spawn(async move {
+            let mut publish = tokio::time::interval(PUBLISH_INTERVAL);
+            loop {
+                tokio::select! {
+                    event = events.recv() => match event {
+                        Ok(event) => paper.on_stream_event(event),
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("Paper exchange lagged, skipped {} stream events", skipped)
+                        }
+                        Err(RecvError::Closed) => {
+                            info!("Subscription event channel closed, stopping paper exchange");
+                            break;
+                        }
+                    },
+                    _ = publish.tick() => paper.publish_pending(),
+                }
+            }
+        });
+    }
+
+    fn publish_pending(&self) {
+        let due: Vec<StreamEvent> = self.book.lock().unwrap().pending.drain(..).collect();
+        for event in due {
+            self.subscription_client.publish(event);
+        }
+    }
+
+    fn on_stream_event(&self, event: StreamEvent) {
+        // Private events are our own output echoed back
+        if !matches!(
+            event,
+            StreamEvent::BestBidOffer(_) | StreamEvent::Trade(_) | StreamEvent::BookDepth(_)
+        ) {
+            return;
+        }
+        let Some(event) = to_market_event(&self.registry, event) else {
+            return;
+        };
+        {
+            let mut book = self.book.lock().unwrap();
+            let caused = book.exchange.on_market(&event);
+            book.translate(caused);
+        }
+        self.publish_pending();
+    }
+
+    /// Answer one execute payload the way the gateway would.
+    pub fn execute(&self, payload: &str) -> String {
+        let Ok(payload) = serde_json::from_str::<Value>(payload) else {
+            return failure("execute", "Invalid execute payload");
+        };
+        if let Some(place) = payload.get("place_order") {
+            return self.place(place);
+        }
+        if let Some(cancel) = payload.get("cancel_orders") {
+            let digests = cancel["tx"]["digests"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default();
+            self.cancel_digests(&digests);
+            return success("execute_cancel_orders");
+        }
+        if let Some(cancel) = payload.get("cancel_product_orders") {
+            let sender = cancel["tx"]["sender"].as_str().unwrap_or_default();
+            let product_ids: Vec<u32> = cancel["tx"]["productIds"]
+                .as_array()
+                .map(|ids| {
+                    ids.iter()
+                        .filter_map(|id| id.as_u64())
+                        .map(|id| id as u32)
+                        .collect()
+                })
+                .unwrap_or_default();
+            self.cancel_products(sender, &product_ids);
+            return success("execute_cancel_product_orders");
+        }
+        if let Some(replace) = payload.get("cancel_and_place") {
+            let digests = replace["cancel_tx"]["digests"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default();
+            self.cancel_digests(&digests);
+            return self.place(&replace["place_order"]);
+        }
+        failure("execute", "Unsupported execute in paper trading")
+    }
+
+    fn cancel_digests(&self, digests: &[Value]) {
+        let now_ms = now_ms();
+        let mut book = self.book.lock().unwrap();
+        for digest in digests.iter().filter_map(Value::as_str) {
+            book.cancel(digest, now_ms);
+        }
+    }
+
+    fn cancel_products(&self, sender: &str, product_ids: &[u32]) {
+        let now_ms = now_ms();
+        let mut book = self.book.lock().unwrap();
+        let digests: Vec<String> = book
+            .orders
+            .values()
+            .filter(|order| order.sender.eq_ignore_ascii_case(sender))
+            .filter(|order| product_ids.contains(&order.product_id))
+            .map(|order| order.digest.clone())
+            .collect();
+        for digest in digests {
+            book.cancel(&digest, now_ms);
+        }
+    }
+
+    // Signed `place_order` payloads carry camelCase X18 fields and the order type in the
+    // expiration. The digest is the one the gateway would derive, which the OMS already knows.
+    fn place(&self, place: &Value) -> String {
+        const REQUEST_TYPE: &str = "execute_place_order";
+        let Some(product_id) = place["product_id"].as_u64().map(|id| id as u32) else {
+            return failure(REQUEST_TYPE, "Order product_id is missing");
+        };
+        let Some(signed) = signed_order(&place["order"]) else {
+            return failure(REQUEST_TYPE, "Order fields are missing or invalid");
+        };
+        let Some(digest) = self
+            .registry
+            .book_addr(product_id)
+            .and_then(|book_addr| payload_signer::order_digest(&signed, book_addr))
+        else {
+            return failure(
+                REQUEST_TYPE,
+                &format!("No book contract for product {}", product_id),
+            );
+        };
+        let sender = place["order"]["sender"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let Some(symbol) = self.registry.symbol_for_product(product_id) else {
+            return failure(REQUEST_TYPE, &format!("Unknown product {}", product_id));
+        };
+
+        let request = order_request(symbol.symbol, &signed);
+        let order = PaperOrder {
+            digest: digest.clone(),
+            sender,
+            product_id,
+            instrument: request.instrument.clone(),
+            price: request.price,
+            amount: Decimal::from_x18(signed.amount),
+            remaining: request.quantity,
+        };
+        let placed = self.book.lock().unwrap().place(order, &request, now_ms());
+        if let Err(e) = placed {
+            return failure(REQUEST_TYPE, &e);
+        }
+        self.publish_pending();
+        json!({
+            "status": "success",
+            "signature": "",
+            "data": { "digest": digest },
+            "request_type": REQUEST_TYPE,
+            "id": place["id"].as_u64().unwrap_or_default(),
+            "order_id": 0,
+        })
+        .to_string()
+    }
+
+    // Resting paper orders of one sender, as the gateway's subaccount orders query would list
+    pub fn open_orders(&self, sender: &str, product_ids: &[u32]) -> Vec<VenueOrder> {
+        let book = self.book.lock().unwrap();
+        book.orders
+            .values()
+            .filter(|order| order.sender.eq_ignore_ascii_case(sender))
+            .filter(|order| product_ids.contains(&order.product_id))
+            .map(|order| VenueOrder {
+                order_id: order.digest.clone(),
+                instrument: order.instrument.clone(),
+                side: Side::of(order.amount),
+                price: order.price,
+                quantity: order.amount.abs(),
+                remaining: order.remaining,
+                status: if order.remaining < order.amount.abs() {
+                    VenueOrderStatus::PartiallyFilled
+                } else {
+                    VenueOrderStatus::Open
+                },
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INSTRUMENT: &str = "BTC-PERP";
+    const SENDER: &str = "0xabc0000000000000000000000000000000000000000000000000000000000000";
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn signed(price: &str, amount: &str, order_type: u64) -> Order {
+        Order {
+            sender: type_conv::hex_to_fixed_bytes32(SENDER).unwrap(),
+            priceX18: dec(price).to_x18(),
+            amount: dec(amount).to_x18(),
+            expiration: (order_type << 62) | 1_700_000_000,
+            nonce: 0,
+        }
+    }
+
+    fn symbol(maker: &str, taker: &str) -> Symbol {
+        Symbol {
+            symbol: INSTRUMENT.to_string(),
+            maker_fee_rate_x18: dec(maker).to_x18().to_string(),
+            taker_fee_rate_x18: dec(taker).to_x18().to_string(),
+            ..Symbol::default()
+        }
+    }
+
+    // A book quoting 99 / 101, one lot each side
+    fn book() -> PaperBook {
+        let fees = fee_rates(vec![symbol("-0.0001", "0.001")]).unwrap();
+        let mut book = PaperBook {
+            exchange: SimExchange::new(fees),
+            ..PaperBook::default()
+        };
+        book.exchange.on_market(&MarketEvent::Quote {
+            venue: Venue::Vertex,
+            instrument: INSTRUMENT.to_string(),
+            bid_price: dec("99"),
+            bid_quantity: dec("1"),
+            ask_price: dec("101"),
+            ask_quantity: dec("1"),
+            timestamp_ms: 0,
+        });
+        book
+    }
+
+    fn place(book: &mut PaperBook, digest: &str, signed: &Order) -> Result<(), String> {
+        let request = order_request(INSTRUMENT.to_string(), signed);
+        let order = PaperOrder {
+            digest: digest.to_string(),
+            sender: SENDER.to_string(),
+            product_id: 2,
+            instrument: INSTRUMENT.to_string(),
+            price: request.price,
+            amount: Decimal::from_x18(signed.amount),
+            remaining: request.quantity,
+        };
+        book.place(order, &request, 0)
+    }
+
+    #[test]
+    fn the_order_type_is_read_from_the_top_expiration_bits() {
+        let request = order_request(INSTRUMENT.to_string(), &signed("100.5", "-0.25", 0));
+        assert_eq!(request.side, Side::Sell);
+        assert_eq!(
+            (request.price, request.quantity),
+            (dec("100.5"), dec("0.25"))
+        );
+        assert_eq!(request.time_in_force, TimeInForce::Gtc);
+
+        for (order_type, time_in_force) in [
+            (1, TimeInForce::Ioc),
+            (2, TimeInForce::Fok),
+            (3, TimeInForce::PostOnly),
+        ] {
+            let request = order_request(INSTRUMENT.to_string(), &signed("100", "1", order_type));
+            assert_eq!(request.side, Side::Buy);
+            assert_eq!(request.time_in_force, time_in_force);
+        }
+    }
+
+    #[test]
+    fn paper_trading_needs_every_fee_rate() {
+        assert!(fee_rates(Vec::new()).is_err());
+        let missing = Symbol {
+            symbol: INSTRUMENT.to_string(),
+            ..Symbol::default()
+        };
+        assert!(fee_rates(vec![missing]).is_err());
+
+        let fees = fee_rates(vec![symbol("-0.0001", "0.001")]).unwrap();
+        let rates = &fees[&(Venue::Vertex, INSTRUMENT.to_string())];
+        assert_eq!((rates.maker, rates.taker), (dec("-0.0001"), dec("0.001")));
+    }
+
+    #[test]
+    fn resting_orders_are_cancelled_by_digest_in_any_case() {
+        let mut book = book();
+        place(&mut book, "0xabcd", &signed("98", "2", 0)).unwrap();
+        let Some(StreamEvent::OrderUpdate(placed)) = book.pending.pop_front() else {
+            panic!("expected an order update");
+        };
+        assert_eq!(placed.digest, "0xabcd");
+        assert_eq!(placed.reason, OrderUpdateReason::Placed);
+
+        book.cancel("ABCD", 0);
+        let Some(StreamEvent::OrderUpdate(update)) = book.pending.pop_front() else {
+            panic!("expected an order update");
+        };
+        assert_eq!(update.digest, "0xabcd");
+        assert_eq!(update.reason, OrderUpdateReason::Cancelled);
+        assert!(update.amount.is_zero());
+        assert!(book.orders.is_empty() && book.by_digest.is_empty());
+    }
+
+    #[test]
+    fn fills_stream_like_vertex_with_the_remaining_amount_signed_by_side() {
+        let mut book = book();
+        place(&mut book, "0x01", &signed("100", "-2", 0)).unwrap();
+        book.pending.clear();
+
+        // A buyer printing through the ask takes half a lot of the resting sell
+        let caused = book.exchange.on_market(&MarketEvent::Trade {
+            venue: Venue::Vertex,
+            instrument: INSTRUMENT.to_string(),
+            price: dec("100.5"),
+            quantity: dec("0.5"),
+            taker_side: Side::Buy,
+            timestamp_ms: 7,
+        });
+        book.translate(caused);
+
+        let Some(StreamEvent::Fill(fill)) = book.pending.pop_front() else {
+            panic!("expected a fill");
+        };
+        assert_eq!(fill.order_digest, "0x01");
+        assert_eq!(fill.timestamp, "7000000");
+        assert_eq!((fill.price, fill.filled_qty), (dec("100"), dec("0.5")));
+        assert_eq!(
+            (fill.remaining_qty, fill.original_qty),
+            (dec("1.5"), dec("2"))
+        );
+        assert!(!fill.is_bid && !fill.is_taker);
+        assert_eq!(fill.fee, dec("-0.005"));
+
+        let Some(StreamEvent::OrderUpdate(update)) = book.pending.pop_front() else {
+            panic!("expected an order update");
+        };
+        assert_eq!(update.reason, OrderUpdateReason::Filled);
+        assert_eq!(update.amount, dec("-1.5"));
+        assert_eq!(book.orders["sim-1"].remaining, dec("1.5"));
+    }
+
+    #[test]
+    fn refused_orders_are_not_tracked() {
+        let mut book = book();
+        assert!(place(&mut book, "0x02", &signed("101", "1", 3)).is_err());
+        assert!(book.orders.is_empty() && book.by_digest.is_empty());
+        assert!(book.pending.is_empty());
+    }
+}
@@ -0,0 +1,122 @@
+use std::{pin::Pin, sync::Arc, time::Duration};
+
+use futures::{stream, Stream, StreamExt};
+use log::warn;
+use tokio::sync::broadcast::error::RecvError;
+use tonic::{Request, Response, Status};
+
+use crate::{
+    config::CONFIG,
+    domain::positions::tracker::Position,
+    positions::{
+        position_service_server::PositionService, PositionRecord, PositionsRequest,
+        PositionsResponse,
+    },
+    shared::utils::decimal::Decimal,
+};
+
+use super::client::VertexClient;
+
+impl From<Position> for PositionRecord {
+    fn from(position: Position) -> Self {
+        PositionRecord {
+            sender: position.sender,
+            product_id: position.product_id,
+            amount: position.amount.to_string(),
+            avg_entry_price: position.avg_entry_price.to_string(),
+            realized_pnl: position.realized_pnl.to_string(),
+            unrealized_pnl: position.unrealized_pnl.to_string(),
+            fees: position.fees.to_string(),
+            funding: position.funding.to_string(),
+            mark_price: position.mark_price.map(|p| p.to_string()),
+            updated_at_ms: position.updated_at_ms,
+        }
+    }
+}
+
+impl VertexClient {
+    /// Re-marks open positions against the registry's oracle prices after every refresh.
+    pub fn spawn_position_marker(self: &Arc<Self>) {
+        let client = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(CONFIG.registry_refresh_secs));
+            loop {
+                interval.tick().await;
+                for product_id in client.positions.product_ids() {
+                    let oracle_price = client
+                        .registry
+                        .product(product_id)
+                        .and_then(|p| Decimal::from_x18_str(&p.oracle_price_x18).ok());
+                    if let Some(price) = oracle_price {
+                        client.positions.mark(product_id, price);
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[tonic::async_trait]
+impl PositionService for VertexClient {
+    type StreamPositionsStream =
+        Pin<Box<dyn Stream<Item = Result<PositionRecord, Status>> + Send + 'static>>;
+
+    async fn positions(
+        &self,
+        request: Request<PositionsRequest>,
+    ) -> Result<Response<PositionsResponse>, Status> {
+        let positions_request = request.into_inner();
+        let positions = self
+            .positions
+            .list(
+                positions_request.sender.as_deref(),
+                positions_request.product_id,
+            )
+            .into_iter()
+            .map(PositionRecord::from)
+            .collect();
+        Ok(Response::new(PositionsResponse { positions }))
+    }
+
+    async fn stream_positions(
+        &self,
+        request: Request<PositionsRequest>,
+    ) -> Result<Response<Self::StreamPositionsStream>, Status> {
+        let positions_request = request.into_inner();
+        let sender = positions_request.sender.map(|s| s.to_ascii_lowercase());
+        let product_id = positions_request.product_id;
+
+        // Subscribe before taking the snapshot so no update falls in between
+        let updates = self.positions.subscribe();
+        let snapshot = self.positions.list(sender.as_deref(), product_id);
+
+        let initial = stream::iter(snapshot.into_iter().map(PositionRecord::from)).map(Ok);
+        let live = stream::unfold(
+            (updates, sender, product_id),
+            |(mut updates, sender, product_id)| async move {
+                loop {
+                    match updates.recv().await {
+                        Ok(position) => {
+                            if sender.as_ref().is_some_and(|s| &position.sender != s)
+                                || product_id.is_some_and(|id| position.product_id != id)
+                            {
+                                continue;
+                            }
+                            return Some((
+                                Ok(PositionRecord::from(position)),
+                                (updates, sender, product_id),
+                            ));
+                        }
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("Position stream lagged, skipped {} updates", skipped)
+                        }
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            },
+        );
+
+        Ok(Response::new(Box::pin(initial.chain(live))))
+    }
+}
@@ -0,0 +1,484 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy_primitives::FixedBytes;
+use log::{info, warn};
+use serde::{de::DeserializeOwned, Serialize};
+use tonic::Status;
+
+use crate::{
+    config::CONFIG,
+    domain::{
+        models::vertex::{
+            stream_events::{OrderUpdate, OrderUpdateReason, StreamEvent},
+            subaccount::{
+                GatewayQueryResponse, RestingOrder, SubaccountInfo, SubaccountInfoQuery,
+                SubaccountOrders, SubaccountOrdersQuery,
+            },
+        },
+        oms::{
+            order::{ManagedOrder, OrderState},
+            order_manager::OrderFilter,
+        },
+    },
+    shared::utils::{decimal::Decimal, type_conv},
+    storage::journal::JournalEntry,
+};
+
+use super::{client::VertexClient, execute::EXPIRATION_MASK, helper::VertexHelper};
+
+// Product 0 is the quote asset, its balance is collateral rather than a position
+const QUOTE_PRODUCT_ID: u32 = 0;
+
+#[derive(Debug, Clone)]
+pub enum Discrepancy {
+    // Resting on the exchange but unknown to the OMS
+    UnknownOrder {
+        sender: String,
+        product_id: u32,
+        digest: String,
+    },
+    // Open in the OMS but no longer on the book, so a fill or cancel was missed
+    MissingOrder {
+        order_id: u64,
+        sender: String,
+        product_id: u32,
+        digest: String,
+    },
+    // Resting on both sides but with a different filled size
+    FillDrift {
+        order_id: u64,
+        digest: String,
+        local_filled: Decimal,
+        exchange_filled: Decimal,
+    },
+    PositionDrift {
+        sender: String,
+        product_id: u32,
+        local: Decimal,
+        exchange: Decimal,
+    },
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Discrepancy::UnknownOrder {
+                sender,
+                product_id,
+                digest,
+            } => write!(
+                f,
+                "unknown order {} resting for {} on product {}",
+                digest, sender, product_id
+            ),
+            Discrepancy::MissingOrder {
+                order_id,
+                sender,
+                product_id,
+                digest,
+            } => write!(
+                f,
+                "order {} ({}) for {} on product {} is open locally but not on the exchange",
+                order_id, digest, sender, product_id
+            ),
+            Discrepancy::FillDrift {
+                order_id,
+                digest,
+                local_filled,
+                exchange_filled,
+            } => write!(
+                f,
+                "order {} ({}) filled {} locally but {} on the exchange",
+                order_id, digest, local_filled, exchange_filled
+            ),
+            Discrepancy::PositionDrift {
+                sender,
+                product_id,
+                local,
+                exchange,
+            } => write!(
+                f,
+                "position for {} on product {} is {} locally but {} on the exchange",
+                sender, product_id, local, exchange
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ReconciliationReport {
+    pub discrepancies: Vec<Discrepancy>,
+    pub orphans_cancelled: usize,
+    pub errors: Vec<String>,
+}
+
+fn now_nanos() -> String {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_nanos()
+        .to_string()
+}
+
+impl VertexClient {
+    /// Compares the OMS and risk positions rebuilt from the journal with what Vertex reports.
+    ///
+    /// Runs on startup before any traffic is accepted. Exchange state wins: unknown resting orders
+    /// are adopted (or cancelled when `cancel_orphans` is set), orders missing from the book are
+    /// closed, fill drift is applied to the OMS and positions are overwritten with the exchange
+    /// balances. Every difference is reported.
+    pub async fn reconcile(&self, cancel_orphans: bool) -> ReconciliationReport {
+        let mut report = ReconciliationReport::default();
+        for subaccount in &CONFIG.subaccounts {
+            let sender = type_conv::subaccount_sender_hex(&CONFIG.sender_address, subaccount);
+            if let Err(status) = self
+                .reconcile_orders(&sender, cancel_orphans, &mut report)
+                .await
+            {
+                report
+                    .errors
+                    .push(format!("{} orders: {}", sender, status.message()));
+            }
+            if let Err(status) = self.reconcile_positions(&sender, &mut report).await {
+                report
+                    .errors
+                    .push(format!("{} positions: {}", sender, status.message()));
+            }
+        }
+
+        for discrepancy in &report.discrepancies {
+            warn!("Reconciliation: {}", discrepancy);
+        }
+        for error in &report.errors {
+            warn!("Reconciliation failed: {}", error);
+        }
+        info!(
+            "Reconciliation done: {} discrepancies, {} orphan orders cancelled",
+            report.discrepancies.len(),
+            report.orphans_cancelled
+        );
+        report
+    }
+
+    async fn reconcile_orders(
+        &self,
+        sender: &str,
+        cancel_orphans: bool,
+        report: &mut ReconciliationReport,
+    ) -> Result<(), Status> {
+        let mut resting: HashMap<String, RestingOrder> = HashMap::new();
+        let mut covered: HashSet<u32> = HashSet::new();
+        for product_id in self.registry.product_ids() {
+            if product_id == QUOTE_PRODUCT_ID {
+                continue;
+            }
+            let orders: SubaccountOrders = self
+                .query_gateway(&SubaccountOrdersQuery::new(sender, product_id))
+                .await?;
+            for order in orders.orders {
+                resting.insert(order.digest.to_ascii_lowercase(), order);
+            }
+            covered.insert(product_id);
+        }
+
+        // An order on a product the exchange was not asked about may well still be resting, so
+        // it is left as it is rather than closed
+        let (local, unchecked): (Vec<ManagedOrder>, Vec<ManagedOrder>) = self
+            .order_manager
+            .list(&OrderFilter {
+                sender: Some(sender.to_string()),
+                open_only: true,
+                ..Default::default()
+            })
+            .into_iter()
+            .partition(|o| covered.contains(&o.product_id));
+        for order in &unchecked {
+            warn!(
+                "Not reconciling order {}: product {} is not in the registry",
+                order.id, order.product_id
+            );
+        }
+
+        // Local orders the exchange no longer has
+        for order in local.iter().filter(|o| o.state != OrderState::PendingNew) {
+            let digest = order.digest.clone().unwrap_or_default();
+            if !resting.contains_key(&digest) {
+                self.close_missing(order, &digest);
+                report.discrepancies.push(Discrepancy::MissingOrder {
+                    order_id: order.id,
+                    sender: sender.to_string(),
+                    product_id: order.product_id,
+                    digest,
+                });
+            }
+        }
+
+        // Orders sent right before a crash never got their ack; older journals did not record
+        // their digest either, so those are matched by terms
+        let mut pending: Vec<&ManagedOrder> = local
+            .iter()
+            .filter(|o| o.state == OrderState::PendingNew)
+            .collect();
+
+        let mut orphans: Vec<&RestingOrder> = Vec::new();
+        for (digest, resting_order) in &resting {
+            if let Some(order) = self.order_manager.get_by_digest(digest) {
+                if order.state == OrderState::PendingNew {
+                    pending.retain(|o| o.id != order.id);
+                    self.accept_restored(order.id, digest);
+                }
+                let order = self.order_manager.get(order.id).unwrap_or(order);
+                report
+                    .discrepancies
+                    .extend(self.sync_filled(&order, resting_order));
+            } else if let Some(position) = pending.iter().position(|o| {
+                o.product_id == resting_order.product_id
+                    && o.price == resting_order.price_x18
+                    && o.amount == resting_order.amount
+            }) {
+                let order = pending.swap_remove(position);
+                self.accept_restored(order.id, digest);
+                if let Some(order) = self.order_manager.get(order.id) {
+                    report
+                        .discrepancies
+                        .extend(self.sync_filled(&order, resting_order));
+                }
+            } else {
+                report.discrepancies.push(Discrepancy::UnknownOrder {
+                    sender: sender.to_string(),
+                    product_id: resting_order.product_id,
+                    digest: digest.clone(),
+                });
+                orphans.push(resting_order);
+            }
+        }
+
+        // Whatever is still pending never reached the book
+        for order in pending {
+            self.journal.append(JournalEntry::OrderRejected {
+                order_id: order.id,
+                error: "not found on exchange after restart".to_string(),
+            });
+            self.order_manager
+                .on_rejected(order.id, "not found on exchange after restart");
+        }
+
+        if orphans.is_empty() {
+            return Ok(());
+        }
+        if cancel_orphans {
+            self.cancel_orphans(sender, &orphans).await?;
+            report.orphans_cancelled += orphans.len();
+        } else {
+            for orphan in orphans {
+                self.adopt_orphan(sender, orphan);
+            }
+        }
+        Ok(())
+    }
+
+    async fn reconcile_positions(
+        &self,
+        sender: &str,
+        report: &mut ReconciliationReport,
+    ) -> Result<(), Status> {
+        let info: SubaccountInfo = self
+            .query_gateway(&SubaccountInfoQuery::new(sender))
+            .await?;
+        if !info.exists {
+            return Ok(());
+        }
+
+        for balance in info.balances() {
+            if balance.product_id == QUOTE_PRODUCT_ID {
+                continue;
+            }
+            let local = self.risk_engine.position(sender, balance.product_id);
+            let exchange = balance.balance.amount;
+            if local != exchange {
+                report.discrepancies.push(Discrepancy::PositionDrift {
+                    sender: sender.to_string(),
+                    product_id: balance.product_id,
+                    local,
+                    exchange,
+                });
+                self.risk_engine
+                    .set_position(sender, balance.product_id, exchange);
+            }
+
+            // Entry price implied by the quote balance; spot balances carry none
+            let tracked = self.positions.get(sender, balance.product_id);
+            if tracked
+                .as_ref()
+                .map_or(exchange.is_zero(), |p| p.amount == exchange)
+            {
+                continue;
+            }
+            let entry = if balance.balance.v_quote_balance.is_zero() {
+                tracked.map(|p| p.avg_entry_price).unwrap_or_default()
+            } else {
+                Decimal::ZERO
+                    .checked_sub(balance.balance.v_quote_balance)
+                    .and_then(|cost| cost.checked_div(exchange))
+                    .unwrap_or_default()
+            };
+            self.positions
+                .reset(sender, balance.product_id, exchange, entry);
+        }
+        Ok(())
+    }
+
+    // Bring the OMS filled size in line with the exchange's unfilled amount
+    fn sync_filled(&self, order: &ManagedOrder, resting: &RestingOrder) -> Option<Discrepancy> {
+        let exchange_filled = resting
+            .amount
+            .checked_sub(resting.unfilled_amount)
+            .unwrap_or_default();
+        if exchange_filled == order.filled {
+            return None;
+        }
+
+        let update = OrderUpdate {
+            timestamp: now_nanos(),
+            product_id: resting.product_id,
+            digest: resting.digest.clone(),
+            amount: resting.unfilled_amount,
+            reason: OrderUpdateReason::Filled,
+        };
+        self.journal
+            .append(JournalEntry::OrderUpdate(update.clone()));
+        self.apply_stream_event(&StreamEvent::OrderUpdate(update));
+        Some(Discrepancy::FillDrift {
+            order_id: order.id,
+            digest: resting.digest.clone(),
+            local_filled: order.filled,
+            exchange_filled,
+        })
+    }
+
+    /// Closes every open order restored from a paper journal.
+    ///
+    /// The simulator starts with an empty book, so nothing journaled before a restart still
+    /// rests; their OMS state and risk exposure are released as if they were cancelled.
+    pub fn close_paper_orders(&self) {
+        let open = self.order_manager.list(&OrderFilter {
+            open_only: true,
+            ..Default::default()
+        });
+        for order in &open {
+            match &order.digest {
+                Some(digest) => self.close_missing(order, digest),
+                // Never signed, so never simulated either
+                None => {
+                    let error = "Paper session ended before the order was sent";
+                    self.journal.append(JournalEntry::OrderRejected {
+                        order_id: order.id,
+                        error: error.to_string(),
+                    });
+                    self.order_manager.on_rejected(order.id, error);
+                }
+            }
+        }
+        if !open.is_empty() {
+            info!("Closed {} paper orders from the last session", open.len());
+        }
+    }
+
+    // Gone from the book, so closed whichever way it went; a missed fill shows up as position
+    // drift, which the position pass takes from the exchange
+    fn close_missing(&self, order: &ManagedOrder, digest: &str) {
+        let update = OrderUpdate {
+            timestamp: now_nanos(),
+            product_id: order.product_id,
+            digest: digest.to_string(),
+            amount: order.remaining(),
+            reason: OrderUpdateReason::Cancelled,
+        };
+        self.journal
+            .append(JournalEntry::OrderUpdate(update.clone()));
+        self.apply_stream_event(&StreamEvent::OrderUpdate(update));
+    }
+
+    fn accept_restored(&self, order_id: u64, digest: &str) {
+        self.journal.append(JournalEntry::OrderAccepted {
+            order_id,
+            digest: digest.to_string(),
+        });
+        self.order_manager.on_accepted(order_id, digest);
+        if let Some(order) = self.order_manager.get(order_id) {
+            self.risk_engine.on_order_accepted(
+                &order.sender,
+                digest,
+                order.product_id,
+                order.amount,
+            );
+        }
+    }
+
+    // Start tracking a resting order placed outside this process (or lost from the journal)
+    fn adopt_orphan(&self, sender: &str, orphan: &RestingOrder) {
+        let expiration = orphan.expiration.parse::<u64>().unwrap_or_default() & EXPIRATION_MASK;
+        let order_id = self.order_manager.create_pending(
+            sender,
+            orphan.product_id,
+            &orphan.digest,
+            orphan.price_x18,
+            orphan.amount,
+            expiration,
+        );
+        self.journal.append(JournalEntry::OrderCreated {
+            order_id,
+            sender: sender.to_string(),
+            product_id: orphan.product_id,
+            digest: Some(orphan.digest.to_ascii_lowercase()),
+            price: orphan.price_x18,
+            amount: orphan.amount,
+            expiration,
+        });
+        self.accept_restored(order_id, &orphan.digest);
+        // Already reported as unknown, a partial fill on it is not a second discrepancy
+        if let Some(order) = self.order_manager.get(order_id) {
+            self.sync_filled(&order, orphan);
+        }
+    }
+
+    async fn cancel_orphans(&self, sender: &str, orphans: &[&RestingOrder]) -> Result<(), Status> {
+        let sender_bytes = type_conv::hex_to_fixed_bytes32(sender)
+            .map_err(|e| Status::invalid_argument(format!("Invalid sender: {}", e)))?;
+        let product_ids: Vec<u32> = orphans.iter().map(|o| o.product_id).collect();
+        let digests: Vec<FixedBytes<32>> = orphans
+            .iter()
+            .map(|o| type_conv::hex_to_fixed_bytes32(&o.digest))
+            .collect::<Result<_, _>>()
+            .map_err(|e| Status::internal(format!("Invalid digest from exchange: {}", e)))?;
+
+        self.cancel_orders(sender_bytes, product_ids, digests)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn query_gateway<T: DeserializeOwned, Q: Serialize>(
+        &self,
+        query: &Q,
+    ) -> Result<T, Status> {
+        let query_message = self.construct_query_message(query)?;
+        let response_data = self
+            .send_message_to_gateway(query_message)
+            .await
+            .map_err(|e| Status::unavailable(format!("Gateway query failed: {}", e)))?;
+        let response: GatewayQueryResponse<T> = serde_json::from_str(&response_data)
+            .map_err(|e| Status::internal(format!("Failed to parse JSON: {}", e)))?;
+
+        match response.data {
+            Some(data) if response.status == "success" => Ok(data),
+            _ => Err(Status::internal(format!(
+                "Gateway query failed: {}",
+                response.error.unwrap_or(response.status)
+            ))),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use log::{error, info};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    config::CONFIG,
+    connectors::vertex::gateway_client::GatewayClient,
+    vertex_products::{ProductDetail, ProductsResponse},
+    vertex_query::{ContractsData, ContractsResponse},
+    vertex_symbols::{Symbol, SymbolsResponse},
+};
+
+const CONTRACTS_QUERY: &str = r#"{"type":"contracts"}"#;
+const PRODUCTS_QUERY: &str = r#"{"type":"all_products"}"#;
+const SYMBOLS_QUERY: &str = r#"{"type":"symbols"}"#;
+
+// Point-in-time copy of the exchange metadata, swapped wholesale on every refresh
+#[derive(Debug, Default)]
+struct RegistrySnapshot {
+    contracts: Option<ContractsData>,
+    products: HashMap<u32, ProductDetail>,
+    symbols: HashMap<String, Symbol>,
+    symbols_by_product: HashMap<u32, String>,
+}
+
+/// Caches contracts, products and symbols so the hot path never has to query the gateway.
+///
+/// The registry is loaded once at startup and then refreshed in the background every
+/// `REGISTRY_REFRESH_SECS` seconds. Lookups take a read lock and never block on the network.
+#[derive(Debug)]
+pub struct ProductRegistry {
+    gateway_client: Arc<GatewayClient>,
+    snapshot: RwLock<RegistrySnapshot>,
+}
+
+impl ProductRegistry {
+    pub fn new(gateway_client: Arc<GatewayClient>) -> Self {
+        ProductRegistry {
+            gateway_client,
+            snapshot: RwLock::new(RegistrySnapshot::default()),
+        }
+    }
+
+    // Fetch contracts, products and symbols and replace the cached snapshot
+    pub async fn load(&self) -> Result<(), Box<dyn Error + Send>> {
+        let contracts: ContractsResponse = self.query(CONTRACTS_QUERY).await?;
+        let products: ProductsResponse = self.query(PRODUCTS_QUERY).await?;
+        let symbols: SymbolsResponse = self.query(SYMBOLS_QUERY).await?;
+
+        let mut snapshot = RegistrySnapshot {
+            contracts: contracts.data,
+            ..Default::default()
+        };
+
+        if let Some(data) = products.data {
+            for product in data.spot_products.into_iter().chain(data.perp_products) {
+                snapshot.products.insert(product.product_id, product);
+            }
+        }
+
+        if let Some(data) = symbols.data {
+            for (name, symbol) in data.symbols {
+                snapshot
+                    .symbols_by_product
+                    .insert(symbol.product_id as u32, name.clone());
+                snapshot.symbols.insert(name, symbol);
+            }
+        }
+
+        info!(
+            "Registry loaded {} products and {} symbols",
+            snapshot.products.len(),
+            snapshot.symbols.len()
+        );
+        *self.snapshot.write().unwrap() = snapshot;
+        Ok(())
+    }
+
+    // Keep the snapshot fresh; a failed refresh keeps serving the previous snapshot
+    pub fn spawn_refresh(self: &Arc<Self>) {
+        let registry = Arc::clone(self);
+        let period = Duration::from_secs(CONFIG.registry_refresh_secs);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.tick().await; // first tick completes immediately, startup already loaded
+            loop {
+                interval.tick().await;
+                if let Err(e) = registry.load().await {
+                    error!("Failed to refresh product registry: {}", e);
+                }
+            }
+        });
+    }
+
+    async fn query<T: DeserializeOwned>(&self, message: &str) -> Result<T, Box<dyn Error + Send>> {
+        let response = self
+            .gateway_client
+            .send_message(message.to_string())
+            .await?;
+        serde_json::from_str(&response).map_err(|e| Box::new(e) as Box<dyn Error + Send>)
+    }
+
+    // Verifying contract used to sign orders for the given product
+    pub fn book_addr(&self, product_id: u32) -> Option<String> {
+        let snapshot = self.snapshot.read().unwrap();
+        snapshot
+            .contracts
+            .as_ref()
+            .and_then(|c| c.book_addrs.get(product_id as usize).cloned())
+    }
+
+    // Verifying contract for cancellations and other endpoint transactions
+    pub fn endpoint_addr(&self) -> Option<String> {
+        let snapshot = self.snapshot.read().unwrap();
+        snapshot.contracts.as_ref().map(|c| c.endpoint_addr.clone())
+    }
+
+    pub fn product(&self, product_id: u32) -> Option<ProductDetail> {
+        self.snapshot
+            .read()
+            .unwrap()
+            .products
+            .get(&product_id)
+            .cloned()
+    }
+
+    pub fn product_ids(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = self
+            .snapshot
+            .read()
+            .unwrap()
+            .products
+            .keys()
+            .copied()
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    pub fn symbols(&self) -> Vec<Symbol> {
+        let mut symbols: Vec<Symbol> = self
+            .snapshot
+            .read()
+            .unwrap()
+            .symbols
+            .values()
+            .cloned()
+            .collect();
+        symbols.sort_by_key(|s| s.product_id);
+        symbols
+    }
+
+    pub fn symbol(&self, name: &str) -> Option<Symbol> {
+        self.snapshot.read().unwrap().symbols.get(name).cloned()
+    }
+
+    pub fn symbol_for_product(&self, product_id: u32) -> Option<Symbol> {
+        let snapshot = self.snapshot.read().unwrap();
+        snapshot
+            .symbols_by_product
+            .get(&product_id)
+            .and_then(|name| snapshot.symbols.get(name))
+            .cloned()
+    }
+
+    pub fn product_id_for_symbol(&self, name: &str) -> Option<u32> {
+        self.snapshot
+            .read()
+            .unwrap()
+            .symbols
+            .get(name)
+            .map(|symbol| symbol.product_id as u32)
+    }
+}
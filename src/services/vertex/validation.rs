@@ -0,0 +1,244 @@
+use crate::{
+    config::RoundingPolicy,
+    shared::{
+        errors::connector_error::ConnectorError,
+        utils::decimal::{Decimal, RoundingMode},
+    },
+    vertex_symbols::Symbol,
+};
+
+// Order price and size after checking them against the symbol's book rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidatedOrder {
+    pub price_x18: i128,
+    pub amount_x18: i128,
+}
+
+fn symbol_field(symbol: &Symbol, value: &str, name: &str) -> Result<Decimal, ConnectorError> {
+    Decimal::from_x18_str(value).map_err(|e| {
+        ConnectorError::Internal(format!(
+            "Symbol {} has an unreadable {}: {}",
+            symbol.symbol, name, e
+        ))
+    })
+}
+
+// Increments must be positive for anything to be a multiple of them
+fn increment_field(symbol: &Symbol, value: &str, name: &str) -> Result<Decimal, ConnectorError> {
+    let increment = symbol_field(symbol, value, name)?;
+    if increment <= Decimal::ZERO {
+        return Err(ConnectorError::Internal(format!(
+            "Symbol {} has a non-positive {}: {}",
+            symbol.symbol, name, increment
+        )));
+    }
+    Ok(increment)
+}
+
+/// Checks an order against the symbol's tick size, size increment and minimum size.
+///
+/// Off-increment values are rejected or rounded according to `policy`. Order failures are
+/// `InvalidRequest` so clients can tell them apart from gateway errors; unusable symbol
+/// metadata is `Internal`.
+pub fn validate_order(
+    symbol: &Symbol,
+    price_x18: i128,
+    amount_x18: i128,
+    policy: RoundingPolicy,
+) -> Result<ValidatedOrder, ConnectorError> {
+    let price_increment = increment_field(symbol, &symbol.price_increment_x18, "price increment")?;
+    let size_increment = increment_field(symbol, &symbol.size_increment, "size increment")?;
+    let min_size = symbol_field(symbol, &symbol.min_size, "min size")?;
+
+    let price = Decimal::from_x18(price_x18);
+    let amount = Decimal::from_x18(amount_x18);
+    let is_buy = !amount.is_negative();
+
+    if price <= Decimal::ZERO {
+        return Err(ConnectorError::InvalidRequest(format!(
+            "Price must be positive, got {}",
+            price
+        )));
+    }
+    if amount.is_zero() {
+        return Err(ConnectorError::InvalidRequest(
+            "Order size must not be zero".to_string(),
+        ));
+    }
+
+    let (price_mode, size_mode) = match policy {
+        RoundingPolicy::Reject => {
+            if !price.is_multiple_of(price_increment) {
+                return Err(ConnectorError::InvalidRequest(format!(
+                    "Price {} is not a multiple of the {} tick size {}",
+                    price, symbol.symbol, price_increment
+                )));
+            }
+            if !amount.is_multiple_of(size_increment) {
+                return Err(ConnectorError::InvalidRequest(format!(
+                    "Size {} is not a multiple of the {} size increment {}",
+                    amount, symbol.symbol, size_increment
+                )));
+            }
+            (RoundingMode::Nearest, RoundingMode::Nearest)
+        }
+        RoundingPolicy::Nearest => (RoundingMode::Nearest, RoundingMode::Nearest),
+        RoundingPolicy::Passive if is_buy => (RoundingMode::Floor, RoundingMode::TowardZero),
+        RoundingPolicy::Passive => (RoundingMode::Ceil, RoundingMode::TowardZero),
+    };
+
+    let price = price
+        .round_to_increment(price_increment, price_mode)
+        .ok_or_else(|| {
+            ConnectorError::InvalidRequest(format!(
+                "Price {} cannot be rounded to the {} tick size {}",
+                price, symbol.symbol, price_increment
+            ))
+        })?;
+    let amount = amount
+        .round_to_increment(size_increment, size_mode)
+        .ok_or_else(|| {
+            ConnectorError::InvalidRequest(format!(
+                "Size {} cannot be rounded to the {} size increment {}",
+                amount, symbol.symbol, size_increment
+            ))
+        })?;
+
+    if price <= Decimal::ZERO || amount.is_zero() {
+        return Err(ConnectorError::InvalidRequest(format!(
+            "Order rounds to zero on {} (tick size {}, size increment {})",
+            symbol.symbol, price_increment, size_increment
+        )));
+    }
+
+    // min_size is denominated in quote, i.e. it bounds the order notional
+    let notional = amount
+        .abs()
+        .checked_mul(price)
+        .ok_or_else(|| ConnectorError::InvalidRequest("Order notional overflows".to_string()))?;
+    if notional < min_size {
+        return Err(ConnectorError::InvalidRequest(format!(
+            "Order notional {} is below the {} minimum size {}",
+            notional, symbol.symbol, min_size
+        )));
+    }
+
+    Ok(ValidatedOrder {
+        price_x18: price.to_x18(),
+        amount_x18: amount.to_x18(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn x18(value: &str) -> i128 {
+        value.parse::<Decimal>().unwrap().to_x18()
+    }
+
+    // Tick size 0.5, size increment 0.01, minimum notional 10
+    fn symbol() -> Symbol {
+        Symbol {
+            symbol: "BTC-PERP".to_string(),
+            price_increment_x18: x18("0.5").to_string(),
+            size_increment: x18("0.01").to_string(),
+            min_size: x18("10").to_string(),
+            ..Symbol::default()
+        }
+    }
+
+    fn validate(
+        price: &str,
+        amount: &str,
+        policy: RoundingPolicy,
+    ) -> Result<(i128, i128), ConnectorError> {
+        validate_order(&symbol(), x18(price), x18(amount), policy)
+            .map(|order| (order.price_x18, order.amount_x18))
+    }
+
+    #[test]
+    fn passes_orders_on_increments_unchanged() {
+        for policy in [
+            RoundingPolicy::Reject,
+            RoundingPolicy::Nearest,
+            RoundingPolicy::Passive,
+        ] {
+            assert_eq!(
+                validate("100.5", "-0.25", policy),
+                Ok((x18("100.5"), x18("-0.25")))
+            );
+        }
+    }
+
+    #[test]
+    fn reject_policy_refuses_off_increment_values() {
+        assert!(matches!(
+            validate("100.3", "0.25", RoundingPolicy::Reject),
+            Err(ConnectorError::InvalidRequest(_))
+        ));
+        assert!(matches!(
+            validate("100.5", "0.255", RoundingPolicy::Reject),
+            Err(ConnectorError::InvalidRequest(_))
+        ));
+    }
+
+    #[test]
+    fn passive_rounding_moves_away_from_the_touch_and_shrinks_size() {
+        assert_eq!(
+            validate("100.3", "0.259", RoundingPolicy::Passive),
+            Ok((x18("100"), x18("0.25")))
+        );
+        assert_eq!(
+            validate("100.3", "-0.259", RoundingPolicy::Passive),
+            Ok((x18("100.5"), x18("-0.25")))
+        );
+        assert_eq!(
+            validate("100.3", "0.259", RoundingPolicy::Nearest),
+            Ok((x18("100.5"), x18("0.26")))
+        );
+    }
+
+    #[test]
+    fn refuses_orders_that_round_away_or_fall_below_the_minimum() {
+        for (price, amount) in [("0", "1"), ("100", "0"), ("0.2", "1"), ("100", "0.005")] {
+            assert!(
+                matches!(
+                    validate(price, amount, RoundingPolicy::Passive),
+                    Err(ConnectorError::InvalidRequest(_))
+                ),
+                "{} x {} passed",
+                price,
+                amount
+            );
+        }
+        // 0.09 at 100 is a notional of 9
+        assert!(matches!(
+            validate("100", "0.09", RoundingPolicy::Reject),
+            Err(ConnectorError::InvalidRequest(_))
+        ));
+        assert!(validate("100", "0.1", RoundingPolicy::Reject).is_ok());
+    }
+
+    #[test]
+    fn values_that_cannot_be_rounded_are_refused_rather_than_sent_as_is() {
+        let result = validate_order(&symbol(), i128::MAX, x18("-1"), RoundingPolicy::Passive);
+        assert!(matches!(result, Err(ConnectorError::InvalidRequest(_))));
+    }
+
+    #[test]
+    fn unusable_symbol_metadata_is_an_internal_error() {
+        let mut symbol = symbol();
+        symbol.price_increment_x18 = "0".to_string();
+        assert!(matches!(
+            validate_order(&symbol, x18("100"), x18("1"), RoundingPolicy::Nearest),
+            Err(ConnectorError::Internal(_))
+        ));
+
+        symbol.price_increment_x18 = "half".to_string();
+        assert!(matches!(
+            validate_order(&symbol, x18("100"), x18("1"), RoundingPolicy::Nearest),
+            Err(ConnectorError::Internal(_))
+        ));
+    }
+}
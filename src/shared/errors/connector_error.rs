@@ -0,0 +1,65 @@
+use std::fmt;
+
+use tonic::Status;
+
+// Venue-neutral error returned by every `Connector`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectorError {
+    // The request is malformed or breaks the venue's rules
+    InvalidRequest(String),
+    // The venue refused the request
+    Rejected(String),
+    NotFound(String),
+    // The venue, or local state it needs, is not reachable right now
+    Unavailable(String),
+    // The venue does not support the operation
+    Unsupported(String),
+    // Local failure, e.g. an unparseable response
+    Internal(String),
+}
+
+impl std::error::Error for ConnectorError {}
+
+impl fmt::Display for ConnectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnectorError::InvalidRequest(msg) => write!(f, "invalid request: {}", msg),
+            ConnectorError::Rejected(msg) => write!(f, "rejected by venue: {}", msg),
+            ConnectorError::NotFound(msg) => write!(f, "not found: {}", msg),
+            ConnectorError::Unavailable(msg) => write!(f, "unavailable: {}", msg),
+            ConnectorError::Unsupported(msg) => write!(f, "unsupported: {}", msg),
+            ConnectorError::Internal(msg) => write!(f, "internal error: {}", msg),
+        }
+    }
+}
+
+impl From<Status> for ConnectorError {
+    fn from(status: Status) -> Self {
+        let message = status.message().to_string();
+        match status.code() {
+            tonic::Code::InvalidArgument | tonic::Code::OutOfRange => {
+                ConnectorError::InvalidRequest(message)
+            }
+            tonic::Code::FailedPrecondition | tonic::Code::PermissionDenied => {
+                ConnectorError::Rejected(message)
+            }
+            tonic::Code::NotFound => ConnectorError::NotFound(message),
+            tonic::Code::Unavailable => ConnectorError::Unavailable(message),
+            tonic::Code::Unimplemented => ConnectorError::Unsupported(message),
+            _ => ConnectorError::Internal(message),
+        }
+    }
+}
+
+impl From<ConnectorError> for Status {
+    fn from(err: ConnectorError) -> Self {
+        match err {
+            ConnectorError::InvalidRequest(msg) => Status::invalid_argument(msg),
+            ConnectorError::Rejected(msg) => Status::failed_precondition(msg),
+            ConnectorError::NotFound(msg) => Status::not_found(msg),
+            ConnectorError::Unavailable(msg) => Status::unavailable(msg),
+            ConnectorError::Unsupported(msg) => Status::unimplemented(msg),
+            ConnectorError::Internal(msg) => Status::internal(msg),
+        }
+    }
+}
@@ -1,2 +1,4 @@
 pub mod api_error;
 pub mod connect_error;
+pub mod connector_error;
+pub mod strategy_error;
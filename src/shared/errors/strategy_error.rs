@@ -0,0 +1,39 @@
+use std::fmt;
+
+use tonic::Status;
+
+// Error from starting, stopping or configuring a strategy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StrategyError {
+    // No factory is registered under this kind
+    UnknownKind(String),
+    AlreadyRunning(String),
+    NotRunning(String),
+    // The strategy refused its parameters
+    InvalidParams(String),
+}
+
+impl std::error::Error for StrategyError {}
+
+impl fmt::Display for StrategyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StrategyError::UnknownKind(kind) => write!(f, "unknown strategy kind: {}", kind),
+            StrategyError::AlreadyRunning(id) => write!(f, "strategy {} is already running", id),
+            StrategyError::NotRunning(id) => write!(f, "strategy {} is not running", id),
+            StrategyError::InvalidParams(msg) => write!(f, "invalid parameters: {}", msg),
+        }
+    }
+}
+
+impl From<StrategyError> for Status {
+    fn from(err: StrategyError) -> Self {
+        match err {
+            StrategyError::UnknownKind(_) | StrategyError::InvalidParams(_) => {
+                Status::invalid_argument(err.to_string())
+            }
+            StrategyError::AlreadyRunning(_) => Status::already_exists(err.to_string()),
+            StrategyError::NotRunning(_) => Status::not_found(err.to_string()),
+        }
+    }
+}
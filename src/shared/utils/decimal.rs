@@ -0,0 +1,422 @@
+use alloy_primitives::U256;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+// Vertex expresses prices and amounts as integers scaled by 1e18 (X18)
+pub const X18_DECIMALS: usize = 18;
+pub const X18_SCALE: i128 = 1_000_000_000_000_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecimalError {
+    Empty,
+    Invalid(String),
+    TooPrecise(String),
+    Overflow,
+}
+
+impl std::error::Error for DecimalError {}
+
+impl fmt::Display for DecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecimalError::Empty => write!(f, "empty decimal string"),
+            DecimalError::Invalid(s) => write!(f, "invalid decimal: {}", s),
+            DecimalError::TooPrecise(s) => {
+                write!(f, "{} has more than {} decimal places", s, X18_DECIMALS)
+            }
+            DecimalError::Overflow => write!(f, "decimal overflow"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    Floor,
+    Ceil,
+    Nearest,
+    TowardZero,
+}
+
+/// Fixed-point decimal with 18 fractional digits, stored as its X18 integer.
+///
+/// Parsing and formatting are exact, so `"0.1"` maps to `100000000000000000` and back without
+/// going through floating point.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Decimal(i128);
+
+impl Decimal {
+    pub const ZERO: Decimal = Decimal(0);
+    pub const ONE: Decimal = Decimal(X18_SCALE);
+
+    pub const fn from_x18(raw: i128) -> Self {
+        Decimal(raw)
+    }
+
+    pub const fn to_x18(self) -> i128 {
+        self.0
+    }
+
+    // Parse a raw X18 integer string as sent by the gateway
+    pub fn from_x18_str(value: &str) -> Result<Self, DecimalError> {
+        value
+            .trim()
+            .parse::<i128>()
+            .map(Decimal)
+            .map_err(|_| DecimalError::Invalid(value.to_string()))
+    }
+
+    pub fn from_int(value: i64) -> Self {
+        Decimal(value as i128 * X18_SCALE)
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    // Saturates at the largest value rather than overflowing on the most negative one
+    pub fn abs(self) -> Self {
+        Decimal(self.0.saturating_abs())
+    }
+
+    pub fn checked_add(self, other: Decimal) -> Option<Decimal> {
+        self.0.checked_add(other.0).map(Decimal)
+    }
+
+    pub fn checked_sub(self, other: Decimal) -> Option<Decimal> {
+        self.0.checked_sub(other.0).map(Decimal)
+    }
+
+    // Products of two X18 values need 256 bits before rescaling
+    pub fn checked_mul(self, other: Decimal) -> Option<Decimal> {
+        let product = U256::from(self.0.unsigned_abs()) * U256::from(other.0.unsigned_abs());
+        let scaled = product / U256::from(X18_SCALE as u128);
+        Self::with_sign(scaled, (self.0 < 0) != (other.0 < 0))
+    }
+
+    pub fn checked_div(self, other: Decimal) -> Option<Decimal> {
+        if other.0 == 0 {
+            return None;
+        }
+        let numerator = U256::from(self.0.unsigned_abs()) * U256::from(X18_SCALE as u128);
+        let quotient = numerator / U256::from(other.0.unsigned_abs());
+        Self::with_sign(quotient, (self.0 < 0) != (other.0 < 0))
+    }
+
+    fn with_sign(magnitude: U256, negative: bool) -> Option<Decimal> {
+        let magnitude: u128 = magnitude.try_into().ok()?;
+        let value = i128::try_from(magnitude).ok()?;
+        Some(Decimal(if negative { -value } else { value }))
+    }
+
+    pub fn is_multiple_of(self, increment: Decimal) -> bool {
+        increment.0 == 0 || self.0 % increment.0 == 0
+    }
+
+    // Snap to a multiple of `increment`, e.g. a tick size; None on a zero increment or overflow
+    pub fn round_to_increment(self, increment: Decimal, mode: RoundingMode) -> Option<Decimal> {
+        let step = increment.0.checked_abs().filter(|step| *step != 0)?;
+        let floor = self.0.div_euclid(step).checked_mul(step)?;
+        let remainder = self.0 - floor;
+        if remainder == 0 {
+            return Some(self);
+        }
+
+        let ceil = floor.checked_add(step)?;
+        let rounded = match mode {
+            RoundingMode::Floor => floor,
+            RoundingMode::Ceil => ceil,
+            RoundingMode::Nearest if remainder * 2 >= step => ceil,
+            RoundingMode::Nearest => floor,
+            RoundingMode::TowardZero if self.0 < 0 => ceil,
+            RoundingMode::TowardZero => floor,
+        };
+        Some(Decimal(rounded))
+    }
+
+    // Lossy, only meant for statistics and reporting
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / X18_SCALE as f64
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = DecimalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        if trimmed.is_empty() {
+            return Err(DecimalError::Empty);
+        }
+
+        let (negative, unsigned) = match trimmed.as_bytes()[0] {
+            b'-' => (true, &trimmed[1..]),
+            b'+' => (false, &trimmed[1..]),
+            _ => (false, trimmed),
+        };
+        let (int_part, frac_part) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+
+        let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if (int_part.is_empty() && frac_part.is_empty())
+            || !all_digits(int_part)
+            || !all_digits(frac_part)
+        {
+            return Err(DecimalError::Invalid(s.to_string()));
+        }
+
+        // Extra digits are only acceptable when they carry no value
+        let frac_trimmed = frac_part.trim_end_matches('0');
+        if frac_trimmed.len() > X18_DECIMALS {
+            return Err(DecimalError::TooPrecise(s.to_string()));
+        }
+
+        let int_value: i128 = if int_part.is_empty() {
+            0
+        } else {
+            int_part.parse().map_err(|_| DecimalError::Overflow)?
+        };
+        let frac_value: i128 = if frac_trimmed.is_empty() {
+            0
+        } else {
+            format!("{:0<width$}", frac_trimmed, width = X18_DECIMALS)
+                .parse()
+                .map_err(|_| DecimalError::Invalid(s.to_string()))?
+        };
+
+        // Built with its sign so the most negative X18 value still parses
+        let scaled = int_value.checked_mul(X18_SCALE);
+        let value = if negative {
+            scaled
+                .and_then(|v| v.checked_neg())
+                .and_then(|v| v.checked_sub(frac_value))
+        } else {
+            scaled.and_then(|v| v.checked_add(frac_value))
+        };
+        value.map(Decimal).ok_or(DecimalError::Overflow)
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let magnitude = self.0.unsigned_abs();
+        let scale = X18_SCALE as u128;
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let int_part = magnitude / scale;
+        let frac_part = magnitude % scale;
+
+        if frac_part == 0 {
+            write!(f, "{}{}", sign, int_part)
+        } else {
+            let frac = format!("{:0>width$}", frac_part, width = X18_DECIMALS);
+            write!(f, "{}{}.{}", sign, int_part, frac.trim_end_matches('0'))
+        }
+    }
+}
+
+// Serialized as a decimal string so JSON never rounds through f64
+impl Serialize for Decimal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DecimalVisitor;
+
+        impl<'de> de::Visitor<'de> for DecimalVisitor {
+            type Value = Decimal;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a decimal string or number")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Decimal, E> {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Decimal, E> {
+                Ok(Decimal::from_int(v))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Decimal, E> {
+                i64::try_from(v)
+                    .map(Decimal::from_int)
+                    .map_err(|_| E::custom(DecimalError::Overflow))
+            }
+
+            // Some venues send JSON numbers; the shortest round-trip form keeps what they sent
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Decimal, E> {
+                if !v.is_finite() {
+                    return Err(E::custom(DecimalError::Invalid(v.to_string())));
+                }
+                v.to_string().parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(DecimalVisitor)
+    }
+}
+
+// `#[serde(with = "x18")]` for fields carried on the wire as raw X18 integer strings
+pub mod x18 {
+    use super::Decimal;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_x18().to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        Decimal::from_x18_str(&raw).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(human: &str, raw: i128) {
+        let parsed: Decimal = human.parse().unwrap();
+        assert_eq!(parsed.to_x18(), raw, "{} parsed", human);
+        assert_eq!(parsed.to_string(), human, "{} formatted", human);
+        assert_eq!(Decimal::from_x18_str(&raw.to_string()).unwrap(), parsed);
+    }
+
+    #[test]
+    fn human_and_x18_round_trip() {
+        round_trip("0", 0);
+        round_trip("1", X18_SCALE);
+        round_trip("-1", -X18_SCALE);
+        round_trip("0.1", 100_000_000_000_000_000);
+        round_trip("42000.5", 42_000_500_000_000_000_000_000);
+        round_trip("-0.000000000000000001", -1);
+        round_trip("0.000000000000000001", 1);
+        round_trip(
+            "123456789.123456789123456789",
+            123_456_789_123_456_789_123_456_789,
+        );
+    }
+
+    #[test]
+    fn i128_bounds_round_trip() {
+        round_trip("170141183460469231731.687303715884105727", i128::MAX);
+        round_trip("-170141183460469231731.687303715884105728", i128::MIN);
+    }
+
+    #[test]
+    fn parsing_normalizes_equivalent_forms() {
+        let parse = |s: &str| s.parse::<Decimal>().unwrap().to_x18();
+        assert_eq!(parse("+1.50"), parse("1.5"));
+        assert_eq!(parse(".5"), parse("0.5"));
+        assert_eq!(parse("5."), parse("5"));
+        assert_eq!(parse(" 2 "), parse("2"));
+        assert_eq!(parse("-0"), 0);
+        assert_eq!(parse("1.0000000000000000000000"), X18_SCALE);
+    }
+
+    #[test]
+    fn parsing_rejects_bad_input() {
+        assert_eq!("".parse::<Decimal>(), Err(DecimalError::Empty));
+        assert_eq!("  ".parse::<Decimal>(), Err(DecimalError::Empty));
+        assert!(matches!(
+            "abc".parse::<Decimal>(),
+            Err(DecimalError::Invalid(_))
+        ));
+        assert!(matches!(
+            "1.2.3".parse::<Decimal>(),
+            Err(DecimalError::Invalid(_))
+        ));
+        assert!(matches!(
+            "-".parse::<Decimal>(),
+            Err(DecimalError::Invalid(_))
+        ));
+        assert!(matches!(
+            ".".parse::<Decimal>(),
+            Err(DecimalError::Invalid(_))
+        ));
+        assert!(matches!(
+            "1e5".parse::<Decimal>(),
+            Err(DecimalError::Invalid(_))
+        ));
+        assert!(matches!(
+            "0.0000000000000000001".parse::<Decimal>(),
+            Err(DecimalError::TooPrecise(_))
+        ));
+        assert_eq!(
+            "170141183460469231731.687303715884105728".parse::<Decimal>(),
+            Err(DecimalError::Overflow)
+        );
+        assert_eq!(
+            "-170141183460469231731.687303715884105729".parse::<Decimal>(),
+            Err(DecimalError::Overflow)
+        );
+        assert_eq!(
+            "999999999999999999999999".parse::<Decimal>(),
+            Err(DecimalError::Overflow)
+        );
+    }
+
+    #[test]
+    fn abs_saturates_at_the_minimum() {
+        assert_eq!(
+            Decimal::from_x18(i128::MIN).abs(),
+            Decimal::from_x18(i128::MAX)
+        );
+        assert_eq!(Decimal::from_x18(-5).abs(), Decimal::from_x18(5));
+        assert_eq!(
+            Decimal::from_x18(i128::MAX).abs(),
+            Decimal::from_x18(i128::MAX)
+        );
+    }
+
+    #[test]
+    fn checked_arithmetic_reports_overflow() {
+        let max = Decimal::from_x18(i128::MAX);
+        assert_eq!(max.checked_add(Decimal::from_x18(1)), None);
+        assert_eq!(
+            Decimal::from_x18(i128::MIN).checked_sub(Decimal::from_x18(1)),
+            None
+        );
+        assert_eq!(max.checked_mul(Decimal::from_int(2)), None);
+        assert_eq!(Decimal::ONE.checked_div(Decimal::ZERO), None);
+        assert_eq!(
+            Decimal::from_int(3).checked_mul("0.5".parse().unwrap()),
+            Some("1.5".parse().unwrap())
+        );
+        assert_eq!(
+            Decimal::from_int(-3).checked_div(Decimal::from_int(2)),
+            Some("-1.5".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn serde_keeps_every_digit() {
+        let value: Decimal = "0.123456789012345678".parse().unwrap();
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, "\"0.123456789012345678\"");
+        assert_eq!(serde_json::from_str::<Decimal>(&json).unwrap(), value);
+        assert_eq!(
+            serde_json::from_str::<Decimal>("0.1").unwrap().to_x18(),
+            100_000_000_000_000_000
+        );
+    }
+
+    #[test]
+    fn rounds_to_increment() {
+        let tick: Decimal = "0.5".parse().unwrap();
+        let price: Decimal = "10.3".parse().unwrap();
+        let round = |mode| price.round_to_increment(tick, mode).unwrap().to_string();
+        assert_eq!(round(RoundingMode::Floor), "10");
+        assert_eq!(round(RoundingMode::Ceil), "10.5");
+        assert_eq!(round(RoundingMode::Nearest), "10.5");
+        assert_eq!(
+            price.round_to_increment(Decimal::ZERO, RoundingMode::Floor),
+            None
+        );
+    }
+}
@@ -1,3 +1,4 @@
+pub mod decimal;
 pub mod eth_signer;
 pub mod type_conv;
 pub mod websocket_utils;
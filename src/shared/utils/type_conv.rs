@@ -44,10 +44,19 @@ pub fn fixed_bytes_to_hex(bytes: &FixedBytes<32>) -> String {
     hex::encode(bytes)
 }
 
-pub fn string_and_i128(value: &str) -> i128 {
-    return value.parse::<i128>().map(|val| val as i128).unwrap();
+pub fn string_and_i128(value: &str) -> Result<i128, std::num::ParseIntError> {
+    value.parse::<i128>()
 }
 
-pub fn hex_to_bytes(hex_str: &str) -> Vec<u8> {
-    hex::decode(hex_str).unwrap()
+pub fn hex_to_bytes(hex_str: &str) -> Result<Vec<u8>, hex::FromHexError> {
+    hex::decode(hex_str)
+}
+
+// Vertex subaccounts are the 20 byte owner address followed by a 12 byte name
+pub fn subaccount_sender_hex(address: &str, subaccount_name: &str) -> String {
+    format!(
+        "0x{}{:0<24}",
+        address.trim_start_matches("0x"),
+        hex::encode(subaccount_name.as_bytes())
+    )
 }
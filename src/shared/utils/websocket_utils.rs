@@ -1,11 +1,7 @@
-use futures_util::stream::SplitStream;
-use futures_util::StreamExt;
 use log::{error, info};
 use tokio::net::TcpStream;
 use tokio::time::{sleep, Duration};
-use tokio_tungstenite::{
-    connect_async, tungstenite::protocol::Message, MaybeTlsStream, WebSocketStream,
-};
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
 use url::Url;
 
 use crate::shared::errors::connect_error::ConnectError;
@@ -37,27 +33,3 @@ pub async fn connect_websocket(
         }
     }
 }
-
-/// Listens to messages from the WebSocket stream and handles them.
-///
-/// # Arguments
-/// * `ws_stream` - The WebSocket stream to listen to.
-///
-/// # Note
-/// This function demonstrates handling incoming WebSocket messages and errors in a robust manner,
-/// suitable for a production environment.
-pub async fn handle_websocket_messages(
-    mut ws_reader: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
-) {
-    while let Some(message) = ws_reader.next().await {
-        match message {
-            Ok(msg) => match msg {
-                Message::Text(text) => info!("Received text message: {}", text),
-                Message::Binary(bin) => info!("Received binary message: {:?}", bin),
-                // Handle other message types as necessary
-                _ => info!("Received other message."),
-            },
-            Err(e) => info!("Error receiving message: {:?}", e),
-        }
-    }
-}
@@ -0,0 +1,483 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    thread::{self, JoinHandle},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::{error, info, warn};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use crate::{
+    domain::models::vertex::stream_events::{Fill, OrderUpdate, PositionChange},
+    shared::utils::decimal::Decimal,
+};
+
+const SEGMENT_PREFIX: &str = "journal-";
+const SEGMENT_SUFFIX: &str = ".log";
+const SNAPSHOT_PREFIX: &str = "snapshot-";
+const SNAPSHOT_SUFFIX: &str = ".json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JournalEntry {
+    OrderCreated {
+        order_id: u64,
+        sender: String,
+        product_id: u32,
+        // Locally computed order digest; absent in journals written before it was recorded
+        digest: Option<String>,
+        price: Decimal,
+        amount: Decimal,
+        expiration: u64,
+    },
+    // Exactly what went to the gateway, signature included
+    SignedTx {
+        tx_type: String,
+        order_id: Option<u64>,
+        payload: serde_json::Value,
+    },
+    // Raw gateway reply, kept verbatim for the audit trail
+    GatewayResponse {
+        tx_type: String,
+        order_id: Option<u64>,
+        response: String,
+    },
+    OrderAccepted {
+        order_id: u64,
+        digest: String,
+    },
+    OrderRejected {
+        order_id: u64,
+        error: String,
+    },
+    OrdersCancelled {
+        sender: String,
+        digests: Vec<String>,
+    },
+    ProductsCancelled {
+        sender: String,
+        product_ids: Vec<u32>,
+    },
+    OrderUpdate(OrderUpdate),
+    Fill(Fill),
+    PositionChange(PositionChange),
+}
+
+// One line of a segment file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalRecord {
+    pub seq: u64,
+    pub ts_ms: u64,
+    pub entry: JournalEntry,
+}
+
+// A snapshot file: the owner's state once every record up to `seq` was applied
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot<S> {
+    seq: u64,
+    ts_ms: u64,
+    state: S,
+}
+
+// What `Journal::open` found on disk: the newest snapshot, if any, and the records after it
+#[derive(Debug)]
+pub struct JournalReplay<S> {
+    pub state: Option<S>,
+    pub records: Vec<JournalRecord>,
+}
+
+#[derive(Debug)]
+enum Command {
+    Append {
+        entry: JournalEntry,
+        ts_ms: u64,
+        // Told the outcome once the record is on disk (and synced with `fsync`)
+        ack: Option<oneshot::Sender<io::Result<()>>>,
+    },
+    Snapshot(serde_json::Value),
+}
+
+#[derive(Debug)]
+struct SegmentWriter {
+    dir: PathBuf,
+    max_segment_bytes: u64,
+    fsync: bool,
+    file: BufWriter<File>,
+    segment: u64,
+    written: u64,
+    seq: u64,
+}
+
+/// Append-only journal of signed transactions, gateway responses and fills.
+///
+/// Records are newline-delimited JSON spread over numbered segment files in `dir`
+/// (`journal-0000000001.log`, ...). Segments are never rewritten: every start opens a fresh
+/// segment and a new one is started once the current one reaches `max_segment_bytes`.
+///
+/// Writes happen on a dedicated thread, so `append` never blocks the caller on disk I/O; records
+/// still reach the file in append order, each flushed (and synced with `fsync`) as it is
+/// written. `append_acked` waits for that write, for records that must be on disk before
+/// whatever they describe happens. `snapshot` stores the owner's state as `snapshot-<segment>.json`, covering every
+/// segment before that number, and `open` replays only the newest readable snapshot and the
+/// segments from it on. Older segments stay on disk as the audit trail.
+#[derive(Debug)]
+pub struct Journal {
+    commands: Option<Sender<Command>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_millis() as u64
+}
+
+fn numbered_path(dir: &Path, prefix: &str, number: u64, suffix: &str) -> PathBuf {
+    dir.join(format!("{}{:010}{}", prefix, number, suffix))
+}
+
+fn segment_path(dir: &Path, segment: u64) -> PathBuf {
+    numbered_path(dir, SEGMENT_PREFIX, segment, SEGMENT_SUFFIX)
+}
+
+fn snapshot_path(dir: &Path, segment: u64) -> PathBuf {
+    numbered_path(dir, SNAPSHOT_PREFIX, segment, SNAPSHOT_SUFFIX)
+}
+
+fn open_segment(dir: &Path, segment: u64) -> io::Result<BufWriter<File>> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(segment_path(dir, segment))?;
+    Ok(BufWriter::new(file))
+}
+
+// Numbers of the `<prefix><number><suffix>` files in `dir`, oldest first
+fn list_numbered(dir: &Path, prefix: &str, suffix: &str) -> io::Result<Vec<u64>> {
+    let mut numbers: Vec<u64> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            name.strip_prefix(prefix)?
+                .strip_suffix(suffix)?
+                .parse()
+                .ok()
+        })
+        .collect();
+    numbers.sort_unstable();
+    Ok(numbers)
+}
+
+fn read_segment(dir: &Path, segment: u64, records: &mut Vec<JournalRecord>) -> io::Result<()> {
+    let reader = BufReader::new(File::open(segment_path(dir, segment))?);
+    for (line_no, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<JournalRecord>(&line) {
+            Ok(record) => records.push(record),
+            // A crash mid-write leaves a torn last line; skip it rather than refuse to start
+            Err(e) => warn!(
+                "Skipping unreadable journal record in segment {} line {}: {}",
+                segment,
+                line_no + 1,
+                e
+            ),
+        }
+    }
+    Ok(())
+}
+
+// An unreadable snapshot is skipped, falling back to an older one or to the first segment
+fn read_snapshot<S: DeserializeOwned>(dir: &Path, segment: u64) -> Option<Snapshot<S>> {
+    let read = File::open(snapshot_path(dir, segment))
+        .map_err(|e| e.to_string())
+        .and_then(|file| serde_json::from_reader(BufReader::new(file)).map_err(|e| e.to_string()));
+    match read {
+        Ok(snapshot) => Some(snapshot),
+        Err(e) => {
+            warn!("Skipping unreadable journal snapshot {}: {}", segment, e);
+            None
+        }
+    }
+}
+
+impl SegmentWriter {
+    fn run(mut self, commands: Receiver<Command>) {
+        for command in commands {
+            let result = match command {
+                Command::Append { entry, ts_ms, ack } => {
+                    let result = self.append(entry, ts_ms);
+                    if let Some(ack) = ack {
+                        // The waiting caller decides what a failed write means for it
+                        let outcome = match &result {
+                            Ok(()) => Ok(()),
+                            Err(e) => Err(io::Error::new(e.kind(), e.to_string())),
+                        };
+                        let _ = ack.send(outcome);
+                    }
+                    result
+                }
+                Command::Snapshot(state) => self.snapshot(state),
+            };
+            // Failures are logged, never allowed to stop trading
+            if let Err(e) = result {
+                error!("Failed to write journal: {}", e);
+            }
+        }
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        self.segment += 1;
+        self.file = open_segment(&self.dir, self.segment)?;
+        self.written = 0;
+        Ok(())
+    }
+
+    fn append(&mut self, entry: JournalEntry, ts_ms: u64) -> io::Result<()> {
+        if self.written >= self.max_segment_bytes {
+            self.rotate()?;
+        }
+
+        self.seq += 1;
+        let record = JournalRecord {
+            seq: self.seq,
+            ts_ms,
+            entry,
+        };
+        let mut line = serde_json::to_string(&record)?;
+        line.push('\n');
+
+        self.file.write_all(line.as_bytes())?;
+        self.file.flush()?;
+        if self.fsync {
+            self.file.get_ref().sync_data()?;
+        }
+        self.written += line.len() as u64;
+        Ok(())
+    }
+
+    // Start a fresh segment and store the state as covering everything before it; the file is
+    // renamed into place once synced, so a crash never leaves a partial snapshot behind
+    fn snapshot(&mut self, state: serde_json::Value) -> io::Result<()> {
+        if self.written > 0 {
+            self.rotate()?;
+        }
+        let snapshot = Snapshot {
+            seq: self.seq,
+            ts_ms: now_ms(),
+            state,
+        };
+        let path = snapshot_path(&self.dir, self.segment);
+        let tmp_path = path.with_extension("json.tmp");
+        let mut file = BufWriter::new(File::create(&tmp_path)?);
+        serde_json::to_writer(&mut file, &snapshot)?;
+        file.flush()?;
+        file.get_ref().sync_all()?;
+        fs::rename(&tmp_path, &path)?;
+
+        for older in list_numbered(&self.dir, SNAPSHOT_PREFIX, SNAPSHOT_SUFFIX)? {
+            if older < self.segment {
+                fs::remove_file(snapshot_path(&self.dir, older))?;
+            }
+        }
+        info!(
+            "Wrote journal snapshot {} at seq {}",
+            self.segment, self.seq
+        );
+        Ok(())
+    }
+}
+
+impl Journal {
+    // Open the journal in `dir`, returning the newest snapshot and the records written after it
+    pub fn open<S: DeserializeOwned>(
+        dir: impl AsRef<Path>,
+        max_segment_bytes: u64,
+        fsync: bool,
+    ) -> io::Result<(Journal, JournalReplay<S>)> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let snapshot = list_numbered(&dir, SNAPSHOT_PREFIX, SNAPSHOT_SUFFIX)?
+            .into_iter()
+            .rev()
+            .find_map(|segment| Some((segment, read_snapshot::<S>(&dir, segment)?)));
+        let first_segment = snapshot.as_ref().map_or(0, |(segment, _)| *segment);
+
+        let segments = list_numbered(&dir, SEGMENT_PREFIX, SEGMENT_SUFFIX)?;
+        let mut records = Vec::new();
+        for segment in segments.iter().filter(|s| **s >= first_segment) {
+            read_segment(&dir, *segment, &mut records)?;
+        }
+
+        let snapshot_seq = snapshot.as_ref().map_or(0, |(_, s)| s.seq);
+        let segment = segments
+            .last()
+            .map_or(1, |last| last + 1)
+            .max(first_segment);
+        let writer = SegmentWriter {
+            file: open_segment(&dir, segment)?,
+            dir,
+            max_segment_bytes,
+            fsync,
+            segment,
+            written: 0,
+            seq: records.last().map_or(0, |r| r.seq).max(snapshot_seq),
+        };
+
+        let (commands, receiver) = mpsc::channel();
+        let worker = thread::Builder::new()
+            .name("journal".to_string())
+            .spawn(move || writer.run(receiver))?;
+
+        let journal = Journal {
+            commands: Some(commands),
+            worker: Some(worker),
+        };
+        let replay = JournalReplay {
+            state: snapshot.map(|(_, s)| s.state),
+            records,
+        };
+        Ok((journal, replay))
+    }
+
+    fn send(&self, command: Command) -> bool {
+        let sent = self
+            .commands
+            .as_ref()
+            .is_some_and(|commands| commands.send(command).is_ok());
+        if !sent {
+            error!("Journal writer has stopped, dropping journal write");
+        }
+        sent
+    }
+
+    // Queue an entry for the writer thread; returns without waiting for the disk
+    pub fn append(&self, entry: JournalEntry) {
+        self.send(Command::Append {
+            entry,
+            ts_ms: now_ms(),
+            ack: None,
+        });
+    }
+
+    // Queue an entry and wait until the writer has flushed it (and synced it with `fsync`).
+    // Entries appended earlier are written first, so they are on disk too once this returns
+    pub async fn append_acked(&self, entry: JournalEntry) -> io::Result<()> {
+        let (ack, written) = oneshot::channel();
+        let stopped = || io::Error::new(io::ErrorKind::BrokenPipe, "journal writer has stopped");
+        if !self.send(Command::Append {
+            entry,
+            ts_ms: now_ms(),
+            ack: Some(ack),
+        }) {
+            return Err(stopped());
+        }
+        written.await.map_err(|_| stopped())?
+    }
+
+    // Record `state` as the result of every entry appended so far. The caller must not let
+    // entries it has not applied to `state` yet be appended before this call
+    pub fn snapshot<S: Serialize>(&self, state: &S) {
+        match serde_json::to_value(state) {
+            Ok(state) => {
+                self.send(Command::Snapshot(state));
+            }
+            Err(e) => error!("Failed to serialize journal snapshot: {}", e),
+        }
+    }
+}
+
+impl Drop for Journal {
+    // Let the writer drain what is queued before the journal goes away
+    fn drop(&mut self) {
+        self.commands.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("journal-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn rejected(order_id: u64) -> JournalEntry {
+        JournalEntry::OrderRejected {
+            order_id,
+            error: "test".to_string(),
+        }
+    }
+
+    fn open(dir: &Path) -> (Journal, JournalReplay<u32>) {
+        Journal::open(dir, 1 << 20, false).unwrap()
+    }
+
+    #[test]
+    fn replay_starts_from_the_newest_snapshot() {
+        let dir = test_dir("snapshot");
+        let (journal, replay) = open(&dir);
+        assert!(replay.state.is_none() && replay.records.is_empty());
+        journal.append(rejected(1));
+        journal.append(rejected(2));
+        journal.snapshot(&7u32);
+        journal.append(rejected(3));
+        drop(journal);
+
+        let (journal, replay) = open(&dir);
+        assert_eq!(replay.state, Some(7));
+        let seqs: Vec<u64> = replay.records.iter().map(|r| r.seq).collect();
+        assert_eq!(seqs, vec![3]);
+        journal.append(rejected(4));
+        drop(journal);
+
+        let (_, replay) = open(&dir);
+        let seqs: Vec<u64> = replay.records.iter().map(|r| r.seq).collect();
+        assert_eq!(seqs, vec![3, 4]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn acked_appends_are_on_disk_when_they_return() {
+        let dir = test_dir("acked");
+        let (journal, _) = open(&dir);
+        journal.append(rejected(1));
+        journal.append_acked(rejected(2)).await.unwrap();
+
+        // Read back while the writer is still running
+        let mut records = Vec::new();
+        read_segment(&dir, 1, &mut records).unwrap();
+        let seqs: Vec<u64> = records.iter().map(|r| r.seq).collect();
+        assert_eq!(seqs, vec![1, 2]);
+        drop(journal);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn an_unreadable_snapshot_falls_back_to_full_replay() {
+        let dir = test_dir("torn-snapshot");
+        let (journal, _) = open(&dir);
+        journal.append(rejected(1));
+        drop(journal);
+        fs::write(snapshot_path(&dir, 5), "{\"seq\":").unwrap();
+
+        let (_, replay) = open(&dir);
+        assert!(replay.state.is_none());
+        assert_eq!(replay.records.len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,344 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Lines, Write},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use flate2::{read::MultiGzDecoder, write::GzEncoder, Compression};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::{
+    connectors::vertex::subscription_client::RawFrame,
+    domain::models::vertex::stream_events::StreamEvent,
+};
+
+const FILE_PREFIX: &str = "market-";
+const FILE_SUFFIX: &str = ".jsonl.gz";
+// Buffered lines reach the file at least this often while events keep coming
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+// One line of a recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    // When the service received the message, unix milliseconds
+    pub received_ms: u64,
+    // The subscription message as Vertex sent it, tagged by its "type" field
+    pub event: StreamEvent,
+}
+
+#[derive(Debug)]
+struct RecordingWriter {
+    file: BufWriter<GzEncoder<File>>,
+    opened_ms: u64,
+    written: u64,
+}
+
+impl RecordingWriter {
+    // Complete the gzip member so the file reads back without a truncation warning
+    fn finish(self) -> io::Result<()> {
+        self.file
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .finish()?;
+        Ok(())
+    }
+}
+
+/// Records the Vertex subscription messages to compressed, rotating files.
+///
+/// Files in `dir` are named `market-<opened unix ms>.jsonl.gz`, so a directory listing sorts
+/// them in time order. Each is gzip-compressed newline-delimited JSON, one `RecordedEvent` per
+/// line:
+///
+/// `{"received_ms":1718000000123,"event":{"type":"best_bid_offer","product_id":2,...}}`
+///
+/// `event` is the subscription message exactly as Vertex sent it (`book_depth`, `trade`,
+/// `best_bid_offer`, `order_update`, `fill`, `position_change`). Only websocket messages are
+/// recorded; events the service publishes itself, like paper fills or a replay, are not. A new
+/// file is started once the current one has been open `rotate_secs` or holds `max_file_bytes` of
+/// uncompressed lines. Data is flushed every second, so after a crash everything but the last
+/// second reads back.
+#[derive(Debug)]
+pub struct Recorder {
+    dir: PathBuf,
+    max_file_bytes: u64,
+    rotate_ms: u64,
+    writer: Option<RecordingWriter>,
+    last_flush: Instant,
+}
+
+fn recording_path(dir: &Path, opened_ms: u64) -> PathBuf {
+    dir.join(format!("{}{:013}{}", FILE_PREFIX, opened_ms, FILE_SUFFIX))
+}
+
+/// Recording files in `dir`, oldest first.
+pub fn list_recordings(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files: Vec<(u64, PathBuf)> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let opened_ms = name
+                .strip_prefix(FILE_PREFIX)?
+                .strip_suffix(FILE_SUFFIX)?
+                .parse()
+                .ok()?;
+            Some((opened_ms, entry.path()))
+        })
+        .collect();
+    files.sort_unstable();
+    Ok(files.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Records of one recording file in the order they were written, read as they are needed.
+#[derive(Debug)]
+pub struct RecordingReader {
+    path: PathBuf,
+    // Gone once the file ends or breaks off
+    lines: Option<Lines<BufReader<MultiGzDecoder<File>>>>,
+    line_no: usize,
+}
+
+impl Iterator for RecordingReader {
+    type Item = RecordedEvent;
+
+    fn next(&mut self) -> Option<RecordedEvent> {
+        loop {
+            let line = self.lines.as_mut()?.next();
+            self.line_no += 1;
+            let line = match line {
+                Some(Ok(line)) => line,
+                None => {
+                    self.lines = None;
+                    return None;
+                }
+                // A crash leaves the file without its gzip trailer; keep what was flushed
+                Some(Err(e)) => {
+                    warn!(
+                        "Recording {} ends early at line {}: {}",
+                        self.path.display(),
+                        self.line_no,
+                        e
+                    );
+                    self.lines = None;
+                    return None;
+                }
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<RecordedEvent>(&line) {
+                Ok(event) => return Some(event),
+                Err(e) => warn!(
+                    "Skipping unreadable record in {} line {}: {}",
+                    self.path.display(),
+                    self.line_no,
+                    e
+                ),
+            }
+        }
+    }
+}
+
+/// Open one recording file for reading, a line at a time.
+pub fn read_recording(path: &Path) -> io::Result<RecordingReader> {
+    let reader = BufReader::new(MultiGzDecoder::new(File::open(path)?));
+    Ok(RecordingReader {
+        path: path.to_path_buf(),
+        lines: Some(reader.lines()),
+        line_no: 0,
+    })
+}
+
+impl Recorder {
+    pub fn open(dir: impl AsRef<Path>, max_file_bytes: u64, rotate_secs: u64) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Recorder {
+            dir,
+            max_file_bytes,
+            rotate_ms: rotate_secs.saturating_mul(1000),
+            writer: None,
+            last_flush: Instant::now(),
+        })
+    }
+
+    /// Record the frames on their own thread so compression never holds up the async workers.
+    pub fn spawn(mut self, mut frames: broadcast::Receiver<RawFrame>) -> io::Result<()> {
+        info!("Recording market data to {}", self.dir.display());
+        thread::Builder::new()
+            .name("market-recorder".to_string())
+            .spawn(move || {
+                loop {
+                    match frames.blocking_recv() {
+                        Ok(frame) => {
+                            if let Err(e) = self.record(&frame) {
+                                error!("Failed to record market data: {}", e);
+                                // Start over in a fresh file rather than append to a broken one
+                                self.writer = None;
+                            }
+                        }
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!(
+                                "Market data recorder lagged, {} events not recorded",
+                                skipped
+                            )
+                        }
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+                if let Some(writer) = self.writer.take() {
+                    if let Err(e) = writer.finish() {
+                        error!("Failed to close recording: {}", e);
+                    }
+                }
+            })?;
+        Ok(())
+    }
+
+    fn record(&mut self, frame: &RawFrame) -> io::Result<()> {
+        let received_ms = frame.received_ms;
+        let rotate = self.writer.as_ref().is_some_and(|writer| {
+            writer.written >= self.max_file_bytes
+                || received_ms.saturating_sub(writer.opened_ms) >= self.rotate_ms
+        });
+        if rotate {
+            if let Some(writer) = self.writer.take() {
+                writer.finish()?;
+            }
+        }
+        if self.writer.is_none() {
+            self.writer = Some(self.create(received_ms)?);
+        }
+        let Some(writer) = self.writer.as_mut() else {
+            return Ok(());
+        };
+
+        // The frame is already JSON; it only has to stay on one line
+        let text = frame.text.replace(['\n', '\r'], " ");
+        let line = format!("{{\"received_ms\":{},\"event\":{}}}\n", received_ms, text);
+        writer.file.write_all(line.as_bytes())?;
+        writer.written += line.len() as u64;
+
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            writer.file.flush()?;
+            self.last_flush = Instant::now();
+        }
+        Ok(())
+    }
+
+    fn create(&self, now_ms: u64) -> io::Result<RecordingWriter> {
+        // Never reuse a name, even when rotating twice within a millisecond
+        let mut opened_ms = now_ms;
+        while recording_path(&self.dir, opened_ms).exists() {
+            opened_ms += 1;
+        }
+        let file = File::create(recording_path(&self.dir, opened_ms))?;
+        Ok(RecordingWriter {
+            file: BufWriter::new(GzEncoder::new(file, Compression::default())),
+            opened_ms,
+            written: 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::domain::models::vertex::stream_events::BestBidOffer;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("recorder-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    // A best bid/offer message as Vertex would send it, pretty-printed over several lines
+    fn frame(received_ms: u64, product_id: u32) -> RawFrame {
+        let event = StreamEvent::BestBidOffer(BestBidOffer {
+            timestamp: (received_ms * 1_000_000).to_string(),
+            product_id,
+            bid_price: "99".parse().unwrap(),
+            bid_qty: "1".parse().unwrap(),
+            ask_price: "101".parse().unwrap(),
+            ask_qty: "1".parse().unwrap(),
+        });
+        RawFrame {
+            received_ms,
+            text: Arc::from(serde_json::to_string_pretty(&event).unwrap()),
+        }
+    }
+
+    fn received(path: &Path) -> Vec<u64> {
+        read_recording(path)
+            .unwrap()
+            .map(|event| event.received_ms)
+            .collect()
+    }
+
+    #[test]
+    fn recordings_rotate_by_age_and_size_and_read_back_in_order() {
+        let dir = test_dir("rotate");
+        let mut recorder = Recorder::open(&dir, 1 << 20, 1).unwrap();
+        for (received_ms, product_id) in [(1_000, 2), (1_500, 4), (2_000, 2)] {
+            recorder.record(&frame(received_ms, product_id)).unwrap();
+        }
+        // A file over the size limit rotates on the next frame, under a fresh name
+        recorder.max_file_bytes = 1;
+        recorder.record(&frame(2_000, 4)).unwrap();
+        recorder.writer.take().unwrap().finish().unwrap();
+
+        let files = list_recordings(&dir).unwrap();
+        let names: Vec<String> = files
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "market-0000000001000.jsonl.gz",
+                "market-0000000002000.jsonl.gz",
+                "market-0000000002001.jsonl.gz",
+            ]
+        );
+
+        assert_eq!(received(&files[0]), vec![1_000, 1_500]);
+        let first: Vec<RecordedEvent> = read_recording(&files[0]).unwrap().collect();
+        let StreamEvent::BestBidOffer(bbo) = &first[1].event else {
+            panic!("expected a best bid/offer");
+        };
+        assert_eq!((bbo.product_id, bbo.ask_price), (4, "101".parse().unwrap()));
+        assert_eq!(received(&files[1]), vec![2_000]);
+        assert_eq!(received(&files[2]), vec![2_000]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_recording_cut_off_by_a_crash_reads_back_up_to_the_last_flush() {
+        let dir = test_dir("truncated");
+        let mut recorder = Recorder::open(&dir, 1 << 20, 3_600).unwrap();
+        recorder.record(&frame(1_000, 2)).unwrap();
+        recorder.record(&frame(1_001, 2)).unwrap();
+        recorder.writer.as_mut().unwrap().file.flush().unwrap();
+        recorder.record(&frame(1_002, 2)).unwrap();
+
+        // What a crash leaves on disk: the flushed data and no gzip trailer
+        let path = list_recordings(&dir).unwrap().remove(0);
+        let crashed = recording_path(&dir, 0);
+        fs::copy(&path, &crashed).unwrap();
+        assert_eq!(received(&crashed), vec![1_000, 1_001]);
+
+        // A clean close keeps everything, and losing just the trailer loses nothing
+        recorder.writer.take().unwrap().finish().unwrap();
+        assert_eq!(received(&path), vec![1_000, 1_001, 1_002]);
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&crashed, &bytes[..bytes.len() - 8]).unwrap();
+        assert_eq!(received(&crashed), vec![1_000, 1_001, 1_002]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}